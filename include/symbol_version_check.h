@@ -0,0 +1,57 @@
+/* C interface to symbol-version-check, provided by the library built as a cdylib (libsymbol_version_check.so). */
+
+#ifndef SYMBOL_VERSION_CHECK_H
+#define SYMBOL_VERSION_CHECK_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The result of checking a file. */
+typedef struct svc_report svc_report;
+
+/* A symbol a file fails for. library is NULL if it isn't known. */
+typedef struct {
+    const char *name;
+    const char *version;
+    const char *library;
+} svc_symbol;
+
+/* Checks a file against maximum versions, e.g. "GLIBC_2.17". The report must be freed with svc_report_free. If the
+ * arguments are invalid, the report has an error. */
+svc_report *svc_check_file(const char *path, const char *const *requirements, size_t count);
+
+/* Checks the size bytes of a file's contents at data as svc_check_file does. The report's file is empty. data may be
+ * null if size is 0; if it is null otherwise, the report has an error. */
+svc_report *svc_check_data(const unsigned char *data, size_t size, const char *const *requirements, size_t count);
+
+/* Allocates size bytes in the library's memory, for hosts such as JavaScript calling the WebAssembly build. The memory
+ * must be freed with svc_free. */
+unsigned char *svc_alloc(size_t size);
+
+/* Frees memory allocated with svc_alloc, given the same size. */
+void svc_free(unsigned char *pointer, size_t size);
+
+/* Returns 1 if the file passed, or 0 if it failed or couldn't be checked. */
+int svc_report_passed(const svc_report *report);
+
+/* Returns the reason the file couldn't be checked, or NULL if it was checked. */
+const char *svc_report_error(const svc_report *report);
+
+/* Returns the symbols the file fails for, setting count to the number of them. */
+const svc_symbol *svc_report_symbols(const svc_report *report, size_t *count);
+
+/* Returns the report as JSON, including the violations other than symbols. It is empty if the file couldn't be
+ * checked. */
+const char *svc_report_json(const svc_report *report);
+
+/* Frees a report, and the strings and symbols returned for it. Freeing NULL does nothing. */
+void svc_report_free(svc_report *report);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,481 @@
+use crate::{binary, cpio, elf, oci};
+use anyhow::{Context, anyhow};
+#[cfg(feature = "squashfs")]
+use backhand::{FilesystemReader, InnerNode};
+use flate2::read::GzDecoder;
+use object::elf::{ELFMAG, FileHeader32, FileHeader64};
+use object::read::archive::ArchiveFile;
+use object::read::elf::FileHeader;
+use object::{Endianness, FileKind};
+use std::io::{Cursor, Read};
+
+/// Compression which may be applied to an archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(data: &[u8]) -> Compression {
+        if data.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if data.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    pub fn decompress<'a>(self, data: &'a [u8]) -> anyhow::Result<Box<dyn Read + 'a>> {
+        match self {
+            Compression::None => Ok(Box::new(data)),
+            Compression::Gzip => Ok(Box::new(GzDecoder::new(data))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Box::new(
+                zstd::Decoder::with_buffer(data).with_context(|| "Error reading zstd stream")?,
+            )),
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => Err(anyhow!("zstd support isn't included in this build")),
+        }
+    }
+}
+
+const TAR_BLOCK_SIZE: usize = 512;
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+const SQUASHFS_MAGIC: &[u8] = b"hsqs";
+const APPIMAGE_TYPE2_MAGIC: &[u8] = b"AI\x02";
+const APPIMAGE_MAGIC_OFFSET: usize = 8;
+
+/// Archive formats which may contain ELF files to be checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// Zip archives, including Java archives (.jar)
+    Zip,
+    /// Tar archives, optionally gzip or zstd compressed, including npm packages (.tgz)
+    Tar(Compression),
+    /// Container images written by `docker save`
+    OciImage,
+    /// Cpio archives, optionally gzip or zstd compressed, including initramfs images
+    Cpio,
+    /// Squashfs filesystem images
+    Squashfs,
+    /// Type 2 AppImages, which consist of an ELF runtime followed by a squashfs filesystem image
+    AppImage,
+    /// Static libraries (.a), which are `ar` archives of object files
+    StaticLibrary,
+}
+
+impl ArchiveKind {
+    pub fn detect(data: &[u8]) -> Option<ArchiveKind> {
+        if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+            Some(ArchiveKind::Zip)
+        } else if is_tar(data) && oci::is_image_archive(data) {
+            Some(ArchiveKind::OciImage)
+        } else if is_tar_archive(data) {
+            Some(ArchiveKind::Tar(Compression::detect(data)))
+        } else if cpio::is_cpio_archive(data) {
+            Some(ArchiveKind::Cpio)
+        } else if data.starts_with(&object::archive::MAGIC) {
+            Some(ArchiveKind::StaticLibrary)
+        } else if data.starts_with(SQUASHFS_MAGIC) {
+            Some(ArchiveKind::Squashfs)
+        } else if elf::is_elf(data)
+            && data
+                .get(APPIMAGE_MAGIC_OFFSET..APPIMAGE_MAGIC_OFFSET + APPIMAGE_TYPE2_MAGIC.len())
+                .is_some_and(|magic| magic == APPIMAGE_TYPE2_MAGIC)
+        {
+            Some(ArchiveKind::AppImage)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_tar_archive(data: &[u8]) -> bool {
+    Compression::detect(data).decompress(data).is_ok_and(is_tar)
+}
+
+/// Checks whether the stream starts with a tar header, by reading only the first block.
+fn is_tar(reader: impl Read) -> bool {
+    let mut header = Vec::with_capacity(TAR_BLOCK_SIZE);
+    match reader.take(TAR_BLOCK_SIZE as u64).read_to_end(&mut header) {
+        Ok(_) => header
+            .get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len())
+            .is_some_and(|magic| magic == TAR_MAGIC),
+        Err(_) => false,
+    }
+}
+
+/// Calls `visit` with the name and contents of each ELF file contained in the archive. Entries which are not ELF
+/// files are skipped.
+pub fn visit_elf_entries(
+    kind: ArchiveKind,
+    data: &[u8],
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    match kind {
+        ArchiveKind::Zip => visit_zip_elf_entries(data, visit),
+        ArchiveKind::Tar(compression) => {
+            visit_tar_elf_entries(compression.decompress(data)?, visit)
+        }
+        ArchiveKind::OciImage => oci::visit_image_elf_entries(data, visit),
+        ArchiveKind::Cpio => cpio::visit_cpio_elf_entries(data, visit),
+        ArchiveKind::Squashfs => visit_squashfs_elf_entries(data, 0, visit),
+        ArchiveKind::AppImage => visit_squashfs_elf_entries(data, elf_size(data)?, visit),
+        ArchiveKind::StaticLibrary => visit_ar_elf_entries(data, visit),
+    }
+}
+
+fn visit_ar_elf_entries(data: &[u8], visit: &mut dyn FnMut(&str, &[u8])) -> anyhow::Result<()> {
+    let archive = ArchiveFile::parse(data).with_context(|| "Error reading ar archive")?;
+    for member in archive.members() {
+        let member = member.with_context(|| "Error reading ar archive member")?;
+        let name = String::from_utf8_lossy(member.name()).to_string();
+        let contents = member
+            .data(data)
+            .with_context(|| format!("Error reading ar archive member {}", name))?;
+        if binary::is_binary(contents) {
+            visit(&name, contents);
+        }
+    }
+    Ok(())
+}
+
+fn visit_zip_elf_entries(data: &[u8], visit: &mut dyn FnMut(&str, &[u8])) -> anyhow::Result<()> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(data)).with_context(|| "Error reading zip archive")?;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .with_context(|| "Error reading zip archive entry")?;
+        if !entry.is_file() {
+            continue;
+        }
+        let name = entry
+            .name()
+            .with_context(|| "Error reading zip archive entry name")?
+            .to_string();
+        if let Some(contents) = read_elf_entry(&mut entry)
+            .with_context(|| format!("Error reading zip archive entry {}", name))?
+        {
+            visit(&name, &contents);
+        }
+    }
+    Ok(())
+}
+
+fn visit_tar_elf_entries(
+    reader: impl Read,
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive
+        .entries()
+        .with_context(|| "Error reading tar archive")?
+    {
+        let mut entry = entry.with_context(|| "Error reading tar archive entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .with_context(|| "Error reading tar archive entry name")?
+            .to_string_lossy()
+            .to_string();
+        if let Some(contents) = read_elf_entry(&mut entry)
+            .with_context(|| format!("Error reading tar archive entry {}", name))?
+        {
+            visit(&name, &contents);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "squashfs")]
+fn visit_squashfs_elf_entries(
+    data: &[u8],
+    offset: u64,
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    let filesystem = FilesystemReader::from_reader_with_offset(Cursor::new(data), offset)
+        .with_context(|| "Error reading squashfs filesystem")?;
+    for node in filesystem.files() {
+        let InnerNode::File(file) = &node.inner else {
+            continue;
+        };
+        let name = node
+            .fullpath
+            .strip_prefix("/")
+            .unwrap_or(&node.fullpath)
+            .to_string_lossy()
+            .to_string();
+        if let Some(contents) = read_elf_entry(&mut filesystem.file(file).reader())
+            .with_context(|| format!("Error reading squashfs entry {}", name))?
+        {
+            visit(&name, &contents);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "squashfs"))]
+fn visit_squashfs_elf_entries(
+    _data: &[u8],
+    _offset: u64,
+    _visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    Err(anyhow!("Squashfs support isn't included in this build"))
+}
+
+/// Returns the size of the ELF file at the start of `data`, assuming the section header table is at its end, as
+/// is the case for the AppImage runtime.
+fn elf_size(data: &[u8]) -> anyhow::Result<u64> {
+    fn section_table_end<Elf: FileHeader<Endian = Endianness>>(data: &[u8]) -> anyhow::Result<u64> {
+        let header = Elf::parse(data).with_context(|| "Error parsing ELF header")?;
+        let endian = header
+            .endian()
+            .with_context(|| "Error parsing ELF header")?;
+        let shoff: u64 = header.e_shoff(endian).into();
+        Ok(shoff + u64::from(header.e_shentsize(endian)) * u64::from(header.e_shnum(endian)))
+    }
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => section_table_end::<FileHeader32<Endianness>>(data),
+        FileKind::Elf64 => section_table_end::<FileHeader64<Endianness>>(data),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+/// Reads an archive entry if it is an ELF file, or another binary which can be checked, avoiding reading the remainder
+/// of the entry if it is not.
+pub fn read_elf_entry(entry: &mut impl Read) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut contents = Vec::new();
+    entry
+        .by_ref()
+        .take(ELFMAG.len() as u64)
+        .read_to_end(&mut contents)?;
+    if !binary::is_binary(&contents) {
+        return Ok(None);
+    }
+    entry.read_to_end(&mut contents)?;
+    Ok(Some(contents))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    /// Contents of an archive entry which is identified as an ELF file, for tests which don't need to parse it.
+    pub const ELF_STUB: &[u8] = b"\x7fELF\x02\x01\x01";
+
+    pub fn test_elf() -> Vec<u8> {
+        std::fs::read(std::env::current_exe().unwrap()).unwrap()
+    }
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, contents) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    pub fn build_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            builder.append_data(&mut header, name, *contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    pub fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::none());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[cfg(all(feature = "squashfs", feature = "xz"))]
+    fn build_squashfs(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut filesystem = backhand::FilesystemWriter::default();
+        let header = backhand::NodeHeader::new(0o755, 0, 0, 0);
+        for (name, contents) in entries {
+            if let Some(parent) = std::path::Path::new(name).parent()
+                && !parent.as_os_str().is_empty()
+            {
+                filesystem.push_dir_all(parent, header).unwrap();
+            }
+            filesystem.push_file(*contents, name, header).unwrap();
+        }
+        let mut data = Cursor::new(Vec::new());
+        filesystem.write(&mut data).unwrap();
+        data.into_inner()
+    }
+
+    /// Builds a GNU `ar` archive, as used for static libraries.
+    pub fn build_ar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = object::archive::MAGIC.to_vec();
+        for (name, contents) in entries {
+            let header = format!(
+                "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                format!("{}/", name),
+                0,
+                0,
+                0,
+                644,
+                contents.len()
+            );
+            data.extend(header.as_bytes());
+            data.extend(*contents);
+            if contents.len() % 2 == 1 {
+                data.push(b'\n');
+            }
+        }
+        data
+    }
+
+    fn collect_elf_entries(kind: ArchiveKind, data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut entries = Vec::new();
+        visit_elf_entries(kind, data, &mut |name, contents| {
+            entries.push((name.to_string(), contents.to_vec()))
+        })
+        .unwrap();
+        entries
+    }
+
+    #[test]
+    fn detects_zip() {
+        let data = build_zip(&[("a.txt", b"hello")]);
+        assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::Zip));
+    }
+
+    #[test]
+    fn doesnt_detect_elf_as_archive() {
+        assert_eq!(ArchiveKind::detect(&test_elf()), None);
+    }
+
+    #[test]
+    fn zip_visits_only_elf_entries() {
+        let data = build_zip(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n"),
+            ("native/linux-x86_64/libfoo.so", ELF_STUB),
+            ("com/example/Foo.class", b"\xca\xfe\xba\xbe"),
+        ]);
+        let entries = collect_elf_entries(ArchiveKind::Zip, &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "native/linux-x86_64/libfoo.so");
+        assert_eq!(entries[0].1, ELF_STUB);
+    }
+
+    #[test]
+    fn detects_tar() {
+        let data = build_tar(&[("package/package.json", b"{}")]);
+        assert_eq!(
+            ArchiveKind::detect(&data),
+            Some(ArchiveKind::Tar(Compression::None))
+        );
+    }
+
+    #[test]
+    fn detects_tar_gz() {
+        let data = gzip(&build_tar(&[("package/package.json", b"{}")]));
+        assert_eq!(
+            ArchiveKind::detect(&data),
+            Some(ArchiveKind::Tar(Compression::Gzip))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn detects_tar_zst() {
+        let data = zstd::encode_all(&build_tar(&[("a.txt", b"hello")])[..], 1).unwrap();
+        assert_eq!(
+            ArchiveKind::detect(&data),
+            Some(ArchiveKind::Tar(Compression::Zstd))
+        );
+    }
+
+    #[test]
+    fn doesnt_detect_gzipped_non_tar() {
+        let data = gzip(b"just some text");
+        assert_eq!(ArchiveKind::detect(&data), None);
+    }
+
+    #[test]
+    fn tar_gz_visits_only_elf_entries() {
+        let data = gzip(&build_tar(&[
+            ("package/package.json", b"{}"),
+            ("package/prebuilds/linux-x64/addon.node", ELF_STUB),
+            ("package/index.js", b"module.exports = {};"),
+        ]));
+        let entries = collect_elf_entries(ArchiveKind::Tar(Compression::Gzip), &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "package/prebuilds/linux-x64/addon.node");
+        assert_eq!(entries[0].1, ELF_STUB);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn tar_zst_visits_elf_entries() {
+        let tar = build_tar(&[("release/bin/app", ELF_STUB), ("release/README", b"x")]);
+        let data = zstd::encode_all(&tar[..], 1).unwrap();
+        let entries = collect_elf_entries(ArchiveKind::Tar(Compression::Zstd), &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "release/bin/app");
+    }
+
+    #[test]
+    #[cfg(all(feature = "squashfs", feature = "xz"))]
+    fn squashfs_visits_only_elf_entries() {
+        let data = build_squashfs(&[
+            ("usr/bin/app", ELF_STUB),
+            ("AppRun.desktop", b"[Desktop Entry]"),
+        ]);
+        assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::Squashfs));
+        let entries = collect_elf_entries(ArchiveKind::Squashfs, &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "usr/bin/app");
+        assert_eq!(entries[0].1, ELF_STUB);
+    }
+
+    #[test]
+    #[cfg(all(feature = "squashfs", feature = "xz"))]
+    fn appimage_visits_elf_entries_in_embedded_squashfs() {
+        let mut data = test_elf();
+        data[APPIMAGE_MAGIC_OFFSET..APPIMAGE_MAGIC_OFFSET + APPIMAGE_TYPE2_MAGIC.len()]
+            .copy_from_slice(APPIMAGE_TYPE2_MAGIC);
+        data.extend(build_squashfs(&[("usr/lib/libfoo.so", ELF_STUB)]));
+        assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::AppImage));
+        let entries = collect_elf_entries(ArchiveKind::AppImage, &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "usr/lib/libfoo.so");
+    }
+
+    #[test]
+    fn static_library_visits_only_elf_members() {
+        let data = build_ar(&[("getrandom.o", ELF_STUB), ("README", b"not an object")]);
+        assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::StaticLibrary));
+        let entries = collect_elf_entries(ArchiveKind::StaticLibrary, &data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "getrandom.o");
+        assert_eq!(entries[0].1, ELF_STUB);
+    }
+
+    #[test]
+    fn zip_invalid_archive_is_error() {
+        let result = visit_elf_entries(ArchiveKind::Zip, b"PK\x03\x04garbage", &mut |_, _| {});
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Context;
+use flate2::read::ZlibDecoder;
+use object::{Object, ObjectSection};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Read;
+
+/// The section `cargo auditable` embeds a binary's dependency tree in, as zlib-compressed JSON.
+const DEPENDENCIES_SECTION: &str = ".dep-v0";
+
+/// A package in the dependency tree recorded by `cargo auditable`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Where the package came from, e.g. `crates.io` or `local`
+    pub source: String,
+    /// Whether the package is only used at build time (`build`), rather than being linked in (`runtime`)
+    #[serde(default = "runtime")]
+    pub kind: String,
+    /// The indexes of the packages this package depends on
+    #[serde(default)]
+    pub dependencies: Vec<usize>,
+    /// Whether this is the package the binary was built from
+    #[serde(default)]
+    pub root: bool,
+}
+
+fn runtime() -> String {
+    "runtime".to_string()
+}
+
+#[derive(Deserialize)]
+struct VersionInfo {
+    packages: Vec<Package>,
+}
+
+/// Reads the dependency tree a binary built with `cargo auditable` embeds, if any.
+pub fn read_packages(data: &[u8]) -> anyhow::Result<Option<Vec<Package>>> {
+    let file = object::File::parse(data).with_context(|| "Error parsing file")?;
+    let Some(section) = file.section_by_name(DEPENDENCIES_SECTION) else {
+        return Ok(None);
+    };
+    let compressed = section
+        .data()
+        .with_context(|| "Error reading cargo auditable dependencies")?;
+    parse_packages(compressed).map(Some)
+}
+
+fn parse_packages(compressed: &[u8]) -> anyhow::Result<Vec<Package>> {
+    let mut json = Vec::new();
+    ZlibDecoder::new(compressed)
+        .read_to_end(&mut json)
+        .with_context(|| "Error decompressing cargo auditable dependencies")?;
+    let info: VersionInfo = serde_json::from_slice(&json)
+        .with_context(|| "Error parsing cargo auditable dependencies")?;
+    Ok(info.packages)
+}
+
+/// Finds the package a Rust crate was built from, by its crate name, in which hyphens are replaced with underscores.
+pub fn find_crate<'a>(packages: &'a [Package], krate: &str) -> Option<&'a Package> {
+    packages
+        .iter()
+        .find(|package| package.kind == "runtime" && package.name.replace('-', "_") == krate)
+}
+
+/// Finds the shortest chain of dependencies from the root package to a package, e.g. `my-app`, `reqwest`, `ring`.
+pub fn dependency_path<'a>(packages: &'a [Package], package: &Package) -> Vec<&'a Package> {
+    let Some(root) = packages.iter().position(|p| p.root) else {
+        return Vec::new();
+    };
+    let mut previous: Vec<Option<usize>> = vec![None; packages.len()];
+    let mut queue = VecDeque::from([root]);
+    while let Some(index) = queue.pop_front() {
+        if packages[index] == *package {
+            let mut path = vec![&packages[index]];
+            let mut current = index;
+            while let Some(parent) = previous[current] {
+                path.push(&packages[parent]);
+                current = parent;
+            }
+            path.reverse();
+            return path;
+        }
+        for &dependency in &packages[index].dependencies {
+            if dependency < packages.len() && dependency != root && previous[dependency].is_none() {
+                previous[dependency] = Some(index);
+                queue.push_back(dependency);
+            }
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    const DEPENDENCIES: &str = r#"{"packages":[
+        {"name":"my-app","version":"0.1.0","source":"local","dependencies":[1],"root":true},
+        {"name":"reqwest","version":"0.12.5","source":"crates.io","dependencies":[2]},
+        {"name":"ring","version":"0.17.8","source":"crates.io","dependencies":[3]},
+        {"name":"cc","version":"1.1.6","source":"crates.io","kind":"build"}
+    ]}"#;
+
+    fn packages() -> Vec<Package> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(DEPENDENCIES.as_bytes()).unwrap();
+        parse_packages(&encoder.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_packages() {
+        let packages = packages();
+        assert_eq!(packages.len(), 4);
+        assert_eq!(packages[2].name, "ring");
+        assert_eq!(packages[2].kind, "runtime");
+        assert_eq!(packages[3].kind, "build");
+        assert!(packages[0].root);
+        assert!(find_crate(&packages, "cc").is_none());
+    }
+
+    #[test]
+    fn finds_dependency_path() {
+        let packages = packages();
+        let ring = find_crate(&packages, "ring").unwrap();
+        let path: Vec<&str> = dependency_path(&packages, ring)
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+        assert_eq!(path, vec!["my-app", "reqwest", "ring"]);
+    }
+
+    #[test]
+    fn reads_no_packages_without_section() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert!(read_packages(&data).unwrap().is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::version::NamespacedVersion;
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A policy from PyPA auditwheel's `policy.json`, such as `manylinux_2_17`. Only the symbol versions are used.
+#[derive(Debug, Deserialize)]
+struct AuditwheelPolicy {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// The versions permitted in each namespace, by architecture
+    #[serde(default)]
+    symbol_versions: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+/// Reads the versions permitted by a policy in an auditwheel `policy.json` file. The policy is named with its
+/// architecture, e.g. `manylinux_2_17_x86_64`, or using one of its aliases, e.g. `manylinux2014_x86_64`.
+pub fn read_policy_versions(
+    file: &Path,
+    policy_name: &str,
+) -> anyhow::Result<Vec<NamespacedVersion>> {
+    let data = fs::read(file)
+        .with_context(|| format!("Error reading auditwheel policy file {}", file.display()))?;
+    let policies: Vec<AuditwheelPolicy> = serde_json::from_slice(&data)
+        .with_context(|| format!("Error parsing auditwheel policy file {}", file.display()))?;
+
+    let mut names = Vec::new();
+    for policy in &policies {
+        for (arch, symbol_versions) in &policy.symbol_versions {
+            let matches = std::iter::once(&policy.name)
+                .chain(&policy.aliases)
+                .any(|name| format!("{}_{}", name, arch) == policy_name);
+            if !matches {
+                names.push(format!("{}_{}", policy.name, arch));
+                continue;
+            }
+            let versions = symbol_versions
+                .iter()
+                .flat_map(|(namespace, versions)| {
+                    versions.iter().map(move |version| {
+                        NamespacedVersion::parse(&format!("{}_{}", namespace, version))
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+                .with_context(|| format!("Invalid symbol version in policy {}", policy_name))?;
+            if versions.is_empty() {
+                return Err(anyhow!("Policy {} has no symbol versions", policy_name));
+            }
+            return Ok(versions);
+        }
+    }
+    Err(anyhow!(
+        "Unknown policy {} in {}, expected one of: {}",
+        policy_name,
+        file.display(),
+        names.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    const POLICY_JSON: &[u8] = br#"[
+        {"name": "linux", "aliases": [], "priority": 0, "symbol_versions": {}, "lib_whitelist": []},
+        {
+            "name": "manylinux_2_17",
+            "aliases": ["manylinux2014"],
+            "priority": 80,
+            "symbol_versions": {
+                "i686": {"GLIBC": ["2.0", "2.17"]},
+                "x86_64": {"GLIBC": ["2.2.5", "2.17"], "CXXABI": ["1.3", "1.3.7", "TM_1"]}
+            },
+            "lib_whitelist": ["libc.so.6"],
+            "blacklist": {}
+        }
+    ]"#;
+
+    #[test]
+    fn reads_policy_versions_by_name_and_alias() {
+        let dir = TempDir::new("auditwheel");
+        let file = dir.write("policy.json", POLICY_JSON);
+        let versions = read_policy_versions(&file, "manylinux_2_17_x86_64").unwrap();
+        let names: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "CXXABI_1.3",
+                "CXXABI_1.3.7",
+                "CXXABI_TM_1",
+                "GLIBC_2.2.5",
+                "GLIBC_2.17"
+            ]
+        );
+        assert_eq!(
+            read_policy_versions(&file, "manylinux2014_i686")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn unknown_policy_is_error() {
+        let dir = TempDir::new("auditwheel-unknown");
+        let file = dir.write("policy.json", POLICY_JSON);
+        let error = read_policy_versions(&file, "manylinux_2_17_aarch64").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .ends_with("expected one of: manylinux_2_17_i686, manylinux_2_17_x86_64")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// The violations known when a baseline was written, by file, which don't fail later checks. Violations are recorded as
+/// they are reported by `--format json`, and matched on the fields identifying them, so that a baseline still matches
+/// when details such as a symbol's type or demangled name are added or change.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    files: BTreeMap<String, Vec<Value>>,
+}
+
+impl Baseline {
+    pub fn read(path: &Path) -> anyhow::Result<Baseline> {
+        let data =
+            fs::read(path).with_context(|| format!("Error reading baseline {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Error parsing baseline {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let data =
+            serde_json::to_string_pretty(self).expect("JSON values can always be serialized");
+        fs::write(path, data + "\n")
+            .with_context(|| format!("Error writing baseline {}", path.display()))
+    }
+
+    /// Records a violation of a file.
+    pub fn add(&mut self, file: &Path, violation: Value) {
+        self.files
+            .entry(file.display().to_string())
+            .or_default()
+            .push(violation);
+    }
+
+    /// Checks whether a violation of a file was known when the baseline was written.
+    pub fn contains(&self, file: &Path, violation: &Value) -> bool {
+        let key = violation_key(violation);
+        self.files
+            .get(&file.display().to_string())
+            .is_some_and(|violations| violations.iter().any(|known| violation_key(known) == key))
+    }
+
+    /// Checks whether the same violations are recorded for two files, so that files with the same contents get the same
+    /// result.
+    pub fn has_same_entries(&self, file: &Path, other: &Path) -> bool {
+        let entries = |file: &Path| {
+            self.files
+                .get(&file.display().to_string())
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        };
+        let keys = |file: &Path| {
+            entries(file)
+                .iter()
+                .map(violation_key)
+                .collect::<BTreeSet<_>>()
+        };
+        keys(file) == keys(other)
+    }
+
+    /// Returns the number of violations recorded, and the number of files they are in.
+    pub fn count(&self) -> (usize, usize) {
+        (self.files.values().map(Vec::len).sum(), self.files.len())
+    }
+}
+
+/// The fields of violations which identify them: the kind, the symbol's name and version and the library it is needed
+/// from, or for other kinds, what they are about.
+const KEY_FIELDS: [&str; 11] = [
+    "kind",
+    "name",
+    "platform",
+    "version",
+    "file",
+    "library",
+    "symbol",
+    "feature",
+    "interpreter",
+    "node",
+    "namespace",
+];
+
+/// The values of a violation's identifying fields, as strings, which are `None` for fields it doesn't have.
+pub fn violation_key(violation: &Value) -> Vec<Option<String>> {
+    KEY_FIELDS
+        .iter()
+        .map(|field| match violation.get(field) {
+            None | Some(Value::Null) => None,
+            Some(Value::String(value)) => Some(value.clone()),
+            Some(value) => Some(value.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_violations() {
+        let dir = TempDir::new("baseline");
+        let path = dir.path.join("baseline.json");
+        let stat = json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33"});
+        let mut baseline = Baseline::default();
+        baseline.add(Path::new("bin/app"), stat.clone());
+        baseline.add(Path::new("bin/app"), json!({"kind": "text_relocations"}));
+        baseline.write(&path).unwrap();
+
+        let baseline = Baseline::read(&path).unwrap();
+        assert_eq!(baseline.count(), (2, 1));
+        assert!(baseline.contains(Path::new("bin/app"), &stat));
+        assert!(!baseline.contains(Path::new("bin/other"), &stat));
+        assert!(!baseline.contains(
+            Path::new("bin/app"),
+            &json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.34"})
+        ));
+        // Details which don't identify the violation don't affect matching
+        assert!(baseline.contains(
+            Path::new("bin/app"),
+            &json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33", "type": "func", "demangled": "stat"})
+        ));
+        assert!(!baseline.contains(
+            Path::new("bin/app"),
+            &json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33", "file": "libc.so.6"})
+        ));
+        assert!(baseline.has_same_entries(Path::new("bin/other"), Path::new("lib/other")));
+        assert!(!baseline.has_same_entries(Path::new("bin/app"), Path::new("bin/other")));
+    }
+}
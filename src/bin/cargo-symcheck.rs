@@ -0,0 +1,3 @@
+fn main() {
+    symbol_version_check::cargo::run();
+}
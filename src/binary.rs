@@ -0,0 +1,58 @@
+use crate::elf::UnreadableVersion;
+use crate::macho::DeploymentTarget;
+use crate::symbols::SymbolVersion;
+use crate::version::NamespacedVersion;
+use crate::{elf, macho, pe};
+use anyhow::anyhow;
+
+/// The versions required by a binary, along with any symbols whose versions couldn't be read.
+#[derive(Default)]
+pub struct RequiredVersions {
+    pub symbols: Vec<SymbolVersion>,
+    /// The minimum OS versions of a Mach-O file
+    pub deployment_targets: Vec<DeploymentTarget>,
+    pub unreadable: Vec<UnreadableVersion>,
+}
+
+impl RequiredVersions {
+    /// Returns the versions of the symbols and deployment targets.
+    pub fn versions(&self) -> impl Iterator<Item = &NamespacedVersion> {
+        self.symbols
+            .iter()
+            .map(|symbol| &symbol.version)
+            .chain(self.deployment_targets.iter().map(|target| &target.version))
+    }
+}
+
+/// Checks whether the data is a binary in a format which can be checked, i.e. an ELF, Mach-O or PE file.
+pub fn is_binary(data: &[u8]) -> bool {
+    elf::is_elf(data) || macho::is_macho(data) || pe::is_pe(data)
+}
+
+/// Returns the versions required by a binary. These are the versions of an ELF file's undefined dynamic symbols,
+/// including hidden references, the deployment targets of a Mach-O file, or the API sets imported by a PE file. ELF
+/// symbols whose versions can't be read are returned separately, but it is an error if the version table can't be
+/// read at all.
+pub fn get_required_versions(data: &[u8]) -> anyhow::Result<RequiredVersions> {
+    if macho::is_macho(data) {
+        Ok(RequiredVersions {
+            deployment_targets: macho::get_deployment_targets(data)?,
+            ..RequiredVersions::default()
+        })
+    } else if pe::is_pe(data) {
+        Ok(RequiredVersions {
+            symbols: pe::get_api_set_imports(data)?,
+            ..RequiredVersions::default()
+        })
+    } else {
+        let references = elf::get_version_references(data)?;
+        if let Some(table) = references.unreadable.iter().find(|u| u.symbol.is_none()) {
+            return Err(anyhow!("{}", table));
+        }
+        Ok(RequiredVersions {
+            symbols: [references.symbols, references.hidden_symbols].concat(),
+            deployment_targets: Vec::new(),
+            unreadable: references.unreadable,
+        })
+    }
+}
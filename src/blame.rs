@@ -0,0 +1,500 @@
+use anyhow::{Context, anyhow};
+use object::read::elf::{ElfFile, ElfFile32, ElfFile64, FileHeader};
+use object::{
+    Architecture, Endianness, FileKind, Object, ObjectSection, ObjectSymbol, ObjectSymbolTable,
+    RelocationTarget, SectionKind, SymbolKind,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// The places in a file's source which reference each symbol, by symbol name.
+pub type References = BTreeMap<String, BTreeSet<String>>;
+
+/// Finds where in a file's source the given undefined symbols are referenced from. Relocatable objects are blamed from
+/// their relocations, giving the source file and the function referencing the symbol, e.g. `random.c (fill_buffer)`.
+/// Other files are blamed from their debug info, if any, giving the compilation units which declare the symbol, as
+/// GCC does for the functions each compilation unit calls.
+pub fn find_references(data: &[u8], symbols: &BTreeSet<&str>) -> anyhow::Result<References> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => find_elf_references(&ElfFile32::parse(data)?, symbols),
+        FileKind::Elf64 => find_elf_references(&ElfFile64::parse(data)?, symbols),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn find_elf_references<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    symbols: &BTreeSet<&str>,
+) -> anyhow::Result<References> {
+    if elf.elf_header().e_type(elf.endianness()) == object::elf::ET_REL {
+        find_relocation_references(elf, symbols)
+    } else {
+        find_debug_info_references(elf, symbols).with_context(|| "Error reading debug info")
+    }
+}
+
+fn find_relocation_references<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    symbols: &BTreeSet<&str>,
+) -> anyhow::Result<References> {
+    let source_file = elf
+        .symbols()
+        .find(|symbol| symbol.kind() == SymbolKind::File)
+        .and_then(|symbol| symbol.name().ok());
+    let mut references = References::new();
+    for section in elf.sections() {
+        if section.name().is_ok_and(|name| name.starts_with(".debug")) {
+            continue;
+        }
+        for (offset, relocation) in section.relocations() {
+            let RelocationTarget::Symbol(index) = relocation.target() else {
+                continue;
+            };
+            let symbol = elf
+                .symbol_by_index(index)
+                .with_context(|| "Error reading relocation symbol")?;
+            let name = symbol.name().with_context(|| "Error reading symbol name")?;
+            if !symbol.is_undefined() || !symbols.contains(name) {
+                continue;
+            }
+            let function = elf.symbols().find(|function| {
+                function.kind() == SymbolKind::Text
+                    && function.section_index() == Some(section.index())
+                    && (function.address()..function.address() + function.size()).contains(&offset)
+            });
+            let function = function.and_then(|function| function.name().ok());
+            let source = match (source_file, function) {
+                (Some(file), Some(function)) => format!("{} ({})", file, function),
+                (Some(file), None) => file.to_string(),
+                (None, Some(function)) => function.to_string(),
+                (None, None) => continue,
+            };
+            references
+                .entry(name.to_string())
+                .or_default()
+                .insert(source);
+        }
+    }
+    Ok(references)
+}
+
+fn find_debug_info_references<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    symbols: &BTreeSet<&str>,
+) -> anyhow::Result<References> {
+    let endian = if elf.is_little_endian() {
+        gimli::RunTimeEndian::Little
+    } else {
+        gimli::RunTimeEndian::Big
+    };
+    let sections = gimli::DwarfSections::load(|id| -> anyhow::Result<Cow<[u8]>> {
+        Ok(match elf.section_by_name(id.name()) {
+            Some(section) => section.uncompressed_data()?,
+            None => Cow::Borrowed(&[]),
+        })
+    })?;
+    let dwarf = sections.borrow(|section| gimli::EndianSlice::new(section, endian));
+
+    let mut references = References::new();
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let unit = dwarf.unit(header)?;
+        let Some(unit_name) = unit.name.map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != gimli::DW_TAG_subprogram
+                || entry.attr_value(gimli::DW_AT_declaration)?
+                    != Some(gimli::AttributeValue::Flag(true))
+            {
+                continue;
+            }
+            let Some(name) = entry
+                .attr_value(gimli::DW_AT_linkage_name)?
+                .or(entry.attr_value(gimli::DW_AT_name)?)
+            else {
+                continue;
+            };
+            let name = dwarf.attr_string(&unit, name)?.to_string_lossy();
+            if symbols.contains(&*name) {
+                references
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(unit_name.clone());
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// The symbols called by each Rust crate, by crate name.
+pub type CrateReferences = BTreeMap<String, BTreeSet<String>>;
+
+/// Finds the Rust crates whose functions call the given imported symbols, from the demangled names of the functions
+/// containing the call sites. Only x86-64 files are supported: call sites are found by scanning their code for direct
+/// calls and jumps to the symbols' PLT entries, and indirect calls and jumps through their GOT entries, and need the
+/// file's symbol table to find the functions containing them.
+pub fn find_rust_crates(data: &[u8], symbols: &BTreeSet<&str>) -> anyhow::Result<CrateReferences> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => find_elf_rust_crates(&ElfFile32::parse(data)?, symbols),
+        FileKind::Elf64 => find_elf_rust_crates(&ElfFile64::parse(data)?, symbols),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn find_elf_rust_crates<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    symbols: &BTreeSet<&str>,
+) -> anyhow::Result<CrateReferences> {
+    const PLT_ENTRY_SIZE: u64 = 16;
+    let mut crates = CrateReferences::new();
+    let (Some(relocations), Some(dynamic_symbols)) =
+        (elf.dynamic_relocations(), elf.dynamic_symbol_table())
+    else {
+        return Ok(crates);
+    };
+    if elf.architecture() != Architecture::X86_64 {
+        return Ok(crates);
+    }
+
+    // The GOT entries the symbols are loaded from, and the PLT entries which jump through them
+    let mut got_entries: HashMap<u64, &str> = HashMap::new();
+    for (offset, relocation) in relocations {
+        if let RelocationTarget::Symbol(index) = relocation.target()
+            && let Ok(symbol) = dynamic_symbols.symbol_by_index(index)
+            && let Ok(name) = symbol.name()
+            && let Some(name) = symbols.get(name)
+        {
+            got_entries.insert(offset, name);
+        }
+    }
+    let mut plt_entries: HashMap<u64, &str> = HashMap::new();
+    let is_plt = |name: &str| name == ".plt" || name.starts_with(".plt.");
+    for section in elf.sections() {
+        if !section.name().is_ok_and(is_plt) {
+            continue;
+        }
+        let code = section.data()?;
+        for (offset, target) in indirect_jumps(code, section.address()) {
+            if let Some(name) = got_entries.get(&target) {
+                let entry = section.address() + offset / PLT_ENTRY_SIZE * PLT_ENTRY_SIZE;
+                plt_entries.insert(entry, name);
+            }
+        }
+    }
+
+    let mut functions: Vec<(u64, u64, &str)> = elf
+        .symbols()
+        .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.size() > 0)
+        .filter_map(|symbol| Some((symbol.address(), symbol.size(), symbol.name().ok()?)))
+        .collect();
+    functions.sort();
+    let containing_function = |address: u64| {
+        let index = functions.partition_point(|(start, _, _)| *start <= address);
+        let (start, size, name) = functions.get(index.checked_sub(1)?)?;
+        (address < start + size).then_some(*name)
+    };
+
+    for section in elf.sections() {
+        if section.kind() != SectionKind::Text || section.name().is_ok_and(is_plt) {
+            continue;
+        }
+        let code = section.data()?;
+        let calls = direct_calls(code, section.address())
+            .filter_map(|(offset, target)| Some((offset, *plt_entries.get(&target)?)))
+            .chain(
+                indirect_jumps(code, section.address())
+                    .filter_map(|(offset, target)| Some((offset, *got_entries.get(&target)?))),
+            );
+        for (offset, name) in calls {
+            if let Some(function) = containing_function(section.address() + offset)
+                && let Some(krate) = rust_crate(function)
+            {
+                crates.entry(krate).or_default().insert(name.to_string());
+            }
+        }
+    }
+    Ok(crates)
+}
+
+/// Finds the x86-64 direct calls and jumps with 32-bit displacements (`call rel32` and `jmp rel32`) which may be in
+/// code at an address, giving the offset of each and its target. As the code isn't disassembled, these may include
+/// bytes which happen to look like instructions.
+fn direct_calls(code: &[u8], address: u64) -> impl Iterator<Item = (u64, u64)> + '_ {
+    code.windows(5)
+        .enumerate()
+        .filter(|(_, bytes)| matches!(bytes[0], 0xe8 | 0xe9))
+        .map(move |(offset, bytes)| {
+            let displacement = i32::from_le_bytes(bytes[1..5].try_into().unwrap());
+            let next = address + offset as u64 + 5;
+            (offset as u64, next.wrapping_add_signed(displacement.into()))
+        })
+}
+
+/// Finds the x86-64 indirect calls and jumps through RIP-relative addresses (`call *disp32(%rip)` and
+/// `jmp *disp32(%rip)`) which may be in code at an address, giving the offset of each and the address it loads its
+/// target from.
+fn indirect_jumps(code: &[u8], address: u64) -> impl Iterator<Item = (u64, u64)> + '_ {
+    code.windows(6)
+        .enumerate()
+        .filter(|(_, bytes)| bytes[0] == 0xff && matches!(bytes[1], 0x15 | 0x25))
+        .map(move |(offset, bytes)| {
+            let displacement = i32::from_le_bytes(bytes[2..6].try_into().unwrap());
+            let next = address + offset as u64 + 6;
+            (offset as u64, next.wrapping_add_signed(displacement.into()))
+        })
+}
+
+/// Returns the crate a Rust function belongs to, from the first component of its demangled path, e.g. `ring` for
+/// `ring::rand::fill` or `reqwest` for `<reqwest::Client as core::fmt::Debug>::fmt`.
+pub fn rust_crate(function: &str) -> Option<String> {
+    let demangled = format!("{:#}", rustc_demangle::try_demangle(function).ok()?);
+    let path = demangled.trim_start_matches('<');
+    let end = path
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(path.len());
+    (end > 0).then(|| path[..end].to_string())
+}
+
+/// Reads the cross reference table of a GNU linker map file, as written with `-Wl,-Map=FILE,--cref`, giving the input
+/// files which reference each symbol, such as archive members (e.g. `libfoo.a(random.o)`).
+pub fn read_link_map(path: &Path) -> anyhow::Result<References> {
+    let map = fs::read_to_string(path)
+        .with_context(|| format!("Error reading link map {}", path.display()))?;
+    if !map.contains(CROSS_REFERENCE_TABLE) {
+        return Err(anyhow!(
+            "No cross reference table in link map {}, link with -Wl,--cref",
+            path.display()
+        ));
+    }
+    Ok(parse_cross_references(&map))
+}
+
+const CROSS_REFERENCE_TABLE: &str = "Cross Reference Table";
+
+/// Parses a cross reference table, in which each symbol is followed by the file defining it, then the files
+/// referencing it, each on its own line. Versioned names, such as `getrandom@@GLIBC_2.25`, are merged with the
+/// unversioned name.
+fn parse_cross_references(map: &str) -> References {
+    let mut references = References::new();
+    let Some((_, table)) = map.split_once(CROSS_REFERENCE_TABLE) else {
+        return references;
+    };
+    let mut symbol: Option<&str> = None;
+    let mut defined = false;
+    for line in table
+        .lines()
+        .skip_while(|line| !line.starts_with("Symbol"))
+        .skip(1)
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let file = if line.starts_with(char::is_whitespace) {
+            Some(line.trim())
+        } else {
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap_or_default();
+            symbol = Some(name.split('@').next().unwrap_or(name));
+            defined = false;
+            fields.next()
+        };
+        if let (Some(symbol), Some(file)) = (symbol, file) {
+            // The first file given is the one defining the symbol
+            if defined {
+                references
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .insert(file.to_string());
+            }
+            defined = true;
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a little endian 64-bit object file compiled from `random.c`, whose function `fill_buffer` calls
+    /// `getrandom`.
+    fn build_object_with_relocation() -> Vec<u8> {
+        let section_names = b"\0.text\0.rela.text\0.symtab\0.strtab\0.shstrtab\0";
+        let strings = b"\0random.c\0fill_buffer\0getrandom\0";
+        let symbol = |name: u32, info: u8, section: u16, size: u64| {
+            let mut symbol = Vec::new();
+            symbol.extend(name.to_le_bytes());
+            symbol.push(info);
+            symbol.push(0);
+            symbol.extend(section.to_le_bytes());
+            symbol.extend(0u64.to_le_bytes());
+            symbol.extend(size.to_le_bytes());
+            symbol
+        };
+        let mut symbols = symbol(0, 0, 0, 0);
+        symbols.extend(symbol(1, object::elf::STT_FILE, object::elf::SHN_ABS, 0));
+        symbols.extend(symbol(
+            10,
+            (object::elf::STB_GLOBAL << 4) | object::elf::STT_FUNC,
+            1,
+            16,
+        ));
+        symbols.extend(symbol(22, object::elf::STB_GLOBAL << 4, 0, 0));
+        let mut relocation = Vec::new();
+        relocation.extend(4u64.to_le_bytes());
+        relocation.extend(((3u64 << 32) | u64::from(object::elf::R_X86_64_PLT32)).to_le_bytes());
+        relocation.extend((-4i64).to_le_bytes());
+
+        let text_offset = 64;
+        let relocation_offset = text_offset + 16;
+        let symbols_offset = relocation_offset + relocation.len();
+        let strings_offset = symbols_offset + symbols.len();
+        let names_offset = strings_offset + strings.len();
+        let headers_offset = (names_offset + section_names.len()).next_multiple_of(8);
+        let mut data = crate::elf::tests::build_elf(object::elf::ET_REL, &[0u8; 16]);
+        data[40..48].copy_from_slice(&(headers_offset as u64).to_le_bytes());
+        data[60..62].copy_from_slice(&6u16.to_le_bytes());
+        data[62..64].copy_from_slice(&5u16.to_le_bytes());
+        data.extend(&relocation);
+        data.extend(&symbols);
+        data.extend(strings);
+        data.extend(section_names);
+        data.resize(headers_offset, 0);
+        let sections = [
+            (1, object::elf::SHT_PROGBITS, text_offset, 16, 0, 0, 0),
+            (
+                7,
+                object::elf::SHT_RELA,
+                relocation_offset,
+                relocation.len(),
+                3,
+                1,
+                24,
+            ),
+            (
+                18,
+                object::elf::SHT_SYMTAB,
+                symbols_offset,
+                symbols.len(),
+                4,
+                2,
+                24,
+            ),
+            (
+                26,
+                object::elf::SHT_STRTAB,
+                strings_offset,
+                strings.len(),
+                0,
+                0,
+                0,
+            ),
+            (
+                34,
+                object::elf::SHT_STRTAB,
+                names_offset,
+                section_names.len(),
+                0,
+                0,
+                0,
+            ),
+        ];
+        data.extend([0u8; 64]);
+        for (name, sh_type, offset, size, link, info, entsize) in sections {
+            data.extend((name as u32).to_le_bytes());
+            data.extend(sh_type.to_le_bytes());
+            data.extend([0u8; 16]);
+            data.extend((offset as u64).to_le_bytes());
+            data.extend((size as u64).to_le_bytes());
+            data.extend((link as u32).to_le_bytes());
+            data.extend((info as u32).to_le_bytes());
+            data.extend(8u64.to_le_bytes());
+            data.extend((entsize as u64).to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn finds_references_from_relocations() {
+        let data = build_object_with_relocation();
+        let references = find_references(&data, &BTreeSet::from(["getrandom"])).unwrap();
+        assert_eq!(
+            references,
+            References::from([(
+                "getrandom".to_string(),
+                BTreeSet::from(["random.c (fill_buffer)".to_string()])
+            )])
+        );
+        assert!(
+            find_references(&data, &BTreeSet::from(["stat"]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parses_link_map_cross_references() {
+        let map = "\
+Archive member included to satisfy reference by file (symbol)
+
+./libx.a(random.o)            /tmp/ccl19jB5.o (fill_buffer)
+
+Cross Reference Table
+
+Symbol                                            File
+fill_buffer                                       ./libx.a(random.o)
+                                                  /tmp/ccl19jB5.o
+getrandom                                         /lib/x86_64-linux-gnu/libc.so.6
+                                                  ./libx.a(random.o)
+getrandom@@GLIBC_2.25                             /lib/x86_64-linux-gnu/libc.so.6
+a_very_long_symbol_name_which_doesnt_fit_in_the_column
+                                                  ./libx.a(long.o)
+                                                  ./libx.a(other.o)
+";
+        let references = parse_cross_references(map);
+        assert_eq!(
+            references["getrandom"],
+            BTreeSet::from(["./libx.a(random.o)".to_string()])
+        );
+        assert_eq!(
+            references["a_very_long_symbol_name_which_doesnt_fit_in_the_column"],
+            BTreeSet::from(["./libx.a(other.o)".to_string()])
+        );
+        assert_eq!(references.len(), 3);
+    }
+
+    #[test]
+    fn finds_rust_crates() {
+        assert_eq!(
+            rust_crate("_ZN4ring4rand7sysrand4fill17h0123456789abcdefE").as_deref(),
+            Some("ring")
+        );
+        assert_eq!(
+            rust_crate(
+                "_ZN52_$LT$reqwest..Client$u20$as$u20$core..fmt..Debug$GT$3fmt17h0123456789abcdefE"
+            )
+            .as_deref(),
+            Some("reqwest")
+        );
+        assert_eq!(rust_crate("fill_buffer"), None);
+    }
+
+    #[test]
+    fn finds_rust_crates_calling_symbols() {
+        // The test executable is linked against glibc 2.34 by the standard library
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let crates = find_rust_crates(&data, &BTreeSet::from(["pthread_create"])).unwrap();
+        assert!(crates["std"].contains("pthread_create"));
+    }
+
+    #[test]
+    fn finds_no_references_without_debug_info() {
+        let data = crate::elf::tests::build_elf_with_interpreter("/lib64/ld-linux-x86-64.so.2");
+        let symbols = BTreeSet::from(["getrandom"]);
+        assert!(find_references(&data, &symbols).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,388 @@
+//! The `cargo symcheck` subcommand, which checks the binaries and cdylibs cargo has built, with settings from the
+//! `symbol-version-check` tables of the packages' and workspace's metadata.
+
+use crate::cli;
+use crate::config::Config;
+use crate::presets::UserPresets;
+use anyhow::{Context, anyhow};
+use clap::{Args, Parser};
+use serde::Deserialize;
+use serde_json::Value;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The key of the metadata tables the settings are read from, e.g. `[package.metadata.symbol-version-check]`.
+const METADATA_KEY: &str = "symbol-version-check";
+
+#[derive(Parser)]
+#[command(bin_name = "cargo")]
+enum CargoArgs {
+    /// Check the versions of the symbols the binaries and cdylibs cargo has built need
+    Symcheck(SymcheckArgs),
+}
+
+#[derive(Args)]
+struct SymcheckArgs {
+    #[arg(
+        long = "manifest-path",
+        value_name = "PATH",
+        help = "Path to Cargo.toml"
+    )]
+    manifest_path: Option<PathBuf>,
+
+    #[arg(
+        short = 'p',
+        long = "package",
+        value_name = "SPEC",
+        help = "Package whose artifacts to check (default: all workspace members)"
+    )]
+    packages: Vec<String>,
+
+    #[arg(
+        long = "release",
+        conflicts_with = "profile",
+        help = "Check artifacts built with the release profile"
+    )]
+    release: bool,
+
+    #[arg(
+        long = "profile",
+        value_name = "PROFILE-NAME",
+        help = "Check artifacts built with the given profile"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long = "target",
+        value_name = "TRIPLE",
+        help = "Check artifacts built for the given target triple"
+    )]
+    target: Option<String>,
+
+    #[arg(
+        last = true,
+        value_name = "ARGS",
+        help = "Further arguments for symbol-version-check (e.g. -- -m GLIBC_2.17 --format json)"
+    )]
+    args: Vec<OsString>,
+}
+
+/// The parts of `cargo metadata`'s output used to find the artifacts.
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    target_directory: PathBuf,
+    /// The workspace's metadata table
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    targets: Vec<Target>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+/// A built binary or cdylib to be checked, and the settings it is checked with.
+#[derive(Debug, PartialEq)]
+struct Artifact {
+    package: String,
+    path: PathBuf,
+    config: Config,
+}
+
+/// Runs `cargo symcheck`, exiting with the result.
+pub fn run() {
+    let CargoArgs::Symcheck(args) = CargoArgs::parse();
+    let check_args = match check_args(&args) {
+        Ok(check_args) => check_args,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(2);
+        }
+    };
+    cli::run_from(check_args)
+}
+
+/// Returns the arguments to run symbol-version-check with to check the artifacts which have been built.
+fn check_args(args: &SymcheckArgs) -> anyhow::Result<Vec<OsString>> {
+    let metadata = read_metadata(args.manifest_path.as_deref())?;
+    let profile_dir = match (&args.profile, args.release) {
+        (Some(profile), _) => profile_dir(profile),
+        (None, true) => "release",
+        (None, false) => "debug",
+    };
+    let mut out_dir = metadata.target_directory.clone();
+    if let Some(target) = &args.target {
+        out_dir.push(target);
+    }
+    out_dir.push(profile_dir);
+
+    let artifacts = find_artifacts(&metadata, &args.packages, &out_dir, args.target.as_deref())?;
+    let Some(first) = artifacts.first() else {
+        return Err(anyhow!(
+            "No built binaries or cdylibs found in {}; build them first",
+            out_dir.display()
+        ));
+    };
+    if let Some(other) = artifacts.iter().find(|a| a.config != first.config) {
+        return Err(anyhow!(
+            "Packages {} and {} have different {} settings; check them separately with -p",
+            first.package,
+            other.package,
+            METADATA_KEY
+        ));
+    }
+
+    let mut check_args = vec![OsString::from("symbol-version-check")];
+    check_args.extend(config_args(&first.config));
+    check_args.extend(args.args.iter().cloned());
+    check_args.extend(artifacts.iter().map(|a| a.path.clone().into_os_string()));
+    Ok(check_args)
+}
+
+fn read_metadata(manifest_path: Option<&Path>) -> anyhow::Result<Metadata> {
+    let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.args(["metadata", "--format-version", "1", "--no-deps"]);
+    if let Some(manifest_path) = manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+    let output = command
+        .output()
+        .with_context(|| "Error running cargo metadata")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice(&output.stdout).with_context(|| "Error parsing cargo metadata")
+}
+
+/// Returns the directory a profile's artifacts are built in.
+fn profile_dir(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    }
+}
+
+/// Finds the binaries and cdylibs of the given packages, or of all workspace members, which have been built in
+/// `out_dir`, with each package's settings.
+fn find_artifacts(
+    metadata: &Metadata,
+    packages: &[String],
+    out_dir: &Path,
+    target: Option<&str>,
+) -> anyhow::Result<Vec<Artifact>> {
+    if let Some(missing) = packages
+        .iter()
+        .find(|name| !metadata.packages.iter().any(|p| &&p.name == name))
+    {
+        return Err(anyhow!("Package {} isn't in the workspace", missing));
+    }
+    let workspace_config = read_config(&metadata.metadata)
+        .with_context(|| format!("Error reading workspace.metadata.{}", METADATA_KEY))?;
+
+    let mut artifacts = Vec::new();
+    for package in &metadata.packages {
+        let selected = if packages.is_empty() {
+            metadata.workspace_members.contains(&package.id)
+        } else {
+            packages.contains(&package.name)
+        };
+        if !selected {
+            continue;
+        }
+        let context = || {
+            format!(
+                "Error reading package.metadata.{} of {}",
+                METADATA_KEY, package.name
+            )
+        };
+        let mut config = Config::default();
+        config
+            .merge(&workspace_config, &UserPresets::default())
+            .with_context(|| format!("Error reading workspace.metadata.{}", METADATA_KEY))?;
+        config
+            .merge(
+                &read_config(&package.metadata).with_context(context)?,
+                &UserPresets::default(),
+            )
+            .with_context(context)?;
+        for target_info in &package.targets {
+            for kind in &target_info.kind {
+                let Some(file_name) = artifact_file_name(kind, &target_info.name, target) else {
+                    continue;
+                };
+                let path = out_dir.join(file_name);
+                if path.is_file() {
+                    artifacts.push(Artifact {
+                        package: package.name.clone(),
+                        path,
+                        config: config.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(artifacts)
+}
+
+/// Reads the settings from a metadata table, which are given as in config files.
+fn read_config(metadata: &Value) -> anyhow::Result<Config> {
+    match metadata.get(METADATA_KEY) {
+        Some(settings) => Ok(Config::deserialize(settings)?),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Returns the file name cargo gives a binary or cdylib target built for the given target triple, or the host.
+fn artifact_file_name(kind: &str, name: &str, target: Option<&str>) -> Option<String> {
+    let os = match target {
+        Some(target) if target.contains("-windows") => "windows",
+        Some(target) if target.contains("-apple-") => "macos",
+        Some(_) => "linux",
+        None => std::env::consts::OS,
+    };
+    let lib_name = name.replace('-', "_");
+    match (kind, os) {
+        ("bin", "windows") => Some(format!("{}.exe", name)),
+        ("bin", _) => Some(name.to_string()),
+        ("cdylib", "windows") => Some(format!("{}.dll", lib_name)),
+        ("cdylib", "macos") => Some(format!("lib{}.dylib", lib_name)),
+        ("cdylib", _) => Some(format!("lib{}.so", lib_name)),
+        _ => None,
+    }
+}
+
+/// Returns the symbol-version-check arguments giving the settings. Presets have already been resolved to maximum
+/// versions.
+fn config_args(config: &Config) -> Vec<OsString> {
+    let mut args = Vec::new();
+    if !config.max_version.is_empty() {
+        args.push("-m".into());
+        args.push(config.max_version.join(",").into());
+    }
+    if config.allow_private == Some(true) {
+        args.push("--allow-private".into());
+    }
+    if let Some(allowed_libs) = &config.allowed_libs {
+        args.push("--allowed-libs".into());
+        args.push(allowed_libs.join(",").into());
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use serde_json::json;
+
+    fn metadata(target_directory: &Path) -> Metadata {
+        serde_json::from_value(json!({
+            "packages": [
+                {
+                    "id": "app 0.1.0",
+                    "name": "app",
+                    "targets": [
+                        {"name": "app", "kind": ["bin"]},
+                        {"name": "app-tool", "kind": ["bin"]},
+                        {"name": "app", "kind": ["lib"]}
+                    ],
+                    "metadata": {"symbol-version-check": {"max-version": ["GLIBC_2.28"], "allow-private": true}}
+                },
+                {
+                    "id": "plugin 0.1.0",
+                    "name": "plugin",
+                    "targets": [{"name": "my-plugin", "kind": ["cdylib", "rlib"]}],
+                    "metadata": null
+                }
+            ],
+            "workspace_members": ["app 0.1.0", "plugin 0.1.0"],
+            "target_directory": target_directory,
+            "metadata": {"symbol-version-check": {"max-version": ["GLIBC_2.17", "GLIBCXX_3.4.19"]}}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn names_artifacts_for_targets() {
+        let name = |kind, target| artifact_file_name(kind, "my-plugin", Some(target));
+        assert_eq!(
+            name("cdylib", "x86_64-unknown-linux-gnu").as_deref(),
+            Some("libmy_plugin.so")
+        );
+        assert_eq!(
+            name("cdylib", "aarch64-apple-darwin").as_deref(),
+            Some("libmy_plugin.dylib")
+        );
+        assert_eq!(
+            name("cdylib", "x86_64-pc-windows-msvc").as_deref(),
+            Some("my_plugin.dll")
+        );
+        assert_eq!(
+            name("bin", "x86_64-pc-windows-gnu").as_deref(),
+            Some("my-plugin.exe")
+        );
+        assert_eq!(
+            name("bin", "x86_64-unknown-linux-musl").as_deref(),
+            Some("my-plugin")
+        );
+        assert_eq!(name("rlib", "x86_64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn finds_built_artifacts_with_package_settings() {
+        let dir = TempDir::new("cargo-artifacts");
+        dir.write("target/release/app", b"");
+        dir.write("target/release/libmy_plugin.so", b"");
+        let metadata = metadata(&dir.path.join("target"));
+        let out_dir = dir.path.join("target/release");
+        let target = Some("x86_64-unknown-linux-gnu");
+
+        let artifacts = find_artifacts(&metadata, &[], &out_dir, target).unwrap();
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0].path, out_dir.join("app"));
+        assert_eq!(
+            artifacts[0].config.max_version,
+            vec!["GLIBCXX_3.4.19", "GLIBC_2.28"]
+        );
+        assert_eq!(artifacts[0].config.allow_private, Some(true));
+        assert_eq!(artifacts[1].path, out_dir.join("libmy_plugin.so"));
+        assert_eq!(
+            artifacts[1].config.max_version,
+            vec!["GLIBC_2.17", "GLIBCXX_3.4.19"]
+        );
+        assert_eq!(
+            config_args(&artifacts[0].config),
+            vec!["-m", "GLIBCXX_3.4.19,GLIBC_2.28", "--allow-private"]
+        );
+
+        let artifacts =
+            find_artifacts(&metadata, &["plugin".to_string()], &out_dir, target).unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].package, "plugin");
+        assert!(find_artifacts(&metadata, &["other".to_string()], &out_dir, target).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        let metadata = json!({"symbol-version-check": {"max-versions": ["GLIBC_2.17"]}});
+        assert!(read_config(&metadata).is_err());
+        assert_eq!(read_config(&Value::Null).unwrap(), Config::default());
+    }
+}
@@ -0,0 +1,5015 @@
+use crate::archive::ArchiveKind;
+use crate::baseline::Baseline;
+use crate::blame::References;
+use crate::compatibility::Compatibility;
+use crate::config::{Config, PolicyFinder};
+use crate::dedup::ContentIndex;
+use crate::diff::RequirementChange;
+use crate::elf::UnsupportedKind;
+use crate::exceptions::{ExceptionEntry, Exceptions};
+use crate::exports::ExportChange;
+use crate::filter::{Arch, ElfFilter, ElfType, SkipReason};
+use crate::hardening::HardeningFeature;
+use crate::inventory::Inventory;
+use crate::metrics::Metrics;
+use crate::policy::{
+    Details, HiddenVersions, MissingVersionTable, MuslBinaries, NamespaceGap, Policy, Severity,
+    Violation,
+};
+use crate::presets::UserPresets;
+use crate::report::Report;
+use crate::requirements::{UnlistedNamespaces, VersionRequirements};
+use crate::soname::SonameRequirements;
+use crate::symbols::{SymbolBinding, SymbolType, SymbolVersion};
+use crate::sysroot::{LibraryPath, SysrootLibraries};
+#[cfg(feature = "tui")]
+use crate::tui;
+use crate::version::{NamespacedVersion, Version};
+use crate::walk::{WalkError, WalkOptions};
+use crate::watch::Watcher;
+use crate::{FileOutcome, FileReport};
+use crate::{
+    archive, auditable, auditwheel, binary, blame, compatibility, denylist, diff, elf, exceptions,
+    exports, git, glibc_db, inventory, macho, nix, policy, presets, registry, report, requirements,
+    sysroot, walk,
+};
+use anyhow::{Context, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+#[derive(Clone, Debug, ValueEnum)]
+enum ColorChoice {
+    #[value(alias = "yes", alias = "true", alias = "on")]
+    Always,
+    #[value(alias = "no", alias = "false", alias = "off")]
+    Never,
+    Auto,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum DemangleChoice {
+    Cpp,
+    Rust,
+    #[value(alias = "no", alias = "off")]
+    None,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// A CycloneDX bill of materials with a component for each file checked
+    #[value(name = "cyclonedx")]
+    CycloneDx,
+    /// A line for each file which fails, warns or couldn't be checked, for hooks
+    Short,
+}
+
+#[derive(Parser)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+struct CliArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        name = "max_version",
+        short = 'm',
+        value_delimiter = ',',
+        help = "Maximum permitted version(s) (e.g. GLIBC_2.17, or @FILE to read them from a file), unless given by a config file (default: $SYMBOL_VERSION_CHECK_MAX, when no other maximum versions are given, below those of config files)"
+    )]
+    max_version: Vec<String>,
+
+    #[arg(
+        long = "preset",
+        value_name = "PRESET",
+        help = "Built-in maximum versions for a target platform (e.g. freebsd-13 or manylinux2014), which -m overrides for the same namespace"
+    )]
+    preset: Vec<String>,
+
+    #[arg(
+        long = "presets-file",
+        value_name = "FILE",
+        help = "JSON file defining further presets, each mapping namespaces to maximum versions (e.g. {\"myorg-baseline\": {\"GLIBC\": \"2.17\"}})"
+    )]
+    presets_files: Vec<PathBuf>,
+
+    #[arg(
+        long = "list-presets",
+        help = "List the built-in presets, and those defined in presets files, and exit"
+    )]
+    list_presets: bool,
+
+    #[arg(
+        long = "target",
+        value_name = "DISTRO",
+        help = "Maximum versions shipped by a distribution (e.g. rhel8 or ubuntu-20.04), which -m overrides for the same namespace"
+    )]
+    target: Vec<String>,
+
+    #[arg(
+        long = "max-gcc-runtime",
+        value_name = "RELEASE",
+        help = "Maximum versions of libstdc++ and libgcc_s shipped with a GCC release (e.g. 8), which -m overrides for the same namespace"
+    )]
+    max_gcc_runtime: Option<String>,
+
+    #[arg(
+        long = "list-targets",
+        help = "List the distributions which can be targeted and exit"
+    )]
+    list_targets: bool,
+
+    #[arg(
+        long = "from-sysroot",
+        value_name = "DIR",
+        help = "Use the newest versions defined by the glibc, libstdc++ and libgcc_s libraries in a sysroot as maximum versions, which -m overrides for the same namespace"
+    )]
+    from_sysroot: Option<PathBuf>,
+
+    #[arg(
+        long = "from-library",
+        value_name = "FILE",
+        help = "Permit only the versions defined by a library (e.g. an old libc.so.6) in the namespaces it defines"
+    )]
+    from_library: Vec<PathBuf>,
+
+    #[arg(
+        long = "auditwheel-policy",
+        value_name = "FILE",
+        requires = "policy_name",
+        help = "Permit only the symbol versions of a policy in PyPA auditwheel's policy.json"
+    )]
+    auditwheel_policy: Option<PathBuf>,
+
+    #[arg(
+        long = "policy-name",
+        value_name = "NAME",
+        requires = "auditwheel_policy",
+        help = "The auditwheel policy to use, with its architecture (e.g. manylinux_2_17_x86_64)"
+    )]
+    policy_name: Option<String>,
+
+    #[arg(
+        long = "min-version",
+        value_name = "VERSION",
+        help = "Minimum version which files must reference in its namespace (e.g. GLIBC_2.17)"
+    )]
+    min_version: Vec<String>,
+
+    #[arg(
+        long = "default-max",
+        value_name = "VERSION",
+        help = "Maximum version for namespaces with no maximum version given (e.g. 1.1 to fail OPENSSL_3.0.0)"
+    )]
+    default_max: Option<String>,
+
+    #[arg(
+        long = "deny-unlisted",
+        conflicts_with = "default_max",
+        help = "Fail symbols with versions in namespaces with no maximum version given"
+    )]
+    deny_unlisted: bool,
+
+    #[arg(
+        long = "require-all-namespaces",
+        value_enum,
+        value_name = "REPORT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "fail",
+        help = "Fail (or warn about) files referencing versioned namespaces with no maximum version given"
+    )]
+    require_all_namespaces: Option<Severity>,
+
+    #[arg(
+        long = "strict-requirements",
+        help = "Fail if a maximum version given with -m is in a namespace none of the checked files reference"
+    )]
+    strict_requirements: bool,
+
+    #[arg(
+        long = "errors-exit-code",
+        value_name = "CODE",
+        help = "Exit code when there was an error checking files, instead of 1"
+    )]
+    errors_exit_code: Option<u8>,
+
+    #[arg(
+        long = "fail-exit-code",
+        value_name = "CODE",
+        help = "Exit code when files failed the check, instead of 3"
+    )]
+    fail_exit_code: Option<u8>,
+
+    #[arg(
+        long = "treat-errors-as-failures",
+        help = "Exit as if files failed the check when there was an error checking files"
+    )]
+    treat_errors_as_failures: bool,
+
+    #[arg(
+        long = "warn-only",
+        help = "Report failures, but exit as if all files passed the check unless there was an error checking files"
+    )]
+    warn_only: bool,
+
+    #[arg(
+        long = "ignore-symbol",
+        value_name = "REGEX",
+        help = "Regular expression matching the names of symbols not to check against maximum versions (e.g. '^__asan_')"
+    )]
+    ignore_symbol: Vec<String>,
+
+    #[arg(
+        long = "deny-symbol",
+        value_name = "SYMBOL",
+        value_delimiter = ',',
+        help = "Symbols which ELF files may not import, whatever their version (e.g. 'gets,strcpy')"
+    )]
+    deny_symbol: Vec<String>,
+
+    #[arg(
+        long = "deny-dangerous-symbols",
+        help = "Fail ELF files importing libc functions which can't be used safely, such as gets, strcpy and system"
+    )]
+    deny_dangerous_symbols: bool,
+
+    #[arg(
+        long = "check-removed-symbols",
+        value_enum,
+        value_name = "REPORT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "warn",
+        help = "Warn about (or fail) ELF files importing symbols which newer glibc only keeps for compatibility"
+    )]
+    check_removed_symbols: Option<Severity>,
+
+    #[arg(
+        long = "write-baseline",
+        value_name = "FILE",
+        conflicts_with = "baseline",
+        help = "Write the violations found to a baseline file, for use with --baseline, and pass"
+    )]
+    write_baseline: Option<PathBuf>,
+
+    #[arg(
+        long = "metrics-file",
+        value_name = "FILE",
+        conflicts_with_all = ["profile", "serve", "watch"],
+        help = "Write the number of files checked and failed, and the newest version needed in each namespace, to a file as OpenMetrics gauges"
+    )]
+    metrics_file: Option<PathBuf>,
+
+    #[arg(
+        long = "baseline",
+        value_name = "FILE",
+        help = "Only report violations which aren't in a baseline file written by --write-baseline"
+    )]
+    baseline: Option<PathBuf>,
+
+    #[arg(
+        long = "exceptions",
+        value_name = "FILE",
+        help = "JSON file of accepted symbol@VERSION violations, which are reported as warnings until they expire"
+    )]
+    exceptions: Vec<PathBuf>,
+
+    #[arg(
+        long = "allow",
+        value_name = "SYMBOL@VERSION",
+        help = "Symbol with a version newer than permitted to report as a warning (e.g. memfd_create@GLIBC_2.27)"
+    )]
+    allow: Vec<String>,
+
+    #[arg(
+        long = "no-config",
+        help = "Ignore .symbol-version-check.json config files in the checked files' directories and their parents"
+    )]
+    no_config: bool,
+
+    #[arg(
+        required_unless_present_any = ["images", "nix_closures", "staged", "serve", "list_presets", "list_targets"],
+        help = "ELF files, archives or directories to analyze"
+    )]
+    files: Vec<PathBuf>,
+
+    #[arg(
+        long = "staged",
+        help = "Analyze the files staged in the current git repository, skipping those which are not ELF files or archives"
+    )]
+    staged: bool,
+
+    #[arg(
+        long = "watch",
+        conflicts_with_all = ["profile", "write_baseline"],
+        help = "After checking the files, keep watching them and check them again as they are rewritten, until interrupted"
+    )]
+    watch: bool,
+
+    #[arg(
+        long = "tui",
+        conflicts_with_all = ["watch", "profile", "write_baseline", "aggregate", "top"],
+        help = "Explore the results interactively in the terminal, with a list of files and the violations of the file selected"
+    )]
+    tui: bool,
+
+    #[arg(
+        long = "serve",
+        conflicts_with_all = ["files", "watch", "tui", "profile", "write_baseline", "transitive"],
+        help = "Keep running, checking the files of each JSON request read from standard input, or --socket, and writing a JSON response. Config files are read again when they change, and the policies of the 16 most recent maximum versions requests give are kept"
+    )]
+    serve: bool,
+
+    #[arg(
+        long = "socket",
+        value_name = "PATH",
+        requires = "serve",
+        help = "Unix socket to accept requests on with --serve, instead of standard input"
+    )]
+    socket: Option<PathBuf>,
+
+    #[arg(
+        long = "image",
+        value_name = "IMAGE",
+        help = "Container image to pull from a registry and analyze (e.g. ghcr.io/org/app:tag)"
+    )]
+    images: Vec<String>,
+
+    #[arg(
+        long = "nix-closure",
+        value_name = "STORE_PATH",
+        help = "Nix store path whose runtime closure is analyzed"
+    )]
+    nix_closures: Vec<PathBuf>,
+
+    #[arg(
+        long = "color",
+        short = 'c',
+        value_enum,
+        default_value = "auto",
+        help = "When to use colors"
+    )]
+    color: ColorChoice,
+
+    #[arg(
+        long = "demangle",
+        short = 'd',
+        value_enum,
+        default_value = "none",
+        help = "Print demangled symbol names"
+    )]
+    demangle: DemangleChoice,
+
+    #[arg(
+        long = "follow-symlinks",
+        overrides_with = "no_follow_symlinks",
+        help = "Follow symbolic links when searching directories"
+    )]
+    follow_symlinks: bool,
+
+    #[arg(
+        long = "no-follow-symlinks",
+        overrides_with = "follow_symlinks",
+        help = "Skip symbolic links when searching directories (default)"
+    )]
+    no_follow_symlinks: bool,
+
+    #[arg(
+        long = "respect-gitignore",
+        help = "Skip files excluded by .gitignore when searching directories"
+    )]
+    respect_gitignore: bool,
+
+    #[arg(
+        long = "include",
+        value_name = "GLOB",
+        help = "Only check files matching this glob when searching directories (e.g. '*.so*')"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        help = "Skip files and directories matching this glob when searching directories (e.g. '*.debug')"
+    )]
+    exclude: Vec<String>,
+
+    #[arg(
+        long = "allowed-libs",
+        value_name = "GLOB",
+        value_delimiter = ',',
+        help = "Libraries which ELF files may depend on through DT_NEEDED entries, as globs (e.g. 'libc.so.6,libm.so.6,libgcc_s.so.*')"
+    )]
+    allowed_libs: Option<Vec<String>>,
+
+    #[arg(
+        long = "resolve-against",
+        value_name = "DIR",
+        help = "Fail ELF files needing libraries which aren't in a sysroot's library directories, or importing symbols, with their versions, which the libraries there don't export"
+    )]
+    resolve_against: Option<PathBuf>,
+
+    #[arg(
+        long = "transitive",
+        help = "Also check the libraries ELF files need, and the libraries those need, found in --library-path directories or the --resolve-against sysroot"
+    )]
+    transitive: bool,
+
+    #[arg(
+        long = "library-path",
+        value_name = "DIR",
+        requires = "transitive",
+        help = "Directory to search for the libraries ELF files need with --transitive, before the --resolve-against sysroot"
+    )]
+    library_path: Vec<PathBuf>,
+
+    #[arg(
+        long = "expect-interp",
+        value_name = "GLOB",
+        value_delimiter = ',',
+        help = "Program interpreters which ELF executables may use, as paths or globs (e.g. /lib64/ld-linux-x86-64.so.2)"
+    )]
+    expect_interp: Option<Vec<String>>,
+
+    #[arg(
+        long = "max-kernel",
+        value_name = "VERSION",
+        help = "Newest minimum Linux kernel version which ELF files may be built for, as given by their ABI tag (e.g. 3.10)"
+    )]
+    max_kernel: Option<String>,
+
+    #[arg(
+        long = "hardening",
+        help = "Report the hardening features of each ELF file: RELRO, PIE, NX, BIND_NOW and stack protector"
+    )]
+    hardening: bool,
+
+    #[arg(
+        long = "require-hardening",
+        value_name = "FEATURE",
+        value_delimiter = ',',
+        help = "Hardening features which ELF files must have"
+    )]
+    require_hardening: Vec<HardeningFeature>,
+
+    #[arg(
+        long = "deny-textrel",
+        help = "Fail ELF files with text relocations (DT_TEXTREL), which break on hardened systems"
+    )]
+    deny_textrel: bool,
+
+    #[arg(
+        long = "require-build-id",
+        help = "Fail ELF files without a GNU build ID"
+    )]
+    require_build_id: bool,
+
+    #[arg(
+        long = "estimate-static-glibc",
+        help = "Estimate the glibc version statically linked executables were linked against from embedded version strings"
+    )]
+    estimate_static_glibc: bool,
+
+    #[arg(
+        long = "distros",
+        help = "Report the oldest release of each distribution (RHEL, Ubuntu, Debian and Amazon Linux) each ELF file can run on, based on the distribution targets"
+    )]
+    distros: bool,
+
+    #[arg(
+        long = "collapse-shared",
+        conflicts_with = "aggregate",
+        help = "Print violations which several files fail or warn for once, after the results, with the number of files, instead of under each file; verbose output still lists them under each file"
+    )]
+    collapse_shared: bool,
+
+    #[arg(
+        long = "aggregate",
+        help = "Report the violations of all files which fail as one list, each with the number of files failing for it, instead of the results of each file"
+    )]
+    aggregate: bool,
+
+    #[arg(
+        long = "top",
+        value_name = "N",
+        help = "After the results, list the N symbols and N versions which the most files fail for"
+    )]
+    top: Option<usize>,
+
+    #[arg(
+        long = "hints",
+        help = "Print hints for avoiding common symbol versions which files fail for, such as memcpy@GLIBC_2.14"
+    )]
+    hints: bool,
+
+    #[arg(
+        long = "blame",
+        help = "Report where in ELF files' source the symbols they fail for are referenced from, using relocations in object files and debug info in other files, and which Rust crates call them"
+    )]
+    blame: bool,
+
+    #[arg(
+        long = "auditable",
+        help = "Report the crates and versions Rust binaries built with cargo auditable were built from, with the dependency path to each crate calling the symbols they fail for when given with --blame"
+    )]
+    auditable: bool,
+
+    #[arg(
+        long = "link-map",
+        value_name = "FILE",
+        requires = "blame",
+        help = "GNU linker map file written with -Wl,-Map=FILE,--cref, from which symbols are also blamed on the input files referencing them, such as archive members"
+    )]
+    link_map: Vec<PathBuf>,
+
+    #[arg(
+        long = "max-soname",
+        value_name = "SONAME",
+        value_delimiter = ',',
+        help = "Maximum version of a library which ELF files may depend on, as a soname (e.g. libssl.so.1.1)"
+    )]
+    max_soname: Vec<String>,
+
+    #[arg(
+        long = "allowed-dlls",
+        value_name = "GLOB",
+        value_delimiter = ',',
+        help = "DLLs which Windows binaries may import, as case-insensitive globs (e.g. 'kernel32.dll,api-ms-win-crt-*')"
+    )]
+    allowed_dlls: Option<Vec<String>>,
+
+    #[arg(
+        long = "allow-private",
+        help = "Permit symbols with private versions, such as GLIBC_PRIVATE, which are otherwise failures"
+    )]
+    allow_private: bool,
+
+    #[arg(
+        long = "hidden-versions",
+        value_enum,
+        default_value = "include",
+        help = "How to handle symbols explicitly bound to a version (symbol@VERSION), flagged as hidden"
+    )]
+    hidden_versions: HiddenVersions,
+
+    #[arg(
+        long = "coarse",
+        help = "Only check the versions needed from each library, not the symbols needing them, which is faster"
+    )]
+    coarse: bool,
+
+    #[arg(
+        long = "symbol-type",
+        value_name = "TYPE",
+        value_enum,
+        value_delimiter = ',',
+        help = "Only check ELF symbol references of these types"
+    )]
+    symbol_type: Vec<SymbolType>,
+
+    #[arg(
+        long = "symbol-binding",
+        value_name = "BINDING",
+        value_enum,
+        value_delimiter = ',',
+        help = "Only check ELF symbol references with these bindings"
+    )]
+    symbol_binding: Vec<SymbolBinding>,
+
+    #[arg(
+        long = "missing-version-table",
+        value_enum,
+        default_value = "error",
+        help = "How to handle ELF files without a symbol version table, such as files with no versioned dependencies"
+    )]
+    missing_version_table: MissingVersionTable,
+
+    #[arg(
+        long = "glibc-database",
+        help = "Check x86_64 ELF files without a symbol version table, such as object files and static archives, against the glibc versions new links bind the symbols they reference to"
+    )]
+    glibc_database: bool,
+
+    #[arg(
+        long = "musl",
+        value_enum,
+        default_value = "pass",
+        help = "How to handle ELF files linked against musl, whose symbol versions can't be checked"
+    )]
+    musl: MuslBinaries,
+
+    #[arg(
+        long = "best-effort",
+        help = "Warn about symbol versions and version tables which can't be read, checking the rest, instead of failing"
+    )]
+    best_effort: bool,
+
+    #[arg(
+        long = "expect-version-nodes",
+        value_name = "VERSION",
+        value_delimiter = ',',
+        help = "Version nodes which ELF files must define, failing files which define any others (e.g. LIBFOO_1.0,LIBFOO_1.1)"
+    )]
+    expect_version_nodes: Option<Vec<String>>,
+
+    #[arg(
+        long = "skip-non-elf",
+        help = "Skip files which are not ELF files or archives, instead of reporting an error"
+    )]
+    skip_non_elf: bool,
+
+    #[arg(
+        long = "arch",
+        value_enum,
+        value_delimiter = ',',
+        help = "Only check ELF files for these architectures, skipping others"
+    )]
+    arch: Vec<Arch>,
+
+    #[arg(
+        long = "only",
+        value_enum,
+        value_delimiter = ',',
+        help = "Only check ELF files of these types, skipping others"
+    )]
+    only: Vec<ElfType>,
+
+    #[arg(
+        long = "skip-kernel-modules",
+        help = "Skip Linux kernel modules, instead of reporting them as KERNEL_MODULE"
+    )]
+    skip_kernel_modules: bool,
+
+    #[arg(
+        long = "skip-core-dumps",
+        help = "Skip core dumps, instead of reporting them as CORE"
+    )]
+    skip_core_dumps: bool,
+
+    #[arg(
+        long = "skip-debuginfo",
+        help = "Skip separate debug info files, instead of reporting them as DEBUGINFO"
+    )]
+    skip_debuginfo: bool,
+
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        help = "Report the header, build ID and program interpreter of each ELF file, and the type and binding of each symbol"
+    )]
+    verbose: bool,
+
+    #[arg(
+        long = "format",
+        value_enum,
+        default_value = "text",
+        conflicts_with = "inventory",
+        help = "Output format. JSON output always includes the header, build ID and program interpreter of each ELF file, and CycloneDX output the versions and libraries each file needs"
+    )]
+    format: OutputFormat,
+
+    #[arg(
+        long = "inventory",
+        help = "Report the maximum version required in each namespace across all files, instead of checking them"
+    )]
+    inventory: bool,
+
+    #[arg(
+        long = "stats",
+        conflicts_with_all = ["inventory", "format"],
+        help = "Report how many files need each version in each namespace, and how many would pass each version as a maximum, instead of checking them"
+    )]
+    stats: bool,
+
+    #[arg(
+        long = "list",
+        conflicts_with_all = ["inventory", "stats", "format"],
+        help = "Report the maximum version each file references in each namespace, instead of checking them"
+    )]
+    list: bool,
+
+    #[arg(
+        long = "oldest-compatible",
+        conflicts_with_all = ["inventory", "stats", "list", "format"],
+        help = "Report the oldest manylinux preset and distribution target each file is compatible with, instead of checking them"
+    )]
+    oldest_compatible: bool,
+
+    #[arg(
+        long = "profile",
+        value_name = "NAME",
+        value_delimiter = ',',
+        conflicts_with_all = ["max_version", "preset", "target", "max_gcc_runtime", "from_sysroot", "write_baseline", "inventory", "stats", "list", "oldest_compatible"],
+        help = "Preset or target to check every file against in one pass, instead of -m, reporting the results for each (e.g. rhel7,ubuntu-20.04)"
+    )]
+    profile: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compare the symbol versions needed by two builds of a file, failing if the new build needs a newer version in
+    /// any namespace
+    Diff {
+        #[arg(help = "The old build of the file")]
+        old: PathBuf,
+        #[arg(help = "The new build of the file")]
+        new: PathBuf,
+    },
+    /// Compare two reports written by --format json, failing if the later report has violations failing files which the
+    /// earlier one doesn't
+    Compare {
+        #[arg(help = "The earlier report")]
+        before: PathBuf,
+        #[arg(help = "The later report")]
+        after: PathBuf,
+    },
+    /// Compare the symbols exported by two builds of a shared library, failing if any exported symbol version was
+    /// removed
+    DiffExports {
+        #[arg(help = "The old build of the library")]
+        old: PathBuf,
+        #[arg(help = "The new build of the library")]
+        new: PathBuf,
+    },
+    /// Print a GNU linker version script template for the symbols a shared library exports
+    VersionScript {
+        #[arg(help = "The shared library")]
+        library: PathBuf,
+        #[arg(
+            long = "node",
+            help = "Version node for unversioned symbols (default: based on the file name, e.g. LIBFOO_1.0)"
+        )]
+        node: Option<String>,
+    },
+    /// Print the versions of glibc which export a symbol, the library which provides it, and the distribution targets
+    /// which ship the version new links bind it to
+    Explain {
+        #[arg(
+            help = "The symbol, optionally with a version, e.g. getrandom or getrandom@GLIBC_2.25"
+        )]
+        symbol: String,
+    },
+}
+
+/// The sources of files to analyze given on the command line.
+struct Inputs<'a> {
+    files: &'a [PathBuf],
+    images: &'a [String],
+    nix_closures: &'a [PathBuf],
+    walk_options: WalkOptions,
+    skip_non_elf: bool,
+    filter: ElfFilter,
+    /// When set, the libraries ELF files need are found in these directories and checked too
+    library_path: Option<LibraryPath>,
+}
+
+/// A file found from the inputs. Files found indirectly, such as within a Nix closure, and all files when
+/// `--skip-non-elf` is given, are only checked if they are ELF files or archives.
+struct InputFile {
+    path: PathBuf,
+    skip_non_elf: bool,
+}
+
+/// The environment variable giving maximum versions when no maximum versions are given on the command line, separated
+/// by commas. Config files take precedence over them.
+const MAX_VERSION_ENV: &str = "SYMBOL_VERSION_CHECK_MAX";
+
+const EXIT_PASSED: i32 = 0;
+const EXIT_ERROR_CHECKING_FILES: i32 = 1;
+const EXIT_BAD_ARGS: i32 = 2;
+const EXIT_FILES_FAILED_CHECK: i32 = 3;
+
+enum FileCheckResult {
+    Pass,
+    /// A statically linked executable which passed, having no version references to check
+    Static,
+    /// A file linked against musl which passed, glibc symbol versioning not being applicable
+    Musl,
+    /// A kind of ELF file, such as a kernel module, which has no dynamic symbol versions to check
+    Unsupported(UnsupportedKind),
+    /// Passed, with violations which are only warnings
+    Warn(Vec<Violation>),
+    Fail(Vec<Violation>),
+    Error(anyhow::Error),
+    Skipped(SkipReason),
+    /// Has the same contents as the given file, so has the same result
+    Duplicate(PathBuf),
+}
+
+struct FileResult {
+    file: PathBuf,
+    result: FileCheckResult,
+    /// Information reported about the file alongside its result, such as its build ID
+    details: Details,
+}
+
+impl FileResult {
+    fn new(file: PathBuf, check_result: anyhow::Result<Vec<Violation>>) -> Self {
+        let result = match check_result {
+            Ok(violations) if violations.is_empty() => FileCheckResult::Pass,
+            Ok(violations) => FileCheckResult::Fail(violations),
+            Err(e) => FileCheckResult::Error(e),
+        };
+        Self {
+            file,
+            result,
+            details: Details::default(),
+        }
+    }
+}
+
+struct CheckResult {
+    file_results: Vec<FileResult>,
+}
+
+/// The violations of all the files which failed, merged.
+#[derive(Debug, Default, PartialEq)]
+struct Aggregate {
+    /// The number of files checked, and the number which failed, including files with the same contents as others
+    checked: usize,
+    failed: usize,
+    /// The newest version needed in each namespace with violations, by any file
+    gaps: Vec<NamespaceGap>,
+    /// Each violation, with the number of files failing for it, most first
+    violations: Vec<(Violation, usize)>,
+}
+
+/// The symbols and versions which the most files fail for, with the number of files failing for each, most first.
+#[derive(Debug, Default, PartialEq)]
+struct TopOffenders {
+    /// Symbols, as `symbol@VERSION`
+    symbols: Vec<(String, usize)>,
+    versions: Vec<(String, usize)>,
+}
+
+impl CheckResult {
+    fn has_errors(&self) -> bool {
+        self.file_results
+            .iter()
+            .any(|r| matches!(r.result, FileCheckResult::Error(_)))
+    }
+
+    fn has_failures(&self) -> bool {
+        self.file_results
+            .iter()
+            .any(|r| matches!(r.result, FileCheckResult::Fail(_)))
+    }
+
+    /// Summarizes the files skipped for each reason, e.g. "Skipped 3 non-ELF files".
+    fn skipped_summaries(&self) -> Vec<String> {
+        let mut skipped_counts: BTreeMap<SkipReason, usize> = BTreeMap::new();
+        for file_result in &self.file_results {
+            if let FileCheckResult::Skipped(reason) = file_result.result {
+                *skipped_counts.entry(reason).or_default() += 1;
+            }
+        }
+        skipped_counts
+            .into_iter()
+            .map(|(reason, count)| reason.summary(count))
+            .collect()
+    }
+
+    /// The metrics for the files checked, counted as they are in the aggregate, with the newest version any file
+    /// references in each namespace.
+    fn metrics(&self) -> Metrics {
+        let aggregate = self.aggregate();
+        let mut max_versions: BTreeMap<&str, &NamespacedVersion> = BTreeMap::new();
+        for max_version in self
+            .file_results
+            .iter()
+            .flat_map(|file_result| &file_result.details.max_versions)
+        {
+            match max_versions.get(max_version.namespace.as_str()) {
+                Some(newest) if newest.version >= max_version.version => {}
+                _ => {
+                    max_versions.insert(&max_version.namespace, max_version);
+                }
+            }
+        }
+        Metrics {
+            files_checked: aggregate.checked,
+            files_failed: aggregate.failed,
+            max_versions: max_versions.into_values().cloned().collect(),
+        }
+    }
+
+    fn results_by_file(&self) -> HashMap<&Path, &FileResult> {
+        self.file_results
+            .iter()
+            .map(|r| (r.file.as_path(), r))
+            .collect()
+    }
+
+    /// The result of each file, with those of files with the same contents as others being the first file's, as they
+    /// fail for the same violations.
+    fn resolved_results(&self) -> Vec<&FileResult> {
+        let results_by_file = self.results_by_file();
+        self.file_results
+            .iter()
+            .filter_map(|file_result| match &file_result.result {
+                FileCheckResult::Duplicate(first_file) => {
+                    results_by_file.get(first_file.as_path()).copied()
+                }
+                _ => Some(file_result),
+            })
+            .collect()
+    }
+
+    /// Each file's entry in the JSON output.
+    fn files_json(&self, demangle: DemangleChoice) -> Vec<Value> {
+        let results_by_file = self.results_by_file();
+        self.file_results
+            .iter()
+            .map(|file_result| {
+                let first_result = match &file_result.result {
+                    FileCheckResult::Duplicate(first_file) => {
+                        results_by_file.get(first_file.as_path()).copied()
+                    }
+                    _ => None,
+                };
+                file_result_json(file_result, first_result, demangle)
+            })
+            .collect()
+    }
+
+    /// Merges the violations of the files which failed, counting the files failing for each violation.
+    fn aggregate(&self) -> Aggregate {
+        let mut aggregate = Aggregate::default();
+        let mut counts: BTreeMap<&Violation, usize> = BTreeMap::new();
+        let mut gaps: BTreeMap<&str, &NamespaceGap> = BTreeMap::new();
+        for file_result in self.resolved_results() {
+            match &file_result.result {
+                FileCheckResult::Fail(violations) => {
+                    aggregate.checked += 1;
+                    aggregate.failed += 1;
+                    for violation in violations.iter().collect::<BTreeSet<_>>() {
+                        *counts.entry(violation).or_default() += 1;
+                    }
+                    for gap in &file_result.details.gaps {
+                        match gaps.get(gap.namespace.as_str()) {
+                            Some(newest) if newest.needed >= gap.needed => {}
+                            _ => {
+                                gaps.insert(&gap.namespace, gap);
+                            }
+                        }
+                    }
+                }
+                FileCheckResult::Error(_) | FileCheckResult::Skipped(_) => {}
+                _ => aggregate.checked += 1,
+            }
+        }
+        aggregate.gaps = gaps.into_values().cloned().collect();
+        aggregate.violations = counts
+            .into_iter()
+            .map(|(violation, count)| (violation.clone(), count))
+            .collect();
+        // Sorting is stable, so violations failing as many files stay in order
+        aggregate
+            .violations
+            .sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+        aggregate
+    }
+
+    /// Finds the `count` symbols and versions which the most files fail for.
+    fn top_offenders(&self, count: usize) -> TopOffenders {
+        let mut symbols: HashMap<String, usize> = HashMap::new();
+        let mut versions: HashMap<String, usize> = HashMap::new();
+        for file_result in self.resolved_results() {
+            let FileCheckResult::Fail(violations) = &file_result.result else {
+                continue;
+            };
+            // Each file is counted once for each symbol and version, however many times it violates them
+            let mut file_symbols = BTreeSet::new();
+            let mut file_versions = BTreeSet::new();
+            for violation in violations {
+                match violation {
+                    Violation::Symbol(symbol)
+                    | Violation::HiddenSymbol(symbol)
+                    | Violation::UnversionedSymbol(symbol) => {
+                        file_symbols.insert(format!("{}@{}", symbol.name, symbol.version));
+                        file_versions.insert(symbol.version.to_string());
+                    }
+                    Violation::NeededVersion(needed) => {
+                        file_versions.insert(needed.version.clone());
+                    }
+                    Violation::DeploymentTarget {
+                        platform, version, ..
+                    } => {
+                        file_versions.insert(format!("{}_{}", platform, version));
+                    }
+                    _ => {}
+                }
+            }
+            for symbol in file_symbols {
+                *symbols.entry(symbol).or_default() += 1;
+            }
+            for version in file_versions {
+                *versions.entry(version).or_default() += 1;
+            }
+        }
+        let top = |counts: HashMap<String, usize>| {
+            let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+            counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            counts.truncate(count);
+            counts
+        };
+        TopOffenders {
+            symbols: top(symbols),
+            versions: top(versions),
+        }
+    }
+}
+
+fn check_elf(
+    file: PathBuf,
+    data: &[u8],
+    policy: &Policy,
+    filter: &ElfFilter,
+    content_index: &mut ContentIndex,
+) -> FileResult {
+    if let Some(reason) = filter.skip_reason(data) {
+        return FileResult {
+            file,
+            result: FileCheckResult::Skipped(reason),
+            details: Details::default(),
+        };
+    }
+    // Exceptions and baselines are scoped to paths, so copies at other paths may get other results
+    if let Some(first_file) = content_index.find_or_insert(&file, data)
+        && policy.checks_alike(&first_file, &file)
+    {
+        return FileResult {
+            file,
+            result: FileCheckResult::Duplicate(first_file),
+            details: Details::default(),
+        };
+    }
+    if let Some(kind) = elf::get_unsupported_kind(data) {
+        return FileResult {
+            file,
+            result: FileCheckResult::Unsupported(kind),
+            details: Details::default(),
+        };
+    }
+    // Files in containers, such as archives, aren't on disk for their run paths to be found relative to
+    let on_disk = file.is_file().then_some(file.as_path());
+    let check_result = policy.check_at(data, on_disk).map(|violations| {
+        let mut violations = policy.exceptions.apply(&file, violations);
+        violations.retain(|violation| {
+            !policy
+                .baseline
+                .contains(&file, &violation_json(violation, DemangleChoice::None))
+        });
+        violations
+    });
+    let result = match check_result {
+        Ok(violations) => match policy.outcome(data, &violations) {
+            FileOutcome::Static => FileCheckResult::Static,
+            FileOutcome::Musl => FileCheckResult::Musl,
+            FileOutcome::Warn => FileCheckResult::Warn(violations),
+            FileOutcome::Fail => FileCheckResult::Fail(violations),
+            _ => FileCheckResult::Pass,
+        },
+        Err(e) => FileCheckResult::Error(e),
+    };
+    let mut file_result = FileResult {
+        file,
+        result,
+        details: Details::default(),
+    };
+    if !matches!(file_result.result, FileCheckResult::Error(_)) {
+        match policy.details(data) {
+            Ok(details) => file_result.details = details,
+            Err(e) => file_result.result = FileCheckResult::Error(e),
+        }
+    }
+    if let FileCheckResult::Fail(violations) = &file_result.result {
+        file_result.details.gaps = policy.namespace_gaps(violations);
+    }
+    if policy.report_hints
+        && let FileCheckResult::Fail(violations) | FileCheckResult::Warn(violations) =
+            &file_result.result
+    {
+        file_result.details.hints = policy.hints(violations);
+        match policy.compat_versions(data, violations) {
+            Ok(compat) => file_result.details.compat = compat,
+            Err(e) => file_result.details.warnings.push(format!(
+                "{:#}",
+                e.context("Error finding older versions of the symbols")
+            )),
+        }
+    }
+    if policy.report_blame
+        && let FileCheckResult::Fail(violations) | FileCheckResult::Warn(violations) =
+            &file_result.result
+    {
+        match policy.blame(data, violations) {
+            Ok((blame, crates)) => {
+                file_result.details.blame = blame;
+                file_result.details.crates = crates;
+            }
+            Err(e) => file_result.details.warnings.push(format!(
+                "{:#}",
+                e.context("Error finding where the symbols are referenced from")
+            )),
+        }
+    }
+    file_result
+}
+
+/// Checks each ELF file within a container such as an archive or image, reporting results per contained file.
+fn check_container(
+    file: &Path,
+    policy: &Policy,
+    filter: &ElfFilter,
+    content_index: &mut ContentIndex,
+    visit_entries: impl FnOnce(&mut dyn FnMut(&str, &[u8])) -> anyhow::Result<()>,
+) -> Vec<FileResult> {
+    let mut file_results = Vec::new();
+    let visit_result = visit_entries(&mut |name, entry_data| {
+        let entry_file = PathBuf::from(format!("{}:{}", file.display(), name));
+        file_results.push(check_elf(
+            entry_file,
+            entry_data,
+            policy,
+            filter,
+            content_index,
+        ));
+    });
+    match visit_result {
+        // An archive containing no ELF files has nothing which can fail the check
+        Ok(()) if file_results.is_empty() => vec![FileResult::new(file.to_path_buf(), Ok(vec![]))],
+        Ok(()) => file_results,
+        Err(e) => {
+            file_results.push(FileResult::new(file.to_path_buf(), Err(e)));
+            file_results
+        }
+    }
+}
+
+fn check_file(
+    file: &Path,
+    policy: &Policy,
+    filter: &ElfFilter,
+    content_index: &mut ContentIndex,
+    skip_non_elf: bool,
+) -> Vec<FileResult> {
+    match fs::read(file).with_context(|| "Error reading file") {
+        Ok(data) => check_data(file, &data, policy, filter, content_index, skip_non_elf),
+        Err(e) => vec![FileResult::new(file.to_path_buf(), Err(e))],
+    }
+}
+
+/// Checks the contents of a file, which may be an ELF file, or a container of them such as an archive.
+fn check_data(
+    file: &Path,
+    data: &[u8],
+    policy: &Policy,
+    filter: &ElfFilter,
+    content_index: &mut ContentIndex,
+    skip_non_elf: bool,
+) -> Vec<FileResult> {
+    match ArchiveKind::detect(data) {
+        Some(kind) => check_container(file, policy, filter, content_index, |visit| {
+            archive::visit_elf_entries(kind, data, visit)
+        }),
+        // Each architecture slice of a universal binary is checked separately
+        None if macho::is_fat(data) => {
+            check_container(file, policy, filter, content_index, |visit| {
+                macho::visit_fat_slices(data, visit)
+            })
+        }
+        None if skip_non_elf && !binary::is_binary(data) => vec![FileResult {
+            file: file.to_path_buf(),
+            result: FileCheckResult::Skipped(SkipReason::NotElf),
+            details: Details::default(),
+        }],
+        None => vec![check_elf(
+            file.to_path_buf(),
+            data,
+            policy,
+            filter,
+            content_index,
+        )],
+    }
+}
+
+fn check_image(
+    image: &str,
+    policy: &Policy,
+    filter: &ElfFilter,
+    content_index: &mut ContentIndex,
+) -> Vec<FileResult> {
+    check_container(Path::new(image), policy, filter, content_index, |visit| {
+        registry::visit_image_elf_files(image, visit)
+    })
+}
+
+/// Expands the file and Nix closure inputs into the individual files to check.
+fn expand_inputs(inputs: &Inputs) -> Vec<Result<InputFile, WalkError>> {
+    let files = walk::expand_paths(inputs.files, &inputs.walk_options)
+        .into_iter()
+        .map(|f| {
+            f.map(|path| InputFile {
+                path,
+                skip_non_elf: inputs.skip_non_elf,
+            })
+        });
+    let closure_files =
+        inputs
+            .nix_closures
+            .iter()
+            .flat_map(|closure| match nix::closure_paths(closure) {
+                Ok(paths) => walk::expand_paths(&paths, &inputs.walk_options)
+                    .into_iter()
+                    .map(|f| {
+                        f.map(|path| InputFile {
+                            path,
+                            skip_non_elf: true,
+                        })
+                    })
+                    .collect(),
+                Err(e) => vec![Err(WalkError {
+                    path: closure.clone(),
+                    error: e,
+                })],
+            });
+    let mut input_files: Vec<Result<InputFile, WalkError>> = files.chain(closure_files).collect();
+    if let Some(library_path) = &inputs.library_path {
+        input_files.extend(needed_libraries(&input_files, library_path));
+    }
+    input_files
+}
+
+/// Finds the libraries the ELF input files need, directly or through other libraries, which aren't inputs themselves.
+/// Libraries which aren't found are errors.
+fn needed_libraries(
+    input_files: &[Result<InputFile, WalkError>],
+    library_path: &LibraryPath,
+) -> Vec<Result<InputFile, WalkError>> {
+    let input_paths: Vec<&Path> = input_files
+        .iter()
+        .filter_map(|f| f.as_ref().ok())
+        .map(|f| f.path.as_path())
+        .collect();
+    let mut seen: HashSet<PathBuf> = input_paths
+        .iter()
+        .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+        .collect();
+    let mut libraries = Vec::new();
+    let mut missing_libraries = HashSet::new();
+    for path in input_paths {
+        // Files which can't be read, or aren't ELF files, are reported when they are checked
+        let Ok(data) = fs::read(path) else {
+            continue;
+        };
+        if !elf::is_elf(&data) || elf::is_static(&data) {
+            continue;
+        }
+        match library_path.find_closure(path, &data, &mut seen) {
+            Ok(closure) => {
+                libraries.extend(closure.libraries.into_iter().map(|path| {
+                    Ok(InputFile {
+                        path,
+                        skip_non_elf: true,
+                    })
+                }));
+                // The closure can't be checked completely, so each library which isn't found is an error, once
+                for (library, needed_by) in closure.missing {
+                    if missing_libraries.insert(library.clone()) {
+                        libraries.push(Err(WalkError {
+                            path: PathBuf::from(library),
+                            error: anyhow!(
+                                "Not found in the library path, needed by {}",
+                                needed_by.display()
+                            ),
+                        }));
+                    }
+                }
+            }
+            Err(error) => libraries.push(Err(WalkError {
+                path: path.to_path_buf(),
+                error: error.context("Error finding the libraries it needs"),
+            })),
+        }
+    }
+    libraries
+}
+
+/// Checks the files and images given as inputs, each file against the policy found for its directory. Files with the
+/// same contents as a file already checked against the same policy, with the same exceptions and baseline entries, are
+/// reported as duplicates of it rather than being checked again.
+fn check_files(inputs: &Inputs, policies: &mut PolicyFinder) -> CheckResult {
+    let mut content_indexes: HashMap<*const Policy, ContentIndex> = HashMap::new();
+    let mut file_results = Vec::new();
+    for file in expand_inputs(inputs) {
+        let f = match file {
+            Ok(f) => f,
+            Err(e) => {
+                file_results.push(FileResult::new(e.path, Err(e.error)));
+                continue;
+            }
+        };
+        file_results.extend(match policies.policy_for(f.path.parent()) {
+            Ok(policy) => check_file(
+                &f.path,
+                &policy,
+                &inputs.filter,
+                content_indexes.entry(Rc::as_ptr(&policy)).or_default(),
+                f.skip_non_elf,
+            ),
+            Err(e) => vec![FileResult::new(f.path, Err(e))],
+        });
+    }
+    for image in inputs.images {
+        file_results.extend(match policies.policy_for(None) {
+            Ok(policy) => check_image(
+                image,
+                &policy,
+                &inputs.filter,
+                content_indexes.entry(Rc::as_ptr(&policy)).or_default(),
+            ),
+            Err(e) => vec![FileResult::new(PathBuf::from(image), Err(e))],
+        });
+    }
+    CheckResult { file_results }
+}
+
+/// How often watched files are polled for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Checks the files found from the inputs again whenever they are rewritten, printing their results, until interrupted.
+/// Libraries found with `--transitive` and images are only checked the first time.
+fn watch_inputs(
+    inputs: &Inputs,
+    policies: &mut PolicyFinder,
+    format: OutputFormat,
+    demangle: DemangleChoice,
+    verbose: bool,
+) -> ! {
+    let mut watcher = Watcher::new(inputs.files, inputs.walk_options.clone());
+    eprintln!("{}", "Watching for changes, press Ctrl-C to stop".dimmed());
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        let changed = watcher.poll();
+        if changed.is_empty() {
+            continue;
+        }
+        let changed_inputs = Inputs {
+            files: &changed,
+            images: &[],
+            nix_closures: &[],
+            walk_options: inputs.walk_options.clone(),
+            skip_non_elf: inputs.skip_non_elf,
+            filter: inputs.filter.clone(),
+            library_path: None,
+        };
+        eprintln!(
+            "{}",
+            match changed.len() {
+                1 => "1 file changed".to_string(),
+                n => format!("{} files changed", n),
+            }
+            .dimmed()
+        );
+        policies.refresh();
+        let check_result = check_files(&changed_inputs, policies);
+        match format {
+            OutputFormat::Short => print_short_results(&check_result, demangle),
+            _ => print_results(&check_result, demangle, verbose),
+        }
+    }
+}
+
+/// The settings given on the command line, which the files of requests to `--serve` are checked with.
+struct Server {
+    policy: Policy,
+    config: Config,
+    /// Settings from the environment, beneath those of config files
+    defaults: Config,
+    user_presets: UserPresets,
+    discover: bool,
+    walk_options: WalkOptions,
+    skip_non_elf: bool,
+    filter: ElfFilter,
+    demangle: DemangleChoice,
+    /// The policies for the maximum versions of recent requests, or `None` for those of the command line, the most
+    /// recently used last, which are kept between requests so that config files are only read again once changed
+    policies: Vec<(Option<Vec<String>>, PolicyFinder)>,
+}
+
+/// The number of policies for different maximum versions `--serve` keeps between requests.
+const SERVE_POLICIES: usize = 16;
+
+/// A request to check files, given as a line of JSON, e.g.
+/// `{"id": 1, "files": ["app"], "max_version": ["GLIBC_2.17"]}`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServeRequest {
+    /// Any value, which is returned in the response
+    #[serde(default)]
+    id: Value,
+    files: Vec<PathBuf>,
+    /// Maximum versions, which replace those given on the command line
+    max_version: Option<Vec<String>>,
+}
+
+impl Server {
+    fn policies(&mut self, max_version: Option<Vec<String>>) -> anyhow::Result<&mut PolicyFinder> {
+        let mut finder = match self
+            .policies
+            .iter()
+            .position(|(key, _)| *key == max_version)
+        {
+            Some(index) => self.policies.remove(index).1,
+            None => {
+                let (policy, config) = match &max_version {
+                    Some(max_version) => {
+                        let requirements = &self.policy.requirements;
+                        let policy = Policy {
+                            requirements: VersionRequirements::parse(max_version)?
+                                .with_allowed_versions(requirements.allowed_versions().cloned())
+                                .with_unlisted(requirements.unlisted().clone())
+                                .with_minimum_versions(requirements.minimum_versions().cloned())
+                                .with_ignored_symbols(requirements.ignored_symbols())?,
+                            ..self.policy.clone()
+                        };
+                        let config = Config {
+                            max_version: max_version.clone(),
+                            ..self.config.clone()
+                        };
+                        (policy, config)
+                    }
+                    None => (self.policy.clone(), self.config.clone()),
+                };
+                if self.policies.len() == SERVE_POLICIES {
+                    self.policies.remove(0);
+                }
+                PolicyFinder::new(policy, config, self.user_presets.clone(), self.discover)
+                    .with_defaults(self.defaults.clone())
+            }
+        };
+        finder.refresh();
+        self.policies.push((max_version, finder));
+        Ok(&mut self
+            .policies
+            .last_mut()
+            .expect("The policies were just added")
+            .1)
+    }
+
+    /// Checks the files of a request, responding with their results as in the JSON output, or the reason the request
+    /// couldn't be handled.
+    fn respond(&mut self, line: &str) -> Value {
+        let request: ServeRequest = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return json!({"error": format!("Invalid request: {}", e)}),
+        };
+        let inputs = Inputs {
+            files: &request.files,
+            images: &[],
+            nix_closures: &[],
+            walk_options: self.walk_options.clone(),
+            skip_non_elf: self.skip_non_elf,
+            filter: self.filter.clone(),
+            library_path: None,
+        };
+        let demangle = self.demangle;
+        let mut response = match self.policies(request.max_version) {
+            Ok(policies) => {
+                let check_result = check_files(&inputs, policies);
+                json!({ "files": check_result.files_json(demangle) })
+            }
+            Err(e) => json!({"error": format!("{:#}", e)}),
+        };
+        if !request.id.is_null() {
+            response["id"] = request.id;
+        }
+        response
+    }
+}
+
+/// Responds to each line of JSON read from `input` with the line of JSON `respond` gives for it, written to `output`,
+/// until the end of the input.
+fn serve_lines(
+    mut respond: impl FnMut(&str) -> Value,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", respond(&line))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Accepts connections on a Unix socket, reading the requests of each on its own thread, so that an idle connection
+/// doesn't hold up others, until interrupted. The server isn't shared between threads, so requests are passed to this
+/// thread to be responded to, one at a time. An existing socket at the path, such as one left by an earlier server, is
+/// replaced.
+#[cfg(unix)]
+fn serve_socket(server: &mut Server, path: &Path) -> anyhow::Result<()> {
+    use std::io::BufReader;
+    use std::sync::mpsc;
+
+    let listener = bind_socket(path)?;
+    let (requests, received) = mpsc::channel::<(String, mpsc::Sender<Value>)>();
+    let accepting = std::thread::spawn(move || -> anyhow::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream.with_context(|| "Error accepting connection")?;
+            let requests = requests.clone();
+            std::thread::spawn(move || {
+                let respond = |line: &str| {
+                    let (reply, response) = mpsc::channel();
+                    let _ = requests.send((line.to_string(), reply));
+                    response
+                        .recv()
+                        .unwrap_or_else(|_| json!({"error": "The server has stopped"}))
+                };
+                let result = stream
+                    .try_clone()
+                    .and_then(|input| serve_lines(respond, BufReader::new(input), &stream));
+                if let Err(e) = result {
+                    eprintln!("{}: {}", "Warning".yellow(), e);
+                }
+            });
+        }
+        Ok(())
+    });
+    for (line, reply) in received {
+        let _ = reply.send(server.respond(&line));
+    }
+    accepting
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Listens on a Unix socket only accessible to the user, replacing an existing socket at the path. The socket is
+/// created in a directory only the user can enter, and made private, before being moved to the path, so that no other
+/// user can connect to it in between.
+#[cfg(unix)]
+fn bind_socket(path: &Path) -> anyhow::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path).with_context(|| "Error removing existing socket")?;
+    }
+    let mut private_dir = path.as_os_str().to_owned();
+    private_dir.push(format!(".{}", std::process::id()));
+    let private_dir = PathBuf::from(private_dir);
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .with_context(|| format!("Error creating {}", private_dir.display()))?;
+    let private_path = private_dir.join("socket");
+    let listener = UnixListener::bind(&private_path)
+        .and_then(|listener| {
+            fs::set_permissions(&private_path, fs::Permissions::from_mode(0o600))?;
+            fs::rename(&private_path, path)?;
+            Ok(listener)
+        })
+        .with_context(|| format!("Error listening on {}", path.display()));
+    let _ = fs::remove_file(&private_path);
+    let _ = fs::remove_dir(&private_dir);
+    listener
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_server: &mut Server, _path: &Path) -> anyhow::Result<()> {
+    Err(anyhow!("--socket is only supported on Unix"))
+}
+
+/// Checks the inputs against each of the profiles' policies, returning the results for each profile. Each file and
+/// image is only read once, however many profiles there are.
+fn check_matrix(inputs: &Inputs, profiles: &[(String, Policy)]) -> Vec<CheckResult> {
+    let mut content_indexes: Vec<ContentIndex> =
+        profiles.iter().map(|_| ContentIndex::default()).collect();
+    let mut file_results: Vec<Vec<FileResult>> = profiles.iter().map(|_| Vec::new()).collect();
+    // Errors can't be cloned, so each profile's results get a copy of the error's message
+    let push_error = |file_results: &mut Vec<Vec<FileResult>>, path: &Path, e: anyhow::Error| {
+        for results in file_results.iter_mut() {
+            results.push(FileResult::new(path.to_path_buf(), Err(anyhow!("{:#}", e))));
+        }
+    };
+    for file in expand_inputs(inputs) {
+        let f = match file {
+            Ok(f) => f,
+            Err(e) => {
+                push_error(&mut file_results, &e.path, e.error);
+                continue;
+            }
+        };
+        let data = match fs::read(&f.path).with_context(|| "Error reading file") {
+            Ok(data) => data,
+            Err(e) => {
+                push_error(&mut file_results, &f.path, e);
+                continue;
+            }
+        };
+        for (i, (_, policy)) in profiles.iter().enumerate() {
+            file_results[i].extend(check_data(
+                &f.path,
+                &data,
+                policy,
+                &inputs.filter,
+                &mut content_indexes[i],
+                f.skip_non_elf,
+            ));
+        }
+    }
+    for image in inputs.images {
+        let mut image_results: Vec<Vec<FileResult>> = profiles.iter().map(|_| Vec::new()).collect();
+        let visit_result = registry::visit_image_elf_files(image, &mut |name, entry_data| {
+            let entry_file = PathBuf::from(format!("{}:{}", image, name));
+            for (i, (_, policy)) in profiles.iter().enumerate() {
+                image_results[i].push(check_elf(
+                    entry_file.clone(),
+                    entry_data,
+                    policy,
+                    &inputs.filter,
+                    &mut content_indexes[i],
+                ));
+            }
+        });
+        for (results, image_results) in file_results.iter_mut().zip(image_results) {
+            // As with other containers, an image containing no ELF files has nothing which can fail the check
+            if image_results.is_empty() && visit_result.is_ok() {
+                results.push(FileResult::new(PathBuf::from(image), Ok(vec![])));
+            }
+            results.extend(image_results);
+        }
+        if let Err(e) = visit_result {
+            push_error(&mut file_results, Path::new(image), e);
+        }
+    }
+    file_results
+        .into_iter()
+        .map(|file_results| CheckResult { file_results })
+        .collect()
+}
+
+/// Visits the ELF files found in the given files and images, including those within archives. Files which are not ELF
+/// files or archives, which are excluded by the inputs' filter, or which can't be checked, such as kernel modules, are
+/// skipped. Any errors encountered finding and reading files are returned as error results.
+fn visit_elf_inputs(inputs: &Inputs, visit: &mut dyn FnMut(PathBuf, &[u8])) -> Vec<FileResult> {
+    let mut visit_elf = |file: PathBuf, data: &[u8]| {
+        if inputs.filter.skip_reason(data).is_none() && elf::get_unsupported_kind(data).is_none() {
+            visit(file, data);
+        }
+    };
+    let mut container_errors = Vec::new();
+
+    for file in expand_inputs(inputs) {
+        let file = match file {
+            Ok(file) => file.path,
+            Err(e) => {
+                container_errors.push(FileResult::new(e.path, Err(e.error)));
+                continue;
+            }
+        };
+        let data = match fs::read(&file).with_context(|| "Error reading file") {
+            Ok(data) => data,
+            Err(e) => {
+                container_errors.push(FileResult::new(file, Err(e)));
+                continue;
+            }
+        };
+        if let Some(kind) = ArchiveKind::detect(&data) {
+            let visit_result = archive::visit_elf_entries(kind, &data, &mut |name, entry_data| {
+                visit_elf(
+                    PathBuf::from(format!("{}:{}", file.display(), name)),
+                    entry_data,
+                )
+            });
+            if let Err(e) = visit_result {
+                container_errors.push(FileResult::new(file, Err(e)));
+            }
+        } else if macho::is_fat(&data) {
+            let visit_result = macho::visit_fat_slices(&data, &mut |name, slice_data| {
+                visit_elf(
+                    PathBuf::from(format!("{}:{}", file.display(), name)),
+                    slice_data,
+                )
+            });
+            if let Err(e) = visit_result {
+                container_errors.push(FileResult::new(file, Err(e)));
+            }
+        } else if binary::is_binary(&data) {
+            visit_elf(file, &data);
+        }
+    }
+    for image in inputs.images {
+        let visit_result = registry::visit_image_elf_files(image, &mut |name, entry_data| {
+            visit_elf(PathBuf::from(format!("{}:{}", image, name)), entry_data)
+        });
+        if let Err(e) = visit_result {
+            container_errors.push(FileResult::new(PathBuf::from(image), Err(e)));
+        }
+    }
+    container_errors
+}
+
+/// Builds an inventory of the ELF files found in the given files and images. Any errors encountered are returned as
+/// error results, and symbols whose versions can't be read as failures, or as warnings when `best_effort` is set.
+fn take_inventory(inputs: &Inputs, best_effort: bool) -> (Inventory, CheckResult) {
+    let mut inventory = Inventory::default();
+    let mut file_results = Vec::new();
+    let container_errors =
+        visit_elf_inputs(
+            inputs,
+            &mut |file, data| match binary::get_required_versions(data) {
+                Ok(required) => {
+                    inventory.add_file(&file, required.versions());
+                    if !required.unreadable.is_empty() {
+                        let violations = required
+                            .unreadable
+                            .into_iter()
+                            .map(Violation::UnreadableVersion)
+                            .collect();
+                        let result = if best_effort {
+                            FileCheckResult::Warn(violations)
+                        } else {
+                            FileCheckResult::Fail(violations)
+                        };
+                        file_results.push(FileResult {
+                            file,
+                            result,
+                            details: Details::default(),
+                        });
+                    }
+                }
+                Err(e) => file_results.push(FileResult::new(file, Err(e))),
+            },
+        );
+    file_results.extend(container_errors);
+    (inventory, CheckResult { file_results })
+}
+
+/// Finds the maximum version each ELF file found in the given files and images references in each namespace. Any errors
+/// encountered are returned as error results.
+fn list_max_versions(inputs: &Inputs) -> (Vec<(PathBuf, Vec<NamespacedVersion>)>, CheckResult) {
+    let mut listed = Vec::new();
+    let mut file_results = Vec::new();
+    let container_errors =
+        visit_elf_inputs(
+            inputs,
+            &mut |file, data| match binary::get_required_versions(data) {
+                Ok(required) => {
+                    let max_versions = inventory::max_versions(required.versions())
+                        .into_values()
+                        .cloned()
+                        .collect();
+                    listed.push((file, max_versions));
+                }
+                Err(e) => file_results.push(FileResult::new(file, Err(e))),
+            },
+        );
+    file_results.extend(container_errors);
+    (listed, CheckResult { file_results })
+}
+
+/// Finds the oldest manylinux preset and distribution target each ELF file found in the given files and images is
+/// compatible with. Any errors encountered are returned as error results.
+fn find_compatibility(inputs: &Inputs) -> (Vec<(PathBuf, Compatibility)>, CheckResult) {
+    let mut compatibilities = Vec::new();
+    let mut file_results = Vec::new();
+    let container_errors =
+        visit_elf_inputs(
+            inputs,
+            &mut |file, data| match compatibility::find_oldest_compatible(data) {
+                Ok(compatibility) => compatibilities.push((file, compatibility)),
+                Err(e) => file_results.push(FileResult::new(file, Err(e))),
+            },
+        );
+    file_results.extend(container_errors);
+    (compatibilities, CheckResult { file_results })
+}
+
+fn configure_colors(color_choice: &ColorChoice) {
+    match color_choice {
+        ColorChoice::Always => {
+            colored::control::set_override(true);
+        }
+        ColorChoice::Never => {
+            colored::control::set_override(false);
+        }
+        ColorChoice::Auto => {
+            colored::control::unset_override();
+        }
+    }
+}
+
+fn demangle_symbol_name(symbol: &SymbolVersion, demangle: DemangleChoice) -> String {
+    match demangle {
+        DemangleChoice::Cpp => symbol
+            .try_demangle_cpp_name()
+            .unwrap_or_else(|| symbol.name.to_string()),
+        DemangleChoice::Rust => symbol
+            .try_demangle_rust_name()
+            .unwrap_or_else(|| symbol.name.to_string()),
+        DemangleChoice::None => symbol.name.to_string(),
+    }
+}
+
+fn print_symbol(
+    symbol: &SymbolVersion,
+    demangle: DemangleChoice,
+    verbose: bool,
+    note: Option<&str>,
+) {
+    let name = demangle_symbol_name(symbol, demangle);
+    let file = match &symbol.file {
+        None => String::new(),
+        Some(file) => format!(" ({})", file),
+    };
+    let kind = match (symbol.symbol_type, symbol.binding) {
+        (Some(symbol_type), Some(binding)) if verbose => {
+            format!(" [{} {}]", symbol_type, binding)
+        }
+        _ => String::new(),
+    };
+    let note = note.map(|note| format!(" [{}]", note)).unwrap_or_default();
+    println!(
+        "    {}{}{}{}{}{}",
+        name,
+        "@".dimmed(),
+        symbol.version.to_string().red(),
+        file.dimmed(),
+        kind.dimmed(),
+        note.dimmed()
+    );
+}
+
+fn print_violation(violation: &Violation, demangle: DemangleChoice, verbose: bool) {
+    match violation {
+        Violation::Symbol(symbol) => print_symbol(symbol, demangle, verbose, None),
+        Violation::HiddenSymbol(symbol) => print_symbol(symbol, demangle, verbose, Some("hidden")),
+        Violation::UnversionedSymbol(symbol) => print_symbol(
+            symbol,
+            demangle,
+            verbose,
+            Some("unversioned, from glibc database"),
+        ),
+        Violation::DisallowedLibrary { library } => {
+            println!("    {} {}", library.red(), "(not allowed)".dimmed())
+        }
+        Violation::MissingLibrary { library } => {
+            println!(
+                "    {} {}",
+                library.red(),
+                "(not found in sysroot)".dimmed()
+            )
+        }
+        Violation::UnresolvedSymbol(unresolved) => {
+            let version = match &unresolved.version {
+                Some(version) => format!("{}{}", "@".dimmed(), version.red()),
+                None => String::new(),
+            };
+            let library = match &unresolved.library {
+                Some(library) => format!(" ({})", library),
+                None => String::new(),
+            };
+            println!(
+                "    {}{}{} {}",
+                unresolved.name.red(),
+                version,
+                library.dimmed(),
+                "(not exported in sysroot)".dimmed()
+            )
+        }
+        Violation::UnexpectedInterpreter { interpreter } => println!(
+            "    {} {}",
+            interpreter.red(),
+            "(unexpected interpreter)".dimmed()
+        ),
+        Violation::DeploymentTarget {
+            platform,
+            version,
+            weak_imports,
+        } => {
+            let note = if weak_imports.is_empty() {
+                "(deployment target)".to_string()
+            } else {
+                format!(
+                    "(deployment target, weak imports: {})",
+                    weak_imports.join(", ")
+                )
+            };
+            println!(
+                "    {} {} {}",
+                platform.red(),
+                version.to_string().red(),
+                note.dimmed()
+            )
+        }
+        Violation::KernelVersion {
+            version,
+            max_kernel,
+        } => println!(
+            "    Linux {} {}",
+            version.to_string().red(),
+            format!("(minimum kernel version, newer than {})", max_kernel).dimmed()
+        ),
+        Violation::MissingHardening { feature } => println!(
+            "    {} {}",
+            feature.to_string().red(),
+            "(missing hardening)".dimmed()
+        ),
+        Violation::TextRelocations => println!(
+            "    {} {}",
+            "TEXTREL".red(),
+            "(relocations against read-only segments)".dimmed()
+        ),
+        Violation::NewerSoname {
+            library,
+            max_soname,
+        } => println!(
+            "    {} {}",
+            library.red(),
+            format!("(newer than {})", max_soname).dimmed()
+        ),
+        Violation::NeededVersion(needed) => {
+            println!("    {} ({})", needed.version.red(), needed.file.dimmed())
+        }
+        Violation::MissingBuildId => println!("    {} {}", "build ID".red(), "(missing)".dimmed()),
+        Violation::UnexpectedVersionNode { node } => println!(
+            "    {} {}",
+            node.red(),
+            "(unexpected version node)".dimmed()
+        ),
+        Violation::MissingVersionNode { node } => {
+            println!("    {} {}", node.red(), "(missing version node)".dimmed())
+        }
+        Violation::MissingVersionTable => {
+            println!("    {} {}", "version table".red(), "(missing)".dimmed())
+        }
+        Violation::MuslLinked => println!(
+            "    {} {}",
+            "musl".red(),
+            "(musl-linked, glibc symbol versioning not applicable)".dimmed()
+        ),
+        Violation::UnreadableVersion(unreadable) => println!(
+            "    {} {}",
+            unreadable
+                .symbol
+                .as_deref()
+                .unwrap_or("version table")
+                .red(),
+            format!("(unreadable: {})", unreadable.error).dimmed()
+        ),
+        Violation::RemovedSymbol {
+            name,
+            glibc,
+            replacement,
+        } => println!(
+            "    {} {}",
+            name.red(),
+            format!(
+                "(compatibility-only since glibc {}, use {})",
+                glibc, replacement
+            )
+            .dimmed()
+        ),
+        Violation::DeniedSymbol { name, reason } => {
+            let reason = match reason {
+                Some(reason) => format!("(denied: {})", reason),
+                None => "(denied)".to_string(),
+            };
+            println!("    {} {}", name.red(), reason.dimmed())
+        }
+        Violation::ExceptedSymbol {
+            symbol,
+            comment,
+            expires,
+        } => {
+            let mut note = "excepted".to_string();
+            if let Some(expires) = expires {
+                note = format!("{} until {}", note, expires);
+            }
+            if let Some(comment) = comment {
+                note = format!("{}: {}", note, comment);
+            }
+            print_symbol(symbol, demangle, verbose, Some(&note))
+        }
+        Violation::MinimumVersion {
+            min_version,
+            newest: Some(newest),
+        } => println!(
+            "    {} {}",
+            newest.to_string().red(),
+            format!("(newest referenced, older than minimum {})", min_version).dimmed()
+        ),
+        Violation::MinimumVersion {
+            min_version,
+            newest: None,
+        } => println!(
+            "    {} {}",
+            min_version.namespace.red(),
+            format!("(not referenced, minimum {})", min_version).dimmed()
+        ),
+        Violation::UnlistedNamespace { namespace } => println!(
+            "    {} {}",
+            namespace.red(),
+            "(no maximum version given)".dimmed()
+        ),
+        Violation::PrivateSymbol(reference) => println!(
+            "    {}{}{} ({})",
+            reference.name,
+            "@".dimmed(),
+            reference.version.red(),
+            reference.file.as_deref().unwrap_or("private").dimmed()
+        ),
+    }
+}
+
+fn print_gaps(gaps: &[NamespaceGap]) {
+    for gap in gaps {
+        let limit = match &gap.limit {
+            Some(limit) => format!(", limit {}", limit),
+            None => String::new(),
+        };
+        println!(
+            "    {}: needs {}{}",
+            gap.namespace.bold(),
+            gap.needed.to_string().red(),
+            limit
+        );
+    }
+}
+
+fn print_blame(details: &Details) {
+    for (symbol, sources) in &details.blame {
+        let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+        println!(
+            "    {} {} {}",
+            symbol.bold(),
+            "referenced from".dimmed(),
+            sources.join(", ")
+        );
+    }
+    for (krate, symbols) in &details.crates {
+        let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+        // Crates are described with their versions and how they are depended on, if cargo auditable recorded them
+        let package = match auditable::find_crate(&details.packages, krate) {
+            Some(package) => {
+                let path: Vec<&str> = auditable::dependency_path(&details.packages, package)
+                    .iter()
+                    .map(|package| package.name.as_str())
+                    .collect();
+                let path = match path.len() {
+                    0 | 1 => String::new(),
+                    _ => format!(" ({})", path.join(" > ")),
+                };
+                format!(" {}{}", package.version, path.dimmed())
+            }
+            None => String::new(),
+        };
+        println!(
+            "    {} {}{} calls {}",
+            "crate".dimmed(),
+            krate.bold(),
+            package,
+            symbols.join(", ")
+        );
+    }
+}
+
+fn print_hints(details: &Details) {
+    for compat in &details.compat {
+        println!(
+            "    {} {}@{}: {}@{} is also available; bind to it with __asm__(\"{}\")",
+            "hint".cyan(),
+            compat.symbol.name.bold(),
+            compat.symbol.version.to_string().bold(),
+            compat.symbol.name,
+            compat.compat,
+            compat.symver()
+        );
+    }
+    for hint in &details.hints {
+        println!("    {} {}: {}", "hint".cyan(), hint.key.bold(), hint.hint);
+    }
+}
+
+fn print_details(details: &Details) {
+    if let Some(header) = &details.header {
+        let endianness = if header.little_endian {
+            "little"
+        } else {
+            "big"
+        };
+        println!(
+            "    {}",
+            format!(
+                "ELF: {}, {}-bit {} endian, OS ABI {}, type {}",
+                header.machine, header.class, endianness, header.os_abi, header.file_type
+            )
+            .dimmed()
+        );
+    }
+    if let Some(build_id) = &details.build_id {
+        println!("    {}", format!("build ID: {}", build_id).dimmed());
+    }
+    if let Some(interpreter) = &details.interpreter {
+        println!("    {}", format!("interpreter: {}", interpreter).dimmed());
+    }
+    if !details.version_nodes.is_empty() {
+        println!(
+            "    {}",
+            format!("version nodes: {}", details.version_nodes.join(", ")).dimmed()
+        );
+    }
+    if let Some(glibc) = &details.static_glibc {
+        println!(
+            "    {}",
+            format!("estimated glibc: {} (from embedded version strings)", glibc).dimmed()
+        );
+    }
+    if !details.distros.is_empty() {
+        let distros: Vec<String> = details
+            .distros
+            .iter()
+            .map(|distro| match distro.release {
+                Some(release) => format!("{} >= {}", distro.distro, release),
+                None => format!("{} {}", distro.distro, "(no release)".red()),
+            })
+            .collect();
+        println!("    runs on {}", distros.join(", "));
+    }
+    if !details.packages.is_empty() {
+        let packages: Vec<String> = details
+            .packages
+            .iter()
+            .filter(|package| package.kind == "runtime")
+            .map(|package| format!("{} {}", package.name, package.version))
+            .collect();
+        println!(
+            "    {}",
+            format!("crates: {}", packages.join(", ")).dimmed()
+        );
+    }
+    if let Some(hardening) = &details.hardening {
+        println!("    {}", hardening.summary().dimmed());
+    }
+    for warning in &details.warnings {
+        println!("    {}: {}", "Warning".yellow(), warning);
+    }
+}
+
+fn print_error(file: &Path, e: &anyhow::Error) {
+    eprintln!("{}: {}", file.display(), "ERROR".red().bold());
+    let error_chain: String = e
+        .chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(": ");
+    eprintln!("    {}", error_chain.red());
+}
+
+/// Describes a file's result in a line, or returns `None` if it passed or wasn't checked.
+fn short_result(file_result: &FileResult, demangle: DemangleChoice) -> Option<String> {
+    let file = file_result.file.display();
+    let (status, violations) = match &file_result.result {
+        FileCheckResult::Fail(violations) => ("FAIL".red().bold(), violations),
+        FileCheckResult::Warn(violations) => ("WARN".yellow().bold(), violations),
+        FileCheckResult::Error(e) => {
+            return Some(format!("{}: {} {:#}", file, "ERROR".red().bold(), e));
+        }
+        _ => return None,
+    };
+    let mut violations = violations.clone();
+    violations.sort();
+    let descriptions: Vec<String> = violations
+        .iter()
+        .map(|violation| match violation {
+            Violation::Symbol(symbol)
+            | Violation::HiddenSymbol(symbol)
+            | Violation::UnversionedSymbol(symbol) => {
+                format!(
+                    "{}@{}",
+                    demangle_symbol_name(symbol, demangle),
+                    symbol.version
+                )
+            }
+            violation => report::describe_violation(&violation_json(violation, demangle)),
+        })
+        .collect();
+    Some(format!("{}: {} {}", file, status, descriptions.join(", ")))
+}
+
+/// Gives the files' results for exploring in the TUI, with their violations, or the reasons they couldn't be checked.
+#[cfg(feature = "tui")]
+fn tui_files(check_result: &CheckResult) -> Vec<tui::FileItem> {
+    let symbol_text = |symbol: &SymbolVersion, name: &str| match &symbol.file {
+        Some(file) => format!("{}@{} ({})", name, symbol.version, file),
+        None => format!("{}@{}", name, symbol.version),
+    };
+    check_result
+        .file_results
+        .iter()
+        .filter_map(|file_result| {
+            let (result, entries) = match &file_result.result {
+                FileCheckResult::Pass => ("PASS", Vec::new()),
+                FileCheckResult::Static => ("STATIC", Vec::new()),
+                FileCheckResult::Musl => ("MUSL", Vec::new()),
+                FileCheckResult::Duplicate(first_file) => (
+                    "SAME",
+                    vec![tui::Entry {
+                        namespace: None,
+                        text: format!("same as {}", first_file.display()),
+                        demangled: None,
+                    }],
+                ),
+                FileCheckResult::Error(e) => (
+                    "ERROR",
+                    vec![tui::Entry {
+                        namespace: None,
+                        text: format!("{:#}", e),
+                        demangled: None,
+                    }],
+                ),
+                FileCheckResult::Warn(violations) | FileCheckResult::Fail(violations) => {
+                    let mut violations = violations.clone();
+                    violations.sort();
+                    let entries = violations
+                        .iter()
+                        .map(|violation| match violation {
+                            Violation::Symbol(symbol)
+                            | Violation::HiddenSymbol(symbol)
+                            | Violation::UnversionedSymbol(symbol) => tui::Entry {
+                                namespace: Some(symbol.version.namespace.clone()),
+                                text: symbol_text(symbol, &symbol.name),
+                                demangled: symbol
+                                    .try_demangle_cpp_name()
+                                    .or_else(|| symbol.try_demangle_rust_name())
+                                    .map(|name| symbol_text(symbol, &name)),
+                            },
+                            violation => tui::Entry {
+                                namespace: None,
+                                text: report::describe_violation(&violation_json(
+                                    violation,
+                                    DemangleChoice::None,
+                                )),
+                                demangled: None,
+                            },
+                        })
+                        .collect();
+                    match file_result.result {
+                        FileCheckResult::Warn(_) => ("WARN", entries),
+                        _ => ("FAIL", entries),
+                    }
+                }
+                FileCheckResult::Unsupported(_) | FileCheckResult::Skipped(_) => return None,
+            };
+            Some(tui::FileItem {
+                file: file_result.file.display().to_string(),
+                result,
+                entries,
+            })
+        })
+        .collect()
+}
+
+/// Prints a line for each file which fails, warns or couldn't be checked, and nothing for those which pass.
+fn print_short_results(check_result: &CheckResult, demangle: DemangleChoice) {
+    for file_result in &check_result.file_results {
+        if let Some(line) = short_result(file_result, demangle) {
+            println!("{}", line);
+        }
+    }
+}
+
+fn print_results(check_result: &CheckResult, demangle: DemangleChoice, verbose: bool) {
+    print_file_results(check_result, demangle, verbose, &BTreeMap::new());
+}
+
+/// Counts the files which fail or warn for each violation, for the violations of more than one file.
+fn find_shared_violations(check_result: &CheckResult) -> BTreeMap<&Violation, usize> {
+    let mut counts: BTreeMap<&Violation, usize> = BTreeMap::new();
+    for file_result in check_result.resolved_results() {
+        if let FileCheckResult::Warn(violations) | FileCheckResult::Fail(violations) =
+            &file_result.result
+        {
+            for violation in violations.iter().collect::<BTreeSet<_>>() {
+                *counts.entry(violation).or_default() += 1;
+            }
+        }
+    }
+    counts.retain(|_, count| *count > 1);
+    counts
+}
+
+/// Prints the results of each file, printing violations shared by several files once after them, with the number of
+/// files they are shared by. Verbose output still lists every violation of each file.
+fn print_collapsed_results(check_result: &CheckResult, demangle: DemangleChoice, verbose: bool) {
+    let shared = find_shared_violations(check_result);
+    if verbose {
+        print_results(check_result, demangle, verbose);
+    } else {
+        print_file_results(check_result, demangle, verbose, &shared);
+    }
+    if shared.is_empty() {
+        return;
+    }
+    let mut shared: Vec<(&Violation, usize)> = shared.into_iter().collect();
+    // Sorting is stable, so violations shared by as many files stay in order
+    shared.sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+    println!("{}:", "SHARED VIOLATIONS".bold());
+    let mut previous_count = None;
+    for (violation, count) in shared {
+        if previous_count != Some(count) {
+            println!("  {}", format!("in {} files:", count).dimmed());
+            previous_count = Some(count);
+        }
+        print_violation(violation, demangle, verbose);
+    }
+}
+
+/// Prints the violations of a file which aren't shared, followed by the number which are.
+fn print_unshared_violations(
+    violations: &[Violation],
+    shared: &BTreeMap<&Violation, usize>,
+    demangle: DemangleChoice,
+    verbose: bool,
+) {
+    let mut shared_count = 0;
+    for violation in violations {
+        if shared.contains_key(violation) {
+            shared_count += 1;
+        } else {
+            print_violation(violation, demangle, verbose);
+        }
+    }
+    match shared_count {
+        0 => {}
+        1 => println!("    {}", "+ 1 shared violation".dimmed()),
+        n => println!("    {}", format!("+ {} shared violations", n).dimmed()),
+    }
+}
+
+fn print_file_results(
+    check_result: &CheckResult,
+    demangle: DemangleChoice,
+    verbose: bool,
+    shared: &BTreeMap<&Violation, usize>,
+) {
+    let results_by_file: HashMap<&Path, &FileCheckResult> = check_result
+        .file_results
+        .iter()
+        .map(|r| (r.file.as_path(), &r.result))
+        .collect();
+    for file_result in &check_result.file_results {
+        match &file_result.result {
+            FileCheckResult::Pass => {
+                println!("{}: {}", file_result.file.display(), "PASS".green().bold());
+                print_details(&file_result.details);
+            }
+            FileCheckResult::Static => {
+                println!(
+                    "{}: {}",
+                    file_result.file.display(),
+                    "STATIC".green().bold()
+                );
+                print_details(&file_result.details);
+            }
+            FileCheckResult::Musl => {
+                println!(
+                    "{}: {} {}",
+                    file_result.file.display(),
+                    "MUSL".green().bold(),
+                    "(musl-linked, glibc symbol versioning not applicable)".dimmed()
+                );
+                print_details(&file_result.details);
+            }
+            FileCheckResult::Unsupported(kind) => {
+                let description = match kind {
+                    UnsupportedKind::KernelModule => "(kernel module, not checked)",
+                    UnsupportedKind::Core => "(core dump, not checked)",
+                    UnsupportedKind::Debuginfo => "(separate debug info, not checked)",
+                };
+                println!(
+                    "{}: {} {}",
+                    file_result.file.display(),
+                    kind.to_string().yellow().bold(),
+                    description.dimmed()
+                );
+            }
+            FileCheckResult::Warn(violations) => {
+                let mut violations = violations.clone();
+                violations.sort();
+
+                println!("{}: {}", file_result.file.display(), "WARN".yellow().bold());
+                print_unshared_violations(&violations, shared, demangle, verbose);
+                print_blame(&file_result.details);
+                print_hints(&file_result.details);
+                print_details(&file_result.details);
+            }
+            FileCheckResult::Fail(violations) => {
+                let mut violations = violations.clone();
+                violations.sort();
+
+                println!("{}: {}", file_result.file.display(), "FAIL".red().bold());
+                print_gaps(&file_result.details.gaps);
+                print_unshared_violations(&violations, shared, demangle, verbose);
+                print_blame(&file_result.details);
+                print_hints(&file_result.details);
+                print_details(&file_result.details);
+            }
+            FileCheckResult::Error(e) => print_error(&file_result.file, e),
+            FileCheckResult::Skipped(_) => {}
+            FileCheckResult::Duplicate(first_file) => {
+                let same_as = format!("(same as {})", first_file.display());
+                match results_by_file.get(first_file.as_path()) {
+                    Some(FileCheckResult::Pass) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "PASS".green().bold(),
+                        same_as.dimmed()
+                    ),
+                    Some(FileCheckResult::Static) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "STATIC".green().bold(),
+                        same_as.dimmed()
+                    ),
+                    Some(FileCheckResult::Musl) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "MUSL".green().bold(),
+                        same_as.dimmed()
+                    ),
+                    Some(FileCheckResult::Unsupported(kind)) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        kind.to_string().yellow().bold(),
+                        same_as.dimmed()
+                    ),
+                    Some(FileCheckResult::Warn(_)) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "WARN".yellow().bold(),
+                        same_as.dimmed()
+                    ),
+                    Some(FileCheckResult::Fail(_)) => println!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "FAIL".red().bold(),
+                        same_as.dimmed()
+                    ),
+                    _ => eprintln!(
+                        "{}: {} {}",
+                        file_result.file.display(),
+                        "ERROR".red().bold(),
+                        same_as.dimmed()
+                    ),
+                }
+            }
+        }
+    }
+    for summary in check_result.skipped_summaries() {
+        println!("{}", summary.dimmed());
+    }
+}
+
+/// Returns a violation as it is serialized, with the demangled name of its symbol, if any, when it differs.
+fn violation_json(violation: &Violation, demangle: DemangleChoice) -> Value {
+    let mut value = serde_json::to_value(violation).expect("Violations can always be serialized");
+    if let Violation::Symbol(symbol)
+    | Violation::HiddenSymbol(symbol)
+    | Violation::UnversionedSymbol(symbol)
+    | Violation::ExceptedSymbol { symbol, .. } = violation
+    {
+        let demangled = demangle_symbol_name(symbol, demangle);
+        if demangled != symbol.name {
+            value["demangled"] = json!(demangled);
+        }
+    }
+    value
+}
+
+fn details_json(details: &Details) -> Value {
+    let mut value = json!({
+        "build_id": details.build_id,
+        "interpreter": details.interpreter,
+        "version_nodes": details.version_nodes,
+    });
+    if let Some(header) = &details.header {
+        value["elf"] = json!({
+            "machine": header.machine,
+            "class": header.class,
+            "endianness": if header.little_endian { "little" } else { "big" },
+            "os_abi": header.os_abi,
+            "type": header.file_type,
+        });
+    }
+    if let Some(glibc) = &details.static_glibc {
+        value["static_glibc"] = json!(glibc.to_string());
+    }
+    if !details.distros.is_empty() {
+        value["distros"] = details
+            .distros
+            .iter()
+            .map(|distro| json!({"distro": distro.distro, "oldest_release": distro.release}))
+            .collect();
+    }
+    if !details.blame.is_empty() {
+        value["blame"] = json!(details.blame);
+    }
+    if !details.crates.is_empty() {
+        value["crates"] = json!(details.crates);
+    }
+    if !details.packages.is_empty() {
+        value["packages"] = json!(details.packages);
+    }
+    if !details.hints.is_empty() {
+        value["hints"] = details
+            .hints
+            .iter()
+            .map(|hint| json!({"key": hint.key, "hint": hint.hint}))
+            .collect();
+    }
+    if !details.compat.is_empty() {
+        value["compat"] = details
+            .compat
+            .iter()
+            .map(|compat| {
+                json!({
+                    "symbol": compat.symbol.name,
+                    "version": compat.symbol.version.to_string(),
+                    "compat_version": compat.compat.to_string(),
+                    "symver": compat.symver(),
+                })
+            })
+            .collect();
+    }
+    if !details.warnings.is_empty() {
+        value["warnings"] = json!(details.warnings);
+    }
+    if !details.gaps.is_empty() {
+        value["gaps"] = details
+            .gaps
+            .iter()
+            .map(|gap| {
+                json!({
+                    "namespace": gap.namespace,
+                    "needed": gap.needed.to_string(),
+                    "limit": gap.limit.as_ref().map(Version::to_string),
+                })
+            })
+            .collect();
+    }
+    if let Some(hardening) = &details.hardening {
+        value["hardening"] = json!({
+            "relro": hardening.relro,
+            "full_relro": hardening.has(HardeningFeature::FullRelro),
+            "pie": hardening.pie,
+            "nx": hardening.nx,
+            "bind_now": hardening.bind_now,
+            "stack_protector": hardening.stack_protector,
+            "text_relocations": hardening.text_relocations,
+        });
+    }
+    value
+}
+
+fn unsupported_kind_name(kind: UnsupportedKind) -> &'static str {
+    match kind {
+        UnsupportedKind::KernelModule => "kernel_module",
+        UnsupportedKind::Core => "core",
+        UnsupportedKind::Debuginfo => "debuginfo",
+    }
+}
+
+fn file_outcome(result: &FileCheckResult) -> FileOutcome {
+    match result {
+        FileCheckResult::Pass => FileOutcome::Pass,
+        FileCheckResult::Static => FileOutcome::Static,
+        FileCheckResult::Musl => FileOutcome::Musl,
+        FileCheckResult::Unsupported(_) => FileOutcome::Unsupported,
+        FileCheckResult::Warn(_) => FileOutcome::Warn,
+        FileCheckResult::Fail(_) => FileOutcome::Fail,
+        FileCheckResult::Error(_) => FileOutcome::Error,
+        FileCheckResult::Skipped(_) => FileOutcome::Skipped,
+        FileCheckResult::Duplicate(_) => FileOutcome::Duplicate,
+    }
+}
+
+/// The report of a file's result, given the result of the first file with the same contents for a duplicate.
+fn file_report(file_result: &FileResult, first_result: Option<&FileResult>) -> FileReport {
+    let violations = match &file_result.result {
+        FileCheckResult::Warn(violations) | FileCheckResult::Fail(violations) => {
+            let mut violations = violations.clone();
+            violations.sort();
+            violations
+        }
+        _ => Vec::new(),
+    };
+    // Paths which aren't UTF-8 can't be serialized, so they are reported as displayed
+    FileReport {
+        file: PathBuf::from(file_result.file.display().to_string()),
+        result: file_outcome(&file_result.result),
+        violations,
+        error: match &file_result.result {
+            FileCheckResult::Error(e) => Some(format!("{:#}", e)),
+            _ => None,
+        },
+        same_as: match &file_result.result {
+            FileCheckResult::Duplicate(first_file) => {
+                Some(PathBuf::from(first_file.display().to_string()))
+            }
+            _ => None,
+        },
+        same_as_result: first_result.map(|first_result| file_outcome(&first_result.result)),
+    }
+}
+
+/// A file's entry in the JSON output: its report, with the violations' demangled names, and the details of files
+/// which were checked.
+fn file_result_json(
+    file_result: &FileResult,
+    first_result: Option<&FileResult>,
+    demangle: DemangleChoice,
+) -> Value {
+    let report = file_report(file_result, first_result);
+    let mut value = serde_json::to_value(&report).expect("Reports can always be serialized");
+    if !report.violations.is_empty() {
+        value["violations"] = json!(
+            report
+                .violations
+                .iter()
+                .map(|violation| violation_json(violation, demangle))
+                .collect::<Vec<_>>()
+        );
+    }
+    match &file_result.result {
+        FileCheckResult::Unsupported(kind) => value["kind"] = json!(unsupported_kind_name(*kind)),
+        FileCheckResult::Skipped(reason) => {
+            value["reason"] = json!(match reason {
+                SkipReason::NotElf => "not_elf",
+                SkipReason::Architecture => "architecture",
+                SkipReason::ElfType => "elf_type",
+                SkipReason::Unsupported(kind) => unsupported_kind_name(*kind),
+            })
+        }
+        _ => {}
+    }
+    if matches!(
+        file_result.result,
+        FileCheckResult::Pass
+            | FileCheckResult::Static
+            | FileCheckResult::Musl
+            | FileCheckResult::Warn(_)
+            | FileCheckResult::Fail(_)
+    ) {
+        value["details"] = details_json(&file_result.details);
+    }
+    value
+}
+
+fn print_json_results(
+    check_result: &CheckResult,
+    demangle: DemangleChoice,
+    top: Option<&TopOffenders>,
+    aggregate: Option<&Aggregate>,
+) {
+    let mut value = json!({ "files": check_result.files_json(demangle) });
+    if let Some(aggregate) = aggregate {
+        value["aggregate"] = json!({
+            "checked": aggregate.checked,
+            "failed": aggregate.failed,
+            "violations": aggregate
+                .violations
+                .iter()
+                .map(|(violation, count)| {
+                    let mut value = violation_json(violation, demangle);
+                    value["files"] = json!(count);
+                    value
+                })
+                .collect::<Vec<_>>(),
+        });
+    }
+    if let Some(top) = top {
+        let offenders = |offenders: &[(String, usize)]| {
+            offenders
+                .iter()
+                .map(|(name, files)| json!({"name": name, "files": files}))
+                .collect::<Vec<_>>()
+        };
+        value["top"] = json!({
+            "symbols": offenders(&top.symbols),
+            "versions": offenders(&top.versions),
+        });
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&value).expect("JSON values can always be serialized")
+    );
+}
+
+/// Builds a CycloneDX bill of materials with a component for each file checked, with properties giving its result, the
+/// newest version it needs in each namespace and the libraries it needs.
+fn cyclonedx_json(check_result: &CheckResult) -> Value {
+    let property = |name: &str, value: &str| json!({"name": format!("symbol-version-check:{}", name), "value": value});
+    let components: Vec<Value> = check_result
+        .file_results
+        .iter()
+        .filter_map(|file_result| {
+            let result = match &file_result.result {
+                FileCheckResult::Pass => "pass",
+                FileCheckResult::Static => "static",
+                FileCheckResult::Musl => "musl",
+                FileCheckResult::Warn(_) => "warn",
+                FileCheckResult::Fail(_) => "fail",
+                _ => return None,
+            };
+            let name = file_result.file.display().to_string();
+            let details = &file_result.details;
+            let mut properties = vec![property("result", result)];
+            properties.extend(
+                details
+                    .max_versions
+                    .iter()
+                    .map(|version| property("needs", &version.to_string())),
+            );
+            properties.extend(
+                details
+                    .needed_libraries
+                    .iter()
+                    .map(|library| property("needed", library)),
+            );
+            if let Some(build_id) = &details.build_id {
+                properties.push(property("build-id", build_id));
+            }
+            Some(json!({
+                "type": "file",
+                "bom-ref": name,
+                "name": name,
+                "properties": properties,
+            }))
+        })
+        .collect();
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components,
+    })
+}
+
+/// Prints the results as a CycloneDX bill of materials. Files which couldn't be checked are reported separately, as
+/// the bill of materials has no place for them.
+fn print_cyclonedx_results(check_result: &CheckResult) {
+    for file_result in &check_result.file_results {
+        if let FileCheckResult::Error(e) = &file_result.result {
+            print_error(&file_result.file, e);
+        }
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&cyclonedx_json(check_result))
+            .expect("JSON values can always be serialized")
+    );
+}
+
+/// Prints the results of checking files against each profile, under the profile's name.
+fn print_matrix_results(
+    profiles: &[(String, Policy)],
+    check_results: &[CheckResult],
+    demangle: DemangleChoice,
+    verbose: bool,
+) {
+    for ((name, _), check_result) in profiles.iter().zip(check_results) {
+        println!("{}: {}", "PROFILE".bold(), name.bold());
+        print_results(check_result, demangle, verbose);
+    }
+}
+
+fn print_json_matrix_results(
+    profiles: &[(String, Policy)],
+    check_results: &[CheckResult],
+    demangle: DemangleChoice,
+) {
+    let profiles: Vec<Value> = profiles
+        .iter()
+        .zip(check_results)
+        .map(|((name, _), check_result)| {
+            json!({ "profile": name, "files": check_result.files_json(demangle) })
+        })
+        .collect();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({ "profiles": profiles }))
+            .expect("JSON values can always be serialized")
+    );
+}
+
+fn print_max_versions(listed: &[(PathBuf, Vec<NamespacedVersion>)]) {
+    for (file, max_versions) in listed {
+        if max_versions.is_empty() {
+            println!("{}: {}", file.display(), "needs no versions".dimmed());
+        } else {
+            let needs: Vec<String> = max_versions
+                .iter()
+                .map(|version| format!("{} >= {}", version.namespace, version.version))
+                .collect();
+            println!("{}: needs {}", file.display(), needs.join(", ").bold());
+        }
+    }
+}
+
+fn print_compatibility(compatibilities: &[(PathBuf, Compatibility)]) {
+    for (file, compatibility) in compatibilities {
+        let oldest: Vec<&str> = [compatibility.manylinux, compatibility.target]
+            .into_iter()
+            .flatten()
+            .map(|preset| preset.name)
+            .collect();
+        if oldest.is_empty() {
+            println!(
+                "{}: {}",
+                file.display(),
+                "compatible with no preset or target".yellow()
+            );
+        } else {
+            println!(
+                "{}: compatible down to {}",
+                file.display(),
+                oldest.join(" / ").green().bold()
+            );
+        }
+    }
+}
+
+fn print_inventory(inventory: &Inventory) {
+    println!("{}: {} ELF files", "INVENTORY".bold(), inventory.file_count);
+    for namespace in inventory.namespaces.values() {
+        let (first, others) = namespace
+            .required_by
+            .split_first()
+            .expect("namespace is required by at least one file");
+        let others = match others.len() {
+            0 => String::new(),
+            1 => " and 1 other file".to_string(),
+            n => format!(" and {} other files", n),
+        };
+        println!(
+            "    {} (required by {}{})",
+            namespace.max_version.to_string().bold(),
+            first.display(),
+            others.dimmed()
+        );
+    }
+}
+
+/// The width of the longest bar of the histograms printed by `--stats`.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Prints the errors checking files, then the merged violations of the files which failed.
+fn print_aggregate(
+    check_result: &CheckResult,
+    aggregate: &Aggregate,
+    demangle: DemangleChoice,
+    verbose: bool,
+) {
+    for file_result in &check_result.file_results {
+        if let FileCheckResult::Error(e) = &file_result.result {
+            print_error(&file_result.file, e);
+        }
+    }
+    let failed = format!("{} of {} files failed", aggregate.failed, aggregate.checked);
+    println!(
+        "{}: {}",
+        "AGGREGATE".bold(),
+        if aggregate.failed > 0 {
+            failed.red()
+        } else {
+            failed.green()
+        }
+    );
+    print_gaps(&aggregate.gaps);
+    let mut previous_count = None;
+    for (violation, count) in &aggregate.violations {
+        if previous_count != Some(count) {
+            let files = match count {
+                1 => "1 file".to_string(),
+                n => format!("{} files", n),
+            };
+            println!("  {}", format!("in {}:", files).dimmed());
+            previous_count = Some(count);
+        }
+        print_violation(violation, demangle, verbose);
+    }
+}
+
+fn print_top_offenders(top: &TopOffenders) {
+    for (title, offenders) in [
+        ("TOP SYMBOLS", &top.symbols),
+        ("TOP VERSIONS", &top.versions),
+    ] {
+        if offenders.is_empty() {
+            continue;
+        }
+        println!("{}:", title.bold());
+        for (offender, files) in offenders {
+            let files = match files {
+                1 => "1 file".to_string(),
+                n => format!("{} files", n),
+            };
+            println!("    {} ({})", offender.bold(), files);
+        }
+    }
+}
+
+fn print_stats(inventory: &Inventory) {
+    println!("{}: {} ELF files", "STATS".bold(), inventory.file_count);
+    for (namespace, histogram) in &inventory.histograms {
+        println!("    {}", namespace.bold());
+        let most = histogram.values().max().copied().unwrap_or_default();
+        // Files which need no version in the namespace pass any maximum version
+        let mut passing = inventory.file_count - histogram.values().sum::<usize>();
+        for (version, count) in histogram {
+            passing += count;
+            let pass = format!(
+                "{:5.1}% pass",
+                passing as f64 * 100.0 / inventory.file_count as f64
+            );
+            let files = if *count == 1 { "file " } else { "files" };
+            println!(
+                "        {:<10} {:>6} {}  {}  {}",
+                version.to_string(),
+                count,
+                files,
+                pass.dimmed(),
+                "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(most)).cyan()
+            );
+        }
+    }
+}
+
+/// Records the violations of each file which failed or passed with warnings in a baseline.
+fn baseline_from_results(check_result: &CheckResult) -> Baseline {
+    let mut baseline = Baseline::default();
+    for file_result in &check_result.file_results {
+        if let FileCheckResult::Fail(violations) | FileCheckResult::Warn(violations) =
+            &file_result.result
+        {
+            for violation in violations {
+                baseline.add(
+                    &file_result.file,
+                    violation_json(violation, DemangleChoice::None),
+                );
+            }
+        }
+    }
+    baseline
+}
+
+/// Returns the maximum versions in namespaces which none of the checked files reference, such as those with misspelt
+/// namespaces. None are returned if no files were checked.
+fn unmatched_requirements<'a>(
+    check_result: &CheckResult,
+    requirements: &'a [String],
+) -> Vec<&'a String> {
+    let checked: Vec<&FileResult> = check_result
+        .file_results
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.result,
+                FileCheckResult::Pass | FileCheckResult::Warn(_) | FileCheckResult::Fail(_)
+            )
+        })
+        .collect();
+    if checked.is_empty() {
+        return Vec::new();
+    }
+    requirements
+        .iter()
+        .filter(|requirement| {
+            requirements::parse_requirement(requirement).is_ok_and(|(version, _)| {
+                !checked.iter().any(|r| {
+                    r.details.namespaces.iter().any(|namespace| {
+                        requirements::namespace_matches(&version.namespace, namespace)
+                    })
+                })
+            })
+        })
+        .collect()
+}
+
+fn get_exit_code(check_result: CheckResult) -> i32 {
+    match (check_result.has_errors(), check_result.has_failures()) {
+        (true, _) => EXIT_ERROR_CHECKING_FILES,
+        (false, true) => EXIT_FILES_FAILED_CHECK,
+        (false, false) => EXIT_PASSED,
+    }
+}
+
+/// The exit codes requested by `--errors-exit-code`, `--fail-exit-code`, `--treat-errors-as-failures` and
+/// `--warn-only`.
+struct ExitCodes {
+    errors: Option<u8>,
+    failures: Option<u8>,
+    errors_as_failures: bool,
+    warn_only: bool,
+}
+
+impl ExitCodes {
+    fn map(&self, exit_code: i32) -> i32 {
+        let exit_code = match exit_code {
+            EXIT_FILES_FAILED_CHECK if self.warn_only => EXIT_PASSED,
+            EXIT_ERROR_CHECKING_FILES if self.errors_as_failures => EXIT_FILES_FAILED_CHECK,
+            exit_code => exit_code,
+        };
+        let custom = match exit_code {
+            EXIT_ERROR_CHECKING_FILES => self.errors,
+            EXIT_FILES_FAILED_CHECK => self.failures,
+            _ => None,
+        };
+        custom.map_or(exit_code, i32::from)
+    }
+}
+
+/// Compares the symbol versions needed by two builds of a file, returning the exit code.
+fn diff_builds(old: &Path, new: &Path) -> i32 {
+    let read_symbols = |path: &Path| {
+        fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| elf::get_version_references(&data))
+            .map(|references| [references.symbols, references.hidden_symbols].concat())
+            .with_context(|| format!("Error reading version references from {}", path.display()))
+    };
+    let (old_symbols, new_symbols) = match (read_symbols(old), read_symbols(new)) {
+        (Ok(old_symbols), Ok(new_symbols)) => (old_symbols, new_symbols),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {:#}", e);
+            return EXIT_ERROR_CHECKING_FILES;
+        }
+    };
+
+    let namespaces = diff::diff_namespaces(&old_symbols, &new_symbols);
+    for change in &namespaces {
+        let version = |version: &Option<Version>| match version {
+            Some(version) => version.to_string(),
+            None => "none".to_string(),
+        };
+        let new = version(&change.new);
+        println!(
+            "{}: {} {} {}",
+            change.namespace.bold(),
+            version(&change.old),
+            "->".dimmed(),
+            if change.is_newer() {
+                new.red()
+            } else {
+                new.green()
+            }
+        );
+    }
+    for change in diff::diff_requirements(&old_symbols, &new_symbols) {
+        print_requirement_change(&change);
+    }
+    if namespaces.iter().any(|change| change.is_newer()) {
+        EXIT_FILES_FAILED_CHECK
+    } else {
+        EXIT_PASSED
+    }
+}
+
+/// Compares the violations in two reports written by `--format json`, returning the exit code.
+fn compare_reports(before: &Path, after: &Path) -> i32 {
+    let comparison = match (Report::read(before), Report::read(after)) {
+        (Ok(before), Ok(after)) => after.compare(&before),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {:#}", e);
+            return EXIT_ERROR_CHECKING_FILES;
+        }
+    };
+
+    for change in &comparison.added {
+        let violation =
+            report::describe_violation(&violation_json(&change.violation, DemangleChoice::None));
+        println!(
+            "{} {}: {}",
+            if change.failed {
+                "+".red()
+            } else {
+                "+".yellow()
+            },
+            change.file,
+            if change.failed {
+                violation.red()
+            } else {
+                violation.yellow()
+            }
+        );
+    }
+    for change in &comparison.removed {
+        println!(
+            "{} {}: {}",
+            "-".green(),
+            change.file,
+            report::describe_violation(&violation_json(&change.violation, DemangleChoice::None))
+                .green()
+        );
+    }
+    if comparison.has_regressions() {
+        EXIT_FILES_FAILED_CHECK
+    } else {
+        EXIT_PASSED
+    }
+}
+
+/// Reads the symbols exported by the old and new builds of a library and prints the changes between them, returning
+/// the exit code.
+fn diff_exports(old: &Path, new: &Path) -> i32 {
+    let read_exports = |path: &Path| {
+        fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| elf::get_exported_symbols(&data))
+            .with_context(|| format!("Error reading exported symbols from {}", path.display()))
+    };
+    let (old_exports, new_exports) = match (read_exports(old), read_exports(new)) {
+        (Ok(old_exports), Ok(new_exports)) => (old_exports, new_exports),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {:#}", e);
+            return EXIT_ERROR_CHECKING_FILES;
+        }
+    };
+
+    let changes = exports::diff_exports(&old_exports, &new_exports);
+    for change in &changes {
+        print_export_change(change);
+    }
+    if changes.iter().any(ExportChange::is_breaking) {
+        EXIT_FILES_FAILED_CHECK
+    } else {
+        EXIT_PASSED
+    }
+}
+
+/// Prints a version script template for the symbols a library exports, returning the exit code.
+fn print_version_script(library: &Path, node: Option<&str>) -> i32 {
+    let result = fs::read(library)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| {
+            Ok((
+                elf::get_exported_symbols(&data)?,
+                elf::get_version_definitions(&data)?,
+            ))
+        })
+        .with_context(|| format!("Error reading exported symbols from {}", library.display()));
+    let (exports, nodes) = match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            return EXIT_ERROR_CHECKING_FILES;
+        }
+    };
+
+    let default_node = node.map(str::to_string).unwrap_or_else(|| {
+        let file_name = library.file_name().unwrap_or_default().to_string_lossy();
+        exports::default_node_name(&file_name)
+    });
+    print!(
+        "{}",
+        exports::version_script(&exports, &nodes, &default_node)
+    );
+    EXIT_PASSED
+}
+
+/// Prints what is known about a symbol from the glibc database and distribution targets, returning the exit code. The
+/// targets are checked for the version given, or if none is, the version new links bind the symbol to.
+fn explain_symbol(symbol: &str) -> i32 {
+    let (name, version) = match symbol.split_once('@') {
+        Some((name, version)) => (name, Some(version.trim_start_matches('@'))),
+        None => (symbol, None),
+    };
+    let version = match version.map(NamespacedVersion::parse).transpose() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            return EXIT_BAD_ARGS;
+        }
+    };
+    let known = glibc_db::find_symbol(name);
+    let Some(version) = version.or_else(|| known.map(|symbol| symbol.version.clone())) else {
+        eprintln!(
+            "Error: {} is not in the glibc database, give its version instead, e.g. {}@GLIBC_2.17",
+            name, name
+        );
+        return EXIT_BAD_ARGS;
+    };
+
+    println!(
+        "{}{}{}",
+        name.bold(),
+        "@".dimmed(),
+        version.to_string().bold()
+    );
+    match known {
+        Some(symbol) => {
+            println!(
+                "    introduced in {}, provided by {}",
+                symbol.versions[0], symbol.library
+            );
+            if symbol.versions.len() > 1 {
+                println!("    new links bind it to {}", symbol.version);
+            }
+        }
+        None => println!("    {}", "not in the glibc database".dimmed()),
+    }
+    let (shipped, not_shipped): (Vec<_>, Vec<_>) = presets::TARGETS
+        .iter()
+        .filter_map(|target| {
+            compatibility::ships_version(target, &version).map(|ships| (target.name, ships))
+        })
+        .partition(|(_, ships)| *ships);
+    let names = |targets: Vec<(&str, bool)>| {
+        targets
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !shipped.is_empty() {
+        println!("    shipped by {}", names(shipped).green());
+    }
+    if !not_shipped.is_empty() {
+        println!("    not shipped by {}", names(not_shipped).red());
+    }
+    EXIT_PASSED
+}
+
+fn print_requirement_change(change: &RequirementChange) {
+    let join = |name: &str, versions: &[NamespacedVersion]| {
+        versions
+            .iter()
+            .map(|version| format!("{}@{}", name, version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match change {
+        RequirementChange::Added(name, version) => {
+            println!(
+                "{} {}",
+                "+".green(),
+                format!("{}@{}", name, version).green()
+            )
+        }
+        RequirementChange::Removed(name, version) => {
+            println!("{} {}", "-".red(), format!("{}@{}", name, version).red())
+        }
+        RequirementChange::Changed { name, old, new } => println!(
+            "{} {} {} {}",
+            "~".yellow(),
+            join(name, old),
+            "->".dimmed(),
+            join(name, new).yellow()
+        ),
+    }
+}
+
+fn print_export_change(change: &ExportChange) {
+    let join = |symbols: &[elf::ExportedSymbol]| {
+        symbols
+            .iter()
+            .map(|symbol| symbol.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match change {
+        ExportChange::Added(symbol) => println!("{} {}", "+".green(), symbol.to_string().green()),
+        ExportChange::Removed(symbol) => println!("{} {}", "-".red(), symbol.to_string().red()),
+        ExportChange::Reversioned { old, new } => println!(
+            "{} {} {} {}",
+            "~".red(),
+            join(old).red(),
+            "->".dimmed(),
+            join(new).yellow()
+        ),
+    }
+}
+
+/// Runs the command line interface, exiting with the result.
+/// An error writing the results of checking files, which exits with the code for errors checking files rather than
+/// that for bad arguments.
+#[derive(Debug)]
+struct OutputError(anyhow::Error);
+
+impl std::fmt::Display for OutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for OutputError {}
+
+/// Runs a subcommand, returning the exit code.
+fn run_command(command: &Command) -> i32 {
+    match command {
+        Command::Diff { old, new } => diff_builds(old, new),
+        Command::Compare { before, after } => compare_reports(before, after),
+        Command::DiffExports { old, new } => diff_exports(old, new),
+        Command::VersionScript { library, node } => print_version_script(library, node.as_deref()),
+        Command::Explain { symbol } => explain_symbol(symbol),
+    }
+}
+
+/// Prints the built-in presets and those of presets files given with `--presets-file`, or the built-in targets.
+fn print_presets(list_presets: bool, user_presets: &UserPresets) {
+    let table = if list_presets {
+        presets::PRESETS
+    } else {
+        presets::TARGETS
+    };
+    for preset in table {
+        println!(
+            "{}: {} {}",
+            preset.name.bold(),
+            preset.requirements.join(", "),
+            format!("({})", preset.description).dimmed()
+        );
+    }
+    if list_presets {
+        for (name, preset) in &user_presets.presets {
+            println!(
+                "{}: {} {}",
+                name.bold(),
+                preset.requirements.join(", "),
+                format!("(from {})", preset.file.display()).dimmed()
+            );
+        }
+    }
+}
+
+/// Checks the options which can't be used together, or which need something else.
+fn validate_args(args: &CliArgs) -> anyhow::Result<()> {
+    if args.tui {
+        if cfg!(not(feature = "tui")) {
+            anyhow::bail!("--tui isn't included in this build");
+        } else if args.format != OutputFormat::Text {
+            anyhow::bail!("--tui can't be used with --format");
+        } else if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+            anyhow::bail!("--tui needs a terminal for its input and output");
+        }
+    }
+    if args.watch && !matches!(args.format, OutputFormat::Text | OutputFormat::Short) {
+        anyhow::bail!("--watch can only be used with --format text or short");
+    }
+    if args.transitive && args.library_path.is_empty() && args.resolve_against.is_none() {
+        anyhow::bail!(
+            "--transitive needs --library-path or --resolve-against to find libraries in"
+        );
+    }
+    Ok(())
+}
+
+/// Gets the sources of files to analyze, and how to find files within them.
+fn read_inputs(args: &CliArgs) -> anyhow::Result<Inputs<'_>> {
+    let library_path = args
+        .transitive
+        .then(|| LibraryPath::new(&args.library_path, args.resolve_against.as_deref()))
+        .transpose()?;
+    Ok(Inputs {
+        files: &args.files,
+        images: &args.images,
+        nix_closures: &args.nix_closures,
+        walk_options: WalkOptions {
+            follow_symlinks: args.follow_symlinks,
+            respect_gitignore: args.respect_gitignore,
+            include: walk::build_globs(&args.include)?,
+            exclude: walk::build_globs(&args.exclude)?,
+        },
+        skip_non_elf: args.skip_non_elf,
+        filter: ElfFilter {
+            architectures: args.arch.clone(),
+            types: args.only.clone(),
+            skip_unsupported: [
+                (args.skip_kernel_modules, UnsupportedKind::KernelModule),
+                (args.skip_core_dumps, UnsupportedKind::Core),
+                (args.skip_debuginfo, UnsupportedKind::Debuginfo),
+            ]
+            .into_iter()
+            .filter_map(|(skip, kind)| skip.then_some(kind))
+            .collect(),
+        },
+        library_path,
+    })
+}
+
+/// Lists what `--inventory`, `--stats`, `--list` or `--oldest-compatible` ask for, returning the exit code, or `None`
+/// when none of them are given.
+fn run_listing(args: &CliArgs, inputs: &Inputs) -> Option<i32> {
+    let check_result = if args.inventory || args.stats {
+        let (inventory, check_result) = take_inventory(inputs, args.best_effort);
+        print_results(&check_result, args.demangle, args.verbose);
+        if args.inventory {
+            print_inventory(&inventory);
+        } else {
+            print_stats(&inventory);
+        }
+        check_result
+    } else if args.list {
+        let (listed, check_result) = list_max_versions(inputs);
+        print_results(&check_result, args.demangle, args.verbose);
+        print_max_versions(&listed);
+        check_result
+    } else if args.oldest_compatible {
+        let (compatibilities, check_result) = find_compatibility(inputs);
+        print_results(&check_result, args.demangle, args.verbose);
+        print_compatibility(&compatibilities);
+        check_result
+    } else {
+        return None;
+    };
+    Some(get_exit_code(check_result))
+}
+
+/// Gets the maximum versions given with `-m`, presets, targets and sysroots, combined.
+fn read_max_versions(args: &CliArgs, user_presets: &UserPresets) -> anyhow::Result<Vec<String>> {
+    let mut presets = user_presets.find_requirements(&args.preset, &args.target)?;
+    if let Some(release) = &args.max_gcc_runtime {
+        let runtime = presets::find_gcc_runtime(release)?;
+        presets.push(runtime.requirements.iter().map(|r| r.to_string()).collect());
+    }
+    if let Some(sysroot) = &args.from_sysroot {
+        presets.push(sysroot::requirements_from_sysroot(sysroot)?);
+    }
+    presets::combine_requirements(&presets, &args.max_version)
+}
+
+/// Applies the settings of the command line, other than the maximum versions, to requirements.
+fn with_settings(
+    args: &CliArgs,
+    requirements: VersionRequirements,
+) -> anyhow::Result<VersionRequirements> {
+    let mut allowed_versions = Vec::new();
+    for library in &args.from_library {
+        allowed_versions.extend(sysroot::allowed_versions_from_library(library)?);
+    }
+    if let (Some(file), Some(policy_name)) = (&args.auditwheel_policy, &args.policy_name) {
+        allowed_versions.extend(auditwheel::read_policy_versions(file, policy_name)?);
+    }
+    let unlisted = match &args.default_max {
+        Some(version) => UnlistedNamespaces::Max(Version::parse(version)?),
+        None if args.deny_unlisted => UnlistedNamespaces::Deny,
+        None => UnlistedNamespaces::Permit,
+    };
+    let min_versions = args
+        .min_version
+        .iter()
+        .map(|version| NamespacedVersion::parse(version))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    requirements
+        .with_allowed_versions(allowed_versions)
+        .with_unlisted(unlisted)
+        .with_minimum_versions(min_versions)
+        .with_ignored_symbols(&args.ignore_symbol)
+}
+
+/// Reads the exceptions files, adding the symbols given with `--allow` and warning of expired exceptions.
+fn read_exceptions(args: &CliArgs) -> anyhow::Result<Exceptions> {
+    let (mut exceptions, expired) = Exceptions::read(&args.exceptions, &exceptions::today())?;
+    for symbol in &args.allow {
+        exceptions.add(ExceptionEntry {
+            symbol: symbol.clone(),
+            path: None,
+            comment: None,
+            expires: None,
+        })?;
+    }
+    for entry in expired {
+        eprintln!(
+            "{}: The exception for {} expired on {}",
+            "Warning".yellow(),
+            entry.symbol,
+            entry.expires.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(exceptions)
+}
+
+/// Builds the policy files are checked against from the command line.
+fn build_policy(args: &CliArgs, requirements: VersionRequirements) -> anyhow::Result<Policy> {
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(Baseline::read)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut link_map = References::new();
+    for path in &args.link_map {
+        for (symbol, files) in blame::read_link_map(path)? {
+            link_map.entry(symbol).or_default().extend(files);
+        }
+    }
+
+    let mut denied_symbols: BTreeMap<String, Option<String>> = BTreeMap::new();
+    if args.deny_dangerous_symbols {
+        for symbol in denylist::DANGEROUS_SYMBOLS {
+            denied_symbols.insert(symbol.name.to_string(), Some(symbol.reason.to_string()));
+        }
+    }
+    for name in &args.deny_symbol {
+        denied_symbols.entry(name.clone()).or_insert(None);
+    }
+
+    let build_globs = |patterns: &Option<Vec<String>>, windows| {
+        patterns
+            .as_deref()
+            .map(|patterns| policy::build_globs(patterns, windows))
+            .transpose()
+    };
+    Ok(Policy {
+        allowed_libs: build_globs(&args.allowed_libs, false)?,
+        expected_interpreters: build_globs(&args.expect_interp, false)?,
+        max_kernel: args.max_kernel.as_deref().map(Version::parse).transpose()?,
+        required_hardening: args.require_hardening.clone(),
+        deny_textrel: args.deny_textrel,
+        require_build_id: args.require_build_id,
+        report_hardening: args.hardening,
+        estimate_static_glibc: args.estimate_static_glibc,
+        report_distros: args.distros,
+        report_hints: args.hints,
+        report_blame: args.blame,
+        report_packages: args.auditable,
+        link_map: Rc::new(link_map),
+        report_details: args.verbose
+            || !matches!(args.format, OutputFormat::Text | OutputFormat::Short),
+        report_max_versions: args.metrics_file.is_some(),
+        // For warning of maximum versions which match no namespace the files reference
+        report_namespaces: !args.max_version.is_empty(),
+        symbol_types: args.symbol_type.clone(),
+        symbol_bindings: args.symbol_binding.clone(),
+        max_sonames: SonameRequirements::parse(&args.max_soname)?,
+        allowed_dlls: build_globs(&args.allowed_dlls, true)?,
+        sysroot_libraries: args
+            .resolve_against
+            .as_deref()
+            .map(SysrootLibraries::new)
+            .transpose()?
+            .map(Rc::new),
+        deny_private: !args.allow_private,
+        hidden_versions: args.hidden_versions,
+        coarse: args.coarse,
+        missing_version_table: args.missing_version_table,
+        glibc_database: args.glibc_database,
+        musl: args.musl,
+        report_unlisted: args.require_all_namespaces,
+        report_removed: args.check_removed_symbols,
+        denied_symbols,
+        baseline: Rc::new(baseline),
+        exceptions: read_exceptions(args)?,
+        best_effort: args.best_effort,
+        expected_version_nodes: args
+            .expect_version_nodes
+            .as_ref()
+            .map(|nodes| nodes.iter().cloned().collect()),
+        ..Policy::new(requirements)
+    })
+}
+
+/// Checks files against each profile given with `--profile`, returning the exit code.
+fn check_profiles(
+    args: &CliArgs,
+    inputs: &Inputs,
+    policy: &Policy,
+    profiles: Vec<(String, VersionRequirements)>,
+) -> anyhow::Result<i32> {
+    if matches!(args.format, OutputFormat::CycloneDx | OutputFormat::Short) {
+        anyhow::bail!(
+            "--format {} can't be used with --profile",
+            args.format.to_possible_value().unwrap().get_name()
+        );
+    }
+    let profiles: Vec<(String, Policy)> = profiles
+        .into_iter()
+        .map(|(name, requirements)| {
+            let policy = Policy {
+                requirements,
+                ..policy.clone()
+            };
+            (name, policy)
+        })
+        .collect();
+    let check_results = check_matrix(inputs, &profiles);
+    match args.format {
+        OutputFormat::Text => {
+            print_matrix_results(&profiles, &check_results, args.demangle, args.verbose)
+        }
+        OutputFormat::Json | OutputFormat::CycloneDx | OutputFormat::Short => {
+            print_json_matrix_results(&profiles, &check_results, args.demangle)
+        }
+    }
+    Ok(if check_results.iter().any(CheckResult::has_errors) {
+        EXIT_ERROR_CHECKING_FILES
+    } else if check_results.iter().any(CheckResult::has_failures) {
+        EXIT_FILES_FAILED_CHECK
+    } else {
+        EXIT_PASSED
+    })
+}
+
+/// Answers requests to check files until the input or socket is closed.
+fn serve(args: &CliArgs, server: &mut Server) -> anyhow::Result<i32> {
+    match &args.socket {
+        Some(socket) => serve_socket(server, socket)?,
+        None => serve_lines(
+            |line| server.respond(line),
+            std::io::stdin().lock(),
+            std::io::stdout().lock(),
+        )?,
+    }
+    Ok(EXIT_PASSED)
+}
+
+/// Checks files, printing the results and writing any metrics and baseline asked for, returning the exit code.
+fn check(args: &CliArgs, inputs: &Inputs, policies: &mut PolicyFinder) -> anyhow::Result<i32> {
+    let check_result = check_files(inputs, policies);
+
+    let top = args.top.map(|count| check_result.top_offenders(count));
+    let aggregate = args.aggregate.then(|| check_result.aggregate());
+    match args.format {
+        #[cfg(feature = "tui")]
+        OutputFormat::Text if args.tui => {
+            let demangle = !matches!(args.demangle, DemangleChoice::None);
+            tui::run(tui_files(&check_result), demangle).map_err(OutputError)?;
+        }
+        OutputFormat::Text => {
+            match &aggregate {
+                Some(aggregate) => {
+                    print_aggregate(&check_result, aggregate, args.demangle, args.verbose)
+                }
+                None if args.collapse_shared => {
+                    print_collapsed_results(&check_result, args.demangle, args.verbose)
+                }
+                None => print_results(&check_result, args.demangle, args.verbose),
+            }
+            if let Some(top) = &top {
+                print_top_offenders(top);
+            }
+        }
+        OutputFormat::Json => print_json_results(
+            &check_result,
+            args.demangle,
+            top.as_ref(),
+            aggregate.as_ref(),
+        ),
+        OutputFormat::CycloneDx => print_cyclonedx_results(&check_result),
+        OutputFormat::Short => print_short_results(&check_result, args.demangle),
+    }
+
+    if let Some(path) = &args.metrics_file {
+        check_result.metrics().write(path).map_err(OutputError)?;
+    }
+
+    if args.watch {
+        watch_inputs(inputs, policies, args.format, args.demangle, args.verbose);
+    }
+
+    if let Some(path) = &args.write_baseline {
+        let baseline = baseline_from_results(&check_result);
+        baseline.write(path).map_err(OutputError)?;
+        let (violations, files) = baseline.count();
+        eprintln!(
+            "Wrote {} violations in {} files to {}",
+            violations,
+            files,
+            path.display()
+        );
+        return Ok(if check_result.has_errors() {
+            EXIT_ERROR_CHECKING_FILES
+        } else {
+            EXIT_PASSED
+        });
+    }
+
+    let unmatched = unmatched_requirements(&check_result, &args.max_version);
+    for requirement in &unmatched {
+        eprintln!(
+            "{}: -m {} matched no namespace referenced by the checked files",
+            if args.strict_requirements {
+                "Error".red()
+            } else {
+                "Warning".yellow()
+            },
+            requirement
+        );
+    }
+
+    Ok(match get_exit_code(check_result) {
+        EXIT_PASSED if args.strict_requirements && !unmatched.is_empty() => EXIT_FILES_FAILED_CHECK,
+        exit_code => exit_code,
+    })
+}
+
+/// Runs the command with parsed arguments, returning the exit code.
+fn run_args(mut args: CliArgs, exit_codes: &ExitCodes) -> anyhow::Result<i32> {
+    if args.staged {
+        args.files.extend(git::staged_files()?);
+        args.skip_non_elf = true;
+    }
+
+    configure_colors(&args.color);
+
+    if let Some(command) = &args.command {
+        return Ok(run_command(command));
+    }
+
+    // The environment only gives maximum versions when nothing on the command line does, and config files take
+    // precedence over them
+    let mut max_version_from_env = false;
+    if args.max_version.is_empty()
+        && args.profile.is_empty()
+        && args.preset.is_empty()
+        && args.target.is_empty()
+        && args.max_gcc_runtime.is_none()
+        && args.from_sysroot.is_none()
+        && args.from_library.is_empty()
+        && args.auditwheel_policy.is_none()
+        && let Ok(list) = std::env::var(MAX_VERSION_ENV)
+    {
+        args.max_version = requirements::split_requirements(&list);
+        max_version_from_env = true;
+    }
+    args.max_version = requirements::read_requirement_files(&args.max_version)?;
+
+    let user_presets = UserPresets::read(&args.presets_files)?;
+
+    if args.list_presets || args.list_targets {
+        print_presets(args.list_presets, &user_presets);
+        return Ok(EXIT_PASSED);
+    }
+
+    validate_args(&args)?;
+    let inputs = read_inputs(&args)?;
+
+    if let Some(exit_code) = run_listing(&args, &inputs) {
+        return Ok(exit_codes.map(exit_code));
+    }
+
+    let max_versions = read_max_versions(&args, &user_presets)?;
+    let requirements = with_settings(&args, VersionRequirements::parse(&max_versions)?)?;
+    let profiles = args
+        .profile
+        .iter()
+        .map(|name| {
+            let requirements = VersionRequirements::parse(&user_presets.find_profile(name)?)?;
+            Ok((name.clone(), with_settings(&args, requirements)?))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // Without config files, the maximum versions can only be given on the command line, or in requests
+    if args.no_config && requirements.is_empty() && profiles.is_empty() && !args.serve {
+        anyhow::bail!(
+            "No maximum versions given with -m, --preset, --target, --max-gcc-runtime, --from-sysroot, --from-library or --auditwheel-policy"
+        );
+    }
+
+    let policy = build_policy(&args, requirements)?;
+
+    if !profiles.is_empty() {
+        return check_profiles(&args, &inputs, &policy, profiles)
+            .map(|exit_code| exit_codes.map(exit_code));
+    }
+
+    // Presets, targets and sysroots have already been combined with -m
+    let (max_versions, default_max_versions) = if max_version_from_env {
+        (Vec::new(), max_versions)
+    } else {
+        (max_versions, Vec::new())
+    };
+    let defaults = Config {
+        max_version: default_max_versions,
+        ..Config::default()
+    };
+    let config = Config {
+        max_version: max_versions,
+        // Left unset unless given, so that config files can allow private versions
+        allow_private: args.allow_private.then_some(true),
+        allowed_libs: args.allowed_libs.clone(),
+        ..Config::default()
+    };
+    if args.serve {
+        let mut server = Server {
+            policy,
+            config,
+            defaults,
+            user_presets,
+            discover: !args.no_config,
+            walk_options: inputs.walk_options,
+            skip_non_elf: inputs.skip_non_elf,
+            filter: inputs.filter,
+            demangle: args.demangle,
+            policies: Vec::new(),
+        };
+        return serve(&args, &mut server);
+    }
+
+    let mut policies =
+        PolicyFinder::new(policy, config, user_presets, !args.no_config).with_defaults(defaults);
+    check(&args, &inputs, &mut policies).map(|exit_code| exit_codes.map(exit_code))
+}
+
+pub fn run() {
+    run_from(std::env::args_os())
+}
+
+/// Runs the command line interface with the given arguments, the first being the command's name, exiting with the
+/// result.
+pub fn run_from<I, T>(args: I)
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let args = CliArgs::parse_from(args);
+    let exit_codes = ExitCodes {
+        errors: args.errors_exit_code,
+        failures: args.fail_exit_code,
+        errors_as_failures: args.treat_errors_as_failures,
+        warn_only: args.warn_only,
+    };
+    let exit_code = match run_args(args, &exit_codes) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            if e.is::<OutputError>() {
+                exit_codes.map(EXIT_ERROR_CHECKING_FILES)
+            } else {
+                EXIT_BAD_ARGS
+            }
+        }
+    };
+    std::process::exit(exit_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn file_result_new_with_empty_symbols_is_pass() {
+        let result = FileResult::new(PathBuf::from("test.so"), Ok(vec![]));
+        assert!(matches!(result.result, FileCheckResult::Pass));
+    }
+
+    #[test]
+    fn file_result_new_with_symbols_is_fail() {
+        let symbol = SymbolVersion::parse("malloc", "GLIBC_2.14", None).unwrap();
+        let violations = vec![Violation::Symbol(symbol.clone())];
+        let result = FileResult::new(PathBuf::from("test.so"), Ok(violations));
+        match result.result {
+            FileCheckResult::Fail(violations) => {
+                assert_eq!(violations, vec![Violation::Symbol(symbol)]);
+            }
+            _ => panic!("Expected Fail result"),
+        }
+    }
+
+    #[test]
+    fn file_result_new_with_error_is_error() {
+        let error = anyhow!("Test error");
+        let result = FileResult::new(PathBuf::from("test.so"), Err(error));
+        assert!(matches!(result.result, FileCheckResult::Error(_)));
+    }
+
+    #[test]
+    fn check_result_has_errors_true_when_error_present() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("bad.so"),
+                result: FileCheckResult::Error(anyhow!("Test error")),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert!(check_result.has_errors());
+    }
+
+    #[test]
+    fn check_result_has_errors_false_when_no_errors() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("fail.so"),
+                result: FileCheckResult::Fail(vec![Violation::Symbol(
+                    SymbolVersion::parse("malloc", "GLIBC_2.14", None).unwrap(),
+                )]),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert!(!check_result.has_errors());
+    }
+
+    #[test]
+    fn check_result_has_failures_true_when_failure_present() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("fail.so"),
+                result: FileCheckResult::Fail(vec![Violation::Symbol(
+                    SymbolVersion::parse("malloc", "GLIBC_2.14", None).unwrap(),
+                )]),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert!(check_result.has_failures());
+    }
+
+    #[test]
+    fn check_result_has_failures_false_when_no_failures() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("error.so"),
+                result: FileCheckResult::Error(anyhow!("Test error")),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert!(!check_result.has_failures());
+    }
+
+    #[test]
+    fn check_file_reports_error_for_non_elf_file() {
+        let dir = TempDir::new("check-non-elf");
+        let file = dir.write("script.sh", b"#!/bin/sh\n");
+        let policy = Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap());
+        let results = check_file(
+            &file,
+            &policy,
+            &ElfFilter::default(),
+            &mut ContentIndex::default(),
+            false,
+        );
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].result, FileCheckResult::Error(_)));
+    }
+
+    #[test]
+    fn check_file_skips_non_elf_file_when_requested() {
+        let dir = TempDir::new("skip-non-elf");
+        let file = dir.write("script.sh", b"#!/bin/sh\n");
+        let policy = Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap());
+        let results = check_file(
+            &file,
+            &policy,
+            &ElfFilter::default(),
+            &mut ContentIndex::default(),
+            true,
+        );
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].result,
+            FileCheckResult::Skipped(SkipReason::NotElf)
+        ));
+    }
+
+    #[test]
+    fn check_files_summarizes_skipped_non_elf_files() {
+        let dir = TempDir::new("skip-non-elf-summary");
+        dir.write("app", archive::tests::ELF_STUB);
+        dir.write("build.sh", b"#!/bin/sh\n");
+        dir.write("README", b"An application\n");
+        let inputs = Inputs {
+            files: std::slice::from_ref(&dir.path),
+            images: &[],
+            nix_closures: &[],
+            walk_options: WalkOptions::default(),
+            skip_non_elf: true,
+            filter: ElfFilter::default(),
+            library_path: None,
+        };
+        let policy = Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap());
+        let mut policies =
+            PolicyFinder::new(policy, Config::default(), UserPresets::default(), false);
+        let check_result = check_files(&inputs, &mut policies);
+        assert_eq!(
+            check_result.skipped_summaries(),
+            ["Skipped 2 non-ELF files"]
+        );
+    }
+
+    #[test]
+    fn check_files_passes_when_only_non_elf_files_are_skipped() {
+        let dir = TempDir::new("skip-only-non-elf");
+        dir.write("build.sh", b"#!/bin/sh\n");
+        let inputs = Inputs {
+            files: std::slice::from_ref(&dir.path),
+            images: &[],
+            nix_closures: &[],
+            walk_options: WalkOptions::default(),
+            skip_non_elf: true,
+            filter: ElfFilter::default(),
+            library_path: None,
+        };
+        let policy = Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap());
+        let mut policies =
+            PolicyFinder::new(policy, Config::default(), UserPresets::default(), false);
+        let check_result = check_files(&inputs, &mut policies);
+        assert_eq!(check_result.skipped_summaries(), ["Skipped 1 non-ELF file"]);
+        assert_eq!(get_exit_code(check_result), EXIT_PASSED);
+
+        // Without --skip-non-elf, files given directly are errors
+        let mut policies = PolicyFinder::new(
+            Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap()),
+            Config::default(),
+            UserPresets::default(),
+            false,
+        );
+        let file = dir.path.join("build.sh");
+        let inputs = Inputs {
+            files: std::slice::from_ref(&file),
+            skip_non_elf: false,
+            ..inputs
+        };
+        assert_eq!(
+            get_exit_code(check_files(&inputs, &mut policies)),
+            EXIT_ERROR_CHECKING_FILES
+        );
+    }
+
+    #[test]
+    fn check_file_checks_macho_deployment_target() {
+        let dir = TempDir::new("check-macho");
+        let data = macho::tests::build_macho(
+            &[(
+                object::macho::LC_BUILD_VERSION,
+                vec![object::macho::PLATFORM_MACOS, 12 << 16, 0, 0],
+            )],
+            &[],
+        );
+        let file = dir.write("app", &data);
+        let check = |requirement: &str| {
+            let policy =
+                Policy::new(VersionRequirements::parse(&[requirement.to_string()]).unwrap());
+            check_file(
+                &file,
+                &policy,
+                &ElfFilter::default(),
+                &mut ContentIndex::default(),
+                false,
+            )
+        };
+        assert!(matches!(
+            check("macos_12.0")[0].result,
+            FileCheckResult::Pass
+        ));
+        assert!(matches!(
+            &check("macos_11.0")[0].result,
+            FileCheckResult::Fail(v) if matches!(&v[..], [Violation::DeploymentTarget { platform, version, .. }]
+                if platform == "macos" && version.to_string() == "12")
+        ));
+    }
+
+    #[test]
+    fn check_file_classifies_core_dumps() {
+        let dir = TempDir::new("check-core");
+        let file = dir.write("core", &elf::tests::build_elf(object::elf::ET_CORE, &[]));
+        let policy = Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap());
+        let check = |filter: &ElfFilter| {
+            check_file(&file, &policy, filter, &mut ContentIndex::default(), false)
+                .remove(0)
+                .result
+        };
+        assert!(matches!(
+            check(&ElfFilter::default()),
+            FileCheckResult::Unsupported(UnsupportedKind::Core)
+        ));
+        let filter = ElfFilter {
+            skip_unsupported: vec![UnsupportedKind::Core],
+            ..ElfFilter::default()
+        };
+        assert!(matches!(
+            check(&filter),
+            FileCheckResult::Skipped(SkipReason::Unsupported(UnsupportedKind::Core))
+        ));
+    }
+
+    #[test]
+    fn check_file_checks_each_fat_macho_slice() {
+        let dir = TempDir::new("check-fat-macho");
+        let data = macho::tests::build_fat_macho(&[
+            (
+                object::macho::CPU_TYPE_X86_64,
+                macho::tests::build_macos_slice(object::macho::CPU_TYPE_X86_64, 10, 13),
+            ),
+            (
+                object::macho::CPU_TYPE_ARM64,
+                macho::tests::build_macos_slice(object::macho::CPU_TYPE_ARM64, 11, 0),
+            ),
+        ]);
+        let file = dir.write("app", &data);
+        let policy = Policy::new(VersionRequirements::parse(&["macos_10.15".to_string()]).unwrap());
+        let results = check_file(
+            &file,
+            &policy,
+            &ElfFilter::default(),
+            &mut ContentIndex::default(),
+            true,
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].file, dir.path.join("app:x86_64"));
+        assert!(matches!(results[0].result, FileCheckResult::Pass));
+        assert_eq!(results[1].file, dir.path.join("app:arm64"));
+        assert!(matches!(results[1].result, FileCheckResult::Fail(_)));
+    }
+
+    #[test]
+    fn check_elf_reports_static_executables() {
+        let data = elf::tests::build_static_elf(b"GLIBC_2.34\0");
+        let policy = Policy {
+            estimate_static_glibc: true,
+            ..Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap())
+        };
+        let result = check_elf(
+            PathBuf::from("static"),
+            &data,
+            &policy,
+            &ElfFilter::default(),
+            &mut ContentIndex::default(),
+        );
+        assert!(matches!(result.result, FileCheckResult::Static));
+        assert_eq!(
+            result.details.static_glibc,
+            Some(NamespacedVersion::parse("GLIBC_2.34").unwrap())
+        );
+    }
+
+    #[test]
+    fn check_elf_reports_musl_binaries() {
+        let data = elf::tests::build_elf_with_interpreter("/lib/ld-musl-x86_64.so.1");
+        let check = |musl| {
+            let policy = Policy {
+                musl,
+                ..Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap())
+            };
+            check_elf(
+                PathBuf::from("musl"),
+                &data,
+                &policy,
+                &ElfFilter::default(),
+                &mut ContentIndex::default(),
+            )
+            .result
+        };
+        assert!(matches!(check(MuslBinaries::Pass), FileCheckResult::Musl));
+        assert!(matches!(
+            check(MuslBinaries::Fail),
+            FileCheckResult::Fail(v) if v == vec![Violation::MuslLinked]
+        ));
+    }
+
+    #[test]
+    fn check_elf_warns_on_missing_version_table() {
+        let data = elf::tests::build_elf(object::elf::ET_DYN, b"");
+        let policy = Policy {
+            missing_version_table: MissingVersionTable::Warn,
+            ..Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap())
+        };
+        let result = check_elf(
+            PathBuf::from("musl.so"),
+            &data,
+            &policy,
+            &ElfFilter::default(),
+            &mut ContentIndex::default(),
+        );
+        assert!(matches!(
+            &result.result,
+            FileCheckResult::Warn(v) if *v == vec![Violation::MissingVersionTable]
+        ));
+        let check_result = CheckResult {
+            file_results: vec![result],
+        };
+        assert_eq!(get_exit_code(check_result), EXIT_PASSED);
+    }
+
+    #[test]
+    fn inventory_reports_unreadable_symbol_versions() {
+        let dir = TempDir::new("inventory-unreadable");
+        let file = dir.write(
+            "app",
+            &elf::tests::test_executable_with_unreadable_version(),
+        );
+        let inputs = Inputs {
+            files: std::slice::from_ref(&file),
+            images: &[],
+            nix_closures: &[],
+            walk_options: WalkOptions::default(),
+            skip_non_elf: false,
+            filter: ElfFilter::default(),
+            library_path: None,
+        };
+        let (inventory, check_result) = take_inventory(&inputs, false);
+        assert_eq!(inventory.file_count, 1);
+        assert!(inventory.namespaces.contains_key("GLIBC"));
+        assert!(matches!(
+            &check_result.file_results[..],
+            [FileResult { result: FileCheckResult::Fail(v), .. }]
+                if matches!(&v[..], [Violation::UnreadableVersion(_)])
+        ));
+        let (_, check_result) = take_inventory(&inputs, true);
+        assert!(matches!(
+            &check_result.file_results[..],
+            [FileResult {
+                result: FileCheckResult::Warn(_),
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn check_files_reports_copies_as_duplicates() {
+        let dir = TempDir::new("check-duplicates");
+        let first = dir.write("a/app", archive::tests::ELF_STUB);
+        let copy = dir.write("b/app", archive::tests::ELF_STUB);
+        let excepted = dir.write("c/app", archive::tests::ELF_STUB);
+        let baselined = dir.write("d/app", archive::tests::ELF_STUB);
+        let inputs = Inputs {
+            files: std::slice::from_ref(&dir.path),
+            images: &[],
+            nix_closures: &[],
+            walk_options: WalkOptions::default(),
+            skip_non_elf: false,
+            filter: ElfFilter::default(),
+            library_path: None,
+        };
+        let mut exceptions = Exceptions::default();
+        exceptions
+            .add(ExceptionEntry {
+                symbol: "stat@GLIBC_2.33".to_string(),
+                path: Some(format!("{}/c/*", dir.path.display())),
+                comment: None,
+                expires: None,
+            })
+            .unwrap();
+        let mut baseline = Baseline::default();
+        baseline.add(&baselined, json!({"kind": "text_relocations"}));
+        let policy = Policy {
+            exceptions,
+            baseline: Rc::new(baseline),
+            ..Policy::new(VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap())
+        };
+        let mut policies =
+            PolicyFinder::new(policy, Config::default(), UserPresets::default(), false);
+        let results = check_files(&inputs, &mut policies).file_results;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].file, first);
+        assert!(!matches!(results[0].result, FileCheckResult::Duplicate(_)));
+        assert_eq!(results[1].file, copy);
+        assert!(matches!(&results[1].result, FileCheckResult::Duplicate(f) if *f == first));
+        // Copies with their own exceptions or baseline entries are checked
+        assert_eq!(results[2].file, excepted);
+        assert!(!matches!(results[2].result, FileCheckResult::Duplicate(_)));
+        assert_eq!(results[3].file, baselined);
+        assert!(!matches!(results[3].result, FileCheckResult::Duplicate(_)));
+    }
+
+    #[test]
+    fn check_matrix_reports_each_profile() {
+        let files = [
+            std::env::current_exe().unwrap(),
+            PathBuf::from("/nonexistent/app"),
+        ];
+        let inputs = Inputs {
+            files: &files,
+            images: &[],
+            nix_closures: &[],
+            walk_options: WalkOptions::default(),
+            skip_non_elf: false,
+            filter: ElfFilter::default(),
+            library_path: None,
+        };
+        let profile = |name: &str, requirement: &str| {
+            let requirements = VersionRequirements::parse(&[requirement.to_string()]).unwrap();
+            (name.to_string(), Policy::new(requirements))
+        };
+        let profiles = [profile("old", "GLIBC_2.17"), profile("new", "GLIBC_2.99")];
+        let check_results = check_matrix(&inputs, &profiles);
+        assert_eq!(check_results.len(), 2);
+        assert!(matches!(
+            &check_results[0].file_results[..],
+            [
+                FileResult {
+                    result: FileCheckResult::Fail(_),
+                    ..
+                },
+                FileResult {
+                    result: FileCheckResult::Error(_),
+                    ..
+                }
+            ]
+        ));
+        assert!(matches!(
+            &check_results[1].file_results[..],
+            [
+                FileResult {
+                    result: FileCheckResult::Pass,
+                    ..
+                },
+                FileResult {
+                    result: FileCheckResult::Error(_),
+                    ..
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn file_result_json_includes_violations_and_details() {
+        let file_result = FileResult {
+            file: PathBuf::from("app"),
+            result: FileCheckResult::Fail(vec![
+                Violation::MissingBuildId,
+                Violation::Symbol(SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap()),
+            ]),
+            details: Details {
+                header: Some(elf::ElfHeader {
+                    machine: "aarch64".to_string(),
+                    class: 64,
+                    little_endian: true,
+                    os_abi: "SYSV".to_string(),
+                    file_type: "DYN".to_string(),
+                }),
+                interpreter: Some("/lib64/ld-linux-x86-64.so.2".to_string()),
+                ..Details::default()
+            },
+        };
+        assert_eq!(
+            file_result_json(&file_result, None, DemangleChoice::None),
+            json!({
+                "file": "app",
+                "result": "fail",
+                "violations": [
+                    {"kind": "symbol", "name": "stat", "version": "GLIBC_2.33", "file": null},
+                    {"kind": "missing_build_id"},
+                ],
+                "details": {
+                    "build_id": null,
+                    "elf": {
+                        "machine": "aarch64",
+                        "class": 64,
+                        "endianness": "little",
+                        "os_abi": "SYSV",
+                        "type": "DYN",
+                    },
+                    "interpreter": "/lib64/ld-linux-x86-64.so.2",
+                    "version_nodes": [],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn json_output_reads_back_with_same_verdicts() {
+        let file_result = |file: &str, result| FileResult {
+            file: PathBuf::from(file),
+            result,
+            details: Details::default(),
+        };
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(
+                    "app",
+                    FileCheckResult::Fail(vec![Violation::MissingBuildId]),
+                ),
+                file_result(
+                    "lib.so",
+                    FileCheckResult::Warn(vec![Violation::MissingBuildId]),
+                ),
+                file_result("copy/app", FileCheckResult::Duplicate(PathBuf::from("app"))),
+                file_result(
+                    "copy/lib.so",
+                    FileCheckResult::Duplicate(PathBuf::from("lib.so")),
+                ),
+            ],
+        };
+        let report: crate::CheckReport = serde_json::from_value(
+            json!({ "files": check_result.files_json(DemangleChoice::None) }),
+        )
+        .unwrap();
+        assert_eq!(
+            report
+                .files
+                .iter()
+                .map(|file| (file.result, file.passed()))
+                .collect::<Vec<_>>(),
+            vec![
+                (FileOutcome::Fail, false),
+                (FileOutcome::Warn, true),
+                (FileOutcome::Duplicate, false),
+                (FileOutcome::Duplicate, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn serves_requests_as_lines_of_json() {
+        let mut server = Server {
+            policy: Policy::new(VersionRequirements::parse(&["GLIBC_2.0".to_string()]).unwrap()),
+            config: Config::default(),
+            defaults: Config::default(),
+            user_presets: UserPresets::default(),
+            discover: false,
+            walk_options: WalkOptions::default(),
+            skip_non_elf: false,
+            filter: ElfFilter::default(),
+            demangle: DemangleChoice::None,
+            policies: Vec::new(),
+        };
+        let exe = std::env::current_exe().unwrap();
+        let input = format!(
+            "{}\n\n{}\n{}\nnot json\n",
+            json!({"id": 1, "files": [exe]}),
+            json!({"id": "b", "files": ["missing"], "max_version": ["GLIBC_99.0"]}),
+            json!({"files": ["missing"], "max_version": ["GLIBC"]}),
+        );
+        let mut output = Vec::new();
+        serve_lines(|line| server.respond(line), input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["files"][0]["result"], "fail");
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(responses[1]["files"][0]["result"], "error");
+        assert!(responses[2]["error"].is_string());
+        assert!(
+            responses[3]["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid request")
+        );
+        assert_eq!(server.policies.len(), 2);
+    }
+
+    #[test]
+    fn serve_keeps_policies_of_recent_requests() {
+        let mut server = Server {
+            policy: Policy::new(VersionRequirements::parse(&["GLIBC_2.0".to_string()]).unwrap()),
+            config: Config::default(),
+            defaults: Config::default(),
+            user_presets: UserPresets::default(),
+            discover: false,
+            walk_options: WalkOptions::default(),
+            skip_non_elf: false,
+            filter: ElfFilter::default(),
+            demangle: DemangleChoice::None,
+            policies: Vec::new(),
+        };
+        let request = |minor| json!({"files": [], "max_version": [format!("GLIBC_2.{}", minor)]});
+        for minor in 0..=SERVE_POLICIES {
+            server.respond(&request(minor).to_string());
+        }
+        server.respond(&request(1).to_string());
+        let kept: Vec<String> = server
+            .policies
+            .iter()
+            .map(|(max_version, _)| max_version.as_ref().unwrap()[0].clone())
+            .collect();
+        assert_eq!(kept.len(), SERVE_POLICIES);
+        assert!(!kept.contains(&"GLIBC_2.0".to_string()));
+        assert_eq!(kept.last().unwrap(), "GLIBC_2.1");
+    }
+
+    #[test]
+    fn short_result_lists_violations_in_a_line() {
+        let file_result = FileResult::new(
+            PathBuf::from("app"),
+            Ok(vec![
+                Violation::MissingBuildId,
+                Violation::Symbol(SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap()),
+                Violation::DisallowedLibrary {
+                    library: "libssl.so.3".to_string(),
+                },
+            ]),
+        );
+        let line = short_result(&file_result, DemangleChoice::None).unwrap();
+        assert!(line.starts_with("app: "));
+        assert!(line.ends_with(
+            " stat@GLIBC_2.33, disallowed_library (library: libssl.so.3), missing_build_id"
+        ));
+        let error = FileResult::new(PathBuf::from("lib.so"), Err(anyhow!("Error reading file")));
+        assert!(
+            short_result(&error, DemangleChoice::None)
+                .unwrap()
+                .ends_with(" Error reading file")
+        );
+        let passed = FileResult::new(PathBuf::from("tool"), Ok(vec![]));
+        assert_eq!(short_result(&passed, DemangleChoice::None), None);
+    }
+
+    #[test]
+    fn cyclonedx_json_has_component_for_each_file_checked() {
+        let check_result = CheckResult {
+            file_results: vec![
+                FileResult {
+                    file: PathBuf::from("app"),
+                    result: FileCheckResult::Fail(vec![Violation::MissingBuildId]),
+                    details: Details {
+                        max_versions: vec![NamespacedVersion::parse("GLIBC_2.33").unwrap()],
+                        needed_libraries: vec!["libc.so.6".to_string()],
+                        ..Details::default()
+                    },
+                },
+                FileResult::new(PathBuf::from("broken"), Err(anyhow!("Error reading file"))),
+            ],
+        };
+        let bom = cyclonedx_json(&check_result);
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(
+            bom["components"],
+            json!([{
+                "type": "file",
+                "bom-ref": "app",
+                "name": "app",
+                "properties": [
+                    {"name": "symbol-version-check:result", "value": "fail"},
+                    {"name": "symbol-version-check:needs", "value": "GLIBC_2.33"},
+                    {"name": "symbol-version-check:needed", "value": "libc.so.6"},
+                ],
+            }])
+        );
+    }
+
+    #[test]
+    fn aggregates_failures() {
+        let stat = Violation::Symbol(SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap());
+        let getrandom =
+            Violation::Symbol(SymbolVersion::parse("getrandom", "GLIBC_2.25", None).unwrap());
+        let file_result = |file: &str, result, needed: &str| FileResult {
+            file: PathBuf::from(file),
+            result,
+            details: Details {
+                gaps: vec![NamespaceGap {
+                    namespace: "GLIBC".to_string(),
+                    needed: Version::parse(needed).unwrap(),
+                    limit: Some(Version::parse("2.17").unwrap()),
+                }],
+                ..Details::default()
+            },
+        };
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(
+                    "a.so",
+                    FileCheckResult::Fail(vec![getrandom.clone()]),
+                    "2.25",
+                ),
+                file_result(
+                    "b.so",
+                    FileCheckResult::Fail(vec![stat.clone(), getrandom.clone()]),
+                    "2.33",
+                ),
+                file_result(
+                    "c.so",
+                    FileCheckResult::Duplicate(PathBuf::from("a.so")),
+                    "2.25",
+                ),
+                file_result("d.so", FileCheckResult::Pass, "2.25"),
+            ],
+        };
+        let aggregate = check_result.aggregate();
+        assert_eq!((aggregate.checked, aggregate.failed), (4, 3));
+        assert_eq!(aggregate.violations, vec![(getrandom, 3), (stat, 1)]);
+        assert_eq!(aggregate.gaps.len(), 1);
+        assert_eq!(aggregate.gaps[0].needed, Version::parse("2.33").unwrap());
+    }
+
+    #[test]
+    fn counts_metrics() {
+        let file_result = |file: &str, result, max_versions: &[&str]| FileResult {
+            file: PathBuf::from(file),
+            result,
+            details: Details {
+                max_versions: max_versions
+                    .iter()
+                    .map(|version| NamespacedVersion::parse(version).unwrap())
+                    .collect(),
+                ..Details::default()
+            },
+        };
+        let stat = Violation::Symbol(SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap());
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(
+                    "a.so",
+                    FileCheckResult::Fail(vec![stat]),
+                    &["GLIBC_2.33", "GLIBCXX_3.4.21"],
+                ),
+                file_result("b.so", FileCheckResult::Pass, &["GLIBC_2.17", "GCC_4.2"]),
+                file_result("c.so", FileCheckResult::Error(anyhow!("Bad file")), &[]),
+            ],
+        };
+        let max_versions: Vec<String> = ["GCC_4.2", "GLIBC_2.33", "GLIBCXX_3.4.21"]
+            .map(String::from)
+            .to_vec();
+        let metrics = check_result.metrics();
+        assert_eq!((metrics.files_checked, metrics.files_failed), (2, 1));
+        assert_eq!(
+            metrics
+                .max_versions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            max_versions
+        );
+    }
+
+    #[test]
+    fn finds_shared_violations() {
+        let symbol =
+            |name, version| Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap());
+        let file_result = |file: &str, result| FileResult {
+            file: PathBuf::from(file),
+            result,
+            details: Details::default(),
+        };
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(
+                    "a.so",
+                    FileCheckResult::Fail(vec![
+                        symbol("stat", "GLIBC_2.33"),
+                        symbol("getrandom", "GLIBC_2.25"),
+                    ]),
+                ),
+                file_result(
+                    "b.so",
+                    FileCheckResult::Warn(vec![symbol("stat", "GLIBC_2.33")]),
+                ),
+                file_result(
+                    "c.so",
+                    FileCheckResult::Fail(vec![symbol("fstat", "GLIBC_2.33")]),
+                ),
+                // Copies share the violations of the first file
+                file_result(
+                    "copy/c.so",
+                    FileCheckResult::Duplicate(PathBuf::from("c.so")),
+                ),
+            ],
+        };
+        let stat = symbol("stat", "GLIBC_2.33");
+        let fstat = symbol("fstat", "GLIBC_2.33");
+        assert_eq!(
+            find_shared_violations(&check_result),
+            BTreeMap::from([(&stat, 2), (&fstat, 2)])
+        );
+    }
+
+    #[test]
+    fn finds_top_offenders() {
+        let symbol =
+            |name, version| Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap());
+        let file_result = |file: &str, result| FileResult {
+            file: PathBuf::from(file),
+            result,
+            details: Details::default(),
+        };
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(
+                    "a.so",
+                    FileCheckResult::Fail(vec![
+                        symbol("stat", "GLIBC_2.33"),
+                        symbol("getrandom", "GLIBC_2.25"),
+                    ]),
+                ),
+                file_result(
+                    "b.so",
+                    FileCheckResult::Fail(vec![
+                        symbol("stat", "GLIBC_2.33"),
+                        symbol("fstat", "GLIBC_2.33"),
+                    ]),
+                ),
+                file_result(
+                    "c.so",
+                    FileCheckResult::Warn(vec![symbol("getrandom", "GLIBC_2.25")]),
+                ),
+                // Copies fail for the same symbols as the first file
+                file_result(
+                    "copy/b.so",
+                    FileCheckResult::Duplicate(PathBuf::from("b.so")),
+                ),
+            ],
+        };
+        assert_eq!(
+            check_result.top_offenders(2),
+            TopOffenders {
+                symbols: vec![
+                    ("stat@GLIBC_2.33".to_string(), 3),
+                    ("fstat@GLIBC_2.33".to_string(), 2),
+                ],
+                versions: vec![("GLIBC_2.33".to_string(), 3), ("GLIBC_2.25".to_string(), 1),],
+            }
+        );
+    }
+
+    #[test]
+    fn unmatched_requirements_are_reported() {
+        let file_result = |result, namespaces: &[&str]| FileResult {
+            file: PathBuf::from("test.so"),
+            result,
+            details: Details {
+                namespaces: namespaces.iter().map(|n| n.to_string()).collect(),
+                ..Details::default()
+            },
+        };
+        let requirements = ["GLIBC_2.17".to_string(), "GLIBCX_3.4.19".to_string()];
+        let check_result = CheckResult {
+            file_results: vec![
+                file_result(FileCheckResult::Pass, &["GLIBC", "GCC"]),
+                file_result(FileCheckResult::Skipped(SkipReason::ElfType), &["GLIBCX"]),
+            ],
+        };
+        assert_eq!(
+            unmatched_requirements(&check_result, &requirements),
+            vec!["GLIBCX_3.4.19"]
+        );
+        let check_result = CheckResult {
+            file_results: vec![file_result(
+                FileCheckResult::Skipped(SkipReason::ElfType),
+                &[],
+            )],
+        };
+        assert!(unmatched_requirements(&check_result, &requirements).is_empty());
+    }
+
+    #[test]
+    fn get_exit_code_all_pass_returns_success() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("test1.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("test2.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert_eq!(get_exit_code(check_result), EXIT_PASSED);
+    }
+
+    #[test]
+    fn get_exit_code_with_failures_returns_failure_code() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("bad.so"),
+                result: FileCheckResult::Fail(vec![Violation::Symbol(
+                    SymbolVersion::parse("malloc", "GLIBC_2.14", None).unwrap(),
+                )]),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert_eq!(get_exit_code(check_result), EXIT_FILES_FAILED_CHECK);
+    }
+
+    #[test]
+    fn get_exit_code_with_errors_returns_error_code() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("good.so"),
+                result: FileCheckResult::Pass,
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("error.so"),
+                result: FileCheckResult::Error(anyhow!("Test error")),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert_eq!(get_exit_code(check_result), EXIT_ERROR_CHECKING_FILES);
+    }
+
+    #[test]
+    fn get_exit_code_errors_take_precedence_over_failures() {
+        let file_results = vec![
+            FileResult {
+                file: PathBuf::from("fail.so"),
+                result: FileCheckResult::Fail(vec![Violation::Symbol(
+                    SymbolVersion::parse("malloc", "GLIBC_2.14", None).unwrap(),
+                )]),
+                details: Details::default(),
+            },
+            FileResult {
+                file: PathBuf::from("error.so"),
+                result: FileCheckResult::Error(anyhow!("Test error")),
+                details: Details::default(),
+            },
+        ];
+        let check_result = CheckResult { file_results };
+        assert_eq!(get_exit_code(check_result), EXIT_ERROR_CHECKING_FILES);
+    }
+
+    #[test]
+    fn diff_fails_when_newer_versions_are_needed() {
+        let dir = TempDir::new("diff");
+        let old = dir.write("old", &elf::tests::build_shared_library(&[], &[]));
+        let new = std::env::current_exe().unwrap();
+        assert_eq!(diff_builds(&old, &new), EXIT_FILES_FAILED_CHECK);
+        assert_eq!(diff_builds(&new, &old), EXIT_PASSED);
+        assert_eq!(diff_builds(&new, &new), EXIT_PASSED);
+        assert_eq!(
+            diff_builds(&dir.path.join("missing"), &new),
+            EXIT_ERROR_CHECKING_FILES
+        );
+    }
+
+    #[test]
+    fn compare_fails_on_new_violations() {
+        let dir = TempDir::new("compare");
+        let before = dir.write(
+            "before.json",
+            br#"{"files": [{"file": "app", "result": "pass"}]}"#,
+        );
+        let after = dir.write(
+            "after.json",
+            br#"{"files": [{"file": "app", "result": "fail", "violations": [{"kind": "missing_build_id"}]}]}"#,
+        );
+        assert_eq!(compare_reports(&before, &after), EXIT_FILES_FAILED_CHECK);
+        assert_eq!(compare_reports(&after, &before), EXIT_PASSED);
+        assert_eq!(
+            compare_reports(&before, &dir.path.join("missing.json")),
+            EXIT_ERROR_CHECKING_FILES
+        );
+    }
+
+    #[test]
+    fn diff_exports_fails_when_versions_are_removed() {
+        let dir = TempDir::new("diff-exports");
+        let old = dir.write(
+            "libfoo.so.1.0",
+            &elf::tests::build_shared_library(
+                &["LIBFOO_1.0"],
+                &[("foo", Some("LIBFOO_1.0"), true)],
+            ),
+        );
+        let new = dir.write(
+            "libfoo.so.1.1",
+            &elf::tests::build_shared_library(
+                &["LIBFOO_1.0", "LIBFOO_1.1"],
+                &[
+                    ("foo", Some("LIBFOO_1.0"), false),
+                    ("foo", Some("LIBFOO_1.1"), true),
+                ],
+            ),
+        );
+        assert_eq!(diff_exports(&old, &new), EXIT_PASSED);
+        assert_eq!(diff_exports(&new, &old), EXIT_FILES_FAILED_CHECK);
+        assert_eq!(
+            diff_exports(&dir.path.join("missing.so"), &new),
+            EXIT_ERROR_CHECKING_FILES
+        );
+    }
+
+    #[test]
+    fn maps_exit_codes() {
+        let default = ExitCodes {
+            errors: None,
+            failures: None,
+            errors_as_failures: false,
+            warn_only: false,
+        };
+        assert_eq!(
+            default.map(EXIT_ERROR_CHECKING_FILES),
+            EXIT_ERROR_CHECKING_FILES
+        );
+        assert_eq!(
+            default.map(EXIT_FILES_FAILED_CHECK),
+            EXIT_FILES_FAILED_CHECK
+        );
+
+        let custom = ExitCodes {
+            errors: Some(70),
+            failures: Some(1),
+            errors_as_failures: false,
+            warn_only: false,
+        };
+        assert_eq!(custom.map(EXIT_ERROR_CHECKING_FILES), 70);
+        assert_eq!(custom.map(EXIT_FILES_FAILED_CHECK), 1);
+        assert_eq!(custom.map(EXIT_PASSED), EXIT_PASSED);
+
+        let merged = ExitCodes {
+            errors_as_failures: true,
+            ..custom
+        };
+        assert_eq!(merged.map(EXIT_ERROR_CHECKING_FILES), 1);
+    }
+
+    #[test]
+    fn maps_exit_codes_for_warn_only() {
+        let warn_only = ExitCodes {
+            errors: None,
+            failures: None,
+            errors_as_failures: false,
+            warn_only: true,
+        };
+        assert_eq!(warn_only.map(EXIT_FILES_FAILED_CHECK), EXIT_PASSED);
+        assert_eq!(
+            warn_only.map(EXIT_ERROR_CHECKING_FILES),
+            EXIT_ERROR_CHECKING_FILES
+        );
+        assert_eq!(warn_only.map(EXIT_PASSED), EXIT_PASSED);
+
+        // Errors still fail when reported as failures
+        let errors_as_failures = ExitCodes {
+            errors_as_failures: true,
+            ..warn_only
+        };
+        assert_eq!(errors_as_failures.map(EXIT_FILES_FAILED_CHECK), EXIT_PASSED);
+        assert_eq!(
+            errors_as_failures.map(EXIT_ERROR_CHECKING_FILES),
+            EXIT_FILES_FAILED_CHECK
+        );
+
+        let custom = ExitCodes {
+            errors: Some(70),
+            failures: Some(1),
+            ..errors_as_failures
+        };
+        assert_eq!(custom.map(EXIT_FILES_FAILED_CHECK), EXIT_PASSED);
+        assert_eq!(custom.map(EXIT_ERROR_CHECKING_FILES), 1);
+    }
+
+    #[test]
+    fn parses_explain_without_max_versions() {
+        let args = CliArgs::parse_from(["symbol-version-check", "explain", "getrandom@GLIBC_2.25"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Explain { symbol }) if symbol == "getrandom@GLIBC_2.25"
+        ));
+    }
+
+    #[test]
+    fn splits_max_versions() {
+        let args = CliArgs::parse_from([
+            "symbol-version-check",
+            "-m",
+            "GLIBC_2.17,GLIBCXX_3.4.22",
+            "-m",
+            "CXXABI_1.3.7",
+            "my-application",
+        ]);
+        assert_eq!(
+            args.max_version,
+            ["GLIBC_2.17", "GLIBCXX_3.4.22", "CXXABI_1.3.7"]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::binary;
+use crate::presets::{self, Preset};
+use crate::requirements::VersionRequirements;
+use crate::version::{NamespacedVersion, Version};
+
+/// The oldest manylinux preset and distribution target an ELF file is compatible with, if any.
+#[derive(Debug, Default)]
+pub struct Compatibility {
+    pub manylinux: Option<&'static Preset>,
+    pub target: Option<&'static Preset>,
+}
+
+/// The distributions whose releases are distribution targets, by name and the prefix of their targets' names.
+const DISTROS: &[(&str, &str)] = &[
+    ("RHEL", "rhel"),
+    ("Ubuntu", "ubuntu-"),
+    ("Debian", "debian-"),
+    ("Amazon Linux", "amazonlinux"),
+];
+
+/// The oldest release of a distribution a file can run on.
+#[derive(Debug, PartialEq)]
+pub struct DistroRelease {
+    /// The distribution's name, e.g. `Ubuntu`
+    pub distro: &'static str,
+    /// The oldest release whose distribution target permits every version the file references, e.g. `22.04`, if any
+    pub release: Option<&'static str>,
+}
+
+/// Finds the oldest release of each distribution whose distribution target permits all the versions.
+pub fn find_distro_releases(versions: &[&NamespacedVersion]) -> Vec<DistroRelease> {
+    DISTROS
+        .iter()
+        .map(|(distro, prefix)| {
+            let targets = presets::TARGETS
+                .iter()
+                .filter(|target| target.name.starts_with(prefix));
+            DistroRelease {
+                distro,
+                release: oldest_permitting(targets, versions)
+                    .and_then(|target| target.name.strip_prefix(prefix)),
+            }
+        })
+        .collect()
+}
+
+/// Finds the oldest manylinux preset and distribution target whose maximum versions permit every version a file
+/// references.
+pub fn find_oldest_compatible(data: &[u8]) -> anyhow::Result<Compatibility> {
+    let required = binary::get_required_versions(data)?;
+    let versions: Vec<&NamespacedVersion> = required.versions().collect();
+    let manylinux = presets::PRESETS
+        .iter()
+        .filter(|preset| preset.name.starts_with("manylinux_"));
+    Ok(Compatibility {
+        manylinux: oldest_permitting(manylinux, &versions),
+        target: oldest_permitting(presets::TARGETS.iter(), &versions),
+    })
+}
+
+/// Returns the preset with the oldest glibc which permits all the versions.
+fn oldest_permitting(
+    table: impl Iterator<Item = &'static Preset>,
+    versions: &[&NamespacedVersion],
+) -> Option<&'static Preset> {
+    table
+        .filter(|preset| {
+            preset_requirements(preset).is_some_and(|requirements| {
+                versions
+                    .iter()
+                    .all(|version| requirements.is_permitted(version))
+            })
+        })
+        .min_by_key(|preset| glibc_version(preset))
+}
+
+/// Checks whether a distribution target ships a version, i.e. its maximum version in the version's namespace is at
+/// least as new. Returns `None` if the target has no maximum version in the namespace.
+pub fn ships_version(target: &Preset, version: &NamespacedVersion) -> Option<bool> {
+    let requirements = preset_requirements(target)?;
+    requirements
+        .is_listed(&version.namespace)
+        .then(|| requirements.is_permitted(version))
+}
+
+fn preset_requirements(preset: &Preset) -> Option<VersionRequirements> {
+    let requirements: Vec<String> = preset.requirements.iter().map(|r| r.to_string()).collect();
+    VersionRequirements::parse(&requirements).ok()
+}
+
+fn glibc_version(preset: &Preset) -> Option<Version> {
+    preset
+        .requirements
+        .iter()
+        .filter_map(|requirement| NamespacedVersion::parse(requirement).ok())
+        .find(|version| version.namespace == "GLIBC")
+        .map(|version| version.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oldest_target(versions: &[&str]) -> Option<&'static str> {
+        let versions: Vec<NamespacedVersion> = versions
+            .iter()
+            .map(|version| NamespacedVersion::parse(version).unwrap())
+            .collect();
+        let versions: Vec<&NamespacedVersion> = versions.iter().collect();
+        oldest_permitting(presets::TARGETS.iter(), &versions).map(|preset| preset.name)
+    }
+
+    #[test]
+    fn finds_oldest_permitting_target() {
+        assert_eq!(oldest_target(&["GLIBC_2.14", "GLIBCXX_3.4"]), Some("rhel7"));
+        assert_eq!(
+            oldest_target(&["GLIBC_2.25", "GLIBCXX_3.4.22"]),
+            Some("amazonlinux2")
+        );
+        assert_eq!(
+            oldest_target(&["GLIBC_2.17", "GLIBCXX_3.4.30"]),
+            Some("ubuntu-22.04")
+        );
+        assert_eq!(oldest_target(&["GLIBC_2.99"]), None);
+    }
+
+    #[test]
+    fn finds_oldest_distro_releases() {
+        let versions = [
+            NamespacedVersion::parse("GLIBC_2.34").unwrap(),
+            NamespacedVersion::parse("GLIBCXX_3.4.29").unwrap(),
+        ];
+        let releases = find_distro_releases(&versions.iter().collect::<Vec<_>>());
+        let release = |distro| {
+            releases
+                .iter()
+                .find(|release| release.distro == distro)
+                .unwrap()
+                .release
+        };
+        assert_eq!(release("RHEL"), Some("9"));
+        assert_eq!(release("Ubuntu"), Some("22.04"));
+        assert_eq!(release("Debian"), Some("12"));
+        assert_eq!(release("Amazon Linux"), Some("2023"));
+
+        let versions = [NamespacedVersion::parse("GLIBC_2.38").unwrap()];
+        let releases = find_distro_releases(&versions.iter().collect::<Vec<_>>());
+        assert_eq!(
+            releases[2],
+            DistroRelease {
+                distro: "Debian",
+                release: None
+            }
+        );
+    }
+
+    #[test]
+    fn finds_targets_shipping_versions() {
+        let rhel7 = presets::find_target("rhel7").unwrap();
+        let version = |version| NamespacedVersion::parse(version).unwrap();
+        assert_eq!(ships_version(rhel7, &version("GLIBC_2.17")), Some(true));
+        assert_eq!(ships_version(rhel7, &version("GLIBC_2.25")), Some(false));
+        assert_eq!(ships_version(rhel7, &version("FBSD_1.5")), None);
+    }
+
+    #[test]
+    fn finds_oldest_compatible_presets() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let compatibility = find_oldest_compatible(&data).unwrap();
+        // The test executable references versions from glibc 2.34
+        assert!(compatibility.manylinux.is_none());
+        assert!(compatibility.target.is_some());
+    }
+}
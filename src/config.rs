@@ -0,0 +1,409 @@
+use crate::policy::{self, Policy};
+use crate::presets::{self, UserPresets};
+use crate::requirements::{VersionRequirements, parse_requirement};
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// The name of the config files searched for in the directories of the checked files and their parents.
+pub const CONFIG_FILE_NAME: &str = ".symbol-version-check.json";
+
+const NO_REQUIREMENTS: &str = "No maximum versions given on the command line or in a config file";
+
+/// Settings read from a config file, or given on the command line. Settings which are not given are inherited from
+/// config files in parent directories, and settings given on the command line take precedence over all config files.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Whether to stop searching parent directories for further config files.
+    pub root: bool,
+    /// Maximum versions, as given by `-m`, which replace those for the same namespaces from further away.
+    pub max_version: Vec<String>,
+    /// Presets, as given by `--preset`, whose maximum versions are overridden by `max_version`.
+    pub preset: Vec<String>,
+    /// Distributions, as given by `--target`, whose maximum versions are overridden by `max_version`.
+    pub target: Vec<String>,
+    pub allow_private: Option<bool>,
+    pub allowed_libs: Option<Vec<String>>,
+}
+
+impl Config {
+    pub fn read(path: &Path) -> anyhow::Result<Config> {
+        let data = fs::read(path).with_context(|| "Error reading config file")?;
+        serde_json::from_slice(&data).with_context(|| "Error parsing config file")
+    }
+
+    /// Applies settings which take precedence over these, from a config file nearer to the checked files or from the
+    /// command line. Their presets may be built-in or from the given presets files.
+    pub fn merge(&mut self, nearer: &Config, user_presets: &UserPresets) -> anyhow::Result<()> {
+        let presets = user_presets.find_requirements(&nearer.preset, &nearer.target)?;
+        let requirements = presets::combine_requirements(&presets, &nearer.max_version)?;
+        let namespaces = requirements
+            .iter()
+            .map(|requirement| Ok(parse_requirement(requirement)?.0.namespace))
+            .collect::<anyhow::Result<HashSet<String>>>()?;
+        self.max_version.retain(|requirement| {
+            parse_requirement(requirement)
+                .is_ok_and(|(version, _)| !namespaces.contains(&version.namespace))
+        });
+        self.max_version.extend(requirements);
+        if nearer.allow_private.is_some() {
+            self.allow_private = nearer.allow_private;
+        }
+        if nearer.allowed_libs.is_some() {
+            self.allowed_libs.clone_from(&nearer.allowed_libs);
+        }
+        Ok(())
+    }
+}
+
+/// Finds the policy each file is checked against: the policy given on the command line, with the settings of any
+/// config files in the file's directory and its parents filling in those not given on the command line, nearer config
+/// files taking precedence.
+pub struct PolicyFinder {
+    policy: Rc<Policy>,
+    /// The settings given on the command line, which are applied over config files. Its maximum versions include those
+    /// of any presets given on the command line.
+    config: Config,
+    /// Settings from the environment, which config files take precedence over.
+    defaults: Config,
+    user_presets: UserPresets,
+    discover: bool,
+    /// The config file in each directory searched, if any, with its modification time when it was read.
+    configs: HashMap<PathBuf, (Option<SystemTime>, Option<Config>)>,
+    /// The policies built, by the directories of the config files applied.
+    policies: HashMap<Vec<PathBuf>, Rc<Policy>>,
+}
+
+impl PolicyFinder {
+    /// Creates a finder for the policy and settings given on the command line, and the presets defined in presets
+    /// files. Config files are only searched for when `discover` is set.
+    pub fn new(policy: Policy, config: Config, user_presets: UserPresets, discover: bool) -> Self {
+        PolicyFinder {
+            policy: Rc::new(policy),
+            config,
+            defaults: Config::default(),
+            user_presets,
+            discover,
+            configs: HashMap::new(),
+            policies: HashMap::new(),
+        }
+    }
+
+    /// Applies settings beneath those of all config files, such as maximum versions given by the environment, which
+    /// should be in the policy given on the command line but not in its settings.
+    pub fn with_defaults(mut self, defaults: Config) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Returns the policy for files in the given directory, or for files not in a directory, such as those in container
+    /// images, the policy given on the command line.
+    pub fn policy_for(&mut self, dir: Option<&Path>) -> anyhow::Result<Rc<Policy>> {
+        let config_dirs = match dir {
+            Some(dir) if self.discover => self.find_config_dirs(dir)?,
+            _ => Vec::new(),
+        };
+        if config_dirs.is_empty() {
+            if self.policy.requirements.is_empty() {
+                return Err(anyhow!(NO_REQUIREMENTS));
+            }
+            return Ok(self.policy.clone());
+        }
+        if let Some(policy) = self.policies.get(&config_dirs) {
+            return Ok(policy.clone());
+        }
+
+        let mut config = self.defaults.clone();
+        for config_dir in config_dirs.iter().rev() {
+            if let Some((_, Some(nearer))) = self.configs.get(config_dir) {
+                config
+                    .merge(nearer, &self.user_presets)
+                    .with_context(|| config_error(config_dir))?;
+            }
+        }
+        config.merge(&self.config, &self.user_presets)?;
+        let policy = Rc::new(Policy {
+            requirements: VersionRequirements::parse(&config.max_version)?
+                .with_allowed_versions(self.policy.requirements.allowed_versions().cloned())
+                .with_unlisted(self.policy.requirements.unlisted().clone())
+                .with_minimum_versions(self.policy.requirements.minimum_versions().cloned())
+                .with_ignored_symbols(self.policy.requirements.ignored_symbols())?,
+            allowed_libs: config
+                .allowed_libs
+                .as_deref()
+                .map(|patterns| policy::build_globs(patterns, false))
+                .transpose()?,
+            deny_private: !config.allow_private.unwrap_or(false),
+            ..Policy::clone(&self.policy)
+        });
+        if policy.requirements.is_empty() {
+            return Err(anyhow!(NO_REQUIREMENTS));
+        }
+        self.policies.insert(config_dirs, policy.clone());
+        Ok(policy)
+    }
+
+    /// Forgets the config files which have been created, changed or deleted since they were read, by their modification
+    /// times, and the policies built from them, so that they're read again when next needed.
+    pub fn refresh(&mut self) {
+        let read = self.configs.len();
+        self.configs
+            .retain(|dir, (modified, _)| config_modified(dir) == *modified);
+        if self.configs.len() != read {
+            self.policies.clear();
+        }
+    }
+
+    /// Returns the directories of the config files which apply to files in a directory, nearest first, up to and
+    /// including the first root config file.
+    fn find_config_dirs(&mut self, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let mut config_dirs = Vec::new();
+        for dir in dir.ancestors() {
+            if !self.configs.contains_key(dir) {
+                let modified = config_modified(dir);
+                let path = dir.join(CONFIG_FILE_NAME);
+                let config = if path.is_file() {
+                    Some(Config::read(&path).with_context(|| config_error(dir))?)
+                } else {
+                    None
+                };
+                self.configs.insert(dir.to_path_buf(), (modified, config));
+            }
+            if let (_, Some(config)) = &self.configs[dir] {
+                config_dirs.push(dir.to_path_buf());
+                if config.root {
+                    break;
+                }
+            }
+        }
+        Ok(config_dirs)
+    }
+}
+
+/// Returns the modification time of the config file in a directory, or `None` if it has none.
+fn config_modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir.join(CONFIG_FILE_NAME))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn config_error(dir: &Path) -> String {
+    format!("Error in {}", dir.join(CONFIG_FILE_NAME).display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::version::NamespacedVersion;
+
+    fn finder(requirements: &[&str]) -> PolicyFinder {
+        let requirements: Vec<String> = requirements.iter().map(|r| r.to_string()).collect();
+        let policy = Policy::new(VersionRequirements::parse(&requirements).unwrap());
+        let config = Config {
+            max_version: requirements,
+            ..Config::default()
+        };
+        PolicyFinder::new(policy, config, UserPresets::default(), true)
+    }
+
+    fn is_permitted(policy: &Policy, version: &str) -> bool {
+        policy
+            .requirements
+            .is_permitted(&NamespacedVersion::parse(version).unwrap())
+    }
+
+    #[test]
+    fn nearer_config_files_take_precedence() {
+        let dir = TempDir::new("config-precedence");
+        dir.write(
+            CONFIG_FILE_NAME,
+            br#"{"root": true, "max-version": ["GLIBC_2.28", "GLIBCXX_3.4.25"]}"#,
+        );
+        dir.write(
+            "legacy/.symbol-version-check.json",
+            br#"{"max-version": ["GLIBC_2.17"], "allow-private": true}"#,
+        );
+        dir.write("legacy/bin/app", b"");
+        let mut finder = finder(&[]);
+
+        let project = finder.policy_for(Some(&dir.path)).unwrap();
+        assert!(is_permitted(&project, "GLIBC_2.28"));
+        assert!(!is_permitted(&project, "GLIBC_2.29"));
+        assert!(project.deny_private);
+
+        let legacy = finder
+            .policy_for(Some(&dir.path.join("legacy/bin")))
+            .unwrap();
+        assert!(!is_permitted(&legacy, "GLIBC_2.18"));
+        assert!(is_permitted(&legacy, "GLIBCXX_3.4.25"));
+        assert!(!is_permitted(&legacy, "GLIBCXX_3.4.26"));
+        assert!(!legacy.deny_private);
+    }
+
+    #[test]
+    fn refreshes_changed_config_files() {
+        let dir = TempDir::new("config-refresh");
+        let path = dir.write(
+            CONFIG_FILE_NAME,
+            br#"{"root": true, "max-version": ["GLIBC_2.17"]}"#,
+        );
+        let set_modified = |secs| {
+            let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(modified).unwrap();
+        };
+        set_modified(86400);
+        let mut finder = finder(&[]);
+        assert!(!is_permitted(
+            &finder.policy_for(Some(&dir.path)).unwrap(),
+            "GLIBC_2.28"
+        ));
+
+        // Files are only read again once their modification times change
+        fs::write(&path, br#"{"root": true, "max-version": ["GLIBC_2.28"]}"#).unwrap();
+        set_modified(86400);
+        finder.refresh();
+        assert!(!is_permitted(
+            &finder.policy_for(Some(&dir.path)).unwrap(),
+            "GLIBC_2.28"
+        ));
+        set_modified(86401);
+        finder.refresh();
+        assert!(is_permitted(
+            &finder.policy_for(Some(&dir.path)).unwrap(),
+            "GLIBC_2.28"
+        ));
+
+        // New files are found too
+        let sub = dir.write("sub/app", b"").parent().unwrap().to_path_buf();
+        assert!(!is_permitted(
+            &finder.policy_for(Some(&sub)).unwrap(),
+            "GLIBC_2.34"
+        ));
+        dir.write(
+            "sub/.symbol-version-check.json",
+            br#"{"max-version": ["GLIBC_2.34"]}"#,
+        );
+        finder.refresh();
+        assert!(is_permitted(
+            &finder.policy_for(Some(&sub)).unwrap(),
+            "GLIBC_2.34"
+        ));
+    }
+
+    #[test]
+    fn command_line_settings_take_precedence() {
+        let dir = TempDir::new("config-command-line");
+        dir.write(
+            CONFIG_FILE_NAME,
+            br#"{"root": true, "max-version": ["GLIBC_2.17", "GLIBCXX_3.4.25"], "allow-private": true}"#,
+        );
+        let policy = finder(&["GLIBC_2.28"]).policy_for(Some(&dir.path)).unwrap();
+        assert!(is_permitted(&policy, "GLIBC_2.28"));
+        // Settings not given on the command line are filled in by config files
+        assert!(!is_permitted(&policy, "GLIBCXX_3.4.26"));
+        assert!(!policy.deny_private);
+
+        let config = Config {
+            max_version: vec!["GLIBC_2.28".to_string()],
+            allow_private: Some(false),
+            ..Config::default()
+        };
+        let policy = PolicyFinder::new(
+            Policy::new(VersionRequirements::parse(&config.max_version).unwrap()),
+            config,
+            UserPresets::default(),
+            true,
+        )
+        .policy_for(Some(&dir.path))
+        .unwrap();
+        assert!(policy.deny_private);
+    }
+
+    #[test]
+    fn command_line_settings_apply_unless_overridden() {
+        let dir = TempDir::new("config-none");
+        dir.write(CONFIG_FILE_NAME, br#"{"root": true}"#);
+        let mut finder = finder(&["GLIBC_2.17"]);
+        let policy = finder.policy_for(Some(&dir.path.join("sub"))).unwrap();
+        assert!(!is_permitted(&policy, "GLIBC_2.18"));
+        let policy = finder.policy_for(None).unwrap();
+        assert!(is_permitted(&policy, "GLIBC_2.17"));
+    }
+
+    #[test]
+    fn config_files_take_precedence_over_defaults() {
+        let dir = TempDir::new("config-defaults");
+        dir.write(
+            CONFIG_FILE_NAME,
+            br#"{"root": true, "max-version": ["GLIBC_2.28"]}"#,
+        );
+        let defaults = ["GLIBC_2.17".to_string(), "GLIBCXX_3.4.19".to_string()];
+        let mut finder = PolicyFinder::new(
+            Policy::new(VersionRequirements::parse(&defaults).unwrap()),
+            Config::default(),
+            UserPresets::default(),
+            true,
+        )
+        .with_defaults(Config {
+            max_version: defaults.to_vec(),
+            ..Config::default()
+        });
+        let policy = finder.policy_for(Some(&dir.path)).unwrap();
+        assert!(is_permitted(&policy, "GLIBC_2.28"));
+        // Namespaces the config file doesn't give are filled in by the defaults
+        assert!(!is_permitted(&policy, "GLIBCXX_3.4.20"));
+        let policy = finder.policy_for(None).unwrap();
+        assert!(!is_permitted(&policy, "GLIBC_2.18"));
+    }
+
+    #[test]
+    fn allowed_versions_apply_with_config_files() {
+        let dir = TempDir::new("config-allowed");
+        dir.write(
+            CONFIG_FILE_NAME,
+            br#"{"root": true, "max-version": ["GLIBC_2.28"]}"#,
+        );
+        let allowed = ["GLIBC_2.2.5", "GLIBC_2.28"].map(|v| NamespacedVersion::parse(v).unwrap());
+        let policy = Policy::new(
+            VersionRequirements::parse(&[])
+                .unwrap()
+                .with_allowed_versions(allowed),
+        );
+        let mut finder = PolicyFinder::new(policy, Config::default(), UserPresets::default(), true);
+        let policy = finder.policy_for(Some(&dir.path)).unwrap();
+        assert!(is_permitted(&policy, "GLIBC_2.28"));
+        assert!(!is_permitted(&policy, "GLIBC_2.17"));
+    }
+
+    #[test]
+    fn missing_requirements_are_an_error() {
+        let dir = TempDir::new("config-missing");
+        dir.write(CONFIG_FILE_NAME, br#"{"root": true}"#);
+        let mut finder = finder(&[]);
+        assert!(finder.policy_for(Some(&dir.path)).is_err());
+        assert!(finder.policy_for(None).is_err());
+    }
+
+    #[test]
+    fn invalid_config_files_are_an_error() {
+        let dir = TempDir::new("config-invalid");
+        dir.write(CONFIG_FILE_NAME, br#"{"root": true, "max-versions": []}"#);
+        match finder(&["GLIBC_2.17"]).policy_for(Some(&dir.path)) {
+            Err(e) => assert!(format!("{:#}", e).contains("unknown field")),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+}
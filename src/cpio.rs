@@ -0,0 +1,237 @@
+use crate::archive::{self, Compression};
+use anyhow::{Context, anyhow};
+use std::io::{ErrorKind, Read};
+
+const NEWC_MAGIC: &[u8] = b"070701";
+const NEWC_CRC_MAGIC: &[u8] = b"070702";
+const NEWC_HEADER_SIZE: usize = 110;
+const NEWC_ALIGNMENT: u64 = 4;
+const TRAILER_NAME: &str = "TRAILER!!!";
+const FILE_TYPE_MASK: u32 = 0o170000;
+const REGULAR_FILE: u32 = 0o100000;
+
+fn is_cpio(data: &[u8]) -> bool {
+    data.starts_with(NEWC_MAGIC) || data.starts_with(NEWC_CRC_MAGIC)
+}
+
+/// Checks whether the data is a cpio archive, or a gzip or zstd compressed cpio archive.
+pub fn is_cpio_archive(data: &[u8]) -> bool {
+    if is_cpio(data) {
+        return true;
+    }
+    let compression = Compression::detect(data);
+    if compression == Compression::None {
+        return false;
+    }
+    let Ok(mut reader) = compression.decompress(data) else {
+        return false;
+    };
+    let mut magic = [0u8; NEWC_MAGIC.len()];
+    reader.read_exact(&mut magic).is_ok() && is_cpio(&magic)
+}
+
+/// Calls `visit` for each ELF file in a cpio archive. As is the case for initramfs images, the data may consist of
+/// multiple concatenated archives, the last of which may be compressed.
+pub fn visit_cpio_elf_entries(
+    data: &[u8],
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    let mut position = 0;
+    loop {
+        // Archives may be padded with zeroes
+        while data.get(position) == Some(&0) {
+            position += 1;
+        }
+        let remainder = &data[position..];
+        if remainder.is_empty() {
+            return Ok(());
+        }
+        if is_cpio(remainder) {
+            let mut reader = remainder;
+            position += read_archive(&mut reader, visit)? as usize;
+        } else if let compression @ (Compression::Gzip | Compression::Zstd) =
+            Compression::detect(remainder)
+        {
+            return visit_cpio_stream(compression.decompress(remainder)?, visit);
+        } else {
+            return Err(anyhow!(
+                "Unrecognized data at offset {} in cpio archive",
+                position
+            ));
+        }
+    }
+}
+
+/// Visits the ELF files in a stream of one or more concatenated cpio archives.
+fn visit_cpio_stream(
+    mut reader: impl Read,
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    loop {
+        let mut word = [0u8; NEWC_ALIGNMENT as usize];
+        loop {
+            match reader.read_exact(&mut word) {
+                Ok(()) if word == [0; NEWC_ALIGNMENT as usize] => continue,
+                Ok(()) => break,
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e).with_context(|| "Error reading cpio archive"),
+            }
+        }
+        read_archive(&mut (&word[..]).chain(&mut reader), visit)?;
+    }
+}
+
+/// Reads a single cpio archive up to and including its trailer, returning the number of bytes read.
+fn read_archive(reader: &mut dyn Read, visit: &mut dyn FnMut(&str, &[u8])) -> anyhow::Result<u64> {
+    let mut position = 0u64;
+    loop {
+        let mut header = [0u8; NEWC_HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .with_context(|| "Error reading cpio header")?;
+        if !is_cpio(&header) {
+            return Err(anyhow!("Invalid cpio header at offset {}", position));
+        }
+        let mode = header_field(&header, 1)?;
+        let file_size = header_field(&header, 6)? as u64;
+        let name_size = header_field(&header, 11)? as u64;
+        position += NEWC_HEADER_SIZE as u64;
+
+        let mut name = Vec::new();
+        reader
+            .take(name_size)
+            .read_to_end(&mut name)
+            .with_context(|| "Error reading cpio entry name")?;
+        position += name_size;
+        skip(reader, padding(position))?;
+        position += padding(position);
+        let name = String::from_utf8_lossy(name.strip_suffix(b"\0").unwrap_or(&name)).to_string();
+        if name == TRAILER_NAME {
+            return Ok(position);
+        }
+
+        let mut entry = reader.take(file_size);
+        if mode & FILE_TYPE_MASK == REGULAR_FILE
+            && let Some(contents) = archive::read_elf_entry(&mut entry)
+                .with_context(|| format!("Error reading cpio entry {}", name))?
+        {
+            visit(name.trim_start_matches("./"), &contents);
+        }
+        std::io::copy(&mut entry, &mut std::io::sink())
+            .with_context(|| format!("Error reading cpio entry {}", name))?;
+        position += file_size;
+        skip(reader, padding(position))?;
+        position += padding(position);
+    }
+}
+
+/// Parses the hexadecimal header field at `index`, following the six byte magic number.
+fn header_field(header: &[u8], index: usize) -> anyhow::Result<u32> {
+    let start = NEWC_MAGIC.len() + index * 8;
+    let field = std::str::from_utf8(&header[start..start + 8])
+        .ok()
+        .and_then(|field| u32::from_str_radix(field, 16).ok());
+    field.ok_or_else(|| anyhow!("Invalid cpio header field"))
+}
+
+fn padding(position: u64) -> u64 {
+    (NEWC_ALIGNMENT - position % NEWC_ALIGNMENT) % NEWC_ALIGNMENT
+}
+
+fn skip(reader: &mut dyn Read, count: u64) -> anyhow::Result<()> {
+    let skipped = std::io::copy(&mut reader.take(count), &mut std::io::sink())
+        .with_context(|| "Error reading cpio archive")?;
+    if skipped != count {
+        return Err(anyhow!("Unexpected end of cpio archive"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::{ELF_STUB, gzip};
+
+    fn build_cpio(entries: &[(&str, u32, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut append = |name: &str, mode: u32, contents: &[u8]| {
+            data.extend_from_slice(NEWC_MAGIC);
+            let fields = [0, mode, 0, 0, 1, 0, contents.len() as u32, 0, 0, 0, 0];
+            for field in fields {
+                data.extend_from_slice(format!("{:08x}", field).as_bytes());
+            }
+            data.extend_from_slice(format!("{:08x}{:08x}", name.len() + 1, 0).as_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+            data.resize(data.len() + padding(data.len() as u64) as usize, 0);
+            data.extend_from_slice(contents);
+            data.resize(data.len() + padding(data.len() as u64) as usize, 0);
+        };
+        for (name, mode, contents) in entries {
+            append(name, *mode, contents);
+        }
+        append(TRAILER_NAME, 0, b"");
+        data
+    }
+
+    fn collect_paths(data: &[u8]) -> Vec<String> {
+        let mut paths = Vec::new();
+        visit_cpio_elf_entries(data, &mut |path, contents| {
+            assert_eq!(contents, ELF_STUB);
+            paths.push(path.to_string())
+        })
+        .unwrap();
+        paths
+    }
+
+    #[test]
+    fn visits_only_elf_files() {
+        let data = build_cpio(&[
+            ("bin", 0o040755, b""),
+            ("bin/busybox", 0o100755, ELF_STUB),
+            ("init", 0o100755, b"#!/bin/sh\n"),
+            ("lib/libc.so", 0o120777, b"libc.so.6"),
+        ]);
+        assert!(is_cpio_archive(&data));
+        assert_eq!(collect_paths(&data), vec!["bin/busybox"]);
+    }
+
+    #[test]
+    fn visits_gzip_compressed_archive() {
+        let data = gzip(&build_cpio(&[("./sbin/init", 0o100755, ELF_STUB)]));
+        assert!(is_cpio_archive(&data));
+        assert_eq!(collect_paths(&data), vec!["sbin/init"]);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn visits_zstd_compressed_archive() {
+        let data =
+            zstd::encode_all(&build_cpio(&[("sbin/init", 0o100755, ELF_STUB)])[..], 1).unwrap();
+        assert!(is_cpio_archive(&data));
+        assert_eq!(collect_paths(&data), vec!["sbin/init"]);
+    }
+
+    #[test]
+    fn visits_concatenated_archives() {
+        // Early microcode archive, padded, followed by the compressed main archive
+        let mut data = build_cpio(&[("kernel/x86/microcode/GenuineIntel.bin", 0o100644, b"x")]);
+        data.resize(512, 0);
+        let mut main = build_cpio(&[("bin/a", 0o100755, ELF_STUB)]);
+        main.extend(build_cpio(&[("bin/b", 0o100755, ELF_STUB)]));
+        data.extend(gzip(&main));
+        assert_eq!(collect_paths(&data), vec!["bin/a", "bin/b"]);
+    }
+
+    #[test]
+    fn truncated_archive_is_error() {
+        let data = build_cpio(&[("bin/a", 0o100755, ELF_STUB)]);
+        let result = visit_cpio_elf_entries(&data[..NEWC_HEADER_SIZE + 4], &mut |_, _| {});
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn gzipped_text_is_not_cpio_archive() {
+        assert!(!is_cpio_archive(&gzip(b"hello")));
+    }
+}
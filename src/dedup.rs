@@ -0,0 +1,44 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Tracks the contents of the files checked, so that files with the same contents as a file already checked, such as
+/// hard links and copies, are only checked once.
+#[derive(Default)]
+pub struct ContentIndex {
+    first_paths: HashMap<[u8; 32], PathBuf>,
+}
+
+impl ContentIndex {
+    /// Records the contents of a file, returning the path of the first file found with the same contents, if any.
+    pub fn find_or_insert(&mut self, path: &Path, data: &[u8]) -> Option<PathBuf> {
+        let hash: [u8; 32] = Sha256::digest(data).into();
+        match self.first_paths.get(&hash) {
+            Some(first_path) => Some(first_path.clone()),
+            None => {
+                self.first_paths.insert(hash, path.to_path_buf());
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_first_file_with_same_contents() {
+        let mut index = ContentIndex::default();
+        assert_eq!(index.find_or_insert(Path::new("a"), b"x"), None);
+        assert_eq!(index.find_or_insert(Path::new("b"), b"y"), None);
+        assert_eq!(
+            index.find_or_insert(Path::new("c"), b"x"),
+            Some(PathBuf::from("a"))
+        );
+        assert_eq!(
+            index.find_or_insert(Path::new("d"), b"x"),
+            Some(PathBuf::from("a"))
+        );
+    }
+}
@@ -0,0 +1,154 @@
+/// A symbol which binaries shouldn't import, with the reason why.
+pub struct DeniedSymbol {
+    pub name: &'static str,
+    pub reason: &'static str,
+}
+
+/// libc functions which can't be used safely, or are easily misused, as enabled by `--deny-dangerous-symbols`. Their
+/// fortified variants, such as `__strcpy_chk`, aren't included.
+pub const DANGEROUS_SYMBOLS: &[DeniedSymbol] = &[
+    DeniedSymbol {
+        name: "gets",
+        reason: "no bounds checking, use fgets",
+    },
+    DeniedSymbol {
+        name: "getwd",
+        reason: "no bounds checking, use getcwd",
+    },
+    DeniedSymbol {
+        name: "strcpy",
+        reason: "no bounds checking",
+    },
+    DeniedSymbol {
+        name: "strcat",
+        reason: "no bounds checking",
+    },
+    DeniedSymbol {
+        name: "wcscpy",
+        reason: "no bounds checking",
+    },
+    DeniedSymbol {
+        name: "wcscat",
+        reason: "no bounds checking",
+    },
+    DeniedSymbol {
+        name: "sprintf",
+        reason: "no bounds checking, use snprintf",
+    },
+    DeniedSymbol {
+        name: "vsprintf",
+        reason: "no bounds checking, use vsnprintf",
+    },
+    DeniedSymbol {
+        name: "tmpnam",
+        reason: "race between naming and creating the file, use mkstemp",
+    },
+    DeniedSymbol {
+        name: "tmpnam_r",
+        reason: "race between naming and creating the file, use mkstemp",
+    },
+    DeniedSymbol {
+        name: "tempnam",
+        reason: "race between naming and creating the file, use mkstemp",
+    },
+    DeniedSymbol {
+        name: "mktemp",
+        reason: "race between naming and creating the file, use mkstemp",
+    },
+    DeniedSymbol {
+        name: "system",
+        reason: "runs a shell, use posix_spawn or fork and exec",
+    },
+];
+
+/// A glibc symbol which is only provided for compatibility with existing binaries, so can't be linked against, or which
+/// has been removed.
+pub struct RemovedSymbol {
+    pub name: &'static str,
+    /// The version of glibc the symbol became compatibility-only in
+    pub glibc: &'static str,
+    /// What to use instead
+    pub replacement: &'static str,
+}
+
+/// Symbols which binaries built against older versions of glibc may import, but which newer versions only keep for
+/// compatibility or have removed, as enabled by `--check-removed-symbols`.
+pub const REMOVED_SYMBOLS: &[RemovedSymbol] = &[
+    removed("ustat", "2.28", "statfs"),
+    removed("nfsservctl", "2.28", "the nfsd filesystem"),
+    removed("ecb_crypt", "2.28", "a cryptography library"),
+    removed("cbc_crypt", "2.28", "a cryptography library"),
+    removed("des_setparity", "2.28", "a cryptography library"),
+    removed("yp_get_default_domain", "2.28", "libnsl"),
+    removed("yp_bind", "2.28", "libnsl"),
+    removed("yp_match", "2.28", "libnsl"),
+    removed("yp_first", "2.28", "libnsl"),
+    removed("yp_next", "2.28", "libnsl"),
+    removed("yp_all", "2.28", "libnsl"),
+    removed("stime", "2.31", "clock_settime"),
+    removed("sys_errlist", "2.32", "strerror"),
+    removed("_sys_errlist", "2.32", "strerror"),
+    removed("sys_nerr", "2.32", "strerror"),
+    removed("_sys_nerr", "2.32", "strerror"),
+    removed("sys_siglist", "2.32", "strsignal"),
+    removed("_sys_siglist", "2.32", "strsignal"),
+    removed("sys_sigabbrev", "2.32", "sigabbrev_np"),
+    removed("authunix_create", "2.32", "libtirpc"),
+    removed("callrpc", "2.32", "libtirpc"),
+    removed("clnt_create", "2.32", "libtirpc"),
+    removed("clnt_perror", "2.32", "libtirpc"),
+    removed("pmap_set", "2.32", "libtirpc"),
+    removed("pmap_unset", "2.32", "libtirpc"),
+    removed("svc_register", "2.32", "libtirpc"),
+    removed("svc_run", "2.32", "libtirpc"),
+    removed("svctcp_create", "2.32", "libtirpc"),
+    removed("svcudp_create", "2.32", "libtirpc"),
+    removed("__xstat", "2.33", "stat"),
+    removed("__xstat64", "2.33", "stat64"),
+    removed("__fxstat", "2.33", "fstat"),
+    removed("__fxstat64", "2.33", "fstat64"),
+    removed("__lxstat", "2.33", "lstat"),
+    removed("__lxstat64", "2.33", "lstat64"),
+    removed("__fxstatat", "2.33", "fstatat"),
+    removed("__fxstatat64", "2.33", "fstatat64"),
+    removed("__xmknod", "2.33", "mknod"),
+    removed("__xmknodat", "2.33", "mknodat"),
+    removed("__malloc_hook", "2.34", "malloc interposition"),
+    removed("__free_hook", "2.34", "malloc interposition"),
+    removed("__realloc_hook", "2.34", "malloc interposition"),
+    removed("__memalign_hook", "2.34", "malloc interposition"),
+    removed("__after_morecore_hook", "2.34", "malloc interposition"),
+    removed("__morecore", "2.34", "malloc interposition"),
+];
+
+const fn removed(
+    name: &'static str,
+    glibc: &'static str,
+    replacement: &'static str,
+) -> RemovedSymbol {
+    RemovedSymbol {
+        name,
+        glibc,
+        replacement,
+    }
+}
+
+pub fn find_removed_symbol(name: &str) -> Option<&'static RemovedSymbol> {
+    REMOVED_SYMBOLS.iter().find(|symbol| symbol.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_removed_symbols() {
+        assert_eq!(find_removed_symbol("sys_errlist").unwrap().glibc, "2.32");
+        assert!(find_removed_symbol("strerror").is_none());
+        assert!(
+            REMOVED_SYMBOLS
+                .iter()
+                .all(|symbol| !DANGEROUS_SYMBOLS.iter().any(|d| d.name == symbol.name))
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::symbols::SymbolVersion;
+use crate::version::{NamespacedVersion, Version};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A change to the versions a symbol is referenced with between two builds of a file.
+#[derive(Debug, PartialEq)]
+pub enum RequirementChange {
+    /// A symbol version the new build needs which the old build didn't
+    Added(String, NamespacedVersion),
+    /// A symbol version the old build needed which the new build doesn't
+    Removed(String, NamespacedVersion),
+    /// A symbol referenced with different versions, e.g. `memcpy@GLIBC_2.2.5` becoming `memcpy@GLIBC_2.14`
+    Changed {
+        name: String,
+        old: Vec<NamespacedVersion>,
+        new: Vec<NamespacedVersion>,
+    },
+}
+
+/// A change to the newest version two builds of a file need in a namespace, `None` if a build needs no version in it.
+#[derive(Debug, PartialEq)]
+pub struct NamespaceChange {
+    pub namespace: String,
+    pub old: Option<Version>,
+    pub new: Option<Version>,
+}
+
+impl NamespaceChange {
+    /// Checks whether the new build needs a newer version, so runs on fewer systems than the old build.
+    pub fn is_newer(&self) -> bool {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => new > old,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compares the symbol versions two builds of a file reference, returning the changes ordered by symbol name.
+pub fn diff_requirements(old: &[SymbolVersion], new: &[SymbolVersion]) -> Vec<RequirementChange> {
+    let by_name = |symbols: &[SymbolVersion]| {
+        let mut by_name: BTreeMap<String, BTreeSet<NamespacedVersion>> = BTreeMap::new();
+        for symbol in symbols {
+            by_name
+                .entry(symbol.name.clone())
+                .or_default()
+                .insert(symbol.version.clone());
+        }
+        by_name
+    };
+    let old = by_name(old);
+    let new = by_name(new);
+    let empty = BTreeSet::new();
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    let mut changes = Vec::new();
+    for name in names {
+        let old_versions = old.get(name).unwrap_or(&empty);
+        let new_versions = new.get(name).unwrap_or(&empty);
+        if old_versions == new_versions {
+            continue;
+        }
+        if old_versions.is_empty() {
+            changes.extend(
+                new_versions
+                    .iter()
+                    .map(|version| RequirementChange::Added(name.clone(), version.clone())),
+            );
+        } else if new_versions.is_empty() {
+            changes.extend(
+                old_versions
+                    .iter()
+                    .map(|version| RequirementChange::Removed(name.clone(), version.clone())),
+            );
+        } else {
+            changes.push(RequirementChange::Changed {
+                name: name.clone(),
+                old: old_versions.iter().cloned().collect(),
+                new: new_versions.iter().cloned().collect(),
+            });
+        }
+    }
+    changes
+}
+
+/// Compares the newest version two builds of a file need in each namespace, returning the namespaces which changed.
+pub fn diff_namespaces(old: &[SymbolVersion], new: &[SymbolVersion]) -> Vec<NamespaceChange> {
+    let newest = |symbols: &[SymbolVersion]| {
+        let mut newest: BTreeMap<String, Version> = BTreeMap::new();
+        for symbol in symbols {
+            match newest.get(&symbol.version.namespace) {
+                Some(version) if *version >= symbol.version.version => {}
+                _ => {
+                    newest.insert(
+                        symbol.version.namespace.clone(),
+                        symbol.version.version.clone(),
+                    );
+                }
+            }
+        }
+        newest
+    };
+    let old = newest(old);
+    let new = newest(new);
+    let namespaces: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    namespaces
+        .into_iter()
+        .filter(|namespace| old.get(*namespace) != new.get(*namespace))
+        .map(|namespace| NamespaceChange {
+            namespace: namespace.clone(),
+            old: old.get(namespace).cloned(),
+            new: new.get(namespace).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(symbols: &[(&str, &str)]) -> Vec<SymbolVersion> {
+        symbols
+            .iter()
+            .map(|(name, version)| SymbolVersion::parse(name, version, None).unwrap())
+            .collect()
+    }
+
+    fn version(version: &str) -> NamespacedVersion {
+        NamespacedVersion::parse(version).unwrap()
+    }
+
+    #[test]
+    fn diffs_requirements() {
+        let old = symbols(&[
+            ("memcpy", "GLIBC_2.2.5"),
+            ("malloc", "GLIBC_2.2.5"),
+            ("legacy", "GLIBC_2.2.5"),
+        ]);
+        let new = symbols(&[
+            ("memcpy", "GLIBC_2.14"),
+            ("malloc", "GLIBC_2.2.5"),
+            ("getrandom", "GLIBC_2.25"),
+        ]);
+        assert_eq!(
+            diff_requirements(&old, &new),
+            vec![
+                RequirementChange::Added("getrandom".to_string(), version("GLIBC_2.25")),
+                RequirementChange::Removed("legacy".to_string(), version("GLIBC_2.2.5")),
+                RequirementChange::Changed {
+                    name: "memcpy".to_string(),
+                    old: vec![version("GLIBC_2.2.5")],
+                    new: vec![version("GLIBC_2.14")],
+                },
+            ]
+        );
+        assert!(diff_requirements(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn diffs_newest_versions() {
+        let old = symbols(&[("memcpy", "GLIBC_2.2.5"), ("_ZSt4cout", "GLIBCXX_3.4")]);
+        let new = symbols(&[("memcpy", "GLIBC_2.14"), ("__cxa_finalize", "GLIBC_2.2.5")]);
+        let changes = diff_namespaces(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                NamespaceChange {
+                    namespace: "GLIBC".to_string(),
+                    old: Some(Version::parse("2.2.5").unwrap()),
+                    new: Some(Version::parse("2.14").unwrap()),
+                },
+                NamespaceChange {
+                    namespace: "GLIBCXX".to_string(),
+                    old: Some(Version::parse("3.4").unwrap()),
+                    new: None,
+                },
+            ]
+        );
+        assert!(changes[0].is_newer());
+        assert!(!changes[1].is_newer());
+    }
+}
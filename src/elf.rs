@@ -1,56 +1,1449 @@
-use crate::symbols::SymbolVersion;
-use crate::version::NamespacedVersion;
+use crate::symbols::{SymbolBinding, SymbolType, SymbolVersion};
+use crate::version::{NamespacedVersion, Version, is_private_version};
 use anyhow::{Context, anyhow};
-use object::read::elf::{ElfFile, ElfFile32, ElfFile64, FileHeader};
-use object::{Endianness, FileKind, Object, ObjectSymbol};
-use std::fs;
-use std::path::Path;
+use object::read::elf::{
+    Dyn, ElfFile, ElfFile32, ElfFile64, FileHeader, ProgramHeader, SectionHeader, Sym,
+};
+use object::{Endian, Endianness, FileKind, Object, ObjectSymbol};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
-pub fn get_dyn_undef_symbols(file_to_check: &Path) -> anyhow::Result<Vec<SymbolVersion>> {
-    let data = fs::read(file_to_check).with_context(|| "Error reading file")?;
-    match FileKind::parse(&*data).with_context(|| "Error parsing file")? {
-        FileKind::Elf32 => get_elf_dyn_undef_symbols(ElfFile32::parse(&*data)?, &data),
-        FileKind::Elf64 => get_elf_dyn_undef_symbols(ElfFile64::parse(&*data)?, &data),
+pub fn is_elf(data: &[u8]) -> bool {
+    data.starts_with(&object::elf::ELFMAG)
+}
+
+/// A reference from an undefined dynamic symbol to a version which is not numbered, such as `GLIBC_PRIVATE`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnnumberedVersionReference {
+    pub name: String,
+    pub version: String,
+    pub file: Option<String>,
+}
+
+impl UnnumberedVersionReference {
+    /// Checks whether the version is private to the library defining it, such as `GLIBC_PRIVATE`. Private versions may
+    /// change incompatibly between any two releases of the library.
+    pub fn is_private(&self) -> bool {
+        is_private_version(&self.version)
+    }
+}
+
+/// A symbol version which couldn't be read, or, without a symbol, a version table which couldn't be read at all.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnreadableVersion {
+    /// The symbol's name, or its index if its name couldn't be read either
+    pub symbol: Option<String>,
+    pub error: String,
+}
+
+impl Display for UnreadableVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.symbol {
+            Some(symbol) => write!(f, "{}: {}", symbol, self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// A version of a library needed by a file, as listed in its version needs section (`.gnu.version_r`).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NeededVersion {
+    pub version: String,
+    pub file: String,
+}
+
+/// An undefined dynamic symbol imported by a file, with the version it references and the library the version is needed
+/// from, if it is versioned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolImport {
+    pub name: String,
+    pub version: Option<String>,
+    pub file: Option<String>,
+    /// Whether the symbol is weak, so may be left undefined at run time
+    pub is_weak: bool,
+}
+
+/// Identifying information from an ELF file's header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElfHeader {
+    /// The processor architecture (`e_machine`), e.g. `x86_64`, or the number if it isn't known
+    pub machine: String,
+    /// 32 or 64 bits (`EI_CLASS`)
+    pub class: u8,
+    pub little_endian: bool,
+    /// The operating system ABI (`EI_OSABI`), e.g. `SYSV`, `GNU` or `FreeBSD`, or the number if it isn't known
+    pub os_abi: String,
+    /// The file type (`e_type`), e.g. `EXEC`, `DYN` or `CORE`
+    pub file_type: String,
+}
+
+/// The operating system and minimum kernel version a file was built for, as given by its `NT_GNU_ABI_TAG` note
+/// (`.note.ABI-tag`).
+#[derive(Debug, PartialEq)]
+pub struct AbiTag {
+    pub os: String,
+    pub version: Version,
+}
+
+/// A dynamic symbol defined by a file for other files to use, along with the version it is defined with, if any.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExportedSymbol {
+    pub name: String,
+    pub version: Option<String>,
+    /// Whether this is the default version of the symbol (`symbol@@VERSION`), which new links bind to, rather than
+    /// a version kept for compatibility (`symbol@VERSION`)
+    pub is_default: bool,
+}
+
+impl Display for ExportedSymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) if self.is_default => write!(f, "{}@@{}", self.name, version),
+            Some(version) => write!(f, "{}@{}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// The version references of a file's undefined dynamic symbols.
+#[derive(Default)]
+pub struct VersionReferences {
+    /// References to numbered versions
+    pub symbols: Vec<SymbolVersion>,
+    /// References to numbered versions flagged as hidden (`VERSYM_HIDDEN`), which are made when a symbol is explicitly
+    /// bound to a version, as with `symbol@VERSION`
+    pub hidden_symbols: Vec<SymbolVersion>,
+    /// References to versions which are not numbered, such as `GLIBC_PRIVATE`
+    pub unnumbered: Vec<UnnumberedVersionReference>,
+    /// Symbols whose versions couldn't be read, or the version table, if it couldn't be read at all
+    pub unreadable: Vec<UnreadableVersion>,
+}
+
+/// Checks whether a file is a statically linked executable, including static PIE executables, which depends on no
+/// libraries so has no version references.
+pub fn is_static(data: &[u8]) -> bool {
+    match FileKind::parse(data) {
+        Ok(FileKind::Elf32) => ElfFile32::parse(data).is_ok_and(|elf| is_static_elf(&elf)),
+        Ok(FileKind::Elf64) => ElfFile64::parse(data).is_ok_and(|elf| is_static_elf(&elf)),
+        _ => false,
+    }
+}
+
+/// Checks for an executable with no dynamic segment or program interpreter, or a static PIE executable: one marked as
+/// position independent (`DF_1_PIE`), with a dynamic segment for its own relocations but no program interpreter or
+/// needed libraries.
+fn is_static_elf<Elf: FileHeader<Endian = Endianness>>(elf: &ElfFile<Elf>) -> bool {
+    let endian = elf.endianness();
+    let has_segment = |p_type| {
+        elf.elf_program_headers()
+            .iter()
+            .any(|header| header.p_type(endian) == p_type)
+    };
+    if has_segment(object::elf::PT_INTERP) {
+        return false;
+    }
+    match elf.elf_header().e_type(endian) {
+        object::elf::ET_EXEC => !has_segment(object::elf::PT_DYNAMIC),
+        object::elf::ET_DYN => {
+            let dynamic = elf
+                .elf_section_table()
+                .dynamic(endian, elf.data())
+                .ok()
+                .flatten()
+                .map(|(dynamic, _)| dynamic)
+                .unwrap_or_default();
+            let is_marked_pie = dynamic.iter().any(|entry| {
+                entry.tag32(endian) == Some(object::elf::DT_FLAGS_1)
+                    && entry.d_val(endian).into() & u64::from(object::elf::DF_1_PIE) != 0
+            });
+            is_marked_pie
+                && !dynamic
+                    .iter()
+                    .any(|entry| entry.tag32(endian) == Some(object::elf::DT_NEEDED))
+        }
+        _ => false,
+    }
+}
+
+/// A kind of ELF file which has no dynamic symbol versions to check.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UnsupportedKind {
+    /// A Linux kernel module, whose symbols are resolved by the kernel rather than the dynamic linker
+    KernelModule,
+    /// A core dump
+    Core,
+    /// A separate debug info file, such as one written by `objcopy --only-keep-debug`, whose sections other than debug
+    /// info and notes have no contents
+    Debuginfo,
+}
+
+impl Display for UnsupportedKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsupportedKind::KernelModule => write!(f, "KERNEL_MODULE"),
+            UnsupportedKind::Core => write!(f, "CORE"),
+            UnsupportedKind::Debuginfo => write!(f, "DEBUGINFO"),
+        }
+    }
+}
+
+/// Classifies an ELF file which can't meaningfully be checked, returning `None` for other files, including files which
+/// can't be parsed.
+pub fn get_unsupported_kind(data: &[u8]) -> Option<UnsupportedKind> {
+    match FileKind::parse(data) {
+        Ok(FileKind::Elf32) => get_elf_unsupported_kind(&ElfFile32::parse(data).ok()?),
+        Ok(FileKind::Elf64) => get_elf_unsupported_kind(&ElfFile64::parse(data).ok()?),
+        _ => None,
+    }
+}
+
+/// Kernel modules are relocatable files with a `.modinfo` section. Debug info files are recognized by their allocated
+/// sections, other than notes, having no contents, as the sections of a loadable file would.
+fn get_elf_unsupported_kind<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> Option<UnsupportedKind> {
+    let endian = elf.endianness();
+    match elf.elf_header().e_type(endian) {
+        object::elf::ET_CORE => return Some(UnsupportedKind::Core),
+        object::elf::ET_REL if elf.section_by_name(".modinfo").is_some() => {
+            return Some(UnsupportedKind::KernelModule);
+        }
+        _ => {}
+    }
+    let mut allocated = elf.elf_section_table().iter().filter(|section| {
+        section.sh_flags(endian).into() & u64::from(object::elf::SHF_ALLOC) != 0
+            && section.sh_type(endian) != object::elf::SHT_NOTE
+    });
+    let is_debuginfo = allocated.clone().next().is_some()
+        && allocated.all(|section| section.sh_type(endian) == object::elf::SHT_NOBITS);
+    is_debuginfo.then_some(UnsupportedKind::Debuginfo)
+}
+
+/// Checks whether a file has a symbol version table (`.gnu.version`). Files linked against musl, or with no versioned
+/// dependencies, have none.
+pub fn has_version_table(data: &[u8]) -> anyhow::Result<bool> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => has_elf_version_table(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => has_elf_version_table(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn has_elf_version_table<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<bool> {
+    let endian = elf.endianness();
+    Ok(elf
+        .elf_section_table()
+        .iter()
+        .any(|section| section.sh_type(endian) == object::elf::SHT_GNU_VERSYM))
+}
+
+/// Estimates the version of glibc a statically linked executable was linked against, from the newest `GLIBC_2.x`
+/// version string embedded in it. glibc's own symbol version strings are often linked in with it, but this is only an
+/// estimate: they may be missing, or refer to older versions than the glibc linked.
+pub fn estimate_static_glibc(data: &[u8]) -> Option<NamespacedVersion> {
+    const PREFIX: &[u8] = b"GLIBC_2.";
+    let mut newest: Option<NamespacedVersion> = None;
+    let mut rest = data;
+    while let Some(start) = rest
+        .windows(PREFIX.len())
+        .position(|window| window == PREFIX)
+    {
+        let candidate = &rest[start..];
+        let len = PREFIX.len()
+            + candidate[PREFIX.len()..]
+                .iter()
+                .take_while(|&&byte| byte.is_ascii_digit() || byte == b'.')
+                .count();
+        if let Ok(version) = NamespacedVersion::parse(&String::from_utf8_lossy(&candidate[..len]))
+            && newest.as_ref().is_none_or(|newest| version > *newest)
+        {
+            newest = Some(version);
+        }
+        rest = &candidate[len..];
+    }
+    newest
+}
+
+/// Returns the version references of the undefined dynamic symbols. Symbols whose versions can't be read are returned
+/// as unreadable, along with the rest; if the version table can't be read at all, it is the only unreadable version.
+pub fn get_version_references(data: &[u8]) -> anyhow::Result<VersionReferences> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_version_references(ElfFile32::parse(data)?, data),
+        FileKind::Elf64 => get_elf_version_references(ElfFile64::parse(data)?, data),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+/// Returns the libraries a file depends on, as listed by its `DT_NEEDED` entries.
+pub fn get_needed_libraries(data: &[u8]) -> anyhow::Result<Vec<String>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_dynamic_strings(&ElfFile32::parse(data)?, object::elf::DT_NEEDED),
+        FileKind::Elf64 => get_dynamic_strings(&ElfFile64::parse(data)?, object::elf::DT_NEEDED),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+/// Returns the directories a file's run path gives for finding the libraries it depends on: its `DT_RUNPATH` entries,
+/// or if it has none, its `DT_RPATH` entries, which the dynamic linker ignores when there is a `DT_RUNPATH`. The
+/// directories are as given, e.g. `$ORIGIN/../lib`.
+pub fn get_run_path(data: &[u8]) -> anyhow::Result<Vec<String>> {
+    let entries = |tag| match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_dynamic_strings(&ElfFile32::parse(data)?, tag),
+        FileKind::Elf64 => get_dynamic_strings(&ElfFile64::parse(data)?, tag),
+        _ => Err(anyhow!("Unsupported file format")),
+    };
+    let mut run_path = entries(object::elf::DT_RUNPATH)?;
+    if run_path.is_empty() {
+        run_path = entries(object::elf::DT_RPATH)?;
+    }
+    Ok(run_path
+        .iter()
+        .flat_map(|entry| entry.split(':'))
+        .filter(|dir| !dir.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the names of the undefined dynamic symbols, which a file imports from the libraries it depends on.
+pub fn get_imported_symbols(data: &[u8]) -> anyhow::Result<Vec<String>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_imported_symbols(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_imported_symbols(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_imported_symbols<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Vec<String>> {
+    elf.dynamic_symbols()
+        .filter(|s| s.is_undefined())
+        .map(|symbol| {
+            Ok(symbol
+                .name()
+                .with_context(|| "Error reading symbol name")?
+                .to_string())
+        })
+        .filter(|name| !matches!(name, Ok(name) if name.is_empty()))
+        .collect()
+}
+
+/// Returns the undefined dynamic symbols a file imports, with the versions they reference, if any.
+pub fn get_symbol_imports(data: &[u8]) -> anyhow::Result<Vec<SymbolImport>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_symbol_imports(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_symbol_imports(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_symbol_imports<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Vec<SymbolImport>> {
+    let endian = elf.endianness();
+    let version_table = elf
+        .elf_section_table()
+        .versions(endian, elf.data())
+        .with_context(|| "Error getting version table")?;
+
+    let mut imports = Vec::new();
+    for symbol in elf.dynamic_symbols().filter(|s| s.is_undefined()) {
+        let name = symbol.name().with_context(|| "Error reading symbol name")?;
+        if name.is_empty() {
+            continue;
+        }
+        let version = match &version_table {
+            Some(version_table) => version_table
+                .version(version_table.version_index(endian, symbol.index()))
+                .with_context(|| "Error reading symbol version info")?,
+            None => None,
+        };
+        imports.push(SymbolImport {
+            name: name.to_string(),
+            version: version.map(|v| String::from_utf8_lossy(v.name()).to_string()),
+            file: version
+                .and_then(|v| v.file())
+                .map(|f| String::from_utf8_lossy(f).to_string()),
+            is_weak: symbol.is_weak(),
+        });
+    }
+    Ok(imports)
+}
+
+/// Returns the names of the undefined symbols a file references: its imported dynamic symbols, or for relocatable
+/// objects, which have no dynamic symbols, the undefined symbols in its symbol table.
+pub fn get_undefined_symbols(data: &[u8]) -> anyhow::Result<Vec<String>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_undefined_symbols(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_undefined_symbols(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_undefined_symbols<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Vec<String>> {
+    if elf.elf_header().e_type(elf.endianness()) != object::elf::ET_REL {
+        return get_elf_imported_symbols(elf);
+    }
+    elf.symbols()
+        .filter(|s| s.is_undefined() && s.is_global())
+        .map(|symbol| {
+            Ok(symbol
+                .name()
+                .with_context(|| "Error reading symbol name")?
+                .to_string())
+        })
+        .filter(|name| !matches!(name, Ok(name) if name.is_empty()))
+        .collect()
+}
+
+/// Checks whether a dynamically linked file is linked against musl, which doesn't use symbol versioning, as shown by
+/// its program interpreter (e.g. `/lib/ld-musl-x86_64.so.1`) or the libraries it depends on (e.g.
+/// `libc.musl-x86_64.so.1`).
+pub fn is_musl(data: &[u8]) -> anyhow::Result<bool> {
+    let is_musl_name = |path: &str| {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        name.starts_with("ld-musl-") || name.starts_with("libc.musl-")
+    };
+    Ok(
+        get_interpreter(data)?.is_some_and(|interpreter| is_musl_name(&interpreter))
+            || get_needed_libraries(data)?
+                .iter()
+                .any(|library| is_musl_name(library)),
+    )
+}
+
+/// Returns the program interpreter a file is loaded by, as given by its `PT_INTERP` segment. Shared libraries and
+/// statically linked executables have no interpreter.
+pub fn get_interpreter(data: &[u8]) -> anyhow::Result<Option<String>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_interpreter(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_interpreter(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_interpreter<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Option<String>> {
+    for header in elf.elf_program_headers() {
+        if let Some(interpreter) = header
+            .interpreter(elf.endianness(), elf.data())
+            .with_context(|| "Error reading program interpreter")?
+        {
+            return Ok(Some(String::from_utf8_lossy(interpreter).to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the names of the version nodes a file defines (`.gnu.version_d`), such as `LIBFOO_1.0`, excluding the base
+/// definition which names the file itself.
+pub fn get_version_definitions(data: &[u8]) -> anyhow::Result<Vec<String>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_version_definitions(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_version_definitions(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_version_definitions<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Vec<String>> {
+    let endian = elf.endianness();
+    let sections = elf.elf_section_table();
+    let Some((mut verdefs, link)) = sections
+        .gnu_verdef(endian, elf.data())
+        .with_context(|| "Error getting version definitions")?
+    else {
+        return Ok(Vec::new());
+    };
+    let strings = sections
+        .strings(endian, elf.data(), link)
+        .with_context(|| "Error getting version definition strings")?;
+
+    let mut definitions = Vec::new();
+    while let Some((verdef, mut verdauxs)) = verdefs
+        .next()
+        .with_context(|| "Error reading version definitions")?
+    {
+        if verdef.vd_flags.get(endian) & object::elf::VER_FLG_BASE != 0 {
+            continue;
+        }
+        // The first auxiliary entry names the version, and any others name its parents
+        if let Some(verdaux) = verdauxs
+            .next()
+            .with_context(|| "Error reading version definition")?
+        {
+            let name = verdaux
+                .name(endian, strings)
+                .with_context(|| "Error reading version definition name")?;
+            definitions.push(String::from_utf8_lossy(name).to_string());
+        }
+    }
+    Ok(definitions)
+}
+
+/// Returns the dynamic symbols a file defines for other files to use, with their versions.
+pub fn get_exported_symbols(data: &[u8]) -> anyhow::Result<Vec<ExportedSymbol>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_exported_symbols(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_exported_symbols(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_exported_symbols<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Vec<ExportedSymbol>> {
+    let endian = elf.endianness();
+    let version_table = elf
+        .elf_section_table()
+        .versions(endian, elf.data())
+        .with_context(|| "Error getting version table")?;
+
+    let mut exports = Vec::new();
+    for symbol in elf
+        .dynamic_symbols()
+        .filter(|s| !s.is_undefined() && s.is_global())
+    {
+        let name = symbol.name().with_context(|| "Error reading symbol name")?;
+        let (version, is_default) = match &version_table {
+            Some(version_table) => {
+                let version_index = version_table.version_index(endian, symbol.index());
+                let version = version_table
+                    .version(version_index)
+                    .with_context(|| "Error reading symbol version info")?
+                    .map(|v| String::from_utf8_lossy(v.name()).to_string());
+                (version, !version_index.is_hidden())
+            }
+            None => (None, true),
+        };
+        // Linkers define an absolute symbol named after each version node, which isn't part of the interface
+        if version.as_deref() == Some(name) {
+            continue;
+        }
+        exports.push(ExportedSymbol {
+            name: name.to_string(),
+            version,
+            is_default,
+        });
+    }
+    Ok(exports)
+}
+
+/// Returns the GNU build ID of a file (`NT_GNU_BUILD_ID`) in hexadecimal, if it has one.
+pub fn get_build_id(data: &[u8]) -> anyhow::Result<Option<String>> {
+    let build_id = match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => ElfFile32::<Endianness>::parse(data)?.build_id(),
+        FileKind::Elf64 => ElfFile64::<Endianness>::parse(data)?.build_id(),
+        _ => return Err(anyhow!("Unsupported file format")),
+    }
+    .with_context(|| "Error reading build ID")?;
+    Ok(build_id.map(|build_id| {
+        build_id
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }))
+}
+
+/// Returns identifying information from the header of an ELF file.
+pub fn get_header(data: &[u8]) -> anyhow::Result<ElfHeader> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_header(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_header(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_header<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<ElfHeader> {
+    let endian = elf.endianness();
+    let header = elf.elf_header();
+    let machine = match header.e_machine(endian) {
+        object::elf::EM_386 => "i386".to_string(),
+        object::elf::EM_X86_64 => "x86_64".to_string(),
+        object::elf::EM_ARM => "arm".to_string(),
+        object::elf::EM_AARCH64 => "aarch64".to_string(),
+        object::elf::EM_RISCV => "riscv".to_string(),
+        object::elf::EM_PPC => "ppc".to_string(),
+        object::elf::EM_PPC64 => "ppc64".to_string(),
+        object::elf::EM_S390 => "s390".to_string(),
+        object::elf::EM_MIPS => "mips".to_string(),
+        object::elf::EM_SPARC => "sparc".to_string(),
+        object::elf::EM_SPARCV9 => "sparcv9".to_string(),
+        object::elf::EM_LOONGARCH => "loongarch".to_string(),
+        machine => machine.to_string(),
+    };
+    let os_abi = match header.e_ident().os_abi {
+        object::elf::ELFOSABI_SYSV => "SYSV".to_string(),
+        object::elf::ELFOSABI_HPUX => "HP-UX".to_string(),
+        object::elf::ELFOSABI_NETBSD => "NetBSD".to_string(),
+        object::elf::ELFOSABI_GNU => "GNU".to_string(),
+        object::elf::ELFOSABI_SOLARIS => "Solaris".to_string(),
+        object::elf::ELFOSABI_FREEBSD => "FreeBSD".to_string(),
+        object::elf::ELFOSABI_OPENBSD => "OpenBSD".to_string(),
+        object::elf::ELFOSABI_ARM => "ARM".to_string(),
+        object::elf::ELFOSABI_STANDALONE => "standalone".to_string(),
+        os_abi => os_abi.to_string(),
+    };
+    let file_type = match header.e_type(endian) {
+        object::elf::ET_REL => "REL".to_string(),
+        object::elf::ET_EXEC => "EXEC".to_string(),
+        object::elf::ET_DYN => "DYN".to_string(),
+        object::elf::ET_CORE => "CORE".to_string(),
+        file_type => file_type.to_string(),
+    };
+    Ok(ElfHeader {
+        machine,
+        class: if elf.is_64() { 64 } else { 32 },
+        little_endian: elf.is_little_endian(),
+        os_abi,
+        file_type,
+    })
+}
+
+/// Returns the ABI tag of a file, if it has one. This is read from the file's note segments, so that it is found even
+/// if the section headers have been stripped.
+pub fn get_abi_tag(data: &[u8]) -> anyhow::Result<Option<AbiTag>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_abi_tag(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_abi_tag(&ElfFile64::parse(data)?),
         _ => Err(anyhow!("Unsupported file format")),
     }
 }
 
-fn get_elf_dyn_undef_symbols<'data, Elf: FileHeader<Endian = Endianness>>(
+fn get_elf_abi_tag<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Option<AbiTag>> {
+    let endian = elf.endianness();
+    for header in elf.elf_program_headers() {
+        let Some(mut notes) = header
+            .notes(endian, elf.data())
+            .with_context(|| "Error reading notes")?
+        else {
+            continue;
+        };
+        while let Some(note) = notes.next().with_context(|| "Error reading note")? {
+            if note.name() != object::elf::ELF_NOTE_GNU
+                || note.n_type(endian) != object::elf::NT_GNU_ABI_TAG
+            {
+                continue;
+            }
+            let words: Vec<u32> = note
+                .desc()
+                .chunks_exact(4)
+                .map(|word| endian.read_u32_bytes(word.try_into().unwrap()))
+                .collect();
+            let [os, major, minor, patch, ..] = words[..] else {
+                return Err(anyhow!("Invalid ABI tag note"));
+            };
+            let os = match os {
+                object::elf::ELF_NOTE_OS_LINUX => "Linux".to_string(),
+                object::elf::ELF_NOTE_OS_GNU => "GNU".to_string(),
+                object::elf::ELF_NOTE_OS_SOLARIS2 => "Solaris".to_string(),
+                object::elf::ELF_NOTE_OS_FREEBSD => "FreeBSD".to_string(),
+                os => format!("OS {}", os),
+            };
+            return Ok(Some(AbiTag {
+                os,
+                version: Version::parse(&format!("{}.{}.{}", major, minor, patch))?,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the values of the dynamic section entries with the given tag, which must be one with a string value.
+fn get_dynamic_strings<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+    tag: u32,
+) -> anyhow::Result<Vec<String>> {
+    let endian = elf.endianness();
+    let sections = elf.elf_section_table();
+    let Some((entries, strings_index)) = sections
+        .dynamic(endian, elf.data())
+        .with_context(|| "Error getting dynamic section")?
+    else {
+        return Ok(Vec::new());
+    };
+    let strings = sections
+        .strings(endian, elf.data(), strings_index)
+        .with_context(|| "Error getting dynamic strings")?;
+    entries
+        .iter()
+        .filter(|entry| entry.tag32(endian) == Some(tag))
+        .map(|entry| {
+            let value = entry
+                .string(endian, strings)
+                .with_context(|| "Error reading dynamic string")?;
+            Ok(String::from_utf8_lossy(value).to_string())
+        })
+        .collect()
+}
+
+/// Returns the versions needed by a file from each library, without examining the individual symbols which need them.
+pub fn get_needed_versions(data: &[u8]) -> anyhow::Result<Vec<NeededVersion>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_needed_versions(ElfFile32::parse(data)?, data),
+        FileKind::Elf64 => get_elf_needed_versions(ElfFile64::parse(data)?, data),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_needed_versions<'data, Elf: FileHeader<Endian = Endianness>>(
     elf: ElfFile<'data, Elf>,
     data: &'data [u8],
-) -> anyhow::Result<Vec<SymbolVersion>> {
-    let version_table = elf
+) -> anyhow::Result<Vec<NeededVersion>> {
+    if is_static_elf(&elf) {
+        return Ok(Vec::new());
+    }
+    let endian = elf.endianness();
+    let sections = elf
         .elf_header()
-        .sections(elf.endianness(), data)
-        .with_context(|| "Error getting section table")?
-        .versions(elf.endianness(), data)
-        .with_context(|| "Error getting version table")?
+        .sections(endian, data)
+        .with_context(|| "Error getting section table")?;
+    let (mut verneeds, link) = sections
+        .gnu_verneed(endian, data)
+        .with_context(|| "Error getting version needs")?
         .ok_or_else(|| anyhow!("No version table found"))?;
+    let strings = sections
+        .strings(endian, data, link)
+        .with_context(|| "Error getting version needs strings")?;
 
-    elf.dynamic_symbols()
-        .filter(|s| s.is_undefined())
-        .map(|symbol| {
-            let name = symbol.name().with_context(|| "Error reading symbol name")?;
-            let version_index = version_table.version_index(elf.endianness(), symbol.index());
-            let version = version_table
-                .version(version_index)
-                .with_context(|| "Error reading symbol version info")?;
-            match version {
-                Some(v) => {
-                    let version = String::from_utf8_lossy(v.name()).to_string();
-                    let file = v.file().map(|f| String::from_utf8_lossy(f).to_string());
-                    match NamespacedVersion::parse(&version) {
-                        Ok(version) => Ok(Some(SymbolVersion {
-                            name: name.to_string(),
-                            version,
-                            file,
-                        })),
-                        Err(_) => Ok(None),
-                    }
+    let mut needed_versions = Vec::new();
+    while let Some((verneed, mut vernauxs)) = verneeds
+        .next()
+        .with_context(|| "Error reading version needs")?
+    {
+        let file = verneed
+            .file(endian, strings)
+            .with_context(|| "Error reading version needs file")?;
+        while let Some(vernaux) = vernauxs
+            .next()
+            .with_context(|| "Error reading version needs")?
+        {
+            let version = vernaux
+                .name(endian, strings)
+                .with_context(|| "Error reading needed version name")?;
+            needed_versions.push(NeededVersion {
+                version: String::from_utf8_lossy(version).to_string(),
+                file: String::from_utf8_lossy(file).to_string(),
+            });
+        }
+    }
+    Ok(needed_versions)
+}
+
+fn get_elf_version_references<'data, Elf: FileHeader<Endian = Endianness>>(
+    elf: ElfFile<'data, Elf>,
+    data: &'data [u8],
+) -> anyhow::Result<VersionReferences> {
+    if is_static_elf(&elf) {
+        return Ok(VersionReferences::default());
+    }
+    let sections = elf
+        .elf_header()
+        .sections(elf.endianness(), data)
+        .with_context(|| "Error getting section table")?;
+    let mut references = VersionReferences::default();
+    let version_table = match sections.versions(elf.endianness(), data) {
+        Ok(Some(version_table)) => version_table,
+        Ok(None) => return Err(anyhow!("No version table found")),
+        Err(e) => {
+            references.unreadable.push(UnreadableVersion {
+                symbol: None,
+                error: format!("Error getting version table: {}", e),
+            });
+            return Ok(references);
+        }
+    };
+
+    for symbol in elf.dynamic_symbols().filter(|s| s.is_undefined()) {
+        let name = match symbol.name() {
+            Ok(name) => name,
+            Err(e) => {
+                references.unreadable.push(UnreadableVersion {
+                    symbol: Some(format!("symbol {}", symbol.index().0)),
+                    error: format!("Error reading symbol name: {}", e),
+                });
+                continue;
+            }
+        };
+        let version_index = version_table.version_index(elf.endianness(), symbol.index());
+        let v = match version_table.version(version_index) {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                references.unreadable.push(UnreadableVersion {
+                    symbol: Some(name.to_string()),
+                    error: format!("Error reading symbol version info: {}", e),
+                });
+                continue;
+            }
+        };
+        let version = String::from_utf8_lossy(v.name()).to_string();
+        let file = v.file().map(|f| String::from_utf8_lossy(f).to_string());
+        // Private versions, such as FBSDprivate_1.0, may be numbered, but the numbers aren't comparable between
+        // releases
+        let namespaced_version = if is_private_version(&version) {
+            Err(anyhow!("Private version: {}", version))
+        } else {
+            NamespacedVersion::parse(&version)
+        };
+        match namespaced_version {
+            Ok(version) => {
+                let elf_symbol = symbol.elf_symbol();
+                let symbol = SymbolVersion {
+                    name: name.to_string(),
+                    version,
+                    file,
+                    symbol_type: Some(SymbolType::from_elf(elf_symbol.st_type())),
+                    binding: Some(SymbolBinding::from_elf(elf_symbol.st_bind())),
+                };
+                if version_index.is_hidden() {
+                    references.hidden_symbols.push(symbol);
+                } else {
+                    references.symbols.push(symbol);
                 }
-                None => Ok(None),
             }
-        })
-        .filter_map(|symbol| symbol.transpose())
-        .collect::<Result<Vec<_>, _>>()
+            Err(_) => references.unnumbered.push(UnnumberedVersionReference {
+                name: name.to_string(),
+                version,
+                file,
+            }),
+        }
+    }
+    Ok(references)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use object::ObjectSection;
+
+    fn reference(version: &str) -> UnnumberedVersionReference {
+        UnnumberedVersionReference {
+            name: "__libc_dlopen_mode".to_string(),
+            version: version.to_string(),
+            file: Some("libc.so.6".to_string()),
+        }
+    }
+
+    #[test]
+    fn private_versions_are_private() {
+        assert!(reference("GLIBC_PRIVATE").is_private());
+        assert!(!reference("GLIBC_ABI_DT_RELR").is_private());
+        assert!(reference("FBSDprivate_1.0").is_private());
+        assert!(reference("SUNWprivate").is_private());
+    }
+
+    #[test]
+    fn test_executable_needs_libc() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let libraries = get_needed_libraries(&data).unwrap();
+        assert!(
+            libraries
+                .iter()
+                .any(|library| library.starts_with("libc.so"))
+        );
+    }
+
+    #[test]
+    fn reads_run_path() {
+        use object::elf::{DT_NEEDED, DT_RPATH, DT_RUNPATH};
+        let data = build_elf_with_dynamic(&[
+            (DT_NEEDED, "libfoo.so.1"),
+            (DT_RPATH, "/opt/old/lib"),
+            (DT_RUNPATH, "$ORIGIN/../lib:/opt/app/lib"),
+        ]);
+        assert_eq!(get_needed_libraries(&data).unwrap(), ["libfoo.so.1"]);
+        assert_eq!(
+            get_run_path(&data).unwrap(),
+            ["$ORIGIN/../lib", "/opt/app/lib"]
+        );
+        // DT_RPATH only applies without a DT_RUNPATH
+        let data = build_elf_with_dynamic(&[(DT_RPATH, "/opt/old/lib")]);
+        assert_eq!(get_run_path(&data).unwrap(), ["/opt/old/lib"]);
+        assert!(
+            get_run_path(&build_elf_with_dynamic(&[]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_executable_references_have_types_and_bindings() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let references = get_version_references(&data).unwrap();
+        let malloc = references
+            .symbols
+            .iter()
+            .find(|symbol| symbol.name == "malloc")
+            .unwrap();
+        assert_eq!(malloc.symbol_type, Some(SymbolType::Func));
+        assert_eq!(malloc.binding, Some(SymbolBinding::Global));
+    }
+
+    #[test]
+    fn test_executable_imports_malloc() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let imports = get_imported_symbols(&data).unwrap();
+        assert!(imports.iter().any(|name| name == "malloc"));
+        assert!(!imports.iter().any(|name| name.is_empty()));
+    }
+
+    /// Reads the test executable, overwriting the given bytes of one of its sections.
+    pub fn corrupt_test_executable(section: &str, offset: usize, bytes: &[u8]) -> Vec<u8> {
+        let mut data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let (start, _) = ElfFile64::<Endianness>::parse(&*data)
+            .unwrap()
+            .section_by_name(section)
+            .unwrap()
+            .file_range()
+            .unwrap();
+        let start = start as usize + offset;
+        data[start..start + bytes.len()].copy_from_slice(bytes);
+        data
+    }
+
+    /// Reads the test executable, giving the version reference of `malloc` an invalid version index.
+    pub fn test_executable_with_unreadable_version() -> Vec<u8> {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let index = ElfFile64::<Endianness>::parse(&*data)
+            .unwrap()
+            .dynamic_symbols()
+            .find(|symbol| symbol.name() == Ok("malloc"))
+            .unwrap()
+            .index()
+            .0;
+        corrupt_test_executable(".gnu.version", index * 2, &0x7ffeu16.to_le_bytes())
+    }
+
+    #[test]
+    fn reports_unreadable_versions() {
+        let references =
+            get_version_references(&test_executable_with_unreadable_version()).unwrap();
+        assert_eq!(references.unreadable.len(), 1);
+        assert_eq!(references.unreadable[0].symbol.as_deref(), Some("malloc"));
+        assert!(!references.symbols.is_empty());
+
+        // An invalid file name offset in the first version needs entry makes the whole table unreadable
+        let data = corrupt_test_executable(".gnu.version_r", 4, &u32::MAX.to_le_bytes());
+        let references = get_version_references(&data).unwrap();
+        assert_eq!(references.unreadable.len(), 1);
+        assert_eq!(references.unreadable[0].symbol, None);
+        assert!(references.symbols.is_empty());
+    }
+
+    /// Builds a minimal little endian 64-bit executable with no program headers, followed by the given data.
+    pub fn build_static_elf(trailer: &[u8]) -> Vec<u8> {
+        build_elf(object::elf::ET_EXEC, trailer)
+    }
+
+    /// Builds a minimal little endian 64-bit ELF file of the given type, with no program headers or sections, followed
+    /// by the given data.
+    pub fn build_elf(e_type: u16, trailer: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[..4].copy_from_slice(&object::elf::ELFMAG);
+        data[4] = object::elf::ELFCLASS64;
+        data[5] = object::elf::ELFDATA2LSB;
+        data[6] = object::elf::EV_CURRENT;
+        data[16..18].copy_from_slice(&e_type.to_le_bytes());
+        data[18..20].copy_from_slice(&object::elf::EM_X86_64.to_le_bytes());
+        data[20..24].copy_from_slice(&u32::from(object::elf::EV_CURRENT).to_le_bytes());
+        data[52..54].copy_from_slice(&64u16.to_le_bytes());
+        data[54..56].copy_from_slice(&56u16.to_le_bytes());
+        data[58..60].copy_from_slice(&64u16.to_le_bytes());
+        data.extend(trailer);
+        data
+    }
+
+    /// Builds a minimal little endian 64-bit ELF file of the given type with sections of the given names, types and
+    /// flags, each having no contents.
+    pub fn build_elf_with_sections(e_type: u16, sections: &[(&str, u32, u32)]) -> Vec<u8> {
+        const SHDR_SIZE: usize = 64;
+        let mut names = vec![0u8];
+        let mut name_offsets = Vec::new();
+        for (name, _, _) in sections.iter().chain([&(".shstrtab", 0, 0)]) {
+            name_offsets.push(names.len() as u32);
+            names.extend(name.as_bytes());
+            names.push(0);
+        }
+        let names_offset = 64;
+        let headers_offset = names_offset + names.len();
+
+        let mut headers = vec![0u8; SHDR_SIZE];
+        for (index, (_, sh_type, sh_flags)) in sections
+            .iter()
+            .chain([&("", object::elf::SHT_STRTAB, 0)])
+            .enumerate()
+        {
+            let mut header = vec![0u8; SHDR_SIZE];
+            header[..4].copy_from_slice(&name_offsets[index].to_le_bytes());
+            header[4..8].copy_from_slice(&sh_type.to_le_bytes());
+            header[8..16].copy_from_slice(&u64::from(*sh_flags).to_le_bytes());
+            if index == sections.len() {
+                header[24..32].copy_from_slice(&(names_offset as u64).to_le_bytes());
+                header[32..40].copy_from_slice(&(names.len() as u64).to_le_bytes());
+            }
+            headers.extend(header);
+        }
+
+        let section_count = sections.len() as u16 + 2;
+        let mut data = build_elf(e_type, &names);
+        data[40..48].copy_from_slice(&(headers_offset as u64).to_le_bytes());
+        data[60..62].copy_from_slice(&section_count.to_le_bytes());
+        data[62..64].copy_from_slice(&(section_count - 1).to_le_bytes());
+        data.extend(headers);
+        data
+    }
+
+    /// Builds a minimal little endian 64-bit executable loaded by the given program interpreter, with no sections.
+    pub fn build_elf_with_interpreter(interpreter: &str) -> Vec<u8> {
+        const PHDR_SIZE: usize = 56;
+        let interpreter_offset = 64 + PHDR_SIZE;
+        let interpreter_size = interpreter.len() + 1;
+        let mut phdr = vec![0u8; PHDR_SIZE];
+        phdr[..4].copy_from_slice(&object::elf::PT_INTERP.to_le_bytes());
+        phdr[4..8].copy_from_slice(&object::elf::PF_R.to_le_bytes());
+        phdr[8..16].copy_from_slice(&(interpreter_offset as u64).to_le_bytes());
+        phdr[32..40].copy_from_slice(&(interpreter_size as u64).to_le_bytes());
+        phdr[40..48].copy_from_slice(&(interpreter_size as u64).to_le_bytes());
+        phdr.extend(interpreter.as_bytes());
+        phdr.push(0);
+
+        let mut data = build_elf(object::elf::ET_EXEC, &phdr);
+        data[32..40].copy_from_slice(&64u64.to_le_bytes());
+        data[56..58].copy_from_slice(&1u16.to_le_bytes());
+        data
+    }
+
+    /// Builds a minimal little endian 64-bit shared library with a dynamic section of the given entries, each a tag
+    /// such as `DT_NEEDED` and a string, and no symbols.
+    pub fn build_elf_with_dynamic(entries: &[(u32, &str)]) -> Vec<u8> {
+        const SHDR_SIZE: usize = 64;
+        let mut strings = vec![0u8];
+        let mut dynamic = Vec::new();
+        for (tag, value) in entries {
+            dynamic.extend(u64::from(*tag).to_le_bytes());
+            dynamic.extend((strings.len() as u64).to_le_bytes());
+            strings.extend(value.as_bytes());
+            strings.push(0);
+        }
+        dynamic.extend([0u8; 16]);
+
+        let strings_offset = 64;
+        let dynamic_offset = (strings_offset + strings.len()).next_multiple_of(8);
+        let headers_offset = dynamic_offset + dynamic.len();
+        let mut data = build_elf(object::elf::ET_DYN, &strings);
+        data[40..48].copy_from_slice(&(headers_offset as u64).to_le_bytes());
+        data[60..62].copy_from_slice(&3u16.to_le_bytes());
+        data[62..64].copy_from_slice(&1u16.to_le_bytes());
+        data.resize(dynamic_offset, 0);
+        data.extend(&dynamic);
+        let section_header = |sh_type: u32, offset: usize, size: usize, link: u32, entsize: u64| {
+            let mut header = vec![0u8; SHDR_SIZE];
+            header[4..8].copy_from_slice(&sh_type.to_le_bytes());
+            header[24..32].copy_from_slice(&(offset as u64).to_le_bytes());
+            header[32..40].copy_from_slice(&(size as u64).to_le_bytes());
+            header[40..44].copy_from_slice(&link.to_le_bytes());
+            header[56..64].copy_from_slice(&entsize.to_le_bytes());
+            header
+        };
+        data.extend([0u8; SHDR_SIZE]);
+        data.extend(section_header(
+            object::elf::SHT_STRTAB,
+            strings_offset,
+            strings.len(),
+            0,
+            0,
+        ));
+        data.extend(section_header(
+            object::elf::SHT_DYNAMIC,
+            dynamic_offset,
+            dynamic.len(),
+            1,
+            16,
+        ));
+        data
+    }
+
+    /// Builds a minimal little endian 64-bit shared library with a version definitions section defining the given
+    /// versions, after a base definition for the library itself, and dynamic symbols defining the given symbols. Each
+    /// symbol has a name, the version it is defined with, if any, and whether it is the default version.
+    pub fn build_shared_library(
+        versions: &[&str],
+        symbols: &[(&str, Option<&str>, bool)],
+    ) -> Vec<u8> {
+        const VERDEF_SIZE: usize = 20;
+        const VERDAUX_SIZE: usize = 8;
+        const SYM_SIZE: usize = 24;
+        let names: Vec<&str> = std::iter::once("libfoo.so.1")
+            .chain(versions.iter().copied())
+            .collect();
+        let mut strings = vec![0u8];
+        let mut verdefs = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            let flags = if index == 0 {
+                object::elf::VER_FLG_BASE
+            } else {
+                0
+            };
+            let next = if index + 1 < names.len() {
+                VERDEF_SIZE + VERDAUX_SIZE
+            } else {
+                0
+            };
+            verdefs.extend(1u16.to_le_bytes());
+            verdefs.extend(flags.to_le_bytes());
+            verdefs.extend((index as u16 + 1).to_le_bytes());
+            verdefs.extend(1u16.to_le_bytes());
+            verdefs.extend(0u32.to_le_bytes());
+            verdefs.extend((VERDEF_SIZE as u32).to_le_bytes());
+            verdefs.extend((next as u32).to_le_bytes());
+            verdefs.extend((strings.len() as u32).to_le_bytes());
+            verdefs.extend(0u32.to_le_bytes());
+            strings.extend(name.as_bytes());
+            strings.push(0);
+        }
+        let mut dynamic_symbols = vec![0u8; SYM_SIZE];
+        let mut version_indexes = vec![0u8; 2];
+        for (name, version, is_default) in symbols {
+            dynamic_symbols.extend((strings.len() as u32).to_le_bytes());
+            dynamic_symbols.push((object::elf::STB_GLOBAL << 4) | object::elf::STT_FUNC);
+            dynamic_symbols.push(0);
+            dynamic_symbols.extend(1u16.to_le_bytes());
+            dynamic_symbols.extend([0u8; 16]);
+            strings.extend(name.as_bytes());
+            strings.push(0);
+            let index = match version {
+                Some(version) => names.iter().position(|name| name == version).unwrap() as u16 + 1,
+                None => object::elf::VER_NDX_GLOBAL,
+            };
+            let hidden = if *is_default {
+                0
+            } else {
+                object::elf::VERSYM_HIDDEN
+            };
+            version_indexes.extend((index | hidden).to_le_bytes());
+        }
+
+        let verdefs_offset = 64;
+        let strings_offset = verdefs_offset + verdefs.len();
+        let symbols_offset = (strings_offset + strings.len()).next_multiple_of(8);
+        let versions_offset = symbols_offset + dynamic_symbols.len();
+        let section_headers_offset = (versions_offset + version_indexes.len()).next_multiple_of(8);
+        let mut data = build_elf(object::elf::ET_DYN, &[]);
+        data[40..48].copy_from_slice(&(section_headers_offset as u64).to_le_bytes());
+        data[60..62].copy_from_slice(&5u16.to_le_bytes());
+        data[62..64].copy_from_slice(&2u16.to_le_bytes());
+        data.extend(&verdefs);
+        data.extend(&strings);
+        data.resize(symbols_offset, 0);
+        data.extend(&dynamic_symbols);
+        data.extend(&version_indexes);
+        data.resize(section_headers_offset, 0);
+        let section_header =
+            |sh_type: u32, offset: usize, size: usize, link: u32, info: u32, entsize: usize| {
+                let mut header = Vec::new();
+                header.extend(0u32.to_le_bytes());
+                header.extend(sh_type.to_le_bytes());
+                header.extend([0u8; 16]);
+                header.extend((offset as u64).to_le_bytes());
+                header.extend((size as u64).to_le_bytes());
+                header.extend(link.to_le_bytes());
+                header.extend(info.to_le_bytes());
+                header.extend(8u64.to_le_bytes());
+                header.extend((entsize as u64).to_le_bytes());
+                header
+            };
+        data.extend([0u8; 64]);
+        data.extend(section_header(
+            object::elf::SHT_GNU_VERDEF,
+            verdefs_offset,
+            verdefs.len(),
+            2,
+            names.len() as u32,
+            0,
+        ));
+        data.extend(section_header(
+            object::elf::SHT_STRTAB,
+            strings_offset,
+            strings.len(),
+            0,
+            0,
+            0,
+        ));
+        data.extend(section_header(
+            object::elf::SHT_DYNSYM,
+            symbols_offset,
+            dynamic_symbols.len(),
+            2,
+            1,
+            SYM_SIZE,
+        ));
+        data.extend(section_header(
+            object::elf::SHT_GNU_VERSYM,
+            versions_offset,
+            version_indexes.len(),
+            3,
+            0,
+            2,
+        ));
+        data
+    }
+
+    /// Builds a minimal little endian 64-bit relocatable object file with a symbol table of global symbols, each
+    /// having a name and whether it is defined, rather than referenced.
+    pub fn build_object(symbols: &[(&str, bool)]) -> Vec<u8> {
+        const SYM_SIZE: usize = 24;
+        let mut strings = vec![0u8];
+        let mut symbol_table = vec![0u8; SYM_SIZE];
+        for (name, is_defined) in symbols {
+            symbol_table.extend((strings.len() as u32).to_le_bytes());
+            symbol_table.push((object::elf::STB_GLOBAL << 4) | object::elf::STT_FUNC);
+            symbol_table.push(0);
+            let section = if *is_defined { 2u16 } else { 0 };
+            symbol_table.extend(section.to_le_bytes());
+            symbol_table.extend([0u8; 16]);
+            strings.extend(name.as_bytes());
+            strings.push(0);
+        }
+
+        let symbols_offset = 64;
+        let strings_offset = symbols_offset + symbol_table.len();
+        let section_headers_offset = (strings_offset + strings.len()).next_multiple_of(8);
+        let mut data = build_elf(object::elf::ET_REL, &symbol_table);
+        data[40..48].copy_from_slice(&(section_headers_offset as u64).to_le_bytes());
+        data[60..62].copy_from_slice(&3u16.to_le_bytes());
+        data[62..64].copy_from_slice(&2u16.to_le_bytes());
+        data.extend(&strings);
+        data.resize(section_headers_offset, 0);
+        let section_header =
+            |sh_type: u32, offset: usize, size: usize, link: u32, entsize: usize| {
+                let mut header = Vec::new();
+                header.extend(0u32.to_le_bytes());
+                header.extend(sh_type.to_le_bytes());
+                header.extend([0u8; 16]);
+                header.extend((offset as u64).to_le_bytes());
+                header.extend((size as u64).to_le_bytes());
+                header.extend(link.to_le_bytes());
+                header.extend(1u32.to_le_bytes());
+                header.extend(8u64.to_le_bytes());
+                header.extend((entsize as u64).to_le_bytes());
+                header
+            };
+        data.extend([0u8; 64]);
+        data.extend(section_header(
+            object::elf::SHT_SYMTAB,
+            symbols_offset,
+            symbol_table.len(),
+            2,
+            SYM_SIZE,
+        ));
+        data.extend(section_header(
+            object::elf::SHT_STRTAB,
+            strings_offset,
+            strings.len(),
+            0,
+            0,
+        ));
+        data
+    }
+
+    #[test]
+    fn reads_undefined_symbols_of_objects() {
+        let data = build_object(&[("getrandom", false), ("fill", true), ("strlen", false)]);
+        assert_eq!(
+            get_undefined_symbols(&data).unwrap(),
+            vec!["getrandom", "strlen"]
+        );
+        assert!(!has_version_table(&data).unwrap());
+    }
+
+    #[test]
+    fn reads_version_definitions() {
+        let data = build_shared_library(&["LIBFOO_1.0", "LIBFOO_1.1"], &[]);
+        assert_eq!(
+            get_version_definitions(&data).unwrap(),
+            vec!["LIBFOO_1.0", "LIBFOO_1.1"]
+        );
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert!(get_version_definitions(&data).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reads_exported_symbols() {
+        let data = build_shared_library(
+            &["LIBFOO_1.0", "LIBFOO_1.1"],
+            &[
+                ("foo", Some("LIBFOO_1.0"), false),
+                ("foo", Some("LIBFOO_1.1"), true),
+                ("bar", None, true),
+                ("LIBFOO_1.1", Some("LIBFOO_1.1"), true),
+            ],
+        );
+        let exports = get_exported_symbols(&data).unwrap();
+        let exports: Vec<String> = exports.iter().map(|e| e.to_string()).collect();
+        assert_eq!(exports, vec!["foo@LIBFOO_1.0", "foo@@LIBFOO_1.1", "bar"]);
+    }
+
+    #[test]
+    fn detects_musl_executables() {
+        let data = build_elf_with_interpreter("/lib/ld-musl-x86_64.so.1");
+        assert_eq!(
+            get_interpreter(&data).unwrap().as_deref(),
+            Some("/lib/ld-musl-x86_64.so.1")
+        );
+        assert!(!is_static(&data));
+        assert!(is_musl(&data).unwrap());
+        let data = build_elf_with_interpreter("/lib64/ld-linux-x86-64.so.2");
+        assert!(!is_musl(&data).unwrap());
+        assert!(!is_musl(&std::fs::read(std::env::current_exe().unwrap()).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn detects_static_executables() {
+        let data = build_static_elf(b"");
+        assert!(is_static(&data));
+        let references = get_version_references(&data).unwrap();
+        assert!(references.symbols.is_empty() && references.unreadable.is_empty());
+        assert!(get_needed_versions(&data).unwrap().is_empty());
+        assert!(!is_static(
+            &std::fs::read(std::env::current_exe().unwrap()).unwrap()
+        ));
+    }
+
+    #[test]
+    fn estimates_static_glibc_from_newest_version_string() {
+        let data =
+            build_static_elf(b"GLIBC_2.2.5\0GLIBC_PRIVATE\0GLIBC_2.35\0GLIBC_2.17\0GLIBC_2.");
+        assert_eq!(
+            estimate_static_glibc(&data),
+            Some(NamespacedVersion::parse("GLIBC_2.35").unwrap())
+        );
+        assert_eq!(estimate_static_glibc(&build_static_elf(b"")), None);
+    }
+
+    #[test]
+    fn classifies_unsupported_kinds() {
+        assert_eq!(
+            get_unsupported_kind(&build_elf(object::elf::ET_CORE, &[])),
+            Some(UnsupportedKind::Core)
+        );
+        let kernel_module = build_elf_with_sections(
+            object::elf::ET_REL,
+            &[
+                (".text", object::elf::SHT_PROGBITS, object::elf::SHF_ALLOC),
+                (
+                    ".modinfo",
+                    object::elf::SHT_PROGBITS,
+                    object::elf::SHF_ALLOC,
+                ),
+            ],
+        );
+        assert_eq!(
+            get_unsupported_kind(&kernel_module),
+            Some(UnsupportedKind::KernelModule)
+        );
+        let debuginfo = build_elf_with_sections(
+            object::elf::ET_DYN,
+            &[
+                (
+                    ".note.gnu.build-id",
+                    object::elf::SHT_NOTE,
+                    object::elf::SHF_ALLOC,
+                ),
+                (".text", object::elf::SHT_NOBITS, object::elf::SHF_ALLOC),
+                (".debug_info", object::elf::SHT_PROGBITS, 0),
+            ],
+        );
+        assert_eq!(
+            get_unsupported_kind(&debuginfo),
+            Some(UnsupportedKind::Debuginfo)
+        );
+        let object_file = build_elf_with_sections(
+            object::elf::ET_REL,
+            &[(".text", object::elf::SHT_PROGBITS, object::elf::SHF_ALLOC)],
+        );
+        assert_eq!(get_unsupported_kind(&object_file), None);
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert_eq!(get_unsupported_kind(&data), None);
+    }
+
+    #[test]
+    fn test_executable_has_version_table() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert!(has_version_table(&data).unwrap());
+        assert!(!has_version_table(&build_elf(object::elf::ET_DYN, b"")).unwrap());
+    }
+
+    #[test]
+    fn test_executable_has_header() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let header = get_header(&data).unwrap();
+        assert_eq!(header.class, 64);
+        assert!(header.little_endian);
+        assert_eq!(header.file_type, "DYN");
+        let data = build_elf(object::elf::ET_CORE, b"");
+        let header = get_header(&data).unwrap();
+        assert_eq!(header.machine, "x86_64");
+        assert_eq!(header.os_abi, "SYSV");
+        assert_eq!(header.file_type, "CORE");
+    }
+
+    #[test]
+    fn test_executable_has_interpreter() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let interpreter = get_interpreter(&data).unwrap().unwrap();
+        assert!(interpreter.starts_with('/'));
+        assert!(interpreter.contains("ld"));
+    }
+
+    #[test]
+    fn test_executable_has_linux_abi_tag() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let abi_tag = get_abi_tag(&data).unwrap().unwrap();
+        assert_eq!(abi_tag.os, "Linux");
+        assert!(abi_tag.version >= Version::parse("2.6").unwrap());
+    }
+
+    #[test]
+    fn test_executable_has_build_id() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let build_id = get_build_id(&data).unwrap().unwrap();
+        assert!(build_id.len() >= 16);
+        assert!(build_id.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn needed_versions_match_symbol_versions() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let needed_versions = get_needed_versions(&data).unwrap();
+        for symbol in get_version_references(&data).unwrap().symbols {
+            assert!(
+                needed_versions.iter().any(|needed| {
+                    symbol.file.as_ref() == Some(&needed.file)
+                        && NamespacedVersion::parse(&needed.version).ok()
+                            == Some(symbol.version.clone())
+                }),
+                "{} not found",
+                symbol.version
+            );
+        }
+    }
+
+    #[test]
+    fn test_executable_has_no_private_symbols() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let references = get_version_references(&data).unwrap();
+        assert!(!references.symbols.is_empty());
+        assert!(!references.unnumbered.iter().any(|r| r.is_private()));
+    }
 }
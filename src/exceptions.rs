@@ -0,0 +1,283 @@
+use crate::policy::Violation;
+use crate::symbols::SymbolVersion;
+use crate::version::NamespacedVersion;
+use anyhow::{Context, anyhow};
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A known violation which is accepted, as read from an exceptions file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExceptionEntry {
+    /// The symbol and its version, as `symbol@VERSION`
+    pub symbol: String,
+    /// When set, a glob matching the paths of the files the exception applies to
+    pub path: Option<String>,
+    /// Why the violation is accepted
+    pub comment: Option<String>,
+    /// The last day the exception applies, as `YYYY-MM-DD`
+    pub expires: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Exception {
+    name: String,
+    version: NamespacedVersion,
+    path: Option<GlobMatcher>,
+    comment: Option<String>,
+    expires: Option<String>,
+}
+
+/// The exceptions which downgrade matching symbol violations to warnings.
+#[derive(Clone, Debug, Default)]
+pub struct Exceptions {
+    exceptions: Vec<Exception>,
+}
+
+impl Exceptions {
+    /// Reads exceptions files, each a JSON array of exceptions. Exceptions which expired before `today` aren't applied,
+    /// and are returned so they can be reported.
+    pub fn read(
+        files: &[PathBuf],
+        today: &str,
+    ) -> anyhow::Result<(Exceptions, Vec<ExceptionEntry>)> {
+        let mut exceptions = Exceptions::default();
+        let mut expired = Vec::new();
+        for file in files {
+            let data = fs::read(file)
+                .with_context(|| format!("Error reading exceptions file {}", file.display()))?;
+            let entries: Vec<ExceptionEntry> = serde_json::from_slice(&data)
+                .with_context(|| format!("Error parsing exceptions file {}", file.display()))?;
+            for entry in entries {
+                if let Some(expires) = &entry.expires {
+                    check_date(expires).with_context(|| {
+                        format!("Invalid expiry date in exceptions file {}", file.display())
+                    })?;
+                    if expires.as_str() < today {
+                        expired.push(entry);
+                        continue;
+                    }
+                }
+                exceptions.add(entry).with_context(|| {
+                    format!("Invalid exception in exceptions file {}", file.display())
+                })?;
+            }
+        }
+        Ok((exceptions, expired))
+    }
+
+    /// Adds an exception, which applies to all files unless it has a path.
+    pub fn add(&mut self, entry: ExceptionEntry) -> anyhow::Result<()> {
+        let (name, version) = entry
+            .symbol
+            .rsplit_once('@')
+            .ok_or_else(|| anyhow!("Expected symbol@VERSION, got {}", entry.symbol))?;
+        let path = entry
+            .path
+            .as_deref()
+            .map(|path| Ok::<_, anyhow::Error>(Glob::new(path)?.compile_matcher()))
+            .transpose()?;
+        self.exceptions.push(Exception {
+            name: name.to_string(),
+            version: NamespacedVersion::parse(version)?,
+            path,
+            comment: entry.comment,
+            expires: entry.expires,
+        });
+        Ok(())
+    }
+
+    /// Replaces the symbol violations of a file which match an exception with excepted symbols, which are warnings.
+    pub fn apply(&self, file: &Path, violations: Vec<Violation>) -> Vec<Violation> {
+        if self.exceptions.is_empty() {
+            return violations;
+        }
+        violations
+            .into_iter()
+            .map(|violation| match violation {
+                Violation::Symbol(symbol)
+                | Violation::HiddenSymbol(symbol)
+                | Violation::UnversionedSymbol(symbol)
+                    if let Some(exception) = self.find(file, &symbol) =>
+                {
+                    Violation::ExceptedSymbol {
+                        symbol,
+                        comment: exception.comment.clone(),
+                        expires: exception.expires.clone(),
+                    }
+                }
+                violation => violation,
+            })
+            .collect()
+    }
+
+    /// Checks whether the same exceptions apply to two files, which they do unless an exception's path matches only
+    /// one.
+    pub fn apply_alike(&self, file: &Path, other: &Path) -> bool {
+        self.exceptions.iter().all(|exception| {
+            exception
+                .path
+                .as_ref()
+                .is_none_or(|path| path.is_match(file) == path.is_match(other))
+        })
+    }
+
+    fn find(&self, file: &Path, symbol: &SymbolVersion) -> Option<&Exception> {
+        self.exceptions.iter().find(|exception| {
+            exception.name == symbol.name
+                && exception.version == symbol.version
+                && exception
+                    .path
+                    .as_ref()
+                    .is_none_or(|path| path.is_match(file))
+        })
+    }
+}
+
+/// Checks a date is given as `YYYY-MM-DD`, so dates can be compared as strings.
+fn check_date(date: &str) -> anyhow::Result<()> {
+    let valid = date.len() == 10
+        && date.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    if !valid {
+        return Err(anyhow!("Expected a date as YYYY-MM-DD, got {}", date));
+    }
+    Ok(())
+}
+
+/// Returns today's date in UTC, as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    date_from_days((seconds / 86400) as i64)
+}
+
+/// Converts a number of days since 1970-01-01 to a date, using the algorithm from Howard Hinnant's `civil_from_days`.
+fn date_from_days(days: i64) -> String {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn symbol(name: &str, version: &str) -> Violation {
+        Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap())
+    }
+
+    #[test]
+    fn applies_exceptions_until_expiry() {
+        let dir = TempDir::new("exceptions");
+        let file = dir.write(
+            "exceptions.json",
+            br#"[
+                {"symbol": "memfd_create@GLIBC_2.27", "comment": "Fallback when unavailable"},
+                {"symbol": "stat@GLIBC_2.33", "path": "build/legacy/*", "expires": "2026-06-30"},
+                {"symbol": "fstat@GLIBC_2.33", "expires": "2026-01-01"}
+            ]"#,
+        );
+        let (exceptions, expired) = Exceptions::read(&[file], "2026-03-01").unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].symbol, "fstat@GLIBC_2.33");
+
+        let violations = vec![
+            symbol("memfd_create", "GLIBC_2.27"),
+            symbol("stat", "GLIBC_2.33"),
+            symbol("fstat", "GLIBC_2.33"),
+        ];
+        let applied = exceptions.apply(Path::new("build/legacy/app"), violations.clone());
+        assert!(matches!(
+            &applied[..],
+            [
+                Violation::ExceptedSymbol {
+                    comment: Some(_),
+                    ..
+                },
+                Violation::ExceptedSymbol {
+                    expires: Some(_),
+                    ..
+                },
+                Violation::Symbol(_),
+            ]
+        ));
+        assert!(exceptions.apply_alike(Path::new("build/app"), Path::new("build/tool")));
+        assert!(!exceptions.apply_alike(Path::new("build/legacy/app"), Path::new("build/app")));
+        let applied = exceptions.apply(Path::new("build/app"), violations);
+        assert!(matches!(
+            &applied[..],
+            [
+                Violation::ExceptedSymbol { .. },
+                Violation::Symbol(_),
+                Violation::Symbol(_)
+            ]
+        ));
+    }
+
+    #[test]
+    fn added_exceptions_apply_to_all_files() {
+        let mut exceptions = Exceptions::default();
+        let entry = |symbol: &str| ExceptionEntry {
+            symbol: symbol.to_string(),
+            path: None,
+            comment: None,
+            expires: None,
+        };
+        exceptions.add(entry("memfd_create@GLIBC_2.27")).unwrap();
+        assert!(exceptions.add(entry("memfd_create")).is_err());
+        let applied = exceptions.apply(
+            Path::new("bin/app"),
+            vec![
+                symbol("memfd_create", "GLIBC_2.27"),
+                symbol("memfd_create", "GLIBC_2.28"),
+            ],
+        );
+        assert!(matches!(
+            &applied[..],
+            [Violation::ExceptedSymbol { .. }, Violation::Symbol(_)]
+        ));
+    }
+
+    #[test]
+    fn invalid_exceptions_are_an_error() {
+        let dir = TempDir::new("exceptions-invalid");
+        let read = |contents: &[u8]| {
+            Exceptions::read(&[dir.write("exceptions.json", contents)], "2026-01-01").is_err()
+        };
+        assert!(read(br#"[{"symbol": "memfd_create"}]"#));
+        assert!(read(
+            br#"[{"symbol": "stat@GLIBC_2.33", "expires": "30/06/2026"}]"#
+        ));
+        assert!(read(
+            br#"[{"symbol": "stat@GLIBC_2.33", "reason": "unknown"}]"#
+        ));
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(date_from_days(0), "1970-01-01");
+        assert_eq!(date_from_days(19782), "2024-02-29");
+        assert_eq!(date_from_days(20454), "2026-01-01");
+    }
+}
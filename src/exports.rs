@@ -0,0 +1,258 @@
+use crate::elf::ExportedSymbol;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Symbols which linkers export from every shared library, which aren't part of its interface.
+const LINKER_SYMBOLS: [&str; 5] = ["_init", "_fini", "_edata", "_end", "__bss_start"];
+
+/// A change to the versions a symbol is exported with between two builds of a library.
+#[derive(Debug, PartialEq)]
+pub enum ExportChange {
+    /// A new symbol, or a new version of an existing symbol. Compatible with files linked against the old build.
+    Added(ExportedSymbol),
+    /// A symbol which is no longer exported. Breaks files linked against the old build which use it.
+    Removed(ExportedSymbol),
+    /// A symbol exported with different versions, some of the old versions having been removed. Breaks files linked
+    /// against the old build which use one of the removed versions.
+    Reversioned {
+        old: Vec<ExportedSymbol>,
+        new: Vec<ExportedSymbol>,
+    },
+}
+
+impl ExportChange {
+    /// Checks whether the change breaks compatibility with files linked against the old build.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, ExportChange::Added(_))
+    }
+}
+
+/// Compares the symbols exported by two builds of a library, returning the changes ordered by symbol name. Symbols
+/// are identified by their name and version: whether a version is the default doesn't affect files already linked.
+pub fn diff_exports(old: &[ExportedSymbol], new: &[ExportedSymbol]) -> Vec<ExportChange> {
+    type Versions = BTreeMap<Option<String>, ExportedSymbol>;
+    let by_name = |exports: &[ExportedSymbol]| {
+        let mut by_name: BTreeMap<String, Versions> = BTreeMap::new();
+        for export in exports {
+            by_name
+                .entry(export.name.clone())
+                .or_default()
+                .insert(export.version.clone(), export.clone());
+        }
+        by_name
+    };
+    let old = by_name(old);
+    let new = by_name(new);
+    let empty = Versions::new();
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    let mut changes = Vec::new();
+    for name in names {
+        let old_versions = old.get(name).unwrap_or(&empty);
+        let new_versions = new.get(name).unwrap_or(&empty);
+        let missing_from = |versions: &Versions, other: &Versions| -> Vec<ExportedSymbol> {
+            versions
+                .iter()
+                .filter(|(version, _)| !other.contains_key(*version))
+                .map(|(_, export)| export.clone())
+                .collect()
+        };
+        let removed = missing_from(old_versions, new_versions);
+        let added = missing_from(new_versions, old_versions);
+        match (removed.is_empty(), added.is_empty()) {
+            (true, _) => changes.extend(added.into_iter().map(ExportChange::Added)),
+            (false, true) => changes.extend(removed.into_iter().map(ExportChange::Removed)),
+            (false, false) => changes.push(ExportChange::Reversioned {
+                old: old_versions.values().cloned().collect(),
+                new: new_versions.values().cloned().collect(),
+            }),
+        }
+    }
+    changes
+}
+
+/// Returns a version node name for a library's unversioned symbols, based on its file name, e.g. `LIBFOO_1.0` for
+/// `libfoo.so.1`.
+pub fn default_node_name(library: &str) -> String {
+    let name = library.split(".so").next().unwrap_or(library);
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_1.0", name)
+}
+
+/// Generates a GNU linker version script template for a library's exported symbols. Unversioned symbols are placed in
+/// `default_node`, which comes first and makes all other symbols local, and versioned symbols in the node they are
+/// defined with. A symbol with several versions is only placed in the node of its default (`@@`) version, its older
+/// versions being bound by `.symver` directives in the source. `nodes` gives the order of the library's version nodes,
+/// each of which inherits from the previous one.
+pub fn version_script(exports: &[ExportedSymbol], nodes: &[String], default_node: &str) -> String {
+    let defaults: BTreeSet<&str> = exports
+        .iter()
+        .filter(|export| export.is_default)
+        .map(|export| export.name.as_str())
+        .collect();
+    let mut symbols: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for export in exports.iter().filter(|export| {
+        !LINKER_SYMBOLS.contains(&export.name.as_str())
+            && (export.is_default || !defaults.contains(export.name.as_str()))
+    }) {
+        let node = export.version.as_deref().unwrap_or(default_node);
+        symbols.entry(node).or_default().insert(&export.name);
+    }
+
+    let mut ordered_nodes = vec![default_node];
+    ordered_nodes.extend(
+        nodes
+            .iter()
+            .map(String::as_str)
+            .filter(|node| *node != default_node),
+    );
+    let mut script = String::new();
+    let mut previous = None;
+    for node in ordered_nodes {
+        let node_symbols = symbols.get(node);
+        if node_symbols.is_none() && node == default_node && !nodes.is_empty() {
+            continue;
+        }
+        if previous.is_some() {
+            script.push('\n');
+        }
+        writeln!(script, "{} {{", node).unwrap();
+        writeln!(script, "  global:").unwrap();
+        for symbol in node_symbols.into_iter().flatten() {
+            writeln!(script, "    {};", symbol).unwrap();
+        }
+        if previous.is_none() {
+            writeln!(script, "  local:").unwrap();
+            writeln!(script, "    *;").unwrap();
+        }
+        match previous {
+            Some(previous) => writeln!(script, "}} {};", previous).unwrap(),
+            None => writeln!(script, "}};").unwrap(),
+        }
+        previous = Some(node);
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(name: &str, version: &str, is_default: bool) -> ExportedSymbol {
+        ExportedSymbol {
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            is_default,
+        }
+    }
+
+    #[test]
+    fn identical_exports_have_no_changes() {
+        let exports = vec![export("foo", "LIBFOO_1.0", true)];
+        assert!(diff_exports(&exports, &exports).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_symbols() {
+        let old = vec![
+            export("foo", "LIBFOO_1.0", true),
+            export("bar", "LIBFOO_1.0", true),
+        ];
+        let new = vec![
+            export("foo", "LIBFOO_1.0", true),
+            export("baz", "LIBFOO_1.1", true),
+        ];
+        let changes = diff_exports(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                ExportChange::Removed(export("bar", "LIBFOO_1.0", true)),
+                ExportChange::Added(export("baz", "LIBFOO_1.1", true)),
+            ]
+        );
+        assert!(changes[0].is_breaking());
+        assert!(!changes[1].is_breaking());
+    }
+
+    #[test]
+    fn new_default_version_keeping_old_version_is_added() {
+        let old = vec![export("foo", "LIBFOO_1.0", true)];
+        let new = vec![
+            export("foo", "LIBFOO_1.0", false),
+            export("foo", "LIBFOO_1.1", true),
+        ];
+        assert_eq!(
+            diff_exports(&old, &new),
+            vec![ExportChange::Added(export("foo", "LIBFOO_1.1", true))]
+        );
+    }
+
+    #[test]
+    fn reports_reversioned_symbols() {
+        let old = vec![export("foo", "LIBFOO_1.0", true)];
+        let new = vec![export("foo", "LIBFOO_2.0", true)];
+        assert_eq!(
+            diff_exports(&old, &new),
+            vec![ExportChange::Reversioned {
+                old: old.clone(),
+                new: new.clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn derives_default_node_name() {
+        assert_eq!(default_node_name("libfoo.so.1"), "LIBFOO_1.0");
+        assert_eq!(default_node_name("libfoo-bar.so"), "LIBFOO_BAR_1.0");
+        assert_eq!(default_node_name("plugin"), "PLUGIN_1.0");
+    }
+
+    #[test]
+    fn generates_version_script_for_unversioned_library() {
+        let exports = vec![
+            ExportedSymbol {
+                name: "foo".to_string(),
+                version: None,
+                is_default: true,
+            },
+            ExportedSymbol {
+                name: "bar".to_string(),
+                version: None,
+                is_default: true,
+            },
+            ExportedSymbol {
+                name: "_init".to_string(),
+                version: None,
+                is_default: true,
+            },
+        ];
+        assert_eq!(
+            version_script(&exports, &[], "LIBFOO_1.0"),
+            "LIBFOO_1.0 {\n  global:\n    bar;\n    foo;\n  local:\n    *;\n};\n"
+        );
+    }
+
+    #[test]
+    fn generates_version_script_with_existing_nodes() {
+        let exports = vec![
+            export("foo", "LIBFOO_1.0", false),
+            export("foo", "LIBFOO_1.1", true),
+            export("bar", "LIBFOO_1.0", true),
+        ];
+        let nodes = vec!["LIBFOO_1.0".to_string(), "LIBFOO_1.1".to_string()];
+        assert_eq!(
+            version_script(&exports, &nodes, "LIBFOO_1.0"),
+            "LIBFOO_1.0 {\n  global:\n    bar;\n  local:\n    *;\n};\n\n\
+             LIBFOO_1.1 {\n  global:\n    foo;\n} LIBFOO_1.0;\n"
+        );
+    }
+}
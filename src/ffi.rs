@@ -0,0 +1,322 @@
+//! A C interface to the check, for native projects' tooling. The declarations are in `include/symbol_version_check.h`.
+//! Built for WebAssembly, these functions are the module's exports.
+
+use crate::requirements::VersionRequirements;
+use crate::{FileReport, Violation, check_data, check_file};
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::path::Path;
+use std::ptr;
+
+/// A symbol a file fails for, as given to C.
+#[repr(C)]
+pub struct SvcSymbol {
+    pub name: *const c_char,
+    pub version: *const c_char,
+    /// The library the version is needed from, or null if it isn't known
+    pub library: *const c_char,
+}
+
+/// The result of checking a file, owning the strings given to C.
+pub struct SvcReport {
+    passed: bool,
+    error: Option<CString>,
+    json: CString,
+    symbols: Vec<SvcSymbol>,
+    strings: Vec<CString>,
+}
+
+impl SvcReport {
+    fn new(result: anyhow::Result<FileReport>) -> Self {
+        let (passed, error, json) = match &result {
+            Ok(report) => (
+                report.passed(),
+                None,
+                serde_json::to_string(report).expect("Reports can always be serialized"),
+            ),
+            Err(e) => (false, Some(to_c_string(format!("{:#}", e))), String::new()),
+        };
+        let mut report = SvcReport {
+            passed,
+            error,
+            json: to_c_string(json),
+            symbols: Vec::new(),
+            strings: Vec::new(),
+        };
+        for violation in result.iter().flat_map(|report| &report.violations) {
+            if let Violation::Symbol(symbol)
+            | Violation::HiddenSymbol(symbol)
+            | Violation::UnversionedSymbol(symbol) = violation
+            {
+                // Moving a CString doesn't move the string it owns, so the pointers stay valid
+                let name = report.add_string(symbol.name.clone());
+                let version = report.add_string(symbol.version.to_string());
+                let library = match &symbol.file {
+                    Some(file) => report.add_string(file.clone()),
+                    None => ptr::null(),
+                };
+                report.symbols.push(SvcSymbol {
+                    name,
+                    version,
+                    library,
+                });
+            }
+        }
+        report
+    }
+
+    fn add_string(&mut self, string: String) -> *const c_char {
+        let string = to_c_string(string);
+        let pointer = string.as_ptr();
+        self.strings.push(string);
+        pointer
+    }
+}
+
+/// Converts a string for C, dropping any interior nul bytes, which C strings can't contain.
+fn to_c_string(string: String) -> CString {
+    CString::new(string.replace('\0', "")).expect("Nul bytes have been removed")
+}
+
+/// Reads a C string, or returns `None` if it is null or not UTF-8.
+///
+/// # Safety
+///
+/// `string` must be null or point to a nul-terminated string.
+unsafe fn from_c_string<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(string) }.to_str().ok()
+}
+
+/// Reads the maximum versions given to C.
+///
+/// # Safety
+///
+/// `requirements` must point to `count` nul-terminated strings.
+unsafe fn read_requirements(
+    requirements: *const *const c_char,
+    count: usize,
+) -> anyhow::Result<VersionRequirements> {
+    let requirements: Vec<String> = (0..count)
+        .map(|index| {
+            unsafe { from_c_string(*requirements.add(index)) }
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("Requirement {} isn't a UTF-8 string", index))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    VersionRequirements::parse(&requirements)
+}
+
+/// Checks a file against maximum versions, e.g. `GLIBC_2.17`, as `check_file` does. The report must be freed with
+/// `svc_report_free`. If the arguments are invalid, the report has an error.
+///
+/// # Safety
+///
+/// `path` must be a nul-terminated string, and `requirements` must point to `count` nul-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_check_file(
+    path: *const c_char,
+    requirements: *const *const c_char,
+    count: usize,
+) -> *mut SvcReport {
+    let result = (|| {
+        let path = unsafe { from_c_string(path) }
+            .ok_or_else(|| anyhow::anyhow!("The path isn't a UTF-8 string"))?;
+        check_file(Path::new(path), &unsafe {
+            read_requirements(requirements, count)
+        }?)
+    })();
+    Box::into_raw(Box::new(SvcReport::new(result)))
+}
+
+/// Checks the `size` bytes of a file's contents at `data` as `svc_check_file` does, for hosts without a filesystem,
+/// such as browsers running the library as WebAssembly. The report's file is empty.
+///
+/// # Safety
+///
+/// `data` must point to `size` bytes, or may be null if `size` is 0, and `requirements` must point to `count`
+/// nul-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_check_data(
+    data: *const u8,
+    size: usize,
+    requirements: *const *const c_char,
+    count: usize,
+) -> *mut SvcReport {
+    let result = (|| {
+        let data = match (data.is_null(), size) {
+            (true, 0) => &[],
+            (true, _) => anyhow::bail!("The data is null, but its size is {}", size),
+            (false, _) => unsafe { std::slice::from_raw_parts(data, size) },
+        };
+        check_data(data, &unsafe { read_requirements(requirements, count) }?)
+    })();
+    Box::into_raw(Box::new(SvcReport::new(result)))
+}
+
+/// Allocates `size` bytes, for hosts such as JavaScript which must copy arguments into the library's memory. The
+/// memory must be freed with `svc_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn svc_alloc(size: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(size);
+    let pointer = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    pointer
+}
+
+/// Frees memory allocated with `svc_alloc`.
+///
+/// # Safety
+///
+/// `pointer` must have been returned by `svc_alloc` with the same `size`, and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_free(pointer: *mut u8, size: usize) {
+    drop(unsafe { Vec::from_raw_parts(pointer, 0, size) });
+}
+
+/// Returns 1 if the file passed, or 0 if it failed or couldn't be checked.
+///
+/// # Safety
+///
+/// `report` must have been returned by `svc_check_file` or `svc_check_data` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_report_passed(report: *const SvcReport) -> c_int {
+    c_int::from(unsafe { &*report }.passed)
+}
+
+/// Returns the reason the file couldn't be checked, or null if it was checked.
+///
+/// # Safety
+///
+/// `report` must have been returned by `svc_check_file` or `svc_check_data` and not freed. The string is freed with the
+/// report.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_report_error(report: *const SvcReport) -> *const c_char {
+    match &unsafe { &*report }.error {
+        Some(error) => error.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Returns the symbols the file fails for, setting `count` to the number of them.
+///
+/// # Safety
+///
+/// `report` must have been returned by `svc_check_file` or `svc_check_data` and not freed, and `count` must be valid to
+/// write to. The symbols are freed with the report.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_report_symbols(
+    report: *const SvcReport,
+    count: *mut usize,
+) -> *const SvcSymbol {
+    let report = unsafe { &*report };
+    unsafe { *count = report.symbols.len() };
+    report.symbols.as_ptr()
+}
+
+/// Returns the report as JSON, in the form of a file's entry in the JSON output, including the violations other than
+/// symbols. It is empty if the file couldn't be checked.
+///
+/// # Safety
+///
+/// `report` must have been returned by `svc_check_file` or `svc_check_data` and not freed. The string is freed with the
+/// report.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_report_json(report: *const SvcReport) -> *const c_char {
+    unsafe { &*report }.json.as_ptr()
+}
+
+/// Frees a report. Freeing null does nothing.
+///
+/// # Safety
+///
+/// `report` must be null, or have been returned by `svc_check_file` or `svc_check_data` and not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn svc_report_free(report: *mut SvcReport) {
+    if !report.is_null() {
+        drop(unsafe { Box::from_raw(report) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_file_through_c_interface() {
+        let path = CString::new(std::env::current_exe().unwrap().to_str().unwrap()).unwrap();
+        let requirement = CString::new("GLIBC_2.0").unwrap();
+        let requirements = [requirement.as_ptr()];
+        unsafe {
+            let report = svc_check_file(path.as_ptr(), requirements.as_ptr(), 1);
+            assert_eq!(svc_report_passed(report), 0);
+            assert!(svc_report_error(report).is_null());
+            let mut count = 0;
+            let symbols = svc_report_symbols(report, &mut count);
+            assert!(count > 0);
+            let symbol = &*symbols;
+            assert!(!CStr::from_ptr(symbol.name).to_str().unwrap().is_empty());
+            assert!(
+                CStr::from_ptr(symbol.version)
+                    .to_str()
+                    .unwrap()
+                    .starts_with("GLIBC_")
+            );
+            let json = CStr::from_ptr(svc_report_json(report)).to_str().unwrap();
+            assert!(serde_json::from_str::<FileReport>(json).is_ok());
+            svc_report_free(report);
+        }
+    }
+
+    #[test]
+    fn checks_data_through_c_interface() {
+        let contents = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let requirement = CString::new("GLIBC_99.0").unwrap();
+        let requirements = [requirement.as_ptr()];
+        unsafe {
+            let data = svc_alloc(contents.len());
+            ptr::copy_nonoverlapping(contents.as_ptr(), data, contents.len());
+            let report = svc_check_data(data, contents.len(), requirements.as_ptr(), 1);
+            svc_free(data, contents.len());
+            assert_eq!(svc_report_passed(report), 1);
+            assert!(svc_report_error(report).is_null());
+            svc_report_free(report);
+        }
+    }
+
+    #[test]
+    fn checks_null_data() {
+        let requirement = CString::new("GLIBC_2.17").unwrap();
+        let requirements = [requirement.as_ptr()];
+        unsafe {
+            // No data is checked as an empty file, which isn't an ELF file
+            let report = svc_check_data(ptr::null(), 0, requirements.as_ptr(), 1);
+            assert_eq!(svc_report_passed(report), 0);
+            let error = CStr::from_ptr(svc_report_error(report)).to_str().unwrap();
+            assert!(!error.contains("null"));
+            svc_report_free(report);
+            let report = svc_check_data(ptr::null(), 16, requirements.as_ptr(), 1);
+            assert_eq!(svc_report_passed(report), 0);
+            let error = CStr::from_ptr(svc_report_error(report)).to_str().unwrap();
+            assert_eq!(error, "The data is null, but its size is 16");
+            svc_report_free(report);
+        }
+    }
+
+    #[test]
+    fn reports_invalid_requirements_as_error() {
+        let path = CString::new("test.so").unwrap();
+        let requirement = CString::new("GLIBC").unwrap();
+        let requirements = [requirement.as_ptr()];
+        unsafe {
+            let report = svc_check_file(path.as_ptr(), requirements.as_ptr(), 1);
+            assert_eq!(svc_report_passed(report), 0);
+            assert!(!svc_report_error(report).is_null());
+            let mut count = 1;
+            svc_report_symbols(report, &mut count);
+            assert_eq!(count, 0);
+            svc_report_free(report);
+        }
+    }
+}
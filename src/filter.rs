@@ -0,0 +1,260 @@
+use crate::elf::{self, UnsupportedKind};
+use clap::ValueEnum;
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader};
+use object::{Architecture, Endianness, Object, ObjectKind};
+
+/// A processor architecture which ELF files can be selected by.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum Arch {
+    #[value(name = "x86_64", alias = "amd64")]
+    X86_64,
+    #[value(alias = "i686", alias = "x86")]
+    I386,
+    #[value(alias = "arm64")]
+    Aarch64,
+    Arm,
+    Riscv64,
+    Ppc64le,
+    Ppc64,
+    S390x,
+    Loongarch64,
+}
+
+impl Arch {
+    fn matches(self, architecture: Architecture, little_endian: bool) -> bool {
+        match self {
+            Arch::X86_64 => architecture == Architecture::X86_64,
+            Arch::I386 => architecture == Architecture::I386,
+            Arch::Aarch64 => architecture == Architecture::Aarch64,
+            Arch::Arm => architecture == Architecture::Arm,
+            Arch::Riscv64 => architecture == Architecture::Riscv64,
+            Arch::Ppc64le => architecture == Architecture::PowerPc64 && little_endian,
+            Arch::Ppc64 => architecture == Architecture::PowerPc64 && !little_endian,
+            Arch::S390x => architecture == Architecture::S390x,
+            Arch::Loongarch64 => architecture == Architecture::LoongArch64,
+        }
+    }
+}
+
+/// A kind of ELF file which can be selected.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum ElfType {
+    /// Executables, including position independent executables
+    Executables,
+    /// Shared libraries
+    SharedLibs,
+}
+
+/// Classifies an ELF file. Position independent executables have the same type as shared libraries, so are
+/// distinguished by having a program interpreter.
+fn elf_type(file: &object::File) -> Option<ElfType> {
+    let is_pie = match file {
+        object::File::Elf32(elf) => is_position_independent_executable(elf),
+        object::File::Elf64(elf) => is_position_independent_executable(elf),
+        _ => return None,
+    };
+    match file.kind() {
+        ObjectKind::Executable => Some(ElfType::Executables),
+        ObjectKind::Dynamic if is_pie => Some(ElfType::Executables),
+        ObjectKind::Dynamic => Some(ElfType::SharedLibs),
+        _ => None,
+    }
+}
+
+/// Checks whether a shared object is a position independent executable. Newer linkers mark these with `DF_1_PIE`.
+/// Otherwise, a program interpreter is taken to indicate an executable, unless the file also has a soname, as glibc's
+/// `libc.so.6` does.
+fn is_position_independent_executable<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> bool {
+    let endian = elf.endian();
+    let dynamic = elf
+        .elf_section_table()
+        .dynamic(endian, elf.data())
+        .ok()
+        .flatten()
+        .map(|(dynamic, _)| dynamic)
+        .unwrap_or_default();
+    let has_tag = |tag| dynamic.iter().any(|d| d.tag32(endian) == Some(tag));
+    let is_marked_pie = dynamic.iter().any(|d| {
+        d.tag32(endian) == Some(object::elf::DT_FLAGS_1)
+            && d.d_val(endian).into() & u64::from(object::elf::DF_1_PIE) != 0
+    });
+    let has_interpreter = elf
+        .elf_program_headers()
+        .iter()
+        .any(|header| header.p_type(endian) == object::elf::PT_INTERP);
+    is_marked_pie || (has_interpreter && !has_tag(object::elf::DT_SONAME))
+}
+
+/// Why a file was skipped rather than checked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    NotElf,
+    Architecture,
+    ElfType,
+    Unsupported(UnsupportedKind),
+}
+
+impl SkipReason {
+    /// Summarizes the number of files skipped for this reason, e.g. "Skipped 3 non-ELF files".
+    pub fn summary(self, count: usize) -> String {
+        let files = if count == 1 { "file" } else { "files" };
+        match self {
+            SkipReason::NotElf => format!("Skipped {} non-ELF {}", count, files),
+            SkipReason::Architecture => {
+                format!("Skipped {} {} for other architectures", count, files)
+            }
+            SkipReason::ElfType => format!("Skipped {} {} of other types", count, files),
+            SkipReason::Unsupported(kind) => {
+                let plural = if count == 1 { "" } else { "s" };
+                match kind {
+                    UnsupportedKind::KernelModule => {
+                        format!("Skipped {} kernel module{}", count, plural)
+                    }
+                    UnsupportedKind::Core => format!("Skipped {} core dump{}", count, plural),
+                    UnsupportedKind::Debuginfo => {
+                        format!("Skipped {} debug info {}", count, files)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Selects which ELF files are checked.
+#[derive(Clone, Debug, Default)]
+pub struct ElfFilter {
+    /// When not empty, only ELF files for one of these architectures are checked.
+    pub architectures: Vec<Arch>,
+    /// When not empty, only ELF files of these types are checked.
+    pub types: Vec<ElfType>,
+    /// Kinds of files which can't be checked, such as kernel modules, which are skipped rather than reported.
+    pub skip_unsupported: Vec<UnsupportedKind>,
+}
+
+impl ElfFilter {
+    /// Returns the reason an ELF file should be skipped, if any. Files which can't be parsed are not skipped, so that
+    /// the error is reported when they are checked.
+    pub fn skip_reason(&self, data: &[u8]) -> Option<SkipReason> {
+        if !self.skip_unsupported.is_empty()
+            && let Some(kind) = elf::get_unsupported_kind(data)
+            && self.skip_unsupported.contains(&kind)
+        {
+            return Some(SkipReason::Unsupported(kind));
+        }
+        if self.architectures.is_empty() && self.types.is_empty() {
+            return None;
+        }
+        let file = object::File::parse(data).ok()?;
+        let architecture = file.architecture();
+        let little_endian = file.is_little_endian();
+        if !self.architectures.is_empty()
+            && !self
+                .architectures
+                .iter()
+                .any(|arch| arch.matches(architecture, little_endian))
+        {
+            return Some(SkipReason::Architecture);
+        }
+        if !self.types.is_empty()
+            && !elf_type(&file).is_some_and(|elf_type| self.types.contains(&elf_type))
+        {
+            return Some(SkipReason::ElfType);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_elf() -> Vec<u8> {
+        std::fs::read(std::env::current_exe().unwrap()).unwrap()
+    }
+
+    fn host_arch() -> Arch {
+        Arch::from_str(std::env::consts::ARCH, true).unwrap()
+    }
+
+    #[test]
+    fn no_architectures_matches_all() {
+        assert_eq!(ElfFilter::default().skip_reason(&test_elf()), None);
+    }
+
+    #[test]
+    fn matching_architecture_is_not_skipped() {
+        let filter = ElfFilter {
+            architectures: vec![Arch::Riscv64, host_arch()],
+            ..ElfFilter::default()
+        };
+        assert_eq!(filter.skip_reason(&test_elf()), None);
+    }
+
+    #[test]
+    fn other_architecture_is_skipped() {
+        let other = if host_arch() == Arch::S390x {
+            Arch::X86_64
+        } else {
+            Arch::S390x
+        };
+        let filter = ElfFilter {
+            architectures: vec![other],
+            ..ElfFilter::default()
+        };
+        assert_eq!(
+            filter.skip_reason(&test_elf()),
+            Some(SkipReason::Architecture)
+        );
+    }
+
+    #[test]
+    fn test_executable_is_executable() {
+        let data = test_elf();
+        let file = object::File::parse(&data[..]).unwrap();
+        assert_eq!(elf_type(&file), Some(ElfType::Executables));
+    }
+
+    #[test]
+    fn other_type_is_skipped() {
+        let filter = ElfFilter {
+            types: vec![ElfType::SharedLibs],
+            ..ElfFilter::default()
+        };
+        assert_eq!(filter.skip_reason(&test_elf()), Some(SkipReason::ElfType));
+    }
+
+    #[test]
+    fn unsupported_kind_is_skipped() {
+        let filter = ElfFilter {
+            skip_unsupported: vec![UnsupportedKind::Core],
+            ..ElfFilter::default()
+        };
+        let core = elf::tests::build_elf(object::elf::ET_CORE, &[]);
+        assert_eq!(
+            filter.skip_reason(&core),
+            Some(SkipReason::Unsupported(UnsupportedKind::Core))
+        );
+        assert_eq!(filter.skip_reason(&test_elf()), None);
+    }
+
+    #[test]
+    fn summary_counts_files() {
+        assert_eq!(SkipReason::NotElf.summary(1), "Skipped 1 non-ELF file");
+        assert_eq!(
+            SkipReason::Architecture.summary(2),
+            "Skipped 2 files for other architectures"
+        );
+        assert_eq!(
+            SkipReason::Unsupported(UnsupportedKind::KernelModule).summary(3),
+            "Skipped 3 kernel modules"
+        );
+    }
+
+    #[test]
+    fn parses_aliases() {
+        assert_eq!(Arch::from_str("amd64", true).unwrap(), Arch::X86_64);
+        assert_eq!(Arch::from_str("arm64", true).unwrap(), Arch::Aarch64);
+    }
+}
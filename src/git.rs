@@ -0,0 +1,59 @@
+use anyhow::{Context, anyhow};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the files added, copied, modified or renamed in the index of the git repository containing the current
+/// directory, as they are in the working tree.
+pub fn staged_files() -> anyhow::Result<Vec<PathBuf>> {
+    let top_level = git(&["rev-parse", "--show-toplevel"])?;
+    let top_level = PathBuf::from(String::from_utf8_lossy(&top_level).trim());
+    let output = git(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACMR",
+    ])?;
+    Ok(parse_staged_files(&top_level, &output))
+}
+
+fn git(args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .with_context(|| "Error running git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn parse_staged_files(top_level: &Path, output: &[u8]) -> Vec<PathBuf> {
+    output
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| top_level.join(String::from_utf8_lossy(name).as_ref()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_staged_files() {
+        let output = b"bin/app\0lib/lib foo.so\0";
+        assert_eq!(
+            parse_staged_files(Path::new("/repo"), output),
+            vec![
+                PathBuf::from("/repo/bin/app"),
+                PathBuf::from("/repo/lib/lib foo.so")
+            ]
+        );
+        assert!(parse_staged_files(Path::new("/repo"), b"").is_empty());
+    }
+}
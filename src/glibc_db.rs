@@ -0,0 +1,182 @@
+use crate::symbols::SymbolVersion;
+use crate::version::NamespacedVersion;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// The version of each symbol new links bind references to, the library which provides it, and its other versions, one
+/// symbol per line.
+const DATABASE: &str = include_str!("../data/glibc-symbols.txt");
+
+/// The architecture (`e_machine`) the database is generated for. Other architectures were first supported by glibc at
+/// other versions, so have their own versions for many symbols.
+pub const ARCHITECTURE: &str = "x86_64";
+
+/// The symbols whose older versions can be bound to in place of the version new links bind to, one per line.
+const COMPATIBLE_SYMBOLS: &str = include_str!("../data/glibc-compat-symbols.txt");
+
+/// A symbol exported by glibc, with the versions it is exported with.
+#[derive(Debug, PartialEq)]
+pub struct GlibcSymbol {
+    pub name: &'static str,
+    /// The version new links bind references to: the default (`@@`) version, or for symbols glibc only keeps for
+    /// compatibility, the newest
+    pub version: NamespacedVersion,
+    /// Every version the symbol is exported with, oldest first, including `version`
+    pub versions: Vec<NamespacedVersion>,
+    /// The library which provides the symbol, e.g. `libc.so.6`
+    pub library: &'static str,
+}
+
+/// Looks up the versions glibc exports a symbol with. The database is generated from glibc 2.36 for x86_64, so symbols
+/// introduced later, or only exported on other architectures, aren't found, and newer releases may have since given
+/// symbols new default versions; symbols which were first exported on other architectures may have been introduced
+/// earlier there.
+pub fn find_symbol(name: &str) -> Option<&'static GlibcSymbol> {
+    static SYMBOLS: OnceLock<HashMap<&'static str, GlibcSymbol>> = OnceLock::new();
+    SYMBOLS
+        .get_or_init(|| {
+            DATABASE
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| {
+                    let parse = |version| {
+                        NamespacedVersion::parse(version).unwrap_or_else(|e| {
+                            panic!("Invalid glibc database entry {}: {}", line, e)
+                        })
+                    };
+                    let mut fields = line.split_whitespace();
+                    let (Some(name), Some(version), Some(library)) =
+                        (fields.next(), fields.next(), fields.next())
+                    else {
+                        panic!("Invalid glibc database entry {}", line);
+                    };
+                    let version = parse(version);
+                    let mut versions: Vec<NamespacedVersion> = fields.map(parse).collect();
+                    versions.push(version.clone());
+                    versions.sort();
+                    (
+                        name,
+                        GlibcSymbol {
+                            name,
+                            version,
+                            versions,
+                            library,
+                        },
+                    )
+                })
+                .collect()
+        })
+        .get(name)
+}
+
+/// An older version of a symbol a file needs, which the file could be bound to instead.
+#[derive(Debug, PartialEq)]
+pub struct CompatVersion {
+    pub symbol: SymbolVersion,
+    pub compat: NamespacedVersion,
+}
+
+impl CompatVersion {
+    /// The assembler directive binding references to the symbol to the older version, e.g.
+    /// `.symver memcpy,memcpy@GLIBC_2.2.5`.
+    pub fn symver(&self) -> String {
+        format!(
+            ".symver {},{}@{}",
+            self.symbol.name, self.symbol.name, self.compat
+        )
+    }
+}
+
+/// Finds the newest version of a symbol, older than the one needed, which glibc still exports for binaries linked
+/// against older releases. Only symbols known to behave the same with their older versions are given one, as older
+/// versions of others, such as `glob` and `memcpy`, differ in the types they take or how they behave. The versions
+/// known are the x86_64 ones.
+pub fn find_compat_version(symbol: &SymbolVersion) -> Option<CompatVersion> {
+    static COMPATIBLE: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    let compatible = COMPATIBLE.get_or_init(|| {
+        COMPATIBLE_SYMBOLS
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect()
+    });
+    if !compatible.contains(symbol.name.as_str()) {
+        return None;
+    }
+    let older = find_symbol(&symbol.name)?
+        .versions
+        .iter()
+        .rev()
+        .find(|version| version.version < symbol.version.version)?;
+    (older.namespace == symbol.version.namespace).then(|| CompatVersion {
+        symbol: symbol.clone(),
+        compat: older.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_default_versions() {
+        let getrandom = find_symbol("getrandom").unwrap();
+        assert_eq!(getrandom.version.to_string(), "GLIBC_2.25");
+        assert_eq!(getrandom.library, "libc.so.6");
+        assert_eq!(find_symbol("sqrt").unwrap().library, "libm.so.6");
+        // Symbols with several versions are found with the version new links bind to
+        let memcpy = find_symbol("memcpy").unwrap();
+        assert_eq!(memcpy.version.to_string(), "GLIBC_2.14");
+        assert_eq!(
+            memcpy
+                .versions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["GLIBC_2.2.5", "GLIBC_2.14"]
+        );
+        for (name, version) in [
+            ("pow", "GLIBC_2.29"),
+            ("posix_spawn", "GLIBC_2.15"),
+            ("glob", "GLIBC_2.27"),
+            ("dlopen", "GLIBC_2.34"),
+            ("pthread_create", "GLIBC_2.34"),
+        ] {
+            assert_eq!(find_symbol(name).unwrap().version.to_string(), version);
+        }
+        assert!(find_symbol("not_a_glibc_symbol").is_none());
+    }
+
+    #[test]
+    fn finds_compat_versions() {
+        let compat = |name, version| {
+            find_compat_version(&SymbolVersion::parse(name, version, None).unwrap())
+                .map(|compat| compat.symver())
+        };
+        assert_eq!(
+            compat("pow", "GLIBC_2.29"),
+            Some(".symver pow,pow@GLIBC_2.2.5".to_string())
+        );
+        assert_eq!(compat("pow", "GLIBC_2.2.5"), None);
+        // The newest older version is given, as the oldest may have a different interface
+        assert_eq!(
+            compat("timer_create", "GLIBC_2.34"),
+            Some(".symver timer_create,timer_create@GLIBC_2.3.3".to_string())
+        );
+        assert_eq!(compat("getrandom", "GLIBC_2.25"), None);
+        // Older versions which differ in interface or behaviour aren't given
+        for (name, version) in [
+            ("memcpy", "GLIBC_2.14"),
+            ("glob", "GLIBC_2.27"),
+            ("pthread_cond_wait", "GLIBC_2.3.2"),
+            ("realpath", "GLIBC_2.3"),
+        ] {
+            assert_eq!(compat(name, version), None);
+        }
+        for line in COMPATIBLE_SYMBOLS
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+        {
+            assert!(find_symbol(line).unwrap().versions.len() > 1, "{}", line);
+        }
+    }
+}
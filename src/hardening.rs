@@ -0,0 +1,193 @@
+use anyhow::{Context, anyhow};
+use object::elf;
+use object::read::elf::{Dyn, ElfFile, ElfFile32, ElfFile64, FileHeader, ProgramHeader};
+use object::{Endianness, FileKind, Object, ObjectSymbol};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+const STACK_PROTECTOR_SYMBOLS: [&str; 2] = ["__stack_chk_fail", "__stack_chk_guard"];
+
+/// A hardening feature which ELF files can be required to have.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum HardeningFeature {
+    /// Relocation tables made read-only after relocation (`PT_GNU_RELRO`)
+    Relro,
+    /// RELRO with all symbols bound at load time, so the whole GOT is read-only
+    FullRelro,
+    /// Position independent code, allowing the load address to be randomized. Shared libraries always have this.
+    Pie,
+    /// A non-executable stack (`PT_GNU_STACK` without `PF_X`)
+    Nx,
+    /// All symbols bound at load time (`BIND_NOW`)
+    BindNow,
+    /// Stack protector checks, detected by references to `__stack_chk_fail` or `__stack_chk_guard`
+    StackProtector,
+}
+
+impl Display for HardeningFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HardeningFeature::Relro => "RELRO",
+            HardeningFeature::FullRelro => "full RELRO",
+            HardeningFeature::Pie => "PIE",
+            HardeningFeature::Nx => "NX",
+            HardeningFeature::BindNow => "BIND_NOW",
+            HardeningFeature::StackProtector => "stack protector",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The hardening features of an ELF file.
+#[derive(Debug, Default, PartialEq)]
+pub struct Hardening {
+    pub relro: bool,
+    pub pie: bool,
+    pub nx: bool,
+    pub bind_now: bool,
+    pub stack_protector: bool,
+    /// Whether the file has relocations against read-only segments (`DT_TEXTREL`), which must be made writable to be
+    /// relocated
+    pub text_relocations: bool,
+}
+
+impl Hardening {
+    pub fn has(&self, feature: HardeningFeature) -> bool {
+        match feature {
+            HardeningFeature::Relro => self.relro,
+            HardeningFeature::FullRelro => self.relro && self.bind_now,
+            HardeningFeature::Pie => self.pie,
+            HardeningFeature::Nx => self.nx,
+            HardeningFeature::BindNow => self.bind_now,
+            HardeningFeature::StackProtector => self.stack_protector,
+        }
+    }
+
+    /// Summarizes the hardening features, e.g. "RELRO: full, PIE: yes, NX: yes, BIND_NOW: yes, stack protector: no,
+    /// TEXTREL: no".
+    pub fn summary(&self) -> String {
+        let yes_no = |value| if value { "yes" } else { "no" };
+        let relro = match (self.relro, self.bind_now) {
+            (false, _) => "none",
+            (true, false) => "partial",
+            (true, true) => "full",
+        };
+        format!(
+            "RELRO: {}, PIE: {}, NX: {}, BIND_NOW: {}, stack protector: {}, TEXTREL: {}",
+            relro,
+            yes_no(self.pie),
+            yes_no(self.nx),
+            yes_no(self.bind_now),
+            yes_no(self.stack_protector),
+            yes_no(self.text_relocations)
+        )
+    }
+}
+
+pub fn get_hardening(data: &[u8]) -> anyhow::Result<Hardening> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Elf32 => get_elf_hardening(&ElfFile32::parse(data)?),
+        FileKind::Elf64 => get_elf_hardening(&ElfFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_elf_hardening<Elf: FileHeader<Endian = Endianness>>(
+    elf: &ElfFile<Elf>,
+) -> anyhow::Result<Hardening> {
+    let endian = elf.endianness();
+    let mut hardening = Hardening {
+        pie: elf.elf_header().e_type(endian) == elf::ET_DYN,
+        ..Hardening::default()
+    };
+
+    let mut has_gnu_stack = false;
+    for header in elf.elf_program_headers() {
+        match header.p_type(endian) {
+            elf::PT_GNU_RELRO => hardening.relro = true,
+            elf::PT_GNU_STACK => {
+                has_gnu_stack = true;
+                hardening.nx = header.p_flags(endian) & elf::PF_X == 0;
+            }
+            _ => {}
+        }
+    }
+    // Without PT_GNU_STACK, the stack is executable
+    hardening.nx &= has_gnu_stack;
+
+    let dynamic = elf
+        .elf_section_table()
+        .dynamic(endian, elf.data())
+        .with_context(|| "Error getting dynamic section")?
+        .map(|(dynamic, _)| dynamic)
+        .unwrap_or_default();
+    hardening.text_relocations = dynamic.iter().any(|entry| match entry.tag32(endian) {
+        Some(elf::DT_TEXTREL) => true,
+        Some(elf::DT_FLAGS) => entry.d_val(endian).into() & u64::from(elf::DF_TEXTREL) != 0,
+        _ => false,
+    });
+    hardening.bind_now = dynamic.iter().any(|entry| {
+        let value: u64 = entry.d_val(endian).into();
+        match entry.tag32(endian) {
+            Some(elf::DT_BIND_NOW) => true,
+            Some(elf::DT_FLAGS) => value & u64::from(elf::DF_BIND_NOW) != 0,
+            Some(elf::DT_FLAGS_1) => value & u64::from(elf::DF_1_NOW) != 0,
+            _ => false,
+        }
+    });
+
+    hardening.stack_protector = elf.dynamic_symbols().chain(elf.symbols()).any(|symbol| {
+        symbol
+            .name()
+            .is_ok_and(|name| STACK_PROTECTOR_SYMBOLS.contains(&name))
+    });
+    Ok(hardening)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executable_is_hardened() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let hardening = get_hardening(&data).unwrap();
+        assert!(hardening.pie);
+        assert!(hardening.nx);
+        assert!(hardening.relro);
+        assert!(!hardening.text_relocations);
+    }
+
+    #[test]
+    fn full_relro_needs_bind_now() {
+        let hardening = Hardening {
+            relro: true,
+            ..Hardening::default()
+        };
+        assert!(hardening.has(HardeningFeature::Relro));
+        assert!(!hardening.has(HardeningFeature::FullRelro));
+        let hardening = Hardening {
+            bind_now: true,
+            ..hardening
+        };
+        assert!(hardening.has(HardeningFeature::FullRelro));
+    }
+
+    #[test]
+    fn summarizes_features() {
+        let hardening = Hardening {
+            relro: true,
+            pie: true,
+            nx: true,
+            bind_now: false,
+            stack_protector: false,
+            text_relocations: true,
+        };
+        assert_eq!(
+            hardening.summary(),
+            "RELRO: partial, PIE: yes, NX: yes, BIND_NOW: no, stack protector: no, TEXTREL: yes"
+        );
+    }
+}
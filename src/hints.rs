@@ -0,0 +1,133 @@
+use crate::symbols::SymbolVersion;
+
+/// A way to avoid needing a symbol version, as reported by `--hints`.
+#[derive(Debug, PartialEq)]
+pub struct Hint {
+    /// What the hint applies to: a symbol version (e.g. `memcpy@GLIBC_2.14`), a symbol with any version, a version
+    /// (e.g. `GLIBC_2.34`), or a namespace
+    pub key: &'static str,
+    pub hint: &'static str,
+}
+
+const STAT_HINT: &str = "glibc 2.33 made the stat functions real functions rather than wrappers for __xstat; build against an older glibc";
+
+const MATH_HINT: &str = "glibc 2.29 added faster versions without the old error handling; the previous version can be bound to instead";
+
+/// Hints for symbols and versions which commonly make binaries need a newer glibc or GCC runtime than intended.
+pub const HINTS: &[Hint] = &[
+    hint(
+        "memcpy@GLIBC_2.14",
+        "glibc 2.14 headers bind memcpy to a new version which doesn't support overlapping copies; use memmove, or bind to the old version",
+    ),
+    hint(
+        "__libc_start_main@GLIBC_2.34",
+        "every executable linked against glibc 2.34 or newer needs this; build against an older glibc, e.g. in a manylinux container",
+    ),
+    hint(
+        "getrandom",
+        "call the system call directly with syscall(SYS_getrandom, ...), falling back to reading /dev/urandom",
+    ),
+    hint(
+        "getentropy",
+        "call the system call directly with syscall(SYS_getrandom, ...), falling back to reading /dev/urandom",
+    ),
+    hint(
+        "reallocarray",
+        "use realloc, checking the multiplication for overflow",
+    ),
+    hint(
+        "explicit_bzero",
+        "use memset followed by a compiler barrier, e.g. __asm__ __volatile__(\"\" ::: \"memory\")",
+    ),
+    hint(
+        "gettid",
+        "call the system call directly with syscall(SYS_gettid)",
+    ),
+    hint(
+        "memfd_create",
+        "call the system call directly with syscall(SYS_memfd_create, ...)",
+    ),
+    hint(
+        "copy_file_range",
+        "call the system call directly with syscall(SYS_copy_file_range, ...), falling back to read and write",
+    ),
+    hint(
+        "statx",
+        "call the system call directly with syscall(SYS_statx, ...), falling back to stat",
+    ),
+    hint(
+        "fcntl64",
+        "glibc 2.28 headers redirect fcntl to fcntl64; build against an older glibc",
+    ),
+    hint("stat@GLIBC_2.33", STAT_HINT),
+    hint("fstat@GLIBC_2.33", STAT_HINT),
+    hint("lstat@GLIBC_2.33", STAT_HINT),
+    hint("fstatat@GLIBC_2.33", STAT_HINT),
+    hint("stat64@GLIBC_2.33", STAT_HINT),
+    hint("fstat64@GLIBC_2.33", STAT_HINT),
+    hint("lstat64@GLIBC_2.33", STAT_HINT),
+    hint("fstatat64@GLIBC_2.33", STAT_HINT),
+    hint("mknod@GLIBC_2.33", STAT_HINT),
+    hint("mknodat@GLIBC_2.33", STAT_HINT),
+    hint("exp@GLIBC_2.29", MATH_HINT),
+    hint("log@GLIBC_2.29", MATH_HINT),
+    hint("pow@GLIBC_2.29", MATH_HINT),
+    hint("exp2@GLIBC_2.29", MATH_HINT),
+    hint("log2@GLIBC_2.29", MATH_HINT),
+    hint(
+        "GLIBC_2.34",
+        "glibc 2.34 merged libpthread, libdl and librt into libc, so their functions need new versions; build against an older glibc",
+    ),
+    hint(
+        "GLIBCXX",
+        "build with an older GCC, such as a devtoolset on RHEL, or link libstdc++ statically with -static-libstdc++",
+    ),
+    hint(
+        "CXXABI",
+        "build with an older GCC, such as a devtoolset on RHEL, or link libstdc++ statically with -static-libstdc++",
+    ),
+    hint(
+        "GCC",
+        "build with an older GCC, or link libgcc statically with -static-libgcc",
+    ),
+];
+
+const fn hint(key: &'static str, hint: &'static str) -> Hint {
+    Hint { key, hint }
+}
+
+/// Finds the most specific hint for a symbol: one for its symbol version, then for the symbol with any version, its
+/// version, and its namespace.
+pub fn find_hint(symbol: &SymbolVersion) -> Option<&'static Hint> {
+    let keys = [
+        format!("{}@{}", symbol.name, symbol.version),
+        symbol.name.clone(),
+        symbol.version.to_string(),
+        symbol.version.namespace.clone(),
+    ];
+    keys.iter()
+        .find_map(|key| HINTS.iter().find(|hint| hint.key == key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str, version: &str) -> Option<&'static str> {
+        find_hint(&SymbolVersion::parse(name, version, None).unwrap()).map(|hint| hint.key)
+    }
+
+    #[test]
+    fn finds_most_specific_hint() {
+        assert_eq!(key("memcpy", "GLIBC_2.14"), Some("memcpy@GLIBC_2.14"));
+        assert_eq!(key("getrandom", "GLIBC_2.25"), Some("getrandom"));
+        assert_eq!(key("pthread_create", "GLIBC_2.34"), Some("GLIBC_2.34"));
+        assert_eq!(
+            key("__libc_start_main", "GLIBC_2.34"),
+            Some("__libc_start_main@GLIBC_2.34")
+        );
+        assert_eq!(key("stat", "GLIBC_2.33"), Some("stat@GLIBC_2.33"));
+        assert_eq!(key("_ZSt4cout", "GLIBCXX_3.4.30"), Some("GLIBCXX"));
+        assert_eq!(key("memcpy", "GLIBC_2.2.5"), None);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::version::{NamespacedVersion, Version};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The maximum version required in a namespace, and the files which require it.
+pub struct NamespaceInventory {
+    pub max_version: NamespacedVersion,
+    pub required_by: Vec<PathBuf>,
+}
+
+/// Aggregate of the maximum version required in each namespace across a set of ELF files.
+#[derive(Default)]
+pub struct Inventory {
+    pub file_count: usize,
+    pub namespaces: BTreeMap<String, NamespaceInventory>,
+    /// For each namespace, the number of files whose maximum version in it is each version
+    pub histograms: BTreeMap<String, BTreeMap<Version, usize>>,
+}
+
+/// Returns the maximum of the versions in each namespace.
+pub fn max_versions<'a>(
+    versions: impl IntoIterator<Item = &'a NamespacedVersion>,
+) -> BTreeMap<&'a str, &'a NamespacedVersion> {
+    let mut max_versions: BTreeMap<&str, &NamespacedVersion> = BTreeMap::new();
+    for version in versions {
+        let max = max_versions.entry(&version.namespace).or_insert(version);
+        if version > *max {
+            *max = version;
+        }
+    }
+    max_versions
+}
+
+impl Inventory {
+    pub fn add_file<'a>(
+        &mut self,
+        file: &Path,
+        versions: impl IntoIterator<Item = &'a NamespacedVersion>,
+    ) {
+        self.file_count += 1;
+
+        for (namespace, version) in max_versions(versions) {
+            *self
+                .histograms
+                .entry(namespace.to_string())
+                .or_default()
+                .entry(version.version.clone())
+                .or_default() += 1;
+            match self.namespaces.get_mut(namespace) {
+                Some(inventory) if *version > inventory.max_version => {
+                    inventory.max_version = version.clone();
+                    inventory.required_by = vec![file.to_path_buf()];
+                }
+                Some(inventory) if *version == inventory.max_version => {
+                    inventory.required_by.push(file.to_path_buf());
+                }
+                Some(_) => {}
+                None => {
+                    self.namespaces.insert(
+                        namespace.to_string(),
+                        NamespaceInventory {
+                            max_version: version.clone(),
+                            required_by: vec![file.to_path_buf()],
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> NamespacedVersion {
+        NamespacedVersion::parse(version).unwrap()
+    }
+
+    #[test]
+    fn empty_inventory() {
+        let inventory = Inventory::default();
+        assert_eq!(inventory.file_count, 0);
+        assert!(inventory.namespaces.is_empty());
+    }
+
+    #[test]
+    fn finds_maximum_version_of_file_per_namespace() {
+        let versions = [
+            version("GLIBC_2.14"),
+            version("GLIBC_2.2.5"),
+            version("GLIBCXX_3.4"),
+        ];
+        let max_versions: Vec<String> = max_versions(&versions)
+            .values()
+            .map(|version| version.to_string())
+            .collect();
+        assert_eq!(max_versions, ["GLIBC_2.14", "GLIBCXX_3.4"]);
+    }
+
+    #[test]
+    fn tracks_maximum_version_per_namespace() {
+        let mut inventory = Inventory::default();
+        inventory.add_file(
+            Path::new("a"),
+            &[
+                version("GLIBC_2.14"),
+                version("GLIBC_2.2.5"),
+                version("GLIBCXX_3.4"),
+            ],
+        );
+        inventory.add_file(Path::new("b"), &[version("GLIBC_2.25")]);
+        assert_eq!(inventory.file_count, 2);
+        assert_eq!(inventory.namespaces.len(), 2);
+
+        let glibc = &inventory.namespaces["GLIBC"];
+        assert_eq!(
+            glibc.max_version,
+            NamespacedVersion::parse("GLIBC_2.25").unwrap()
+        );
+        assert_eq!(glibc.required_by, vec![PathBuf::from("b")]);
+
+        let glibcxx = &inventory.namespaces["GLIBCXX"];
+        assert_eq!(
+            glibcxx.max_version,
+            NamespacedVersion::parse("GLIBCXX_3.4").unwrap()
+        );
+        assert_eq!(glibcxx.required_by, vec![PathBuf::from("a")]);
+    }
+
+    #[test]
+    fn counts_files_by_maximum_version() {
+        let mut inventory = Inventory::default();
+        inventory.add_file(Path::new("a"), &[version("GLIBC_2.14")]);
+        inventory.add_file(
+            Path::new("b"),
+            &[version("GLIBC_2.14"), version("GLIBC_2.25")],
+        );
+        inventory.add_file(Path::new("c"), &[version("GLIBC_2.25")]);
+        inventory.add_file(Path::new("d"), &[version("GLIBC_2.14")]);
+        let counts: Vec<(String, usize)> = inventory.histograms["GLIBC"]
+            .iter()
+            .map(|(version, count)| (version.to_string(), *count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("2.14".to_string(), 2), ("2.25".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn records_all_files_requiring_maximum_version() {
+        let mut inventory = Inventory::default();
+        inventory.add_file(Path::new("a"), &[version("GLIBC_2.14")]);
+        inventory.add_file(Path::new("b"), &[version("GLIBC_2.2.5")]);
+        inventory.add_file(
+            Path::new("c"),
+            &[version("GLIBC_2.14"), version("GLIBC_2.2.5")],
+        );
+        assert_eq!(
+            inventory.namespaces["GLIBC"].required_by,
+            vec![PathBuf::from("a"), PathBuf::from("c")]
+        );
+    }
+}
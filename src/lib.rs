@@ -0,0 +1,426 @@
+//! Checks the versions of the dynamically linked symbols binaries need against maximum permitted versions, so that
+//! binaries can be checked for compatibility with older platforms from build tools and tests without running the
+//! `symbol-version-check` command.
+
+// Much of the checker is only reachable from the command's settings, so is unused when the command isn't built
+#![cfg_attr(not(feature = "cli"), allow(dead_code))]
+
+#[cfg(feature = "cli")]
+mod archive;
+mod auditable;
+#[cfg(feature = "cli")]
+mod auditwheel;
+mod baseline;
+mod binary;
+mod blame;
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod cargo;
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod cli;
+mod compatibility;
+#[cfg(feature = "cli")]
+mod config;
+#[cfg(feature = "cli")]
+mod cpio;
+#[cfg(feature = "cli")]
+mod dedup;
+mod denylist;
+#[cfg(feature = "cli")]
+mod diff;
+pub mod elf;
+mod exceptions;
+#[cfg(feature = "cli")]
+mod exports;
+pub mod ffi;
+#[cfg(feature = "cli")]
+mod filter;
+#[cfg(feature = "cli")]
+mod git;
+mod glibc_db;
+mod hardening;
+mod hints;
+mod inventory;
+mod macho;
+#[cfg(feature = "cli")]
+mod metrics;
+#[cfg(feature = "cli")]
+mod nix;
+#[cfg(feature = "cli")]
+mod oci;
+mod pe;
+mod policy;
+mod presets;
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg(all(feature = "cli", not(feature = "registry")))]
+mod registry {
+    pub fn visit_image_elf_files(
+        _image: &str,
+        _visit: &mut dyn FnMut(&str, &[u8]),
+    ) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!(
+            "Pulling images from registries isn't included in this build"
+        ))
+    }
+}
+mod report;
+pub mod requirements;
+mod soname;
+pub mod symbols;
+mod sysroot;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "tui")]
+mod tui;
+pub mod version;
+#[cfg(feature = "cli")]
+mod walk;
+#[cfg(feature = "cli")]
+mod watch;
+
+use anyhow::Context;
+use policy::Policy;
+pub use policy::Violation;
+use requirements::VersionRequirements;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a file fared in the check, the `result` of each file's entry in the JSON output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileOutcome {
+    Pass,
+    /// A statically linked executable, having no version references to check
+    Static,
+    /// Linked against musl, glibc symbol versioning not being applicable
+    Musl,
+    /// A kind of ELF file, such as a kernel module, which has no dynamic symbol versions to check
+    Unsupported,
+    /// Passed, with violations which are only warnings
+    Warn,
+    Fail,
+    Error,
+    Skipped,
+    /// Has the same contents as another file, so has the same outcome
+    Duplicate,
+}
+
+impl FileOutcome {
+    pub fn passed(self) -> bool {
+        !matches!(self, FileOutcome::Fail | FileOutcome::Error)
+    }
+}
+
+/// The result of checking a file against maximum versions.
+///
+/// Reports are serialized with the same fields as each file's entry in the JSON output, so the JSON output can be read
+/// as a [`CheckReport`]. The serialized form is stable: fields may be added, but won't be removed, renamed or change
+/// meaning, nor will those of the violations, symbols and versions within it.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileReport {
+    pub file: PathBuf,
+    pub result: FileOutcome,
+    /// The reasons the file failed or was warned about, empty if it passed without warnings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+    /// The reason the file couldn't be checked, if it couldn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The file a duplicate has the same contents as
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_as: Option<PathBuf>,
+    /// The outcome of the file a duplicate has the same contents as
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_as_result: Option<FileOutcome>,
+}
+
+impl FileReport {
+    fn error(file: PathBuf, e: anyhow::Error) -> Self {
+        FileReport {
+            file,
+            result: FileOutcome::Error,
+            violations: Vec::new(),
+            error: Some(format!("{:#}", e)),
+            same_as: None,
+            same_as_result: None,
+        }
+    }
+
+    /// Whether the file passed, a duplicate passing if the file it has the same contents as did.
+    pub fn passed(&self) -> bool {
+        match (self.result, self.same_as_result) {
+            (FileOutcome::Duplicate, Some(same_as_result)) => same_as_result.passed(),
+            (result, _) => result.passed(),
+        }
+    }
+}
+
+/// The results of checking several files, serialized as in the JSON output, with the same stability as [`FileReport`].
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckReport {
+    pub files: Vec<FileReport>,
+}
+
+impl CheckReport {
+    pub fn passed(&self) -> bool {
+        self.files.iter().all(FileReport::passed)
+    }
+}
+
+/// Checks an ELF, Mach-O or PE file against maximum versions, as the command does with its default settings: failing
+/// symbols with newer versions, and symbols with private versions such as `GLIBC_PRIVATE`.
+pub fn check_file(path: &Path, requirements: &VersionRequirements) -> anyhow::Result<FileReport> {
+    let data = fs::read(path).with_context(|| "Error reading file")?;
+    Ok(FileReport {
+        file: path.to_path_buf(),
+        ..check_data(&data, requirements)?
+    })
+}
+
+/// Checks the contents of a file as [`check_file`] does, returning a report with an empty `file`, for use where there
+/// is no filesystem, such as in WebAssembly.
+pub fn check_data(data: &[u8], requirements: &VersionRequirements) -> anyhow::Result<FileReport> {
+    let policy = Policy::new(requirements.clone());
+    let violations = policy.check(data)?;
+    Ok(FileReport {
+        file: PathBuf::new(),
+        result: policy.outcome(data, &violations),
+        violations,
+        error: None,
+        same_as: None,
+        same_as_result: None,
+    })
+}
+
+/// Asserts that a file passes the check against maximum versions, e.g. `GLIBC_2.17`, as [`check_file`] checks it,
+/// panicking with the reasons it failed otherwise, so that a crate's tests can enforce the platforms its binaries
+/// support. [`assert_max_versions!`] calls this with a list of versions.
+#[track_caller]
+pub fn assert_passes(path: impl AsRef<Path>, requirements: &[&str]) {
+    let path = path.as_ref();
+    let requirements = requirements
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+    let parsed = VersionRequirements::parse(&requirements)
+        .unwrap_or_else(|e| panic!("Invalid maximum versions: {:#}", e));
+    let report = check_file(path, &parsed)
+        .unwrap_or_else(|e| panic!("{} couldn't be checked: {:#}", path.display(), e));
+    if !report.passed() {
+        let violations = report
+            .violations
+            .iter()
+            .map(|violation| {
+                let value =
+                    serde_json::to_value(violation).expect("Violations can always be serialized");
+                format!("\n    {}", report::describe_violation(&value))
+            })
+            .collect::<String>();
+        panic!(
+            "{} fails the check against {}:{}",
+            path.display(),
+            requirements.join(", "),
+            violations
+        );
+    }
+}
+
+/// Asserts that a file passes the check against maximum versions, panicking with the reasons it failed otherwise:
+/// ```no_run
+/// #[test]
+/// fn supports_centos_7() {
+///     symbol_version_check::assert_max_versions!("target/release/libmylib.so", ["GLIBC_2.17", "GLIBCXX_3.4.19"]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_max_versions {
+    ($path:expr, [$($requirement:expr),* $(,)?] $(,)?) => {
+        $crate::assert_passes($path, &[$($requirement),*])
+    };
+}
+
+/// Progress in checking files, reported as each file is started and completed.
+#[derive(Debug)]
+pub enum FileEvent<'a> {
+    /// A file is about to be checked, the `index`th, from zero, of `total`
+    Checking {
+        file: &'a Path,
+        index: usize,
+        total: usize,
+    },
+    /// A file has been checked
+    Checked(FileReport),
+}
+
+/// Checks each file as [`check_file`] does, reporting the files which couldn't be checked with the error.
+pub fn check_files(paths: &[PathBuf], requirements: &VersionRequirements) -> CheckReport {
+    let mut report = CheckReport::default();
+    check_files_with(paths, requirements, |event| {
+        if let FileEvent::Checked(file_report) = event {
+            report.files.push(file_report);
+        }
+    });
+    report
+}
+
+/// Checks each file as [`check_files`] does, calling `on_event` as each file is started and completed, rather than
+/// collecting the reports, so that progress can be shown and results streamed for large numbers of files.
+pub fn check_files_with(
+    paths: &[PathBuf],
+    requirements: &VersionRequirements,
+    mut on_event: impl FnMut(FileEvent),
+) {
+    for (index, path) in paths.iter().enumerate() {
+        on_event(FileEvent::Checking {
+            file: path,
+            index,
+            total: paths.len(),
+        });
+        let file_report =
+            check_file(path, requirements).unwrap_or_else(|e| FileReport::error(path.clone(), e));
+        on_event(FileEvent::Checked(file_report));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_file_against_requirements() {
+        let file = std::env::current_exe().unwrap();
+        let requirements = |max: &str| VersionRequirements::parse(&[max.to_string()]).unwrap();
+        let report = check_file(&file, &requirements("GLIBC_2.0")).unwrap();
+        assert!(!report.passed());
+        assert!(
+            report
+                .violations
+                .iter()
+                .all(|violation| matches!(violation, Violation::Symbol(_)))
+        );
+        assert!(
+            check_file(&file, &requirements("GLIBC_99.0"))
+                .unwrap()
+                .passed()
+        );
+    }
+
+    #[test]
+    fn checks_data_as_file_is_checked() {
+        let file = std::env::current_exe().unwrap();
+        let requirements = VersionRequirements::parse(&["GLIBC_2.0".to_string()]).unwrap();
+        let report = check_data(&fs::read(&file).unwrap(), &requirements).unwrap();
+        assert!(!report.violations.is_empty());
+        assert_eq!(report.file, PathBuf::new());
+        let file_report = check_file(&file, &requirements).unwrap();
+        assert_eq!(report.result, file_report.result);
+        assert_eq!(report.violations, file_report.violations);
+        assert!(check_data(b"not an ELF file", &requirements).is_err());
+    }
+
+    #[test]
+    fn warns_for_warn_only_requirements() {
+        let file = std::env::current_exe().unwrap();
+        let requirements = VersionRequirements::parse(&["GLIBC_2.0:warn".to_string()]).unwrap();
+        let report = check_file(&file, &requirements).unwrap();
+        assert_eq!(report.result, FileOutcome::Warn);
+        assert!(!report.violations.is_empty());
+        assert!(report.passed());
+        crate::assert_max_versions!(&file, ["GLIBC_2.0:warn"]);
+    }
+
+    #[test]
+    fn reports_static_executables() {
+        let requirements = VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap();
+        let report = check_data(&elf::tests::build_static_elf(b""), &requirements).unwrap();
+        assert_eq!(report.result, FileOutcome::Static);
+    }
+
+    #[test]
+    fn asserts_file_passes() {
+        crate::assert_max_versions!(std::env::current_exe().unwrap(), ["GLIBC_99.0"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "fails the check against GLIBC_2.0:\n    symbol ")]
+    fn assertion_lists_violations() {
+        crate::assert_max_versions!(std::env::current_exe().unwrap(), ["GLIBC_2.0"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "couldn't be checked: Error reading file")]
+    fn assertion_fails_for_unreadable_file() {
+        assert_passes("missing", &["GLIBC_2.17"]);
+    }
+
+    #[test]
+    fn reads_json_output_as_check_report() {
+        let json = r#"{
+            "files": [
+                {
+                    "file": "app",
+                    "result": "fail",
+                    "violations": [
+                        {"kind": "symbol", "name": "stat", "version": "GLIBC_2.33", "file": "libc.so.6", "type": "func"},
+                        {"kind": "disallowed_library", "library": "libssl.so.3"},
+                        {"kind": "excepted_symbol", "name": "getrandom", "version": "GLIBC_2.25", "file": null,
+                         "comment": "fallback", "expires": null}
+                    ],
+                    "details": {"build_id": null}
+                },
+                {"file": "lib.so", "result": "pass", "details": {}},
+                {"file": "missing", "result": "error", "error": "Error reading file"},
+                {"file": "plugin.so", "result": "warn", "violations": [{"kind": "missing_build_id"}], "details": {}},
+                {"file": "copy/app", "result": "duplicate", "same_as": "app", "same_as_result": "fail"}
+            ]
+        }"#;
+        let report: CheckReport = serde_json::from_str(json).unwrap();
+        assert_eq!(report.files.len(), 5);
+        let violations = &report.files[0].violations;
+        assert!(matches!(&violations[0], Violation::Symbol(symbol)
+            if symbol.version.to_string() == "GLIBC_2.33"
+                && symbol.symbol_type == Some(symbols::SymbolType::Func)));
+        assert!(
+            matches!(&violations[2], Violation::ExceptedSymbol { symbol, comment, .. }
+            if symbol.name == "getrandom" && comment.as_deref() == Some("fallback"))
+        );
+        assert!(!report.files[0].passed());
+        assert!(report.files[1].passed());
+        assert_eq!(report.files[2].error.as_deref(), Some("Error reading file"));
+        assert!(report.files[3].passed());
+        assert!(!report.files[4].passed());
+        assert!(!report.passed());
+        let round_trip: CheckReport =
+            serde_json::from_value(serde_json::to_value(&report).unwrap()).unwrap();
+        assert_eq!(round_trip, report);
+    }
+
+    #[test]
+    fn reports_events_as_files_are_checked() {
+        let exe = std::env::current_exe().unwrap();
+        let paths = vec![exe.clone(), exe.with_file_name("missing")];
+        let requirements = VersionRequirements::parse(&["GLIBC_99.0".to_string()]).unwrap();
+        let mut events = Vec::new();
+        check_files_with(&paths, &requirements, |event| match event {
+            FileEvent::Checking { file, index, total } => events.push(format!(
+                "checking {} {}/{}",
+                file == paths[index],
+                index,
+                total
+            )),
+            FileEvent::Checked(report) => events.push(format!("checked {}", report.passed())),
+        });
+        assert_eq!(
+            events,
+            vec![
+                "checking true 0/2",
+                "checked true",
+                "checking true 1/2",
+                "checked false"
+            ]
+        );
+    }
+}
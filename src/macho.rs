@@ -0,0 +1,397 @@
+use crate::version::{NamespacedVersion, Version};
+use anyhow::{Context, anyhow};
+use object::macho;
+use object::read::macho::{
+    FatArch, MachHeader, MachOFatFile, MachOFatFile32, MachOFatFile64, MachOFile, MachOFile32,
+    MachOFile64, Nlist,
+};
+use object::{Endianness, FileKind};
+
+const MACHO_MAGICS: [u32; 2] = [macho::MH_MAGIC, macho::MH_MAGIC_64];
+
+pub fn is_macho(data: &[u8]) -> bool {
+    let Some(magic) = data.first_chunk::<4>() else {
+        return false;
+    };
+    MACHO_MAGICS.contains(&u32::from_be_bytes(*magic))
+        || MACHO_MAGICS.contains(&u32::from_le_bytes(*magic))
+}
+
+/// Returns whether the data is a universal (fat) Mach-O file, which contains a Mach-O file for each of several
+/// architectures. Java class files share the 32-bit fat magic number, but are told apart by their class file version
+/// in place of the architecture count, which starts at 45.
+pub fn is_fat(data: &[u8]) -> bool {
+    let Some(header) = data.first_chunk::<8>() else {
+        return false;
+    };
+    let magic = u32::from_be_bytes(header[..4].try_into().unwrap());
+    let nfat_arch = u32::from_be_bytes(header[4..].try_into().unwrap());
+    match magic {
+        macho::FAT_MAGIC => (1..45).contains(&nfat_arch),
+        macho::FAT_MAGIC_64 => true,
+        _ => false,
+    }
+}
+
+/// Visits each architecture slice of a universal (fat) Mach-O file, passing the slice's architecture name (e.g.
+/// `x86_64` or `arm64`) and data.
+pub fn visit_fat_slices(data: &[u8], visit: &mut dyn FnMut(&str, &[u8])) -> anyhow::Result<()> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::MachOFat32 => visit_macho_fat_slices(MachOFatFile32::parse(data)?, data, visit),
+        FileKind::MachOFat64 => visit_macho_fat_slices(MachOFatFile64::parse(data)?, data, visit),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn visit_macho_fat_slices<Fat: FatArch>(
+    fat: MachOFatFile<Fat>,
+    data: &[u8],
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    for arch in fat.arches() {
+        let name = cpu_name(arch.cputype(), arch.cpusubtype());
+        let slice = arch
+            .data(data)
+            .with_context(|| format!("Error reading {} slice", name))?;
+        visit(&name, slice);
+    }
+    Ok(())
+}
+
+fn cpu_name(cputype: u32, cpusubtype: u32) -> String {
+    match cputype {
+        macho::CPU_TYPE_X86_64 => "x86_64".to_string(),
+        macho::CPU_TYPE_X86 => "i386".to_string(),
+        macho::CPU_TYPE_ARM64
+            if cpusubtype & !macho::CPU_SUBTYPE_MASK == macho::CPU_SUBTYPE_ARM64E =>
+        {
+            "arm64e".to_string()
+        }
+        macho::CPU_TYPE_ARM64 => "arm64".to_string(),
+        macho::CPU_TYPE_ARM64_32 => "arm64_32".to_string(),
+        macho::CPU_TYPE_ARM => "arm".to_string(),
+        macho::CPU_TYPE_POWERPC => "ppc".to_string(),
+        macho::CPU_TYPE_POWERPC64 => "ppc64".to_string(),
+        cputype => format!("cpu{}", cputype),
+    }
+}
+
+/// The minimum OS version a Mach-O file is built to run on, as given by one of its load commands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentTarget {
+    /// The platform and its minimum version, e.g. `macos_11`, checked against a requirement such as `macos_11.0`
+    pub version: NamespacedVersion,
+    /// The symbols the file imports weakly, so can run without
+    pub weak_imports: Vec<String>,
+}
+
+/// Returns the minimum OS versions a Mach-O file is built to run on, as given by its `LC_BUILD_VERSION` or
+/// `LC_VERSION_MIN_*` load commands.
+pub fn get_deployment_targets(data: &[u8]) -> anyhow::Result<Vec<DeploymentTarget>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::MachO32 => get_macho_deployment_targets(MachOFile32::parse(data)?),
+        FileKind::MachO64 => get_macho_deployment_targets(MachOFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_macho_deployment_targets<Mach: MachHeader<Endian = Endianness>>(
+    macho: MachOFile<Mach>,
+) -> anyhow::Result<Vec<DeploymentTarget>> {
+    let endian = macho.endian();
+    let weak_imports = weak_imports(&macho)?;
+
+    let mut targets = Vec::new();
+    let mut commands = macho
+        .macho_load_commands()
+        .with_context(|| "Error reading load commands")?;
+    while let Some(command) = commands
+        .next()
+        .with_context(|| "Error reading load command")?
+    {
+        let (platform, min_version) = match command.cmd() {
+            macho::LC_BUILD_VERSION => {
+                let build_version: &macho::BuildVersionCommand<Endianness> = command.data()?;
+                (
+                    platform_name(build_version.platform.get(endian)),
+                    build_version.minos.get(endian),
+                )
+            }
+            cmd @ (macho::LC_VERSION_MIN_MACOSX
+            | macho::LC_VERSION_MIN_IPHONEOS
+            | macho::LC_VERSION_MIN_TVOS
+            | macho::LC_VERSION_MIN_WATCHOS) => {
+                let version_min: &macho::VersionMinCommand<Endianness> = command.data()?;
+                let platform = match cmd {
+                    macho::LC_VERSION_MIN_MACOSX => "macos",
+                    macho::LC_VERSION_MIN_IPHONEOS => "ios",
+                    macho::LC_VERSION_MIN_TVOS => "tvos",
+                    _ => "watchos",
+                };
+                (platform.to_string(), version_min.version.get(endian))
+            }
+            _ => continue,
+        };
+        targets.push(DeploymentTarget {
+            version: NamespacedVersion {
+                namespace: platform,
+                version: decode_version(min_version)?,
+            },
+            weak_imports: weak_imports.clone(),
+        });
+    }
+    Ok(targets)
+}
+
+/// Returns the names of the undefined symbols marked as weak references.
+fn weak_imports<Mach: MachHeader<Endian = Endianness>>(
+    macho: &MachOFile<Mach>,
+) -> anyhow::Result<Vec<String>> {
+    let endian = macho.endian();
+    let symbols = macho.macho_symbol_table();
+    symbols
+        .iter()
+        .filter(|nlist| {
+            !nlist.is_stab()
+                && nlist.n_type() & macho::N_TYPE == macho::N_UNDF
+                && nlist.n_type() & macho::N_EXT != 0
+                && nlist.n_desc(endian) & macho::N_WEAK_REF != 0
+        })
+        .map(|nlist| {
+            let name = nlist
+                .name(endian, symbols.strings())
+                .map_err(|_| anyhow!("Error reading symbol name"))?;
+            Ok(String::from_utf8_lossy(name).to_string())
+        })
+        .collect()
+}
+
+fn platform_name(platform: u32) -> String {
+    match platform {
+        macho::PLATFORM_MACOS => "macos".to_string(),
+        macho::PLATFORM_IOS => "ios".to_string(),
+        macho::PLATFORM_TVOS => "tvos".to_string(),
+        macho::PLATFORM_WATCHOS => "watchos".to_string(),
+        macho::PLATFORM_BRIDGEOS => "bridgeos".to_string(),
+        macho::PLATFORM_MACCATALYST => "maccatalyst".to_string(),
+        macho::PLATFORM_IOSSIMULATOR => "iossimulator".to_string(),
+        macho::PLATFORM_TVOSSIMULATOR => "tvossimulator".to_string(),
+        macho::PLATFORM_WATCHOSSIMULATOR => "watchossimulator".to_string(),
+        macho::PLATFORM_DRIVERKIT => "driverkit".to_string(),
+        macho::PLATFORM_XROS => "visionos".to_string(),
+        macho::PLATFORM_XROSSIMULATOR => "visionossimulator".to_string(),
+        platform => format!("platform{}", platform),
+    }
+}
+
+/// Decodes a version encoded in nibbles as `xxxx.yy.zz`.
+fn decode_version(version: u32) -> anyhow::Result<Version> {
+    Version::parse(&format!(
+        "{}.{}.{}",
+        version >> 16,
+        (version >> 8) & 0xff,
+        version & 0xff
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds a minimal little endian 64-bit Mach-O executable with the given load commands, and a symbol table
+    /// containing the given undefined symbols and their `n_desc` flags.
+    pub fn build_macho(commands: &[(u32, Vec<u32>)], undefined_symbols: &[(&str, u16)]) -> Vec<u8> {
+        const HEADER_SIZE: usize = 32;
+        const SYMTAB_COMMAND_SIZE: usize = 24;
+        const NLIST_SIZE: usize = 16;
+
+        let mut load_commands = Vec::new();
+        for (cmd, fields) in commands {
+            load_commands.extend(cmd.to_le_bytes());
+            load_commands.extend((8 + fields.len() as u32 * 4).to_le_bytes());
+            for field in fields {
+                load_commands.extend(field.to_le_bytes());
+            }
+        }
+        let symbols_offset = HEADER_SIZE + load_commands.len() + SYMTAB_COMMAND_SIZE;
+        let mut strings = vec![0u8];
+        let mut symbols = Vec::new();
+        for (name, desc) in undefined_symbols {
+            symbols.extend((strings.len() as u32).to_le_bytes());
+            symbols.push(macho::N_UNDF | macho::N_EXT);
+            symbols.push(0);
+            symbols.extend(desc.to_le_bytes());
+            symbols.extend(0u64.to_le_bytes());
+            strings.extend(name.as_bytes());
+            strings.push(0);
+        }
+        let strings_offset = symbols_offset + undefined_symbols.len() * NLIST_SIZE;
+        for field in [
+            macho::LC_SYMTAB,
+            SYMTAB_COMMAND_SIZE as u32,
+            symbols_offset as u32,
+            undefined_symbols.len() as u32,
+            strings_offset as u32,
+            strings.len() as u32,
+        ] {
+            load_commands.extend(field.to_le_bytes());
+        }
+
+        let mut data = Vec::new();
+        for field in [
+            macho::MH_MAGIC_64,
+            macho::CPU_TYPE_ARM64,
+            0,
+            macho::MH_EXECUTE,
+            commands.len() as u32 + 1,
+            load_commands.len() as u32,
+            0,
+            0,
+        ] {
+            data.extend(field.to_le_bytes());
+        }
+        data.extend(load_commands);
+        data.extend(symbols);
+        data.extend(strings);
+        data
+    }
+
+    /// Builds a universal (fat) Mach-O file containing the given slices and their CPU types.
+    pub fn build_fat_macho(slices: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        const HEADER_SIZE: usize = 8;
+        const FAT_ARCH_SIZE: usize = 20;
+        const ALIGN: u32 = 4;
+
+        let mut header = Vec::new();
+        header.extend(macho::FAT_MAGIC.to_be_bytes());
+        header.extend((slices.len() as u32).to_be_bytes());
+        let mut contents = Vec::new();
+        let contents_offset = HEADER_SIZE + slices.len() * FAT_ARCH_SIZE;
+        for (cputype, slice) in slices {
+            while contents.len() % (1 << ALIGN) != 0 {
+                contents.push(0);
+            }
+            for field in [
+                *cputype,
+                0,
+                (contents_offset + contents.len()) as u32,
+                slice.len() as u32,
+                ALIGN,
+            ] {
+                header.extend(field.to_be_bytes());
+            }
+            contents.extend(slice);
+        }
+        header.extend(contents);
+        header
+    }
+
+    /// Builds a Mach-O executable for the given CPU type, built for the given macOS version.
+    pub fn build_macos_slice(cputype: u32, major: u32, minor: u32) -> Vec<u8> {
+        let mut data = build_macho(
+            &[(
+                macho::LC_BUILD_VERSION,
+                vec![macho::PLATFORM_MACOS, encode_version(major, minor, 0), 0, 0],
+            )],
+            &[],
+        );
+        data[4..8].copy_from_slice(&cputype.to_le_bytes());
+        data
+    }
+
+    fn encode_version(major: u32, minor: u32, patch: u32) -> u32 {
+        (major << 16) | (minor << 8) | patch
+    }
+
+    #[test]
+    fn detects_macho_magic() {
+        assert!(is_macho(&build_macho(&[], &[])));
+        assert!(!is_macho(b"\x7fELF\x02\x01\x01"));
+        assert!(!is_macho(b"\xfe"));
+    }
+
+    #[test]
+    fn reads_build_version() {
+        let data = build_macho(
+            &[(
+                macho::LC_BUILD_VERSION,
+                vec![macho::PLATFORM_MACOS, encode_version(11, 0, 0), 0, 0],
+            )],
+            &[("_malloc", 0)],
+        );
+        let targets = get_deployment_targets(&data).unwrap();
+        assert_eq!(
+            targets,
+            vec![DeploymentTarget {
+                version: NamespacedVersion::parse("macos_11.0").unwrap(),
+                weak_imports: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reads_version_min() {
+        let data = build_macho(
+            &[(
+                macho::LC_VERSION_MIN_MACOSX,
+                vec![encode_version(10, 13, 4), 0],
+            )],
+            &[],
+        );
+        let targets = get_deployment_targets(&data).unwrap();
+        assert_eq!(
+            targets[0].version,
+            NamespacedVersion::parse("macos_10.13.4").unwrap()
+        );
+    }
+
+    #[test]
+    fn lists_weak_imports() {
+        let data = build_macho(
+            &[(
+                macho::LC_BUILD_VERSION,
+                vec![macho::PLATFORM_IOS, encode_version(14, 0, 0), 0, 0],
+            )],
+            &[
+                ("_malloc", 0),
+                ("_os_log_create", macho::N_WEAK_REF),
+                ("_os_signpost_enabled", macho::N_WEAK_REF),
+            ],
+        );
+        let targets = get_deployment_targets(&data).unwrap();
+        assert_eq!(targets[0].version.namespace, "ios");
+        assert_eq!(
+            targets[0].weak_imports,
+            vec!["_os_log_create", "_os_signpost_enabled"]
+        );
+    }
+
+    #[test]
+    fn detects_fat_magic() {
+        let fat = build_fat_macho(&[(macho::CPU_TYPE_ARM64, build_macho(&[], &[]))]);
+        assert!(is_fat(&fat));
+        assert!(!is_macho(&fat));
+        assert!(!is_fat(&build_macho(&[], &[])));
+        // A Java class file, with version 52.0 in place of the architecture count
+        assert!(!is_fat(b"\xca\xfe\xba\xbe\x00\x00\x00\x34"));
+    }
+
+    #[test]
+    fn visits_fat_slices() {
+        let x86_64 = build_macos_slice(macho::CPU_TYPE_X86_64, 10, 13);
+        let arm64 = build_macos_slice(macho::CPU_TYPE_ARM64, 11, 0);
+        let fat = build_fat_macho(&[
+            (macho::CPU_TYPE_X86_64, x86_64.clone()),
+            (macho::CPU_TYPE_ARM64, arm64.clone()),
+        ]);
+        let mut slices = Vec::new();
+        visit_fat_slices(&fat, &mut |name, data| {
+            slices.push((name.to_string(), data.to_vec()))
+        })
+        .unwrap();
+        assert_eq!(
+            slices,
+            vec![("x86_64".to_string(), x86_64), ("arm64".to_string(), arm64)]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::version::{NamespacedVersion, Version};
+use anyhow::Context;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const PREFIX: &str = "symbol_version_check_";
+
+/// The gauges written for a check, in the OpenMetrics text format, for scraping and graphing scans over time.
+#[derive(Debug, Default, PartialEq)]
+pub struct Metrics {
+    pub files_checked: usize,
+    pub files_failed: usize,
+    /// The newest version needed in each namespace, by any file
+    pub max_versions: Vec<NamespacedVersion>,
+}
+
+impl Metrics {
+    pub fn to_openmetrics(&self) -> String {
+        let mut text = String::new();
+        write_gauge(&mut text, "files_checked", "Files checked.");
+        writeln!(text, "{}files_checked {}", PREFIX, self.files_checked).unwrap();
+        write_gauge(&mut text, "files_failed", "Files which failed the check.");
+        writeln!(text, "{}files_failed {}", PREFIX, self.files_failed).unwrap();
+        write_gauge(
+            &mut text,
+            "max_required_version",
+            "The newest version needed in each namespace, as major * 1000000 + minor * 1000 + patch.",
+        );
+        for max_version in &self.max_versions {
+            writeln!(
+                text,
+                "{}max_required_version{{namespace=\"{}\"}} {}",
+                PREFIX,
+                escape_label(&max_version.namespace),
+                version_value(&max_version.version)
+            )
+            .unwrap();
+        }
+        text.push_str("# EOF\n");
+        text
+    }
+
+    /// Writes the metrics to a file, replacing it at once so that a scraper never reads part of it.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, self.to_openmetrics())
+            .and_then(|_| fs::rename(&temp_path, path))
+            .with_context(|| format!("Error writing metrics {}", path.display()))
+    }
+}
+
+fn write_gauge(text: &mut String, name: &str, help: &str) {
+    writeln!(text, "# TYPE {}{} gauge", PREFIX, name).unwrap();
+    writeln!(text, "# HELP {}{} {}", PREFIX, name, help).unwrap();
+}
+
+/// A version as a number which orders as versions with up to three parts below 1000 do.
+fn version_value(version: &Version) -> u64 {
+    let parts = version.parts();
+    (0..3).fold(0, |value, index| {
+        value * 1000 + u64::from(parts.get(index).copied().unwrap_or(0))
+    })
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_openmetrics() {
+        let metrics = Metrics {
+            files_checked: 12,
+            files_failed: 3,
+            max_versions: vec![
+                NamespacedVersion::parse("GLIBC_2.34").unwrap(),
+                NamespacedVersion::parse("GLIBCXX_3.4.29").unwrap(),
+            ],
+        };
+        assert_eq!(
+            metrics.to_openmetrics(),
+            "# TYPE symbol_version_check_files_checked gauge\n\
+             # HELP symbol_version_check_files_checked Files checked.\n\
+             symbol_version_check_files_checked 12\n\
+             # TYPE symbol_version_check_files_failed gauge\n\
+             # HELP symbol_version_check_files_failed Files which failed the check.\n\
+             symbol_version_check_files_failed 3\n\
+             # TYPE symbol_version_check_max_required_version gauge\n\
+             # HELP symbol_version_check_max_required_version The newest version needed in each namespace, as major * 1000000 + minor * 1000 + patch.\n\
+             symbol_version_check_max_required_version{namespace=\"GLIBC\"} 2034000\n\
+             symbol_version_check_max_required_version{namespace=\"GLIBCXX\"} 3004029\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn writes_metrics_file() {
+        let dir = crate::test_util::TempDir::new("metrics");
+        let path = dir.path.join("symbol-version-check.prom");
+        Metrics::default().write(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("symbol_version_check_files_checked 0\n"));
+        assert!(text.ends_with("# EOF\n"));
+        assert!(!dir.path.join("symbol-version-check.prom.tmp").exists());
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, anyhow};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the store paths in the runtime closure of a Nix store path, as reported by `nix-store`.
+pub fn closure_paths(store_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let output = Command::new("nix-store")
+        .arg("--query")
+        .arg("--requisites")
+        .arg(store_path)
+        .output()
+        .with_context(|| "Error running nix-store")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "nix-store failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_store_paths(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_store_paths(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_store_paths() {
+        let output = "/nix/store/aaaa-glibc-2.39\n/nix/store/bbbb-hello-2.12\n\n";
+        assert_eq!(
+            parse_store_paths(output),
+            vec![
+                PathBuf::from("/nix/store/aaaa-glibc-2.39"),
+                PathBuf::from("/nix/store/bbbb-hello-2.12")
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_empty_output() {
+        assert!(parse_store_paths("").is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::archive::{self, Compression};
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Component, Path};
+
+const IMAGE_MANIFEST: &str = "manifest.json";
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+/// Entry in the manifest.json file written by `docker save`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageManifest {
+    repo_tags: Option<Vec<String>>,
+    layers: Vec<String>,
+}
+
+/// The ELF files present in a container image's filesystem, built up by applying the image's layers in order.
+#[derive(Default)]
+pub struct ImageFilesystem {
+    elf_files: BTreeMap<String, Vec<u8>>,
+}
+
+impl ImageFilesystem {
+    /// Applies a layer, given as an uncompressed tar stream. Whiteouts in the layer remove files from lower layers,
+    /// and files in the layer replace any files at the same path in lower layers.
+    pub fn apply_layer(&mut self, layer: impl Read) -> anyhow::Result<()> {
+        let mut archive = tar::Archive::new(layer);
+        let mut additions: Vec<(String, Option<Vec<u8>>)> = Vec::new();
+        for entry in archive.entries().with_context(|| "Error reading layer")? {
+            let mut entry = entry.with_context(|| "Error reading layer entry")?;
+            let path = normalize_path(
+                &entry
+                    .path()
+                    .with_context(|| "Error reading layer entry name")?,
+            );
+            let (dir, file_name) = match path.rsplit_once('/') {
+                Some((dir, file_name)) => (dir, file_name),
+                None => ("", path.as_str()),
+            };
+            if file_name == OPAQUE_WHITEOUT {
+                self.remove_children(dir);
+                continue;
+            }
+            if let Some(name) = file_name.strip_prefix(WHITEOUT_PREFIX) {
+                self.remove(&join_path(dir, name));
+                continue;
+            }
+            let entry_type = entry.header().entry_type();
+            let contents = if entry_type.is_file() {
+                archive::read_elf_entry(&mut entry)
+                    .with_context(|| format!("Error reading layer entry {}", path))?
+            } else if entry_type.is_hard_link() {
+                let target = entry
+                    .link_name()
+                    .with_context(|| format!("Error reading link target of {}", path))?
+                    .map(|target| normalize_path(&target));
+                target.and_then(|target| {
+                    additions
+                        .iter()
+                        .rev()
+                        .find(|(path, _)| *path == target)
+                        .map(|(_, contents)| contents.clone())
+                        .unwrap_or_else(|| self.elf_files.get(&target).cloned())
+                })
+            } else {
+                None
+            };
+            additions.push((path, contents));
+        }
+        for (path, contents) in additions {
+            match contents {
+                Some(contents) => {
+                    self.elf_files.insert(path, contents);
+                }
+                None => {
+                    self.elf_files.remove(&path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the file at `path` and, if it is a directory, everything beneath it.
+    fn remove(&mut self, path: &str) {
+        self.elf_files.remove(path);
+        self.remove_children(path);
+    }
+
+    fn remove_children(&mut self, dir: &str) {
+        if dir.is_empty() {
+            self.elf_files.clear();
+            return;
+        }
+        let prefix = format!("{}/", dir);
+        self.elf_files.retain(|path, _| !path.starts_with(&prefix));
+    }
+
+    pub fn elf_files(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.elf_files
+            .iter()
+            .map(|(path, contents)| (path.as_str(), contents.as_slice()))
+    }
+}
+
+/// Converts a path within a layer to a normalized relative form, e.g. `./usr/bin/../lib/x` becomes `usr/lib/x`.
+fn normalize_path(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    parts.join("/")
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Returns the byte ranges of the regular files in an uncompressed tar archive held in memory.
+fn tar_file_ranges(data: &[u8]) -> anyhow::Result<HashMap<String, Range<usize>>> {
+    let mut archive = tar::Archive::new(data);
+    let mut ranges = HashMap::new();
+    for entry in archive
+        .entries()
+        .with_context(|| "Error reading tar archive")?
+    {
+        let entry = entry.with_context(|| "Error reading tar archive entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = normalize_path(
+            &entry
+                .path()
+                .with_context(|| "Error reading tar archive entry name")?,
+        );
+        let start = entry.raw_file_position() as usize;
+        let end = start + entry.size() as usize;
+        ranges.insert(path, start..end);
+    }
+    Ok(ranges)
+}
+
+/// Checks whether an uncompressed tar archive was written by `docker save`.
+pub fn is_image_archive(data: &[u8]) -> bool {
+    let mut archive = tar::Archive::new(data);
+    let Ok(entries) = archive.entries() else {
+        return false;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.path().ok().map(|path| normalize_path(&path)))
+        .any(|path| path == IMAGE_MANIFEST)
+}
+
+/// Opens a layer blob, decompressing it if necessary.
+pub fn open_layer<'a>(blob: &'a [u8]) -> anyhow::Result<Box<dyn Read + 'a>> {
+    Compression::detect(blob).decompress(blob)
+}
+
+/// Calls `visit` for each ELF file in the filesystem of each image in a `docker save` archive. When the archive
+/// contains more than one image, entry names are prefixed with the image's tag.
+pub fn visit_image_elf_entries(
+    data: &[u8],
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    let files = tar_file_ranges(data)?;
+    let manifest_range = files
+        .get(IMAGE_MANIFEST)
+        .ok_or_else(|| anyhow!("No {} found in image archive", IMAGE_MANIFEST))?;
+    let manifests: Vec<ImageManifest> = serde_json::from_slice(&data[manifest_range.clone()])
+        .with_context(|| format!("Error parsing {}", IMAGE_MANIFEST))?;
+
+    for (image_index, manifest) in manifests.iter().enumerate() {
+        let mut filesystem = ImageFilesystem::default();
+        for layer in &manifest.layers {
+            let layer_range = files
+                .get(&normalize_path(Path::new(layer)))
+                .ok_or_else(|| anyhow!("Layer {} not found in image archive", layer))?;
+            filesystem
+                .apply_layer(open_layer(&data[layer_range.clone()])?)
+                .with_context(|| format!("Error applying layer {}", layer))?;
+        }
+        let image_prefix = if manifests.len() > 1 {
+            let image_name = manifest
+                .repo_tags
+                .as_ref()
+                .and_then(|tags| tags.first().cloned())
+                .unwrap_or_else(|| format!("image{}", image_index));
+            format!("{}:", image_name)
+        } else {
+            String::new()
+        };
+        for (path, contents) in filesystem.elf_files() {
+            visit(&format!("{}{}", image_prefix, path), contents);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::{ELF_STUB, build_tar, gzip};
+
+    fn elf_paths(filesystem: &ImageFilesystem) -> Vec<&str> {
+        filesystem.elf_files().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path(Path::new("./usr/bin/ls")), "usr/bin/ls");
+        assert_eq!(normalize_path(Path::new("/usr/bin/../lib/x")), "usr/lib/x");
+    }
+
+    #[test]
+    fn layers_only_contain_elf_files() {
+        let mut filesystem = ImageFilesystem::default();
+        filesystem
+            .apply_layer(&build_tar(&[("usr/bin/app", ELF_STUB), ("etc/hostname", b"x")])[..])
+            .unwrap();
+        assert_eq!(elf_paths(&filesystem), vec!["usr/bin/app"]);
+    }
+
+    #[test]
+    fn whiteout_removes_file_from_lower_layer() {
+        let mut filesystem = ImageFilesystem::default();
+        filesystem
+            .apply_layer(&build_tar(&[("usr/bin/a", ELF_STUB), ("usr/bin/b", ELF_STUB)])[..])
+            .unwrap();
+        filesystem
+            .apply_layer(&build_tar(&[("usr/bin/.wh.a", b"")])[..])
+            .unwrap();
+        assert_eq!(elf_paths(&filesystem), vec!["usr/bin/b"]);
+    }
+
+    #[test]
+    fn whiteout_removes_directory_from_lower_layer() {
+        let mut filesystem = ImageFilesystem::default();
+        filesystem
+            .apply_layer(&build_tar(&[("opt/app/bin/a", ELF_STUB), ("usr/bin/b", ELF_STUB)])[..])
+            .unwrap();
+        filesystem
+            .apply_layer(&build_tar(&[("opt/.wh.app", b"")])[..])
+            .unwrap();
+        assert_eq!(elf_paths(&filesystem), vec!["usr/bin/b"]);
+    }
+
+    #[test]
+    fn opaque_whiteout_hides_lower_layer_but_not_own_files() {
+        let mut filesystem = ImageFilesystem::default();
+        filesystem
+            .apply_layer(&build_tar(&[("opt/app/old", ELF_STUB)])[..])
+            .unwrap();
+        filesystem
+            .apply_layer(
+                &build_tar(&[("opt/app/.wh..wh..opq", b""), ("opt/app/new", ELF_STUB)])[..],
+            )
+            .unwrap();
+        assert_eq!(elf_paths(&filesystem), vec!["opt/app/new"]);
+    }
+
+    #[test]
+    fn non_elf_file_replaces_elf_in_lower_layer() {
+        let mut filesystem = ImageFilesystem::default();
+        filesystem
+            .apply_layer(&build_tar(&[("usr/bin/app", ELF_STUB)])[..])
+            .unwrap();
+        filesystem
+            .apply_layer(&build_tar(&[("usr/bin/app", b"#!/bin/sh\n")])[..])
+            .unwrap();
+        assert!(elf_paths(&filesystem).is_empty());
+    }
+
+    #[test]
+    fn visits_elf_files_in_image_archive() {
+        let layer1 = build_tar(&[("usr/bin/a", ELF_STUB), ("usr/bin/b", ELF_STUB)]);
+        let layer2 = gzip(&build_tar(&[
+            ("usr/bin/.wh.a", b""),
+            ("usr/bin/c", ELF_STUB),
+        ]));
+        let manifest = br#"[{"Config":"config.json","RepoTags":["app:latest"],"Layers":["l1/layer.tar","l2/layer.tar"]}]"#;
+        let image = build_tar(&[
+            ("manifest.json", manifest),
+            ("config.json", b"{}"),
+            ("l1/layer.tar", &layer1),
+            ("l2/layer.tar", &layer2),
+        ]);
+        assert!(is_image_archive(&image));
+
+        let mut paths = Vec::new();
+        visit_image_elf_entries(&image, &mut |path, _| paths.push(path.to_string())).unwrap();
+        assert_eq!(paths, vec!["usr/bin/b", "usr/bin/c"]);
+    }
+
+    #[test]
+    fn plain_tar_is_not_image_archive() {
+        assert!(!is_image_archive(&build_tar(&[("a.txt", b"hello")])));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::symbols::SymbolVersion;
+use crate::version::{NamespacedVersion, Version};
+use anyhow::{Context, anyhow};
+use object::read::pe::{ImageNtHeaders, Import, PeFile, PeFile32, PeFile64};
+use object::{FileKind, LittleEndian as LE};
+
+const API_SET_PREFIXES: [&str; 2] = ["api-ms-win-", "ext-ms-win-"];
+
+/// The functions a PE file imports from a DLL.
+#[derive(Debug, PartialEq)]
+pub struct DllImport {
+    pub dll: String,
+    pub functions: Vec<String>,
+}
+
+pub fn is_pe(data: &[u8]) -> bool {
+    data.starts_with(b"MZ")
+}
+
+/// Returns the DLLs imported by a PE file, including delay-loaded DLLs, along with the functions imported from each.
+/// Functions imported by ordinal are named after the ordinal, e.g. `#12`.
+pub fn get_imports(data: &[u8]) -> anyhow::Result<Vec<DllImport>> {
+    match FileKind::parse(data).with_context(|| "Error parsing file")? {
+        FileKind::Pe32 => get_pe_imports(&PeFile32::parse(data)?),
+        FileKind::Pe64 => get_pe_imports(&PeFile64::parse(data)?),
+        _ => Err(anyhow!("Unsupported file format")),
+    }
+}
+
+fn get_pe_imports<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> anyhow::Result<Vec<DllImport>> {
+    let mut imports = Vec::new();
+    if let Some(table) = pe
+        .import_table()
+        .with_context(|| "Error reading import table")?
+    {
+        let mut descriptors = table.descriptors()?;
+        while let Some(descriptor) = descriptors.next()? {
+            let dll = table.name(descriptor.name.get(LE))?;
+            let thunk_address = match descriptor.original_first_thunk.get(LE) {
+                0 => descriptor.first_thunk.get(LE),
+                address => address,
+            };
+            let mut thunks = table.thunks(thunk_address)?;
+            let mut functions = Vec::new();
+            while let Some(thunk) = thunks.next::<Pe>()? {
+                functions.push(import_name(table.import::<Pe>(thunk)?));
+            }
+            imports.push(DllImport {
+                dll: String::from_utf8_lossy(dll).to_string(),
+                functions,
+            });
+        }
+    }
+    if let Some(table) = pe
+        .data_directories()
+        .delay_load_import_table(pe.data(), &pe.section_table())
+        .with_context(|| "Error reading delay load import table")?
+    {
+        let mut descriptors = table.descriptors()?;
+        while let Some(descriptor) = descriptors.next()? {
+            let dll = table.name(descriptor.dll_name_rva.get(LE))?;
+            let mut thunks = table.thunks(descriptor.import_name_table_rva.get(LE))?;
+            let mut functions = Vec::new();
+            while let Some(thunk) = thunks.next::<Pe>()? {
+                functions.push(import_name(table.import::<Pe>(thunk)?));
+            }
+            imports.push(DllImport {
+                dll: String::from_utf8_lossy(dll).to_string(),
+                functions,
+            });
+        }
+    }
+    Ok(imports)
+}
+
+fn import_name(import: Import) -> String {
+    match import {
+        Import::Name(_, name) => String::from_utf8_lossy(name).to_string(),
+        Import::Ordinal(ordinal) => format!("#{}", ordinal),
+    }
+}
+
+/// Returns the functions a PE file imports from API sets, versioned by the API set, e.g.
+/// `SetEvent@api-ms-win-core-synch-l1_2.0` for `SetEvent` imported from `api-ms-win-core-synch-l1-2-0.dll`.
+pub fn get_api_set_imports(data: &[u8]) -> anyhow::Result<Vec<SymbolVersion>> {
+    Ok(get_imports(data)?
+        .into_iter()
+        .filter_map(|import| {
+            let version = parse_api_set(&import.dll)?;
+            Some(import.functions.into_iter().map(move |name| SymbolVersion {
+                name,
+                version: version.clone(),
+                file: Some(import.dll.clone()),
+                symbol_type: None,
+                binding: None,
+            }))
+        })
+        .flatten()
+        .collect())
+}
+
+/// Parses the name of an API set DLL, such as `api-ms-win-core-synch-l1-2-0.dll`, into the API set's name and
+/// version, e.g. `api-ms-win-core-synch-l1` and `2.0`.
+fn parse_api_set(dll: &str) -> Option<NamespacedVersion> {
+    let dll = dll.to_ascii_lowercase();
+    let name = dll.strip_suffix(".dll").unwrap_or(&dll);
+    if !API_SET_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        return None;
+    }
+    let (rest, minor) = name.rsplit_once('-')?;
+    let (namespace, major) = rest.rsplit_once('-')?;
+    Some(NamespacedVersion {
+        namespace: namespace.to_string(),
+        version: Version::parse(&format!("{}.{}", major, minor)).ok()?,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use object::pe;
+
+    const FILE_ALIGNMENT: usize = 0x200;
+    const SECTION_ADDRESS: u32 = 0x1000;
+
+    /// Builds a minimal 64-bit PE executable importing the given functions from the given DLLs.
+    pub fn build_pe(imports: &[(&str, &[&str])]) -> Vec<u8> {
+        // Import section: descriptors, then lookup tables, then hint/name entries and DLL names
+        let descriptors_size = (imports.len() + 1) * 20;
+        let lookup_tables_size: usize = imports.iter().map(|(_, f)| (f.len() + 1) * 8).sum();
+        let mut descriptors = Vec::new();
+        let mut lookup_tables = Vec::new();
+        let mut names = Vec::new();
+        let names_address = SECTION_ADDRESS as usize + descriptors_size + lookup_tables_size;
+        for (dll, functions) in imports {
+            let lookup_table_address =
+                SECTION_ADDRESS as usize + descriptors_size + lookup_tables.len();
+            for function in *functions {
+                lookup_tables.extend(((names_address + names.len()) as u64).to_le_bytes());
+                names.extend(0u16.to_le_bytes());
+                names.extend(function.as_bytes());
+                names.push(0);
+                names.resize(names.len().next_multiple_of(2), 0);
+            }
+            lookup_tables.extend(0u64.to_le_bytes());
+            let dll_name_address = names_address + names.len();
+            names.extend(dll.as_bytes());
+            names.push(0);
+            names.resize(names.len().next_multiple_of(2), 0);
+            for field in [
+                lookup_table_address,
+                0,
+                0,
+                dll_name_address,
+                lookup_table_address,
+            ] {
+                descriptors.extend((field as u32).to_le_bytes());
+            }
+        }
+        descriptors.resize(descriptors_size, 0);
+        let mut section = [descriptors, lookup_tables, names].concat();
+        section.resize(section.len().next_multiple_of(FILE_ALIGNMENT), 0);
+
+        let mut data = vec![0u8; 64];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&64u32.to_le_bytes());
+        data.extend(b"PE\0\0");
+        // File header
+        data.extend(pe::IMAGE_FILE_MACHINE_AMD64.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend([0u8; 12]);
+        data.extend(240u16.to_le_bytes());
+        data.extend(pe::IMAGE_FILE_EXECUTABLE_IMAGE.to_le_bytes());
+        // Optional header
+        let mut optional = vec![0u8; 240];
+        optional[0..2].copy_from_slice(&pe::IMAGE_NT_OPTIONAL_HDR64_MAGIC.to_le_bytes());
+        optional[24..32].copy_from_slice(&0x140000000u64.to_le_bytes());
+        optional[32..36].copy_from_slice(&SECTION_ADDRESS.to_le_bytes());
+        optional[36..40].copy_from_slice(&(FILE_ALIGNMENT as u32).to_le_bytes());
+        let image_size = SECTION_ADDRESS + (section.len() as u32).next_multiple_of(SECTION_ADDRESS);
+        optional[56..60].copy_from_slice(&image_size.to_le_bytes());
+        optional[60..64].copy_from_slice(&(FILE_ALIGNMENT as u32).to_le_bytes());
+        optional[68..70].copy_from_slice(&pe::IMAGE_SUBSYSTEM_WINDOWS_CUI.to_le_bytes());
+        optional[108..112].copy_from_slice(&16u32.to_le_bytes());
+        let import_directory = 112 + pe::IMAGE_DIRECTORY_ENTRY_IMPORT * 8;
+        optional[import_directory..import_directory + 4]
+            .copy_from_slice(&SECTION_ADDRESS.to_le_bytes());
+        optional[import_directory + 4..import_directory + 8]
+            .copy_from_slice(&(descriptors_size as u32).to_le_bytes());
+        data.extend(optional);
+        // Section header
+        data.extend(b".idata\0\0");
+        for field in [
+            section.len() as u32,
+            SECTION_ADDRESS,
+            section.len() as u32,
+            FILE_ALIGNMENT as u32,
+            0,
+            0,
+            0,
+            pe::IMAGE_SCN_CNT_INITIALIZED_DATA | pe::IMAGE_SCN_MEM_READ,
+        ] {
+            data.extend(field.to_le_bytes());
+        }
+        data.resize(FILE_ALIGNMENT, 0);
+        data.extend(section);
+        data
+    }
+
+    #[test]
+    fn reads_imports() {
+        let data = build_pe(&[
+            ("KERNEL32.dll", &["GetLastError", "SetEvent"]),
+            ("msvcrt.dll", &["malloc"]),
+        ]);
+        assert!(is_pe(&data));
+        assert_eq!(
+            get_imports(&data).unwrap(),
+            vec![
+                DllImport {
+                    dll: "KERNEL32.dll".to_string(),
+                    functions: vec!["GetLastError".to_string(), "SetEvent".to_string()],
+                },
+                DllImport {
+                    dll: "msvcrt.dll".to_string(),
+                    functions: vec!["malloc".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn versions_api_set_imports() {
+        let data = build_pe(&[
+            ("KERNEL32.dll", &["GetLastError"]),
+            ("api-ms-win-core-synch-l1-2-0.dll", &["WaitOnAddress"]),
+        ]);
+        let imports = get_api_set_imports(&data).unwrap();
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].name, "WaitOnAddress");
+        assert_eq!(
+            imports[0].version,
+            NamespacedVersion::parse("api-ms-win-core-synch-l1_2.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_api_set_names() {
+        assert_eq!(
+            parse_api_set("API-MS-WIN-CRT-RUNTIME-L1-1-0.DLL"),
+            Some(NamespacedVersion::parse("api-ms-win-crt-runtime-l1_1.0").unwrap())
+        );
+        assert_eq!(parse_api_set("kernel32.dll"), None);
+        assert_eq!(parse_api_set("api-ms-win-foo"), None);
+    }
+}
@@ -0,0 +1,1580 @@
+use crate::auditable::{self, Package};
+use crate::baseline::Baseline;
+use crate::blame::{self, CrateReferences, References};
+use crate::compatibility::{self, DistroRelease};
+use crate::elf::{
+    ElfHeader, NeededVersion, UnnumberedVersionReference, UnreadableVersion, VersionReferences,
+};
+use crate::exceptions::Exceptions;
+use crate::glibc_db::CompatVersion;
+use crate::hardening::{Hardening, HardeningFeature};
+use crate::hints::{self, Hint};
+use crate::requirements::VersionRequirements;
+use crate::soname::SonameRequirements;
+use crate::symbols::{SymbolBinding, SymbolType, SymbolVersion};
+use crate::sysroot::{SysrootLibraries, UnresolvedSymbol};
+use crate::version::{NamespacedVersion, Version, is_private_version};
+use crate::{FileOutcome, binary, denylist, elf, glibc_db, hardening, inventory, pe};
+use anyhow::{Context, anyhow};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::rc::Rc;
+
+/// A reason a file failed the check. It is serialized as an object with a `kind`, e.g. `symbol`, and the variant's
+/// fields, as in the JSON output.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Violation {
+    /// A symbol with a version newer than permitted
+    Symbol(SymbolVersion),
+    /// A library which the file may not depend on
+    DisallowedLibrary { library: String },
+    /// A library the file depends on which isn't found in the sysroot it is resolved against
+    MissingLibrary { library: String },
+    /// A symbol the file imports which no library it depends on exports, in the sysroot it is resolved against
+    UnresolvedSymbol(UnresolvedSymbol),
+    /// A program interpreter which doesn't match any of the expected interpreters
+    UnexpectedInterpreter { interpreter: String },
+    /// A Mach-O deployment target, the minimum OS version the file is built to run on, newer than permitted, with the
+    /// symbols the file imports weakly, so can run without
+    DeploymentTarget {
+        platform: String,
+        version: Version,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        weak_imports: Vec<String>,
+    },
+    /// A minimum Linux kernel version newer than permitted, as given by the file's ABI tag
+    KernelVersion {
+        version: Version,
+        max_kernel: Version,
+    },
+    /// A hardening feature which is required but missing
+    MissingHardening { feature: HardeningFeature },
+    /// Relocations against read-only segments (`DT_TEXTREL`)
+    TextRelocations,
+    /// No GNU build ID
+    MissingBuildId,
+    /// No symbol version table, as with files linked against musl
+    MissingVersionTable,
+    /// Linked against musl, which doesn't use symbol versioning
+    MuslLinked,
+    /// A symbol version, or the version table, which couldn't be read
+    UnreadableVersion(UnreadableVersion),
+    /// A version node defined by the file which isn't in the expected set
+    UnexpectedVersionNode { node: String },
+    /// A version node in the expected set which the file doesn't define
+    MissingVersionNode { node: String },
+    /// A library with a soname newer than the maximum permitted soname, e.g. `libssl.so.3` when the maximum is
+    /// `libssl.so.1.1`
+    NewerSoname { library: String, max_soname: String },
+    /// A symbol with a private version, such as `GLIBC_PRIVATE`
+    PrivateSymbol(UnnumberedVersionReference),
+    /// A symbol explicitly bound to a version newer than permitted, reported separately from other symbols
+    HiddenSymbol(SymbolVersion),
+    /// An unversioned reference, from an ELF file without a version table, to a glibc symbol which the glibc database
+    /// says new links bind to a version newer than permitted
+    UnversionedSymbol(SymbolVersion),
+    /// A library version newer than permitted, or private, found when only checking the versions needed from each
+    /// library
+    NeededVersion(NeededVersion),
+    /// A versioned namespace referenced by the file with no maximum version or allowed versions
+    UnlistedNamespace { namespace: String },
+    /// A minimum version which no version the file references is at least as new as, with the newest version it
+    /// references in the namespace, if any
+    MinimumVersion {
+        min_version: NamespacedVersion,
+        newest: Option<NamespacedVersion>,
+    },
+    /// A symbol which newer versions of glibc only provide for compatibility with existing binaries, or have removed
+    RemovedSymbol {
+        name: String,
+        glibc: String,
+        replacement: String,
+    },
+    /// A symbol which the file may not import, with the reason, if any
+    DeniedSymbol {
+        name: String,
+        reason: Option<String>,
+    },
+    /// A symbol with a version newer than permitted which is accepted by an exception, so is only a warning
+    ExceptedSymbol {
+        #[serde(flatten)]
+        symbol: SymbolVersion,
+        comment: Option<String>,
+        expires: Option<String>,
+    },
+}
+
+/// How version references flagged as hidden are handled. These are made when a symbol is explicitly bound to a
+/// version, as with `symbol@VERSION`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum HiddenVersions {
+    /// Check hidden references in the same way as other references
+    #[default]
+    Include,
+    /// Ignore hidden references
+    Exclude,
+    /// Check hidden references, reporting them separately from other references
+    Separate,
+}
+
+/// How ELF files without a symbol version table are handled. Files linked against musl, or with no versioned
+/// dependencies, have none.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MissingVersionTable {
+    /// Report an error checking the file
+    #[default]
+    Error,
+    /// Pass the file, as it references no versions newer than permitted
+    Pass,
+    /// Pass the file with a warning
+    Warn,
+    /// Fail the file
+    Fail,
+}
+
+/// How optional checks, such as for versioned namespaces with no maximum version, report what they find.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Severity {
+    /// Pass the file with a warning
+    Warn,
+    /// Fail the file
+    Fail,
+}
+
+/// How ELF files linked against musl are handled. musl doesn't use symbol versioning, so the versions these files need
+/// can't be checked.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MuslBinaries {
+    /// Pass the file, reporting that it is musl-linked
+    #[default]
+    Pass,
+    /// Fail the file
+    Fail,
+}
+
+/// Information reported about a binary alongside its result.
+#[derive(Debug, Default)]
+pub struct Details {
+    pub header: Option<ElfHeader>,
+    pub build_id: Option<String>,
+    pub interpreter: Option<String>,
+    /// The version nodes the file defines, if any
+    pub version_nodes: Vec<String>,
+    pub hardening: Option<Hardening>,
+    /// For statically linked executables, the estimated version of glibc linked
+    pub static_glibc: Option<NamespacedVersion>,
+    /// The versioned namespaces the file references, e.g. `GLIBC`
+    pub namespaces: BTreeSet<String>,
+    /// When reported, the newest version the file references in each namespace
+    pub max_versions: Vec<NamespacedVersion>,
+    /// When reported, the libraries the file depends on (`DT_NEEDED`)
+    pub needed_libraries: Vec<String>,
+    /// For files which failed, the newest version needed in each namespace with violations
+    pub gaps: Vec<NamespaceGap>,
+    /// When reported, the oldest release of each distribution the file can run on
+    pub distros: Vec<DistroRelease>,
+    /// When reported, the hints for the file's symbol violations
+    pub hints: Vec<&'static Hint>,
+    /// When hints are reported, the older versions of the symbols the file fails for which it could use instead
+    pub compat: Vec<CompatVersion>,
+    /// When reported, where in the file's source the symbols it fails for are referenced from
+    pub blame: References,
+    /// When reported, the symbols the file fails for which each Rust crate calls
+    pub crates: CrateReferences,
+    /// When reported, the packages a Rust binary was built from, as recorded by `cargo auditable`
+    pub packages: Vec<Package>,
+    /// Errors finding details, such as where symbols are referenced from, which don't change the file's result
+    pub warnings: Vec<String>,
+}
+
+/// The newest version a file needs in a namespace, compared with the maximum version permitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamespaceGap {
+    pub namespace: String,
+    pub needed: Version,
+    /// The maximum version permitted in the namespace, if it has one
+    pub limit: Option<Version>,
+}
+
+/// Everything a file is checked against.
+#[derive(Clone)]
+pub struct Policy {
+    pub requirements: VersionRequirements,
+    /// When set, the libraries which ELF files may depend on.
+    pub allowed_libs: Option<GlobSet>,
+    /// When set, the program interpreters which ELF executables may be loaded by.
+    pub expected_interpreters: Option<GlobSet>,
+    /// When set, the newest minimum Linux kernel version which ELF files may be built for.
+    pub max_kernel: Option<Version>,
+    /// The hardening features which ELF files must have.
+    pub required_hardening: Vec<HardeningFeature>,
+    /// Whether to fail ELF files with text relocations.
+    pub deny_textrel: bool,
+    /// Whether to fail ELF files without a GNU build ID.
+    pub require_build_id: bool,
+    /// Whether to report the hardening features of each ELF file.
+    pub report_hardening: bool,
+    /// Whether to report the build ID and program interpreter of each ELF file.
+    pub report_details: bool,
+    /// Whether to report the newest version each file references in each namespace, which is also reported with the
+    /// details.
+    pub report_max_versions: bool,
+    /// Whether to report the versioned namespaces each file references.
+    pub report_namespaces: bool,
+    /// Whether to estimate the version of glibc statically linked executables were linked against.
+    pub estimate_static_glibc: bool,
+    /// Whether to report the oldest release of each distribution dynamically linked ELF files can run on.
+    pub report_distros: bool,
+    /// Whether to report hints for avoiding the symbol versions files fail for.
+    pub report_hints: bool,
+    /// Whether to report where in ELF files' source the symbols they fail for are referenced from.
+    pub report_blame: bool,
+    /// Whether to report the packages Rust binaries built with `cargo auditable` were built from.
+    pub report_packages: bool,
+    /// The input files referencing each symbol, from link maps, which symbols are also blamed on.
+    pub link_map: Rc<References>,
+    /// The maximum versions of the libraries which ELF files may depend on.
+    pub max_sonames: SonameRequirements,
+    /// When set, the DLLs which PE files may import.
+    pub allowed_dlls: Option<GlobSet>,
+    /// When set, the sysroot whose libraries the libraries and symbols ELF files need must be found in.
+    pub sysroot_libraries: Option<Rc<SysrootLibraries>>,
+    /// Whether to fail ELF files which reference private versions, such as `GLIBC_PRIVATE`.
+    pub deny_private: bool,
+    /// How version references flagged as hidden are handled.
+    pub hidden_versions: HiddenVersions,
+    /// Whether to check only the versions needed from each library by ELF files, rather than each symbol.
+    pub coarse: bool,
+    /// How ELF files without a symbol version table are handled.
+    pub missing_version_table: MissingVersionTable,
+    /// Whether to check the undefined symbols of ELF files without a version table, such as object files, against the
+    /// versions of glibc new links bind them to.
+    pub glibc_database: bool,
+    /// When set, the version nodes which ELF files must define, no more and no less.
+    pub expected_version_nodes: Option<BTreeSet<String>>,
+    /// When non-empty, only symbol references of these types are checked.
+    pub symbol_types: Vec<SymbolType>,
+    /// When non-empty, only symbol references with these bindings are checked.
+    pub symbol_bindings: Vec<SymbolBinding>,
+    /// How ELF files linked against musl are handled.
+    pub musl: MuslBinaries,
+    /// When set, how versioned namespaces referenced by files with no maximum version or allowed versions are reported.
+    pub report_unlisted: Option<Severity>,
+    /// When set, how ELF files importing symbols which are compatibility-only or removed in newer glibc are reported.
+    pub report_removed: Option<Severity>,
+    /// The symbols which ELF files may not import, with the reason for each, if any.
+    pub denied_symbols: BTreeMap<String, Option<String>>,
+    /// The violations known when a baseline was written, which aren't reported.
+    pub baseline: Rc<Baseline>,
+    /// The accepted symbol violations, which are reported as warnings.
+    pub exceptions: Exceptions,
+    /// Whether to report version tables and symbol versions which can't be read as warnings, rather than failures, or
+    /// errors for version tables.
+    pub best_effort: bool,
+}
+
+impl Policy {
+    pub fn new(requirements: VersionRequirements) -> Self {
+        Policy {
+            requirements,
+            allowed_libs: None,
+            expected_interpreters: None,
+            max_kernel: None,
+            required_hardening: Vec::new(),
+            deny_textrel: false,
+            require_build_id: false,
+            report_hardening: false,
+            report_details: false,
+            report_max_versions: false,
+            report_namespaces: false,
+            estimate_static_glibc: false,
+            report_distros: false,
+            report_hints: false,
+            report_blame: false,
+            report_packages: false,
+            link_map: Rc::default(),
+            max_sonames: SonameRequirements::default(),
+            allowed_dlls: None,
+            sysroot_libraries: None,
+            deny_private: true,
+            hidden_versions: HiddenVersions::default(),
+            coarse: false,
+            missing_version_table: MissingVersionTable::default(),
+            glibc_database: false,
+            expected_version_nodes: None,
+            symbol_types: Vec::new(),
+            symbol_bindings: Vec::new(),
+            musl: MuslBinaries::default(),
+            report_unlisted: None,
+            report_removed: None,
+            denied_symbols: BTreeMap::new(),
+            baseline: Rc::default(),
+            exceptions: Exceptions::default(),
+            best_effort: false,
+        }
+    }
+
+    /// Checks a binary, returning the violations found.
+    pub fn check(&self, data: &[u8]) -> anyhow::Result<Vec<Violation>> {
+        self.check_at(data, None)
+    }
+
+    /// Checks a binary as `check` does, given the path of the file on disk, if it has one, which the libraries found
+    /// through `$ORIGIN` in its run path are relative to.
+    pub fn check_at(&self, data: &[u8], file: Option<&Path>) -> anyhow::Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        let missing_version_table = elf::is_elf(data)
+            && self.missing_version_table != MissingVersionTable::Error
+            && !elf::is_static(data)
+            && !elf::has_version_table(data)?;
+        if elf::is_elf(data) && !elf::is_static(data) && elf::is_musl(data)? {
+            if self.musl == MuslBinaries::Fail {
+                violations.push(Violation::MuslLinked);
+            }
+        } else if self.uses_glibc_database(data)? {
+            violations.extend(
+                self.requirements
+                    .check_symbols(&get_database_symbols(data)?)
+                    .into_iter()
+                    .map(Violation::UnversionedSymbol),
+            );
+        } else if missing_version_table {
+            if matches!(
+                self.missing_version_table,
+                MissingVersionTable::Warn | MissingVersionTable::Fail
+            ) {
+                violations.push(Violation::MissingVersionTable);
+            }
+        } else if elf::is_elf(data) && self.coarse {
+            match elf::get_needed_versions(data) {
+                Ok(needed_versions) => {
+                    violations.extend(self.check_needed_versions(needed_versions))
+                }
+                Err(e) if self.best_effort && elf::has_version_table(data)? => {
+                    violations.push(Violation::UnreadableVersion(UnreadableVersion {
+                        symbol: None,
+                        error: format!("{:#}", e),
+                    }))
+                }
+                Err(e) => return Err(e),
+            }
+        } else if elf::is_elf(data) {
+            let references = elf::get_version_references(data)?;
+            // Symbols whose versions can't be read are reported along with the rest, but if the version table can't
+            // be read at all, the file can't be checked
+            if !self.best_effort
+                && let Some(table) = references.unreadable.iter().find(|u| u.symbol.is_none())
+            {
+                return Err(anyhow!("{}", table));
+            }
+            violations.extend(self.check_version_references(references));
+        } else {
+            let required = binary::get_required_versions(data)?;
+            violations.extend(
+                self.requirements
+                    .check_symbols(&required.symbols)
+                    .into_iter()
+                    .map(Violation::Symbol),
+            );
+            violations.extend(
+                required
+                    .deployment_targets
+                    .iter()
+                    .filter(|target| !self.requirements.is_permitted(&target.version))
+                    .map(|target| Violation::DeploymentTarget {
+                        platform: target.version.namespace.clone(),
+                        version: target.version.version.clone(),
+                        weak_imports: target.weak_imports.clone(),
+                    }),
+            );
+            violations.extend(self.check_namespaces(required.versions()));
+        }
+        // Statically linked executables reference no versions, so can't be checked against minimum versions
+        if self.requirements.minimum_versions().next().is_some()
+            && !(elf::is_elf(data) && elf::is_static(data))
+        {
+            let required = binary::get_required_versions(data)?;
+            violations.extend(
+                self.requirements
+                    .check_minimum_versions(required.versions())
+                    .into_iter()
+                    .map(|(min_version, newest)| Violation::MinimumVersion {
+                        min_version,
+                        newest,
+                    }),
+            );
+        }
+        if !self.denied_symbols.is_empty() && elf::is_elf(data) {
+            violations.extend(
+                elf::get_imported_symbols(data)?
+                    .into_iter()
+                    .filter_map(|name| {
+                        let reason = self.denied_symbols.get(&name)?.clone();
+                        Some(Violation::DeniedSymbol { name, reason })
+                    }),
+            );
+        }
+        // Only symbols imported from glibc are checked, as libraries such as libtirpc provide replacements with the
+        // same names
+        if self.report_removed.is_some() && elf::is_elf(data) && !elf::is_static(data) {
+            let references = elf::get_version_references(data)?;
+            violations.extend(
+                references
+                    .symbols
+                    .iter()
+                    .chain(&references.hidden_symbols)
+                    .filter(|symbol| symbol.version.namespace == "GLIBC")
+                    .filter_map(|symbol| {
+                        let removed = denylist::find_removed_symbol(&symbol.name)?;
+                        Some(Violation::RemovedSymbol {
+                            name: symbol.name.clone(),
+                            glibc: removed.glibc.to_string(),
+                            replacement: removed.replacement.to_string(),
+                        })
+                    }),
+            );
+        }
+        if elf::is_elf(data) && (self.allowed_libs.is_some() || !self.max_sonames.is_empty()) {
+            violations.extend(self.check_needed_libraries(elf::get_needed_libraries(data)?));
+        }
+        if let Some(sysroot_libraries) = &self.sysroot_libraries
+            && elf::is_elf(data)
+            && !elf::is_static(data)
+        {
+            let resolution = sysroot_libraries.resolve(data, file)?;
+            violations.extend(
+                resolution
+                    .missing_libraries
+                    .into_iter()
+                    .map(|library| Violation::MissingLibrary { library }),
+            );
+            violations.extend(
+                resolution
+                    .unresolved_symbols
+                    .into_iter()
+                    .map(Violation::UnresolvedSymbol),
+            );
+        }
+        if let Some(expected_interpreters) = &self.expected_interpreters
+            && elf::is_elf(data)
+            && let Some(interpreter) = elf::get_interpreter(data)?
+            && !expected_interpreters.is_match(&interpreter)
+        {
+            violations.push(Violation::UnexpectedInterpreter { interpreter });
+        }
+        if let Some(max_kernel) = &self.max_kernel
+            && elf::is_elf(data)
+            && let Some(abi_tag) = elf::get_abi_tag(data)?
+            && abi_tag.os == "Linux"
+            && abi_tag.version > *max_kernel
+        {
+            violations.push(Violation::KernelVersion {
+                version: abi_tag.version,
+                max_kernel: max_kernel.clone(),
+            });
+        }
+        if (!self.required_hardening.is_empty() || self.deny_textrel) && elf::is_elf(data) {
+            let hardening = hardening::get_hardening(data)?;
+            violations.extend(
+                self.required_hardening
+                    .iter()
+                    .filter(|feature| !hardening.has(**feature))
+                    .map(|feature| Violation::MissingHardening { feature: *feature }),
+            );
+            if self.deny_textrel && hardening.text_relocations {
+                violations.push(Violation::TextRelocations);
+            }
+        }
+        if self.require_build_id && elf::is_elf(data) && elf::get_build_id(data)?.is_none() {
+            violations.push(Violation::MissingBuildId);
+        }
+        if let Some(expected) = &self.expected_version_nodes
+            && elf::is_elf(data)
+        {
+            violations.extend(check_version_nodes(
+                expected,
+                elf::get_version_definitions(data)?,
+            ));
+        }
+        if let Some(allowed_dlls) = &self.allowed_dlls
+            && pe::is_pe(data)
+        {
+            violations.extend(
+                pe::get_imports(data)?
+                    .into_iter()
+                    .filter(|import| !allowed_dlls.is_match(&import.dll))
+                    .map(|import| Violation::DisallowedLibrary {
+                        library: import.dll,
+                    }),
+            );
+        }
+        Ok(violations)
+    }
+
+    /// Summarizes version violations by namespace, giving the newest version needed in each namespace with violations
+    /// and the maximum version permitted.
+    pub fn namespace_gaps(&self, violations: &[Violation]) -> Vec<NamespaceGap> {
+        let mut needed: BTreeMap<String, Version> = BTreeMap::new();
+        for violation in violations {
+            let version = match violation {
+                Violation::Symbol(symbol)
+                | Violation::HiddenSymbol(symbol)
+                | Violation::UnversionedSymbol(symbol) => symbol.version.clone(),
+                Violation::NeededVersion(needed) => match NamespacedVersion::parse(&needed.version)
+                {
+                    Ok(version) => version,
+                    Err(_) => continue,
+                },
+                Violation::DeploymentTarget {
+                    platform, version, ..
+                } => NamespacedVersion {
+                    namespace: platform.clone(),
+                    version: version.clone(),
+                },
+                _ => continue,
+            };
+            match needed.get(&version.namespace) {
+                Some(newest) if *newest >= version.version => {}
+                _ => {
+                    needed.insert(version.namespace, version.version);
+                }
+            }
+        }
+        needed
+            .into_iter()
+            .map(|(namespace, needed)| NamespaceGap {
+                limit: self.requirements.max_version(&namespace),
+                namespace,
+                needed,
+            })
+            .collect()
+    }
+
+    /// Finds the hints for symbol violations, each hint given once, even if it applies to several symbols.
+    pub fn hints(&self, violations: &[Violation]) -> Vec<&'static Hint> {
+        let mut found: Vec<&'static Hint> = Vec::new();
+        for violation in violations {
+            if let Violation::Symbol(symbol)
+            | Violation::HiddenSymbol(symbol)
+            | Violation::UnversionedSymbol(symbol) = violation
+                && let Some(hint) = hints::find_hint(symbol)
+                && !found.iter().any(|found| found.hint == hint.hint)
+            {
+                found.push(hint);
+            }
+        }
+        found
+    }
+
+    /// Finds the older versions of the symbols of symbol violations which are permitted, from the glibc database. The
+    /// database only has the x86_64 versions of symbols, which other architectures don't export, so only x86_64 ELF
+    /// files are given them.
+    pub fn compat_versions(
+        &self,
+        data: &[u8],
+        violations: &[Violation],
+    ) -> anyhow::Result<Vec<CompatVersion>> {
+        if !elf::is_elf(data) || elf::get_header(data)?.machine != glibc_db::ARCHITECTURE {
+            return Ok(Vec::new());
+        }
+        Ok(violations
+            .iter()
+            .filter_map(|violation| match violation {
+                Violation::Symbol(symbol) => glibc_db::find_compat_version(symbol),
+                _ => None,
+            })
+            .filter(|compat| self.requirements.is_permitted(&compat.compat))
+            .collect())
+    }
+
+    /// Finds where in an ELF file's source the symbols of its symbol violations are referenced from, and which Rust
+    /// crates call them.
+    pub fn blame(
+        &self,
+        data: &[u8],
+        violations: &[Violation],
+    ) -> anyhow::Result<(References, CrateReferences)> {
+        let symbols: BTreeSet<&str> = violations
+            .iter()
+            .filter_map(|violation| match violation {
+                Violation::Symbol(symbol)
+                | Violation::HiddenSymbol(symbol)
+                | Violation::UnversionedSymbol(symbol) => Some(symbol.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        if symbols.is_empty() || !elf::is_elf(data) {
+            return Ok((References::new(), CrateReferences::new()));
+        }
+        let mut references = blame::find_references(data, &symbols)?;
+        for &symbol in &symbols {
+            if let Some(files) = self.link_map.get(symbol) {
+                references
+                    .entry(symbol.to_string())
+                    .or_default()
+                    .extend(files.iter().cloned());
+            }
+        }
+        Ok((references, blame::find_rust_crates(data, &symbols)?))
+    }
+
+    /// Checks whether files with the same contents at two paths get the same result, which they do unless the
+    /// exceptions or baseline entries for their paths differ.
+    pub fn checks_alike(&self, file: &Path, other: &Path) -> bool {
+        self.exceptions.apply_alike(file, other) && self.baseline.has_same_entries(file, other)
+    }
+
+    /// Checks whether a violation is only a warning, which doesn't fail the file.
+    pub fn is_warning(&self, violation: &Violation) -> bool {
+        match violation {
+            Violation::MissingVersionTable => {
+                self.missing_version_table == MissingVersionTable::Warn
+            }
+            Violation::UnreadableVersion(_) => self.best_effort,
+            Violation::ExceptedSymbol { .. } => true,
+            Violation::Symbol(symbol)
+            | Violation::HiddenSymbol(symbol)
+            | Violation::UnversionedSymbol(symbol) => {
+                self.requirements.is_warn_only(&symbol.version.namespace)
+            }
+            Violation::NeededVersion(needed) => NamespacedVersion::parse(&needed.version)
+                .is_ok_and(|version| self.requirements.is_warn_only(&version.namespace)),
+            Violation::DeploymentTarget { platform, .. } => {
+                self.requirements.is_warn_only(platform)
+            }
+            Violation::RemovedSymbol { .. } => self.report_removed == Some(Severity::Warn),
+            Violation::UnlistedNamespace { .. } => self.report_unlisted == Some(Severity::Warn),
+            _ => false,
+        }
+    }
+
+    /// Classifies the outcome of checking a file from its violations: warned about if they are all only warnings, and
+    /// reported as statically linked or linked against musl if it passed with no violations.
+    pub fn outcome(&self, data: &[u8], violations: &[Violation]) -> FileOutcome {
+        if !violations.is_empty() {
+            if violations
+                .iter()
+                .all(|violation| self.is_warning(violation))
+            {
+                FileOutcome::Warn
+            } else {
+                FileOutcome::Fail
+            }
+        } else if elf::is_static(data) {
+            FileOutcome::Static
+        } else if elf::is_musl(data).unwrap_or(false) {
+            FileOutcome::Musl
+        } else {
+            FileOutcome::Pass
+        }
+    }
+
+    /// Returns the information to report about a binary alongside its result.
+    pub fn details(&self, data: &[u8]) -> anyhow::Result<Details> {
+        let mut details = Details::default();
+        let report_max_versions = self.report_details || self.report_max_versions;
+        // The versions are read again, so only when they're reported
+        let versions: Option<Vec<NamespacedVersion>> =
+            if !(report_max_versions || self.report_namespaces || self.report_distros) {
+                None
+            } else if self.uses_glibc_database(data).unwrap_or(false) {
+                get_database_symbols(data)
+                    .ok()
+                    .map(|symbols| symbols.into_iter().map(|symbol| symbol.version).collect())
+            } else {
+                // Files whose versions can't be read have already been reported by the check
+                binary::get_required_versions(data)
+                    .ok()
+                    .map(|required| required.versions().cloned().collect())
+            };
+        let versions = versions.unwrap_or_default();
+        if report_max_versions {
+            details.max_versions = inventory::max_versions(&versions)
+                .into_values()
+                .cloned()
+                .collect();
+        }
+        if self.report_namespaces {
+            details.namespaces = versions
+                .iter()
+                .map(|version| version.namespace.clone())
+                .collect();
+        }
+        if !elf::is_elf(data) {
+            return Ok(details);
+        }
+        if self.report_details {
+            details.header = Some(elf::get_header(data)?);
+            details.build_id = elf::get_build_id(data)?;
+            details.interpreter = elf::get_interpreter(data)?;
+            details.version_nodes = elf::get_version_definitions(data)?;
+            details.needed_libraries = elf::get_needed_libraries(data)?;
+        }
+        if self.report_hardening {
+            details.hardening = Some(hardening::get_hardening(data)?);
+        }
+        if self.estimate_static_glibc && elf::is_static(data) {
+            details.static_glibc = elf::estimate_static_glibc(data);
+        }
+        if self.report_packages {
+            details.packages = auditable::read_packages(data)?.unwrap_or_default();
+        }
+        if self.report_distros && !elf::is_static(data) && !elf::is_musl(data)? {
+            details.distros =
+                compatibility::find_distro_releases(&versions.iter().collect::<Vec<_>>());
+        }
+        Ok(details)
+    }
+
+    /// Reports the versioned namespaces with no maximum version or allowed versions, if they are to be reported.
+    fn check_namespaces<'a>(
+        &self,
+        versions: impl Iterator<Item = &'a NamespacedVersion>,
+    ) -> Vec<Violation> {
+        if self.report_unlisted.is_none() {
+            return Vec::new();
+        }
+        versions
+            .filter(|version| !self.requirements.is_listed(&version.namespace))
+            .map(|version| version.namespace.clone())
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .map(|namespace| Violation::UnlistedNamespace { namespace })
+            .collect()
+    }
+
+    /// Checks whether a file is checked against the glibc database: a dynamically linked ELF file or object file
+    /// without a version table, when the glibc database is enabled.
+    fn uses_glibc_database(&self, data: &[u8]) -> anyhow::Result<bool> {
+        Ok(self.glibc_database
+            && elf::is_elf(data)
+            && !elf::is_static(data)
+            && !elf::has_version_table(data)?)
+    }
+
+    fn check_needed_versions(&self, needed_versions: Vec<NeededVersion>) -> Vec<Violation> {
+        let versions: Vec<NamespacedVersion> = needed_versions
+            .iter()
+            .filter_map(|needed| NamespacedVersion::parse(&needed.version).ok())
+            .collect();
+        let unlisted = self.check_namespaces(versions.iter());
+        needed_versions
+            .into_iter()
+            .filter(|needed| {
+                if is_private_version(&needed.version) {
+                    return self.deny_private;
+                }
+                NamespacedVersion::parse(&needed.version)
+                    .is_ok_and(|version| !self.requirements.is_permitted(&version))
+            })
+            .map(Violation::NeededVersion)
+            .chain(unlisted)
+            .collect()
+    }
+
+    fn check_needed_libraries(&self, libraries: Vec<String>) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for library in libraries {
+            if let Some(max_soname) = self.max_sonames.check_library(&library) {
+                violations.push(Violation::NewerSoname {
+                    library: library.clone(),
+                    max_soname,
+                });
+            }
+            if let Some(allowed_libs) = &self.allowed_libs
+                && !allowed_libs.is_match(&library)
+            {
+                violations.push(Violation::DisallowedLibrary { library });
+            }
+        }
+        violations
+    }
+
+    /// Checks whether a symbol reference is of a type and binding which are checked. Symbols from formats without
+    /// types or bindings are always checked.
+    fn includes_symbol(&self, symbol: &SymbolVersion) -> bool {
+        fn includes<T: PartialEq>(values: &[T], value: Option<T>) -> bool {
+            values.is_empty() || value.is_none_or(|value| values.contains(&value))
+        }
+        includes(&self.symbol_types, symbol.symbol_type)
+            && includes(&self.symbol_bindings, symbol.binding)
+    }
+
+    fn check_version_references(&self, references: VersionReferences) -> Vec<Violation> {
+        let check_symbols = |symbols: &[SymbolVersion]| {
+            self.requirements
+                .check_symbols(symbols)
+                .into_iter()
+                .filter(|symbol| self.includes_symbol(symbol))
+        };
+        let mut violations: Vec<Violation> = check_symbols(&references.symbols)
+            .map(Violation::Symbol)
+            .collect();
+        let hidden_symbols = match self.hidden_versions {
+            HiddenVersions::Exclude => &[][..],
+            _ => &references.hidden_symbols[..],
+        };
+        let unlisted = self.check_namespaces(
+            references
+                .symbols
+                .iter()
+                .chain(hidden_symbols)
+                .filter(|symbol| self.includes_symbol(symbol))
+                .map(|symbol| &symbol.version),
+        );
+        match self.hidden_versions {
+            HiddenVersions::Include => {
+                violations.extend(check_symbols(&references.hidden_symbols).map(Violation::Symbol))
+            }
+            HiddenVersions::Exclude => {}
+            HiddenVersions::Separate => violations
+                .extend(check_symbols(&references.hidden_symbols).map(Violation::HiddenSymbol)),
+        }
+        if self.deny_private {
+            violations.extend(
+                references
+                    .unnumbered
+                    .into_iter()
+                    .filter(|reference| reference.is_private())
+                    .map(Violation::PrivateSymbol),
+            );
+        }
+        violations.extend(
+            references
+                .unreadable
+                .into_iter()
+                .map(Violation::UnreadableVersion),
+        );
+        violations.extend(unlisted);
+        violations
+    }
+}
+
+/// Compares the version nodes a file defines with the expected set.
+fn check_version_nodes(expected: &BTreeSet<String>, defined: Vec<String>) -> Vec<Violation> {
+    let defined: BTreeSet<String> = defined.into_iter().collect();
+    let unexpected = defined
+        .difference(expected)
+        .map(|node| Violation::UnexpectedVersionNode { node: node.clone() });
+    let missing = expected
+        .difference(&defined)
+        .map(|node| Violation::MissingVersionNode { node: node.clone() });
+    unexpected.chain(missing).collect()
+}
+
+/// Returns the undefined symbols of an ELF file found in the glibc database, with the versions new links bind them to.
+/// Symbols which aren't in the database, such as those from other libraries, aren't returned. Files for architectures
+/// other than the database's are an error, as their versions differ.
+fn get_database_symbols(data: &[u8]) -> anyhow::Result<Vec<SymbolVersion>> {
+    let machine = elf::get_header(data)?.machine;
+    if machine != glibc_db::ARCHITECTURE {
+        anyhow::bail!(
+            "The glibc database is only available for {} files, not {}",
+            glibc_db::ARCHITECTURE,
+            machine
+        );
+    }
+    Ok(elf::get_undefined_symbols(data)?
+        .iter()
+        .filter_map(|name| glibc_db::find_symbol(name))
+        .map(|symbol| SymbolVersion {
+            name: symbol.name.to_string(),
+            version: symbol.version.clone(),
+            file: Some(symbol.library.to_string()),
+            symbol_type: None,
+            binding: None,
+        })
+        .collect())
+}
+
+/// Builds a set of globs matching library names or paths, such as `libssl.so.*`, `api-ms-win-crt-*` or
+/// `/lib*/ld-linux-*`. DLL names are matched case-insensitively.
+pub fn build_globs(patterns: &[String], case_insensitive: bool) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .with_context(|| format!("Invalid glob: {}", pattern))?;
+        builder.add(glob);
+    }
+    builder.build().with_context(|| "Error building globs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::tests::build_pe;
+
+    fn policy(requirements: &[&str], allowed_dlls: Option<&[&str]>) -> Policy {
+        let requirements: Vec<String> = requirements.iter().map(|r| r.to_string()).collect();
+        let allowed_dlls = allowed_dlls.map(|patterns| {
+            build_globs(
+                &patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+                true,
+            )
+            .unwrap()
+        });
+        Policy {
+            allowed_dlls,
+            ..Policy::new(VersionRequirements::parse(&requirements).unwrap())
+        }
+    }
+
+    fn references() -> VersionReferences {
+        VersionReferences {
+            symbols: vec![SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap()],
+            hidden_symbols: vec![SymbolVersion::parse("memcpy", "GLIBC_2.14", None).unwrap()],
+            unnumbered: vec![UnnumberedVersionReference {
+                name: "__libc_dlopen_mode".to_string(),
+                version: "GLIBC_PRIVATE".to_string(),
+                file: None,
+            }],
+            unreadable: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn best_effort_warns_about_unreadable_versions() {
+        let data =
+            elf::tests::corrupt_test_executable(".gnu.version_r", 4, &u32::MAX.to_le_bytes());
+        assert!(policy(&["GLIBC_99"], None).check(&data).is_err());
+        let policy = Policy {
+            best_effort: true,
+            ..policy(&["GLIBC_99"], None)
+        };
+        let violations = policy.check(&data).unwrap();
+        assert!(matches!(
+            &violations[..],
+            [Violation::UnreadableVersion(UnreadableVersion {
+                symbol: None,
+                ..
+            })]
+        ));
+        assert!(policy.is_warning(&violations[0]));
+    }
+
+    #[test]
+    fn reports_unreadable_symbol_versions_with_other_violations() {
+        let data = elf::tests::test_executable_with_unreadable_version();
+        let policy = policy(&["GLIBC_2.3"], None);
+        let violations = policy.check(&data).unwrap();
+        let unreadable: Vec<&Violation> = violations
+            .iter()
+            .filter(|v| matches!(v, Violation::UnreadableVersion(_)))
+            .collect();
+        assert!(matches!(
+            &unreadable[..],
+            [Violation::UnreadableVersion(UnreadableVersion { symbol: Some(symbol), .. })]
+                if symbol == "malloc"
+        ));
+        assert!(!policy.is_warning(unreadable[0]));
+        assert!(violations.iter().any(|v| matches!(v, Violation::Symbol(_))));
+    }
+
+    #[test]
+    fn filters_symbols_by_type_and_binding() {
+        let symbol = |name, symbol_type, binding| SymbolVersion {
+            symbol_type: Some(symbol_type),
+            binding: Some(binding),
+            ..SymbolVersion::parse(name, "GLIBC_2.33", None).unwrap()
+        };
+        let references = || VersionReferences {
+            symbols: vec![
+                symbol("stat", SymbolType::Func, SymbolBinding::Global),
+                symbol("errno", SymbolType::Tls, SymbolBinding::Global),
+                symbol("fstat", SymbolType::Func, SymbolBinding::Weak),
+            ],
+            ..VersionReferences::default()
+        };
+        let check = |symbol_types, symbol_bindings| {
+            let policy = Policy {
+                symbol_types,
+                symbol_bindings,
+                ..policy(&["GLIBC_2.17"], None)
+            };
+            policy
+                .check_version_references(references())
+                .into_iter()
+                .map(|violation| match violation {
+                    Violation::Symbol(symbol) => symbol.name,
+                    _ => panic!("Expected symbol violation"),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(check(vec![], vec![]), vec!["stat", "errno", "fstat"]);
+        assert_eq!(check(vec![SymbolType::Tls], vec![]), vec!["errno"]);
+        assert_eq!(check(vec![], vec![SymbolBinding::Weak]), vec!["fstat"]);
+        assert_eq!(
+            check(vec![SymbolType::Func], vec![SymbolBinding::Global]),
+            vec!["stat"]
+        );
+    }
+
+    #[test]
+    fn handles_hidden_versions() {
+        let check = |hidden_versions| {
+            Policy {
+                hidden_versions,
+                deny_private: false,
+                ..policy(&["GLIBC_2.2.5"], None)
+            }
+            .check_version_references(references())
+        };
+        let stat = Violation::Symbol(SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap());
+        let memcpy = SymbolVersion::parse("memcpy", "GLIBC_2.14", None).unwrap();
+        assert_eq!(
+            check(HiddenVersions::Include),
+            vec![stat.clone(), Violation::Symbol(memcpy.clone())]
+        );
+        assert_eq!(check(HiddenVersions::Exclude), vec![stat.clone()]);
+        assert_eq!(
+            check(HiddenVersions::Separate),
+            vec![stat, Violation::HiddenSymbol(memcpy)]
+        );
+    }
+
+    #[test]
+    fn fails_private_versions_unless_allowed() {
+        let violations = policy(&["GLIBC_2.40"], None).check_version_references(references());
+        assert!(
+            matches!(&violations[..], [Violation::PrivateSymbol(r)] if r.version == "GLIBC_PRIVATE")
+        );
+        let policy = Policy {
+            deny_private: false,
+            ..policy(&["GLIBC_2.40"], None)
+        };
+        assert!(policy.check_version_references(references()).is_empty());
+    }
+
+    #[test]
+    fn reports_unlisted_namespaces() {
+        let check = |requirements, report_unlisted| {
+            Policy {
+                report_unlisted,
+                deny_private: false,
+                ..policy(requirements, None)
+            }
+            .check_version_references(references())
+        };
+        assert!(check(&["GLIBCXX_3.4.19"], None).is_empty());
+        assert_eq!(
+            check(&["GLIBCXX_3.4.19"], Some(Severity::Fail)),
+            vec![Violation::UnlistedNamespace {
+                namespace: "GLIBC".to_string()
+            }]
+        );
+        assert!(check(&["GLIBC_2.40"], Some(Severity::Fail)).is_empty());
+
+        let policy = Policy {
+            report_unlisted: Some(Severity::Warn),
+            ..policy(&[], None)
+        };
+        assert!(policy.is_warning(&Violation::UnlistedNamespace {
+            namespace: "GLIBC".to_string()
+        }));
+    }
+
+    #[test]
+    fn checks_needed_versions() {
+        let needed = |version: &str| NeededVersion {
+            version: version.to_string(),
+            file: "libc.so.6".to_string(),
+        };
+        let needed_versions = vec![
+            needed("GLIBC_2.2.5"),
+            needed("GLIBC_2.34"),
+            needed("GLIBC_PRIVATE"),
+            needed("GLIBC_ABI_DT_RELR"),
+        ];
+        assert_eq!(
+            policy(&["GLIBC_2.17"], None).check_needed_versions(needed_versions),
+            vec![
+                Violation::NeededVersion(needed("GLIBC_2.34")),
+                Violation::NeededVersion(needed("GLIBC_PRIVATE")),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_disallowed_needed_library() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let allowed_libs = |patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            Some(build_globs(&patterns, false).unwrap())
+        };
+        let check = |patterns| {
+            Policy {
+                allowed_libs: allowed_libs(patterns),
+                ..policy(&[], None)
+            }
+            .check(&data)
+            .unwrap()
+        };
+        assert!(check(&["*"]).is_empty());
+        let violations = check(&["libm.so.6"]);
+        assert!(violations.contains(&Violation::DisallowedLibrary {
+            library: "libc.so.6".to_string()
+        }));
+        assert!(
+            check(&["LIBC.so.6"]).contains(&Violation::DisallowedLibrary {
+                library: "libc.so.6".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn fails_newer_sonames() {
+        let libraries = vec![
+            "libssl.so.3".to_string(),
+            "libcrypto.so.1.1".to_string(),
+            "libc.so.6".to_string(),
+        ];
+        let policy = Policy {
+            max_sonames: SonameRequirements::parse(&[
+                "libssl.so.1.1".to_string(),
+                "libcrypto.so.1.1".to_string(),
+            ])
+            .unwrap(),
+            ..policy(&[], None)
+        };
+        assert_eq!(
+            policy.check_needed_libraries(libraries),
+            vec![Violation::NewerSoname {
+                library: "libssl.so.3".to_string(),
+                max_soname: "libssl.so.1.1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn fails_unexpected_interpreter() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let check = |pattern: &str| {
+            Policy {
+                expected_interpreters: Some(build_globs(&[pattern.to_string()], false).unwrap()),
+                ..policy(&[], None)
+            }
+            .check(&data)
+            .unwrap()
+        };
+        assert!(check("/*/ld-*").is_empty());
+        let violations = check("/opt/toolchain/ld.so");
+        assert!(
+            matches!(&violations[..], [Violation::UnexpectedInterpreter { interpreter }] if interpreter.contains("ld"))
+        );
+    }
+
+    #[test]
+    fn fails_newer_kernel_version() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let check = |max_kernel: &str| {
+            Policy {
+                max_kernel: Some(Version::parse(max_kernel).unwrap()),
+                ..policy(&[], None)
+            }
+            .check(&data)
+            .unwrap()
+        };
+        assert!(check("99").is_empty());
+        assert!(matches!(
+            &check("2.4")[..],
+            [Violation::KernelVersion { .. }]
+        ));
+    }
+
+    #[test]
+    fn fails_denied_symbols() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let policy = Policy {
+            denied_symbols: BTreeMap::from([
+                ("malloc".to_string(), Some("use the arena".to_string())),
+                ("gets".to_string(), None),
+            ]),
+            ..policy(&["GLIBC_99"], None)
+        };
+        assert_eq!(
+            policy.check(&data).unwrap(),
+            vec![Violation::DeniedSymbol {
+                name: "malloc".to_string(),
+                reason: Some("use the arena".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn fails_below_minimum_version() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let check = |min_version: &str| {
+            Policy {
+                requirements: VersionRequirements::parse(&["GLIBC_99".to_string()])
+                    .unwrap()
+                    .with_minimum_versions([NamespacedVersion::parse(min_version).unwrap()]),
+                ..policy(&[], None)
+            }
+            .check(&data)
+            .unwrap()
+        };
+        assert!(check("GLIBC_2.2").is_empty());
+        assert!(matches!(
+            &check("GLIBC_99")[..],
+            [Violation::MinimumVersion {
+                newest: Some(_),
+                ..
+            }]
+        ));
+        assert!(matches!(
+            &check("NOSUCH_1.0")[..],
+            [Violation::MinimumVersion { newest: None, .. }]
+        ));
+    }
+
+    #[test]
+    fn fails_missing_hardening() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let hardening = hardening::get_hardening(&data).unwrap();
+        let policy = Policy {
+            required_hardening: vec![HardeningFeature::Pie, HardeningFeature::BindNow],
+            ..policy(&[], None)
+        };
+        let violations = policy.check(&data).unwrap();
+        assert_eq!(
+            violations.contains(&Violation::MissingHardening {
+                feature: HardeningFeature::BindNow
+            }),
+            !hardening.bind_now
+        );
+        assert!(!violations.contains(&Violation::MissingHardening {
+            feature: HardeningFeature::Pie
+        }));
+    }
+
+    #[test]
+    fn denies_text_relocations_only_when_present() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let policy = Policy {
+            deny_textrel: true,
+            ..policy(&[], None)
+        };
+        assert!(
+            !policy
+                .check(&data)
+                .unwrap()
+                .contains(&Violation::TextRelocations)
+        );
+    }
+
+    #[test]
+    fn reports_hardening() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert!(
+            policy(&[], None)
+                .details(&data)
+                .unwrap()
+                .hardening
+                .is_none()
+        );
+        let policy = Policy {
+            report_hardening: true,
+            ..policy(&[], None)
+        };
+        let details = policy.details(&data).unwrap();
+        assert!(details.hardening.is_some());
+        assert!(details.build_id.is_none());
+    }
+
+    #[test]
+    fn reports_build_id_and_interpreter() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let policy = Policy {
+            report_details: true,
+            ..policy(&[], None)
+        };
+        let details = policy.details(&data).unwrap();
+        assert_eq!(details.build_id, elf::get_build_id(&data).unwrap());
+        assert!(details.interpreter.is_some());
+        assert!(details.hardening.is_none());
+        assert!(
+            details
+                .needed_libraries
+                .iter()
+                .any(|lib| lib.starts_with("libc.so"))
+        );
+        assert!(
+            details
+                .max_versions
+                .iter()
+                .any(|version| version.namespace == "GLIBC")
+        );
+    }
+
+    #[test]
+    fn requires_build_id() {
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let policy = Policy {
+            require_build_id: true,
+            ..policy(&[], None)
+        };
+        assert!(
+            !policy
+                .check(&data)
+                .unwrap()
+                .contains(&Violation::MissingBuildId)
+        );
+
+        // Change the type of the build ID note so that it is no longer found
+        let mut data = data;
+        let note_header = [
+            &4u32.to_ne_bytes()[..],
+            &20u32.to_ne_bytes(),
+            &3u32.to_ne_bytes(),
+            b"GNU\0",
+        ]
+        .concat();
+        let offset = data
+            .windows(note_header.len())
+            .position(|window| window == note_header)
+            .unwrap();
+        data[offset + 8] = 0xff;
+        assert!(
+            policy
+                .check(&data)
+                .unwrap()
+                .contains(&Violation::MissingBuildId)
+        );
+    }
+
+    #[test]
+    fn checks_objects_against_glibc_database() {
+        let data =
+            elf::tests::build_object(&[("getrandom", false), ("glob", false), ("strlen", false)]);
+        let policy = Policy {
+            glibc_database: true,
+            ..policy(&["GLIBC_2.17"], None)
+        };
+        let violations = policy.check(&data).unwrap();
+        let libc = Some("libc.so.6".to_string());
+        // glob is checked against the version a new link binds it to, not the one which introduced it
+        assert_eq!(
+            violations,
+            vec![
+                Violation::UnversionedSymbol(
+                    SymbolVersion::parse("getrandom", "GLIBC_2.25", libc.clone()).unwrap()
+                ),
+                Violation::UnversionedSymbol(
+                    SymbolVersion::parse("glob", "GLIBC_2.27", libc).unwrap()
+                ),
+            ]
+        );
+        let reporting_namespaces = Policy {
+            report_namespaces: true,
+            ..policy.clone()
+        };
+        assert!(
+            reporting_namespaces
+                .details(&data)
+                .unwrap()
+                .namespaces
+                .contains("GLIBC")
+        );
+        assert!(policy.details(&data).unwrap().namespaces.is_empty());
+        // Without the database, objects have no version table to check
+        assert!(
+            Policy {
+                glibc_database: false,
+                ..policy
+            }
+            .check(&data)
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn glibc_database_is_unavailable_for_other_architectures() {
+        let mut data = elf::tests::build_object(&[("getrandom", false)]);
+        data[18..20].copy_from_slice(&object::elf::EM_AARCH64.to_le_bytes());
+        let policy = Policy {
+            glibc_database: true,
+            report_namespaces: true,
+            ..policy(&["GLIBC_2.17"], None)
+        };
+        let error = policy.check(&data).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The glibc database is only available for x86_64 files, not aarch64"
+        );
+        assert!(policy.details(&data).unwrap().namespaces.is_empty());
+    }
+
+    #[test]
+    fn finds_hints_once() {
+        let symbol =
+            |name, version| Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap());
+        let violations = [
+            symbol("stat", "GLIBC_2.33"),
+            symbol("fstat", "GLIBC_2.33"),
+            symbol("getrandom", "GLIBC_2.25"),
+            symbol("statfs", "GLIBC_2.2.5"),
+        ];
+        let hints: Vec<&str> = policy(&["GLIBC_2.17"], None)
+            .hints(&violations)
+            .iter()
+            .map(|hint| hint.key)
+            .collect();
+        assert_eq!(hints, vec!["stat@GLIBC_2.33", "getrandom"]);
+    }
+
+    #[test]
+    fn finds_permitted_compat_versions() {
+        let symbol =
+            |name, version| Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap());
+        let violations = [
+            symbol("pow", "GLIBC_2.29"),
+            symbol("getrandom", "GLIBC_2.25"),
+        ];
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let compat: Vec<String> = policy(&["GLIBC_2.5"], None)
+            .compat_versions(&data, &violations)
+            .unwrap()
+            .iter()
+            .map(|compat| compat.compat.to_string())
+            .collect();
+        assert_eq!(compat, vec!["GLIBC_2.2.5"]);
+        assert!(
+            policy(&["GLIBC_2.2"], None)
+                .compat_versions(&data, &violations)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn handles_missing_version_table() {
+        let data = elf::tests::build_elf(object::elf::ET_DYN, b"");
+        let check = |missing_version_table| {
+            let policy = Policy {
+                missing_version_table,
+                ..policy(&["GLIBC_2.2.5"], None)
+            };
+            policy
+                .check(&data)
+                .map(|violations| (violations.iter().all(|v| policy.is_warning(v)), violations))
+        };
+        assert!(check(MissingVersionTable::Error).is_err());
+        assert!(matches!(check(MissingVersionTable::Pass), Ok((_, v)) if v.is_empty()));
+        assert!(matches!(
+            check(MissingVersionTable::Warn),
+            Ok((true, v)) if v == vec![Violation::MissingVersionTable]
+        ));
+        assert!(matches!(
+            check(MissingVersionTable::Fail),
+            Ok((false, v)) if v == vec![Violation::MissingVersionTable]
+        ));
+    }
+
+    #[test]
+    fn checks_version_nodes() {
+        let data = elf::tests::build_shared_library(&["LIBFOO_1.0", "LIBFOO_1.2"], &[]);
+        let check = |expected: &[&str]| {
+            Policy {
+                expected_version_nodes: Some(
+                    expected.iter().map(|node| node.to_string()).collect(),
+                ),
+                ..policy(&[], None)
+            }
+            .check(&data)
+            .unwrap()
+        };
+        assert!(check(&["LIBFOO_1.0", "LIBFOO_1.2"]).is_empty());
+        assert_eq!(
+            check(&["LIBFOO_1.0", "LIBFOO_1.1"]),
+            vec![
+                Violation::UnexpectedVersionNode {
+                    node: "LIBFOO_1.2".to_string()
+                },
+                Violation::MissingVersionNode {
+                    node: "LIBFOO_1.1".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_newer_api_set() {
+        let data = build_pe(&[("api-ms-win-core-synch-l1-2-0.dll", &["WaitOnAddress"])]);
+        let violations = policy(&["api-ms-win-core-synch-l1_1.0"], None)
+            .check(&data)
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(&violations[0], Violation::Symbol(s) if s.name == "WaitOnAddress"));
+        assert!(
+            policy(&["api-ms-win-core-synch-l1_2.0"], None)
+                .check(&data)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn fails_disallowed_dll() {
+        let data = build_pe(&[
+            ("KERNEL32.dll", &["GetLastError"]),
+            ("msvcrt.dll", &["malloc"]),
+            ("api-ms-win-crt-heap-l1-1-0.dll", &["malloc"]),
+        ]);
+        let violations = policy(&[], Some(&["kernel32.dll", "api-ms-win-crt-*"]))
+            .check(&data)
+            .unwrap();
+        assert_eq!(
+            violations,
+            vec![Violation::DisallowedLibrary {
+                library: "msvcrt.dll".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn warns_for_warn_only_namespaces() {
+        let policy = policy(&["GLIBC_2.17", "GLIBCXX_3.4.21:warn"], None);
+        let symbol =
+            |version: &str| Violation::Symbol(SymbolVersion::parse("f", version, None).unwrap());
+        assert!(policy.is_warning(&symbol("GLIBCXX_3.4.23")));
+        assert!(!policy.is_warning(&symbol("GLIBC_2.34")));
+    }
+
+    #[test]
+    fn summarizes_gaps_by_namespace() {
+        let policy = policy(&["GLIBC_2.17"], None);
+        let symbol = |name: &str, version: &str| {
+            Violation::Symbol(SymbolVersion::parse(name, version, None).unwrap())
+        };
+        let gaps = policy.namespace_gaps(&[
+            symbol("getrandom", "GLIBC_2.25"),
+            symbol("stat", "GLIBC_2.33"),
+            symbol("EVP_MD_get_size", "OPENSSL_3.0.0"),
+            Violation::MissingBuildId,
+        ]);
+        assert_eq!(
+            gaps,
+            vec![
+                NamespaceGap {
+                    namespace: "GLIBC".to_string(),
+                    needed: Version::parse("2.33").unwrap(),
+                    limit: Some(Version::parse("2.17").unwrap()),
+                },
+                NamespaceGap {
+                    namespace: "OPENSSL".to_string(),
+                    needed: Version::parse("3.0.0").unwrap(),
+                    limit: None,
+                },
+            ]
+        );
+    }
+}
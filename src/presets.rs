@@ -0,0 +1,503 @@
+use crate::requirements::parse_requirement;
+use anyhow::{Context, anyhow};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// A named set of maximum versions for a target platform.
+#[derive(Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub requirements: &'static [&'static str],
+}
+
+/// The built-in presets. FreeBSD's libc adds a new `FBSD_1.x` version node in each major release. illumos kept the
+/// `SUNW_1.x` versions inherited from OpenSolaris, adding new interfaces in `ILLUMOS_0.x` versions.
+///
+/// The manylinux presets give the newest versions of glibc, libstdc++ and libgcc_s permitted by PyPA's policies for
+/// x86_64. musllinux wheels are linked against musl, which has no symbol versions, so their presets permit no glibc
+/// versions at all.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "freebsd-11",
+        description: "FreeBSD 11",
+        requirements: &["FBSD_1.4"],
+    },
+    Preset {
+        name: "freebsd-12",
+        description: "FreeBSD 12",
+        requirements: &["FBSD_1.5"],
+    },
+    Preset {
+        name: "freebsd-13",
+        description: "FreeBSD 13",
+        requirements: &["FBSD_1.6"],
+    },
+    Preset {
+        name: "freebsd-14",
+        description: "FreeBSD 14",
+        requirements: &["FBSD_1.7"],
+    },
+    Preset {
+        name: "freebsd-15",
+        description: "FreeBSD 15",
+        requirements: &["FBSD_1.8"],
+    },
+    Preset {
+        name: "opensolaris",
+        description: "Interfaces inherited from OpenSolaris, without illumos additions",
+        requirements: &["SUNW_1.23", "ILLUMOS_0"],
+    },
+    Preset {
+        name: "manylinux2014",
+        description: "PEP 599 Python wheels, for CentOS 7 and later",
+        requirements: MANYLINUX_2_17,
+    },
+    Preset {
+        name: "manylinux_2_17",
+        description: "PEP 600 Python wheels for glibc 2.17 and later, the same as manylinux2014",
+        requirements: MANYLINUX_2_17,
+    },
+    Preset {
+        name: "manylinux_2_28",
+        description: "PEP 600 Python wheels for glibc 2.28 and later, such as AlmaLinux 8",
+        requirements: &[
+            "GLIBC_2.28",
+            "GLIBCXX_3.4.25",
+            "CXXABI_1.3.11",
+            "CXXABI_TM_1",
+            "GCC_7.0.0",
+        ],
+    },
+    Preset {
+        name: "musllinux_1_1",
+        description: "PEP 656 Python wheels for musl 1.1 and later, which must not use glibc",
+        requirements: &["GLIBC_0"],
+    },
+    Preset {
+        name: "musllinux_1_2",
+        description: "PEP 656 Python wheels for musl 1.2 and later, which must not use glibc",
+        requirements: &["GLIBC_0"],
+    },
+];
+
+const MANYLINUX_2_17: &[&str] = &[
+    "GLIBC_2.17",
+    "GLIBCXX_3.4.19",
+    "CXXABI_1.3.7",
+    "CXXABI_TM_1",
+    "GCC_4.8.0",
+];
+
+/// Distributions which can be targeted, with the newest versions of glibc, libstdc++ and libgcc_s each ships for
+/// x86_64. Where a distribution's libstdc++ is newer than its system compiler, as on Ubuntu, the libstdc++ shipped is
+/// given.
+pub const TARGETS: &[Preset] = &[
+    Preset {
+        name: "rhel7",
+        description: "Red Hat Enterprise Linux 7 and CentOS 7, glibc 2.17 and GCC 4.8",
+        requirements: &["GLIBC_2.17", "GLIBCXX_3.4.19", "CXXABI_1.3.7", "GCC_4.8.0"],
+    },
+    Preset {
+        name: "rhel8",
+        description: "Red Hat Enterprise Linux 8 and its rebuilds, glibc 2.28 and GCC 8",
+        requirements: &["GLIBC_2.28", "GLIBCXX_3.4.25", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "rhel9",
+        description: "Red Hat Enterprise Linux 9 and its rebuilds, glibc 2.34 and GCC 11",
+        requirements: &["GLIBC_2.34", "GLIBCXX_3.4.29", "CXXABI_1.3.13", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "ubuntu-18.04",
+        description: "Ubuntu 18.04, glibc 2.27 and libstdc++ from GCC 8",
+        requirements: &["GLIBC_2.27", "GLIBCXX_3.4.25", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "ubuntu-20.04",
+        description: "Ubuntu 20.04, glibc 2.31 and libstdc++ from GCC 10",
+        requirements: &["GLIBC_2.31", "GLIBCXX_3.4.28", "CXXABI_1.3.12", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "ubuntu-22.04",
+        description: "Ubuntu 22.04, glibc 2.35 and libstdc++ from GCC 12",
+        requirements: &[
+            "GLIBC_2.35",
+            "GLIBCXX_3.4.30",
+            "CXXABI_1.3.13",
+            "GCC_12.0.0",
+        ],
+    },
+    Preset {
+        name: "ubuntu-24.04",
+        description: "Ubuntu 24.04, glibc 2.39 and libstdc++ from GCC 14",
+        requirements: &[
+            "GLIBC_2.39",
+            "GLIBCXX_3.4.33",
+            "CXXABI_1.3.15",
+            "GCC_14.0.0",
+        ],
+    },
+    Preset {
+        name: "debian-10",
+        description: "Debian 10, glibc 2.28 and GCC 8",
+        requirements: &["GLIBC_2.28", "GLIBCXX_3.4.25", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "debian-11",
+        description: "Debian 11, glibc 2.31 and GCC 10",
+        requirements: &["GLIBC_2.31", "GLIBCXX_3.4.28", "CXXABI_1.3.12", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "debian-12",
+        description: "Debian 12, glibc 2.36 and GCC 12",
+        requirements: &[
+            "GLIBC_2.36",
+            "GLIBCXX_3.4.30",
+            "CXXABI_1.3.13",
+            "GCC_12.0.0",
+        ],
+    },
+    Preset {
+        name: "amazonlinux2",
+        description: "Amazon Linux 2, glibc 2.26 and GCC 7",
+        requirements: &["GLIBC_2.26", "GLIBCXX_3.4.24", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "amazonlinux2023",
+        description: "Amazon Linux 2023, glibc 2.34 and GCC 11",
+        requirements: &["GLIBC_2.34", "GLIBCXX_3.4.29", "CXXABI_1.3.13", "GCC_7.0.0"],
+    },
+];
+
+/// The runtime libraries of each GCC release, with the newest versions of libstdc++ and libgcc_s shipped by its latest
+/// point release.
+pub const GCC_RUNTIMES: &[Preset] = &[
+    Preset {
+        name: "4.8",
+        description: "GCC 4.8",
+        requirements: &["GLIBCXX_3.4.19", "CXXABI_1.3.7", "GCC_4.8.0"],
+    },
+    Preset {
+        name: "4.9",
+        description: "GCC 4.9",
+        requirements: &["GLIBCXX_3.4.20", "CXXABI_1.3.8", "GCC_4.8.0"],
+    },
+    Preset {
+        name: "5",
+        description: "GCC 5",
+        requirements: &["GLIBCXX_3.4.21", "CXXABI_1.3.9", "GCC_4.8.0"],
+    },
+    Preset {
+        name: "6",
+        description: "GCC 6",
+        requirements: &["GLIBCXX_3.4.22", "CXXABI_1.3.10", "GCC_4.8.0"],
+    },
+    Preset {
+        name: "7",
+        description: "GCC 7",
+        requirements: &["GLIBCXX_3.4.24", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "8",
+        description: "GCC 8",
+        requirements: &["GLIBCXX_3.4.25", "CXXABI_1.3.11", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "9",
+        description: "GCC 9",
+        requirements: &["GLIBCXX_3.4.28", "CXXABI_1.3.12", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "10",
+        description: "GCC 10",
+        requirements: &["GLIBCXX_3.4.28", "CXXABI_1.3.12", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "11",
+        description: "GCC 11",
+        requirements: &["GLIBCXX_3.4.29", "CXXABI_1.3.13", "GCC_7.0.0"],
+    },
+    Preset {
+        name: "12",
+        description: "GCC 12",
+        requirements: &["GLIBCXX_3.4.30", "CXXABI_1.3.13", "GCC_12.0.0"],
+    },
+    Preset {
+        name: "13",
+        description: "GCC 13",
+        requirements: &["GLIBCXX_3.4.32", "CXXABI_1.3.14", "GCC_13.0.0"],
+    },
+    Preset {
+        name: "14",
+        description: "GCC 14",
+        requirements: &["GLIBCXX_3.4.33", "CXXABI_1.3.15", "GCC_14.0.0"],
+    },
+];
+
+pub fn find_preset(name: &str) -> anyhow::Result<&'static Preset> {
+    find(PRESETS, "preset", name)
+}
+
+pub fn find_target(name: &str) -> anyhow::Result<&'static Preset> {
+    find(TARGETS, "target", name)
+}
+
+pub fn find_gcc_runtime(release: &str) -> anyhow::Result<&'static Preset> {
+    find(GCC_RUNTIMES, "GCC release", release)
+}
+
+fn find(table: &'static [Preset], kind: &str, name: &str) -> anyhow::Result<&'static Preset> {
+    table
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = table.iter().map(|preset| preset.name).collect();
+            anyhow!(
+                "Unknown {} {}, expected one of: {}",
+                kind,
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// A preset defined in a presets file.
+#[derive(Clone, Debug)]
+pub struct UserPreset {
+    pub requirements: Vec<String>,
+    /// The presets file the preset is defined in
+    pub file: PathBuf,
+}
+
+/// The presets defined in presets files, which can be selected alongside the built-in presets.
+#[derive(Clone, Debug, Default)]
+pub struct UserPresets {
+    pub presets: BTreeMap<String, UserPreset>,
+}
+
+impl UserPresets {
+    /// Reads presets files, each mapping preset names to the maximum version of each namespace, e.g.
+    /// `{"myorg-baseline": {"GLIBC": "2.17", "GLIBCXX": "3.4.19"}}`. A preset can't be defined more than once, or with
+    /// the same name as a built-in preset.
+    pub fn read(files: &[PathBuf]) -> anyhow::Result<UserPresets> {
+        let mut presets = BTreeMap::new();
+        for file in files {
+            let data = fs::read(file)
+                .with_context(|| format!("Error reading presets file {}", file.display()))?;
+            let definitions: BTreeMap<String, BTreeMap<String, String>> =
+                serde_json::from_slice(&data)
+                    .with_context(|| format!("Error parsing presets file {}", file.display()))?;
+            for (name, versions) in definitions {
+                if find_preset(&name).is_ok() || presets.contains_key(&name) {
+                    return Err(anyhow!(
+                        "Preset {} in {} is already defined",
+                        name,
+                        file.display()
+                    ));
+                }
+                let requirements = versions
+                    .iter()
+                    .map(|(namespace, version)| {
+                        let requirement = format!("{}_{}", namespace, version);
+                        parse_requirement(&requirement).with_context(|| {
+                            format!("Invalid version for preset {} in {}", name, file.display())
+                        })?;
+                        Ok(requirement)
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                presets.insert(
+                    name,
+                    UserPreset {
+                        requirements,
+                        file: file.clone(),
+                    },
+                );
+            }
+        }
+        Ok(UserPresets { presets })
+    }
+
+    /// Looks up a profile to check files against, which may be a target or a preset, built-in or defined in presets
+    /// files, returning its requirements.
+    pub fn find_profile(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let (presets, targets) = if find_target(name).is_ok() {
+            (vec![], vec![name.to_string()])
+        } else {
+            (vec![name.to_string()], vec![])
+        };
+        let mut requirements = self
+            .find_requirements(&presets, &targets)
+            .with_context(|| format!("Unknown profile {}, expected a preset or target", name))?;
+        Ok(requirements.remove(0))
+    }
+
+    /// Looks up the given presets, which may be built-in or defined in presets files, and targets, returning the
+    /// requirements of each.
+    pub fn find_requirements(
+        &self,
+        presets: &[String],
+        targets: &[String],
+    ) -> anyhow::Result<Vec<Vec<String>>> {
+        let to_strings =
+            |preset: &Preset| preset.requirements.iter().map(|r| r.to_string()).collect();
+        let presets = presets.iter().map(|name| {
+            if let Some(preset) = self.presets.get(name) {
+                return Ok(preset.requirements.clone());
+            }
+            find_preset(name).map(to_strings).map_err(|e| {
+                if self.presets.is_empty() {
+                    return e;
+                }
+                let names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+                anyhow!("{}, or from presets files: {}", e, names.join(", "))
+            })
+        });
+        let targets = targets.iter().map(|name| find_target(name).map(to_strings));
+        presets.chain(targets).collect()
+    }
+}
+
+/// Combines the requirements of the given presets with explicitly given requirements, which take precedence over
+/// preset requirements for the same namespace.
+pub fn combine_requirements(
+    presets: &[Vec<String>],
+    explicit: &[String],
+) -> anyhow::Result<Vec<String>> {
+    let explicit_namespaces: HashSet<String> = explicit
+        .iter()
+        .filter_map(|requirement| parse_requirement(requirement).ok())
+        .map(|(version, _)| version.namespace)
+        .collect();
+    let mut requirements = Vec::new();
+    for preset in presets {
+        for requirement in preset {
+            let namespace = parse_requirement(requirement)?.0.namespace;
+            if !explicit_namespaces.contains(&namespace) {
+                requirements.push(requirement.clone());
+            }
+        }
+    }
+    requirements.extend(explicit.iter().cloned());
+    Ok(requirements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements::VersionRequirements;
+    use crate::test_util::TempDir;
+    use crate::version::NamespacedVersion;
+
+    #[test]
+    fn presets_are_valid() {
+        for preset in PRESETS.iter().chain(TARGETS).chain(GCC_RUNTIMES) {
+            let requirements: Vec<String> =
+                preset.requirements.iter().map(|r| r.to_string()).collect();
+            assert!(VersionRequirements::parse(&requirements).is_ok());
+        }
+    }
+
+    #[test]
+    fn manylinux_presets_limit_libstdcxx() {
+        let presets = UserPresets::default()
+            .find_requirements(&["manylinux2014".to_string()], &[])
+            .unwrap();
+        let requirements = combine_requirements(&presets, &["GLIBC_2.18".to_string()]).unwrap();
+        let requirements = VersionRequirements::parse(&requirements).unwrap();
+        let permitted =
+            |version| requirements.is_permitted(&NamespacedVersion::parse(version).unwrap());
+        assert!(permitted("GLIBC_2.18"));
+        assert!(permitted("GLIBCXX_3.4.19"));
+        assert!(!permitted("GLIBCXX_3.4.20"));
+        assert!(!permitted("CXXABI_1.3.8"));
+        assert!(permitted("CXXABI_TM_1"));
+    }
+
+    #[test]
+    fn unknown_preset_is_error() {
+        assert!(find_preset("freebsd-14").is_ok());
+        assert!(find_preset("freebsd-99").is_err());
+        assert!(find_target("rhel8").is_ok());
+        assert!(find_preset("rhel8").is_err());
+    }
+
+    #[test]
+    fn reads_presets_files() {
+        let dir = TempDir::new("presets-file");
+        let file = dir.write(
+            "presets.json",
+            br#"{"myorg-baseline": {"GLIBC": "2.17", "GLIBCXX": "3.4.19"}}"#,
+        );
+        let user_presets = UserPresets::read(&[file]).unwrap();
+        let requirements = user_presets
+            .find_requirements(
+                &["myorg-baseline".to_string(), "freebsd-13".to_string()],
+                &["rhel8".to_string()],
+            )
+            .unwrap();
+        assert_eq!(requirements[0], vec!["GLIBC_2.17", "GLIBCXX_3.4.19"]);
+        assert_eq!(requirements[1], vec!["FBSD_1.6"]);
+        assert_eq!(requirements[2][0], "GLIBC_2.28");
+        let error = user_presets
+            .find_requirements(&["myorg".to_string()], &[])
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .ends_with("or from presets files: myorg-baseline")
+        );
+    }
+
+    #[test]
+    fn presets_files_cant_redefine_presets() {
+        let dir = TempDir::new("presets-redefined");
+        let builtin = dir.write("builtin.json", br#"{"freebsd-13": {}}"#);
+        assert!(UserPresets::read(&[builtin]).is_err());
+        let first = dir.write("first.json", br#"{"baseline": {"GLIBC": "2.17"}}"#);
+        let second = dir.write("second.json", br#"{"baseline": {"GLIBC": "2.28"}}"#);
+        assert!(UserPresets::read(&[first, second]).is_err());
+        let invalid = dir.write("invalid.json", br#"{"baseline": {"GLIBC": "two"}}"#);
+        assert!(UserPresets::read(&[invalid]).is_err());
+    }
+
+    #[test]
+    fn finds_gcc_runtimes() {
+        assert_eq!(
+            find_gcc_runtime("8").unwrap().requirements,
+            &find_target("rhel8").unwrap().requirements[1..]
+        );
+        let error = find_gcc_runtime("3").unwrap_err();
+        assert!(error.to_string().starts_with("Unknown GCC release 3"));
+    }
+
+    #[test]
+    fn finds_profiles() {
+        let user_presets = UserPresets::default();
+        assert_eq!(user_presets.find_profile("rhel7").unwrap()[0], "GLIBC_2.17");
+        assert_eq!(
+            user_presets.find_profile("manylinux_2_28").unwrap()[0],
+            "GLIBC_2.28"
+        );
+        let error = user_presets.find_profile("rhel99").unwrap_err();
+        assert!(error.to_string().starts_with("Unknown profile rhel99"));
+    }
+
+    #[test]
+    fn unknown_target_is_error() {
+        let error = UserPresets::default()
+            .find_requirements(&[], &["rhel99".to_string()])
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Unknown target rhel99"));
+    }
+
+    #[test]
+    fn explicit_requirements_override_presets() {
+        let presets = UserPresets::default()
+            .find_requirements(&["opensolaris".to_string()], &[])
+            .unwrap();
+        let requirements = combine_requirements(&presets, &["ILLUMOS_0.39".to_string()]).unwrap();
+        assert_eq!(requirements, vec!["SUNW_1.23", "ILLUMOS_0.39"]);
+    }
+}
@@ -0,0 +1,484 @@
+use crate::oci::{self, ImageFilesystem};
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use ureq::Agent;
+use ureq::http::{Response, StatusCode};
+
+const DOCKER_HUB_REGISTRY: &str = "docker.io";
+const DOCKER_HUB_API_HOST: &str = "registry-1.docker.io";
+const DOCKER_HUB_AUTH_KEY: &str = "https://index.docker.io/v1/";
+const DEFAULT_TAG: &str = "latest";
+const MANIFEST_MEDIA_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+/// The largest manifest read when its size isn't known from a descriptor, as registries needn't accept larger ones.
+const MAX_MANIFEST_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Reference to an image in a registry, e.g. `ghcr.io/org/app:tag` or `alpine@sha256:...`.
+#[derive(Debug, PartialEq)]
+pub struct ImageReference {
+    pub registry: String,
+    pub repository: String,
+    pub reference: String,
+}
+
+impl ImageReference {
+    pub fn parse(image: &str) -> anyhow::Result<ImageReference> {
+        let (registry, remainder) = match image.split_once('/') {
+            Some((host, remainder))
+                if host.contains('.') || host.contains(':') || host == "localhost" =>
+            {
+                (host.to_string(), remainder)
+            }
+            _ => (DOCKER_HUB_REGISTRY.to_string(), image),
+        };
+        let (repository, reference) = match remainder.split_once('@') {
+            Some((repository, digest)) => (repository, digest),
+            None => match remainder.rsplit_once(':') {
+                Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+                _ => (remainder, DEFAULT_TAG),
+            },
+        };
+        if repository.is_empty() || reference.is_empty() {
+            return Err(anyhow!("Invalid image reference: {}", image));
+        }
+        let repository = if registry == DOCKER_HUB_REGISTRY && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            repository.to_string()
+        };
+        Ok(ImageReference {
+            registry,
+            repository,
+            reference: reference.to_string(),
+        })
+    }
+
+    fn api_host(&self) -> &str {
+        if self.registry == DOCKER_HUB_REGISTRY {
+            DOCKER_HUB_API_HOST
+        } else {
+            &self.registry
+        }
+    }
+
+    fn auth_key(&self) -> &str {
+        if self.registry == DOCKER_HUB_REGISTRY {
+            DOCKER_HUB_AUTH_KEY
+        } else {
+            &self.registry
+        }
+    }
+}
+
+/// Image manifest or image index, in either OCI or Docker format.
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    digest: String,
+    size: u64,
+    platform: Option<Platform>,
+}
+
+#[derive(Deserialize)]
+struct Platform {
+    architecture: String,
+    os: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// The subset of a Docker client config.json used to find registry credentials.
+#[derive(Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerAuth>,
+}
+
+#[derive(Deserialize)]
+struct DockerAuth {
+    auth: Option<String>,
+}
+
+/// Client for the OCI distribution API, handling anonymous and credentialed bearer token authentication as well
+/// as basic authentication.
+struct RegistryClient {
+    agent: Agent,
+    image: ImageReference,
+    authorization: Option<String>,
+    credentials: Option<String>,
+}
+
+impl RegistryClient {
+    fn new(image: ImageReference) -> Self {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .new_agent();
+        let credentials = find_credentials(image.auth_key());
+        Self {
+            agent,
+            image,
+            authorization: None,
+            credentials,
+        }
+    }
+
+    /// Downloads a manifest or blob, failing if it is larger than `limit`.
+    fn get(&mut self, path: &str, accept: Option<&str>, limit: u64) -> anyhow::Result<Vec<u8>> {
+        let url = format!(
+            "https://{}/v2/{}/{}",
+            self.image.api_host(),
+            self.image.repository,
+            path
+        );
+        let mut response = self.send(&url, accept)?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.authorization.is_none() {
+            let challenge = response
+                .headers()
+                .get("www-authenticate")
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow!("Registry requires authentication but sent no challenge"))?
+                .to_string();
+            self.authorization = Some(self.authenticate(&challenge)?);
+            response = self.send(&url, accept)?;
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Registry request for {} failed: {}",
+                url,
+                response.status()
+            ));
+        }
+        response
+            .body_mut()
+            .with_config()
+            .limit(limit)
+            .read_to_vec()
+            .with_context(|| format!("Error reading response from {}", url))
+    }
+
+    /// Downloads the manifest or blob a descriptor refers to, reading no more than its size, and checks it against its
+    /// digest, so that a registry or proxy can't substitute other content.
+    fn get_descriptor(
+        &mut self,
+        kind: &str,
+        descriptor: &Descriptor,
+        accept: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let data = self.get(
+            &format!("{}/{}", kind, descriptor.digest),
+            accept,
+            descriptor.size,
+        )?;
+        if data.len() as u64 != descriptor.size {
+            return Err(anyhow!(
+                "{} is {} bytes, but its descriptor gives {}",
+                descriptor.digest,
+                data.len(),
+                descriptor.size
+            ));
+        }
+        verify_digest(&data, &descriptor.digest)?;
+        Ok(data)
+    }
+
+    fn send(&self, url: &str, accept: Option<&str>) -> anyhow::Result<Response<ureq::Body>> {
+        let mut request = self.agent.get(url);
+        if let Some(accept) = accept {
+            request = request.header("Accept", accept);
+        }
+        if let Some(authorization) = &self.authorization {
+            request = request.header("Authorization", authorization);
+        }
+        request
+            .call()
+            .with_context(|| format!("Error requesting {}", url))
+    }
+
+    /// Responds to a `WWW-Authenticate` challenge, returning the value to use for the `Authorization` header.
+    fn authenticate(&self, challenge: &str) -> anyhow::Result<String> {
+        let (scheme, params) = parse_challenge(challenge);
+        if scheme.eq_ignore_ascii_case("basic") {
+            return self
+                .credentials
+                .as_ref()
+                .map(|credentials| format!("Basic {}", credentials))
+                .ok_or_else(|| anyhow!("Registry requires credentials but none were found"));
+        }
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| anyhow!("Missing realm in authentication challenge"))?;
+        let mut request = self.agent.get(realm.as_str());
+        for key in ["service", "scope"] {
+            if let Some(value) = params.get(key) {
+                request = request.query(key, value);
+            }
+        }
+        if let Some(credentials) = &self.credentials {
+            request = request.header("Authorization", &format!("Basic {}", credentials));
+        }
+        let mut response = request
+            .call()
+            .with_context(|| format!("Error requesting token from {}", realm))?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Token request to {} failed: {}",
+                realm,
+                response.status()
+            ));
+        }
+        let body = response
+            .body_mut()
+            .read_to_vec()
+            .with_context(|| "Error reading token response")?;
+        let token: TokenResponse =
+            serde_json::from_slice(&body).with_context(|| "Error parsing token response")?;
+        token
+            .token
+            .or(token.access_token)
+            .map(|token| format!("Bearer {}", token))
+            .ok_or_else(|| anyhow!("No token in token response"))
+    }
+}
+
+/// Checks that content has the given `sha256:` digest.
+fn verify_digest(data: &[u8], digest: &str) -> anyhow::Result<()> {
+    let expected = digest
+        .strip_prefix("sha256:")
+        .ok_or_else(|| anyhow!("Unsupported digest algorithm in {}", digest))?;
+    let actual: String = Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if actual != expected {
+        return Err(anyhow!(
+            "Content doesn't match its digest {}, it has digest sha256:{}",
+            digest,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a `WWW-Authenticate` header into its scheme and parameters.
+fn parse_challenge(challenge: &str) -> (String, HashMap<String, String>) {
+    let (scheme, remainder) = challenge
+        .trim()
+        .split_once(' ')
+        .unwrap_or((challenge.trim(), ""));
+    let mut params = HashMap::new();
+    let mut rest = remainder.trim();
+    while let Some((key, value_and_rest)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_lowercase();
+        let (value, remaining) = match value_and_rest.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value_and_rest
+                .split_once(',')
+                .unwrap_or((value_and_rest, "")),
+        };
+        params.insert(key, value.to_string());
+        rest = remaining.trim_start_matches(',').trim();
+    }
+    (scheme.to_string(), params)
+}
+
+/// Looks up base64 encoded `user:password` credentials for a registry in the Docker client configuration.
+fn find_credentials(auth_key: &str) -> Option<String> {
+    let config_dir = std::env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".docker")))?;
+    let data = std::fs::read(config_dir.join("config.json")).ok()?;
+    let config: DockerConfig = serde_json::from_slice(&data).ok()?;
+    config.auths.get(auth_key)?.auth.clone()
+}
+
+/// Name of the current architecture, as used in image platform descriptors.
+fn host_architecture() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        "powerpc64" => "ppc64le",
+        arch => arch,
+    }
+}
+
+fn select_platform_manifest<'a>(
+    index: &'a Manifest,
+    architecture: &str,
+) -> anyhow::Result<&'a Descriptor> {
+    index
+        .manifests
+        .iter()
+        .find(|m| {
+            m.platform
+                .as_ref()
+                .is_some_and(|p| p.os == "linux" && p.architecture == architecture)
+        })
+        .ok_or_else(|| anyhow!("No image found for platform linux/{}", architecture))
+}
+
+/// Pulls an image from a registry and calls `visit` for each ELF file in its filesystem.
+pub fn visit_image_elf_files(
+    image: &str,
+    visit: &mut dyn FnMut(&str, &[u8]),
+) -> anyhow::Result<()> {
+    let image = ImageReference::parse(image)?;
+    let reference = image.reference.clone();
+    let mut client = RegistryClient::new(image);
+
+    let body = client.get(
+        &format!("manifests/{}", reference),
+        Some(MANIFEST_MEDIA_TYPES),
+        MAX_MANIFEST_SIZE,
+    )?;
+    // Images referred to by digest rather than by tag are checked against it
+    if reference.contains(':') {
+        verify_digest(&body, &reference)?;
+    }
+    let mut manifest: Manifest =
+        serde_json::from_slice(&body).with_context(|| "Error parsing image manifest")?;
+    if !manifest.manifests.is_empty() {
+        let descriptor = select_platform_manifest(&manifest, host_architecture())?;
+        let body = client.get_descriptor("manifests", descriptor, Some(MANIFEST_MEDIA_TYPES))?;
+        manifest = serde_json::from_slice(&body).with_context(|| "Error parsing image manifest")?;
+    }
+
+    let mut filesystem = ImageFilesystem::default();
+    for layer in &manifest.layers {
+        let blob = client
+            .get_descriptor("blobs", layer, None)
+            .with_context(|| format!("Error downloading layer {}", layer.digest))?;
+        filesystem
+            .apply_layer(oci::open_layer(&blob)?)
+            .with_context(|| format!("Error applying layer {}", layer.digest))?;
+    }
+    for (path, contents) in filesystem.elf_files() {
+        visit(path, contents);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(registry: &str, repository: &str, reference: &str) -> ImageReference {
+        ImageReference {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            reference: reference.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_full_image_reference() {
+        assert_eq!(
+            ImageReference::parse("ghcr.io/org/app:v1").unwrap(),
+            reference("ghcr.io", "org/app", "v1")
+        );
+    }
+
+    #[test]
+    fn parses_image_reference_with_default_tag() {
+        assert_eq!(
+            ImageReference::parse("ghcr.io/org/app").unwrap(),
+            reference("ghcr.io", "org/app", "latest")
+        );
+    }
+
+    #[test]
+    fn parses_image_reference_with_digest() {
+        assert_eq!(
+            ImageReference::parse("quay.io/org/app@sha256:abcd").unwrap(),
+            reference("quay.io", "org/app", "sha256:abcd")
+        );
+    }
+
+    #[test]
+    fn parses_image_reference_with_registry_port() {
+        assert_eq!(
+            ImageReference::parse("localhost:5000/app:1.0").unwrap(),
+            reference("localhost:5000", "app", "1.0")
+        );
+    }
+
+    #[test]
+    fn parses_docker_hub_official_image() {
+        assert_eq!(
+            ImageReference::parse("debian:bookworm").unwrap(),
+            reference("docker.io", "library/debian", "bookworm")
+        );
+    }
+
+    #[test]
+    fn parses_docker_hub_user_image() {
+        assert_eq!(
+            ImageReference::parse("someuser/app").unwrap(),
+            reference("docker.io", "someuser/app", "latest")
+        );
+    }
+
+    #[test]
+    fn parse_image_reference_error_when_empty() {
+        assert!(ImageReference::parse("").is_err());
+    }
+
+    #[test]
+    fn parses_bearer_challenge() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:org/app:pull""#,
+        );
+        assert_eq!(scheme, "Bearer");
+        assert_eq!(params["realm"], "https://ghcr.io/token");
+        assert_eq!(params["service"], "ghcr.io");
+        assert_eq!(params["scope"], "repository:org/app:pull");
+    }
+
+    #[test]
+    fn parses_basic_challenge() {
+        let (scheme, params) = parse_challenge(r#"Basic realm="Registry""#);
+        assert_eq!(scheme, "Basic");
+        assert_eq!(params["realm"], "Registry");
+    }
+
+    #[test]
+    fn selects_platform_manifest() {
+        let index: Manifest = serde_json::from_str(
+            r#"{"manifests": [
+                {"digest": "sha256:arm", "size": 1, "platform": {"architecture": "arm64", "os": "linux"}},
+                {"digest": "sha256:amd", "size": 1, "platform": {"architecture": "amd64", "os": "linux"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            select_platform_manifest(&index, "amd64").unwrap().digest,
+            "sha256:amd"
+        );
+        assert!(select_platform_manifest(&index, "s390x").is_err());
+    }
+
+    #[test]
+    fn verifies_digests() {
+        let empty = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert!(verify_digest(b"", empty).is_ok());
+        assert!(verify_digest(b"substituted", empty).is_err());
+        assert!(verify_digest(b"", "sha512:abcd").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::baseline::violation_key;
+use crate::{CheckReport, FileOutcome, Violation};
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// The violations of each file in a report written by `--format json`, by profile for reports of several profiles.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    files: BTreeMap<String, FileViolations>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct FileViolations {
+    /// Whether the file failed, rather than passing with warnings
+    failed: bool,
+    violations: Vec<Violation>,
+}
+
+/// The results of checking files against each of several profiles, as written by `--format json` with `--profile`.
+#[derive(Deserialize)]
+struct ProfileReports {
+    profiles: Vec<ProfileReport>,
+}
+
+#[derive(Deserialize)]
+struct ProfileReport {
+    profile: String,
+    #[serde(flatten)]
+    report: CheckReport,
+}
+
+/// A violation which is only in one of two reports being compared.
+#[derive(Debug, PartialEq)]
+pub struct ViolationChange {
+    /// The file, followed by the profile in parentheses for reports of several profiles
+    pub file: String,
+    pub violation: Violation,
+    /// Whether the file failed in the report with the violation
+    pub failed: bool,
+}
+
+/// The differences between the violations of two reports.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReportComparison {
+    /// Violations only in the later report
+    pub added: Vec<ViolationChange>,
+    /// Violations only in the earlier report
+    pub removed: Vec<ViolationChange>,
+}
+
+impl ReportComparison {
+    /// Checks whether the later report has violations which fail files that the earlier report doesn't.
+    pub fn has_regressions(&self) -> bool {
+        self.added.iter().any(|change| change.failed)
+    }
+}
+
+impl Report {
+    pub fn read(path: &Path) -> anyhow::Result<Report> {
+        let data =
+            fs::read(path).with_context(|| format!("Error reading report {}", path.display()))?;
+        let value: Value = serde_json::from_slice(&data)
+            .with_context(|| format!("Error parsing report {}", path.display()))?;
+        Report::from_json(value).with_context(|| format!("Error parsing report {}", path.display()))
+    }
+
+    fn from_json(value: Value) -> anyhow::Result<Report> {
+        let mut report = Report::default();
+        if value.get("profiles").is_some() {
+            let reports: ProfileReports = serde_json::from_value(value)?;
+            for profile in reports.profiles {
+                report.add_files(profile.report, Some(&profile.profile));
+            }
+        } else if value.get("files").is_some() {
+            report.add_files(serde_json::from_value(value)?, None);
+        } else {
+            return Err(anyhow!("Missing list of files"));
+        }
+        Ok(report)
+    }
+
+    fn add_files(&mut self, check_report: CheckReport, profile: Option<&str>) {
+        for file in check_report.files {
+            let name = match profile {
+                Some(profile) => format!("{} ({})", file.file.display(), profile),
+                None => file.file.display().to_string(),
+            };
+            let failed = file.result == FileOutcome::Fail;
+            self.files.insert(
+                name,
+                FileViolations {
+                    failed,
+                    violations: file.violations,
+                },
+            );
+        }
+    }
+
+    /// Compares the violations of each file in an earlier report with those in this later one. Violations are matched
+    /// on the fields identifying them, as baselines are, so reports written with different details, such as with and
+    /// without `--demangle`, can be compared, but the reports must be written with the same paths.
+    pub fn compare(&self, before: &Report) -> ReportComparison {
+        let empty = FileViolations::default();
+        let files: BTreeSet<&String> = self.files.keys().chain(before.files.keys()).collect();
+        let mut comparison = ReportComparison::default();
+        for file in files {
+            let after = self.files.get(file).unwrap_or(&empty);
+            let before = before.files.get(file).unwrap_or(&empty);
+            let only_in = |violations: &FileViolations, other: &FileViolations| {
+                let other_keys: BTreeSet<_> = other.violations.iter().map(key).collect();
+                violations
+                    .violations
+                    .iter()
+                    .filter(|violation| !other_keys.contains(&key(violation)))
+                    .map(|violation| ViolationChange {
+                        file: file.clone(),
+                        violation: violation.clone(),
+                        failed: violations.failed,
+                    })
+                    .collect::<Vec<_>>()
+            };
+            comparison.added.extend(only_in(after, before));
+            comparison.removed.extend(only_in(before, after));
+        }
+        comparison
+    }
+}
+
+fn key(violation: &Violation) -> Vec<Option<String>> {
+    violation_key(&serde_json::to_value(violation).expect("Violations can always be serialized"))
+}
+
+/// Describes a violation as reported in JSON, e.g. `symbol stat@GLIBC_2.33` or
+/// `disallowed_library (library: libfoo.so.1)`.
+pub fn describe_violation(violation: &Value) -> String {
+    let kind = violation
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or("violation");
+    if let (Some(name), Some(version)) = (
+        violation.get("name").and_then(Value::as_str),
+        violation.get("version").and_then(Value::as_str),
+    ) {
+        return format!("{} {}@{}", kind, name, version);
+    }
+    let fields: Vec<String> = violation
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, value)| *key != "kind" && !value.is_null())
+        .map(|(key, value)| match value {
+            Value::String(value) => format!("{}: {}", key, value),
+            value => format!("{}: {}", key, value),
+        })
+        .collect();
+    if fields.is_empty() {
+        kind.to_string()
+    } else {
+        format!("{} ({})", kind, fields.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report(value: Value) -> Report {
+        Report::from_json(value).unwrap()
+    }
+
+    fn violation(value: &Value) -> Violation {
+        serde_json::from_value(value.clone()).unwrap()
+    }
+
+    #[test]
+    fn compares_reports() {
+        let stat = json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33"});
+        let getrandom = json!({"kind": "symbol", "name": "getrandom", "version": "GLIBC_2.25"});
+        let build_id = json!({"kind": "missing_build_id"});
+        let before = report(json!({"files": [
+            {"file": "bin/app", "result": "fail", "violations": [stat, build_id]},
+            {"file": "bin/tool", "result": "pass"},
+        ]}));
+        let after = report(json!({"files": [
+            {"file": "bin/app", "result": "fail", "violations": [stat, getrandom]},
+            {"file": "bin/tool", "result": "warn", "violations": [build_id]},
+        ]}));
+
+        let comparison = after.compare(&before);
+        assert_eq!(
+            comparison.added,
+            vec![
+                ViolationChange {
+                    file: "bin/app".to_string(),
+                    violation: violation(&getrandom),
+                    failed: true,
+                },
+                ViolationChange {
+                    file: "bin/tool".to_string(),
+                    violation: violation(&build_id),
+                    failed: false,
+                },
+            ]
+        );
+        assert_eq!(
+            comparison.removed,
+            vec![ViolationChange {
+                file: "bin/app".to_string(),
+                violation: violation(&build_id),
+                failed: true,
+            }]
+        );
+        assert!(comparison.has_regressions());
+        // New violations in files which only warn aren't regressions
+        let warned = report(json!({"files": [
+            {"file": "bin/app", "result": "fail", "violations": [stat, build_id]},
+            {"file": "bin/tool", "result": "warn", "violations": [build_id]},
+        ]}));
+        assert!(!warned.compare(&before).has_regressions());
+        assert!(!after.compare(&after).has_regressions());
+    }
+
+    #[test]
+    fn matches_violations_on_identifying_fields() {
+        let before = report(json!({"files": [
+            {"file": "bin/app", "result": "fail", "violations": [
+                {"kind": "symbol", "name": "_ZN3foo3barEv", "version": "GLIBCXX_3.4.21", "file": "libstdc++.so.6"},
+            ]},
+        ]}));
+        // As written with --demangle, and with the symbol's type
+        let after = report(json!({"files": [
+            {"file": "bin/app", "result": "fail", "violations": [
+                {"kind": "symbol", "name": "_ZN3foo3barEv", "version": "GLIBCXX_3.4.21", "file": "libstdc++.so.6",
+                 "type": "func", "binding": "global", "demangled": "foo::bar()"},
+            ]},
+        ]}));
+        assert_eq!(after.compare(&before), ReportComparison::default());
+    }
+
+    #[test]
+    fn reads_profiles() {
+        let stat = json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33"});
+        let before = report(json!({"profiles": [
+            {"profile": "el8", "files": [{"file": "app", "result": "pass"}]},
+        ]}));
+        let after = report(json!({"profiles": [
+            {"profile": "el8", "files": [{"file": "app", "result": "fail", "violations": [stat]}]},
+        ]}));
+        let comparison = after.compare(&before);
+        assert_eq!(comparison.added[0].file, "app (el8)");
+        assert!(Report::from_json(json!({"results": []})).is_err());
+    }
+
+    #[test]
+    fn describes_violations() {
+        assert_eq!(
+            describe_violation(
+                &json!({"kind": "symbol", "name": "stat", "version": "GLIBC_2.33", "file": "libc.so.6"})
+            ),
+            "symbol stat@GLIBC_2.33"
+        );
+        assert_eq!(
+            describe_violation(&json!({"kind": "disallowed_library", "library": "libfoo.so.1"})),
+            "disallowed_library (library: libfoo.so.1)"
+        );
+        assert_eq!(
+            describe_violation(&json!({"kind": "missing_build_id"})),
+            "missing_build_id"
+        );
+    }
+}
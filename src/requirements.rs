@@ -1,42 +1,328 @@
 use crate::symbols::SymbolVersion;
-use crate::version::NamespacedVersion;
-use anyhow::anyhow;
-use std::collections::HashMap;
+use crate::version::{NamespacedVersion, Version};
+use anyhow::{Context, anyhow};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::RegexSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct VersionRequirements {
     requirements: HashMap<String, NamespacedVersion>,
+    /// The maximum versions for namespaces matching a pattern, such as `GLIBC*`, which apply to namespaces without a
+    /// maximum version of their own. The first matching pattern applies.
+    patterns: Vec<(GlobSet, NamespacedVersion)>,
+    /// The only versions permitted in some namespaces, such as those defined by a particular library
+    allowed_versions: HashMap<String, BTreeSet<NamespacedVersion>>,
+    unlisted: UnlistedNamespaces,
+    /// The versions binaries must reference at least, by namespace
+    minimum_versions: HashMap<String, NamespacedVersion>,
+    /// The namespaces whose violations are only warnings, as given by `:warn`
+    warn_only: HashSet<String>,
+    /// When set, the names of symbols which aren't checked
+    ignored_symbols: Option<RegexSet>,
+}
+
+/// Parses a maximum version as given by `-m`, such as `GLIBC_2.17`, or `GLIBCXX_3.4.21:warn` for a namespace whose
+/// violations are only warnings, returning the version and whether violations are only warnings.
+pub fn parse_requirement(requirement: &str) -> anyhow::Result<(NamespacedVersion, bool)> {
+    let (version, warn_only) = match requirement.rsplit_once(':') {
+        Some((version, "warn")) => (version, true),
+        Some((version, "fail")) => (version, false),
+        Some((_, severity)) => {
+            return Err(anyhow!(
+                "Invalid severity {} in {}, expected warn or fail",
+                severity,
+                requirement
+            ));
+        }
+        None => (requirement, false),
+    };
+    Ok((NamespacedVersion::parse(version)?, warn_only))
+}
+
+/// Splits a comma-separated list of maximum versions, such as `GLIBC_2.17,GLIBCXX_3.4.19`.
+pub fn split_requirements(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|requirement| !requirement.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Checks whether a namespace given in a requirement is a pattern, such as `GLIBC*` or `OPENSSL_*`.
+pub fn is_namespace_pattern(namespace: &str) -> bool {
+    namespace.contains(['*', '?', '['])
+}
+
+/// Compiles a namespace pattern. A pattern ending in `*` directly after a letter or digit only matches on a namespace
+/// boundary, so `GLIBC*` matches `GLIBC` and `GLIBC_ABI_DT_RELR` but not `GLIBCXX`.
+fn compile_namespace_pattern(pattern: &str) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    match pattern.strip_suffix('*') {
+        Some(prefix) if prefix.ends_with(|c: char| c.is_ascii_alphanumeric()) => {
+            builder.add(Glob::new(prefix)?);
+            builder.add(Glob::new(&format!("{}_*", prefix))?);
+        }
+        _ => {
+            builder.add(Glob::new(pattern)?);
+        }
+    }
+    builder.build()
+}
+
+/// Checks whether a namespace given in a requirement, which may be a pattern, matches a namespace.
+pub fn namespace_matches(requirement: &str, namespace: &str) -> bool {
+    if is_namespace_pattern(requirement) {
+        compile_namespace_pattern(requirement).is_ok_and(|patterns| patterns.is_match(namespace))
+    } else {
+        requirement == namespace
+    }
+}
+
+/// Replaces maximum versions given as `@FILE` with those read from the file, one per line. Blank lines, and anything
+/// following a `#`, are ignored.
+pub fn read_requirement_files(requirements: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for requirement in requirements {
+        let Some(file) = requirement.strip_prefix('@') else {
+            expanded.push(requirement.clone());
+            continue;
+        };
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("Error reading requirements file {}", file))?;
+        expanded.extend(
+            contents
+                .lines()
+                .map(|line| line.split_once('#').map_or(line, |(line, _)| line).trim())
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+    Ok(expanded)
+}
+
+/// How versions are handled in namespaces with neither a maximum version nor allowed versions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum UnlistedNamespaces {
+    #[default]
+    Permit,
+    /// Versions must be no newer than this version, whatever their namespace.
+    Max(Version),
+    Deny,
 }
 
 impl VersionRequirements {
     pub fn parse(requirements: &[String]) -> anyhow::Result<VersionRequirements> {
+        let mut warn_only = HashSet::new();
+        let mut patterns = Vec::new();
         let requirements = requirements.iter().try_fold(HashMap::new(), |mut acc, v| {
-            let nv = NamespacedVersion::parse(v)?;
+            let (nv, warn) = parse_requirement(v)?;
             if acc.contains_key(&nv.namespace) {
                 return Err(anyhow!("Duplicate namespace: {}", nv.namespace));
             }
+            if warn {
+                warn_only.insert(nv.namespace.clone());
+            }
+            if is_namespace_pattern(&nv.namespace) {
+                let matcher = compile_namespace_pattern(&nv.namespace)
+                    .with_context(|| format!("Invalid namespace pattern in {}", v))?;
+                patterns.push((matcher, nv.clone()));
+            }
             acc.insert(nv.namespace.clone(), nv);
             Ok(acc)
         })?;
-        Ok(VersionRequirements { requirements })
+        Ok(VersionRequirements {
+            requirements,
+            patterns,
+            warn_only,
+            allowed_versions: HashMap::new(),
+            unlisted: UnlistedNamespaces::default(),
+            minimum_versions: HashMap::new(),
+            ignored_symbols: None,
+        })
+    }
+
+    /// Permits only the given versions in their namespaces, as well as requiring them to be no newer than the maximum
+    /// version for the namespace, if any.
+    pub fn with_allowed_versions(
+        mut self,
+        versions: impl IntoIterator<Item = NamespacedVersion>,
+    ) -> Self {
+        for version in versions {
+            self.allowed_versions
+                .entry(version.namespace.clone())
+                .or_default()
+                .insert(version);
+        }
+        self
+    }
+
+    /// Returns the versions permitted by `with_allowed_versions`.
+    pub fn allowed_versions(&self) -> impl Iterator<Item = &NamespacedVersion> {
+        self.allowed_versions.values().flatten()
+    }
+
+    /// Sets how versions in namespaces with neither a maximum version nor allowed versions are handled.
+    pub fn with_unlisted(mut self, unlisted: UnlistedNamespaces) -> Self {
+        self.unlisted = unlisted;
+        self
+    }
+
+    pub fn unlisted(&self) -> &UnlistedNamespaces {
+        &self.unlisted
+    }
+
+    /// Requires binaries to reference a version at least as new as each of the given versions in its namespace. Where
+    /// several are given for the same namespace, the newest applies.
+    pub fn with_minimum_versions(
+        mut self,
+        versions: impl IntoIterator<Item = NamespacedVersion>,
+    ) -> Self {
+        for version in versions {
+            match self.minimum_versions.get(&version.namespace) {
+                Some(existing) if *existing >= version => {}
+                _ => {
+                    self.minimum_versions
+                        .insert(version.namespace.clone(), version);
+                }
+            }
+        }
+        self
+    }
+
+    /// Excludes symbols whose names match any of the given regular expressions, such as `^__asan_`, from the check.
+    pub fn with_ignored_symbols(mut self, patterns: &[String]) -> anyhow::Result<Self> {
+        if !patterns.is_empty() {
+            let set = RegexSet::new(patterns).with_context(|| "Invalid symbol pattern")?;
+            self.ignored_symbols = Some(set);
+        }
+        Ok(self)
+    }
+
+    /// Returns the patterns given to `with_ignored_symbols`.
+    pub fn ignored_symbols(&self) -> &[String] {
+        self.ignored_symbols
+            .as_ref()
+            .map_or(&[], |set| set.patterns())
+    }
+
+    /// Returns the versions required by `with_minimum_versions`.
+    pub fn minimum_versions(&self) -> impl Iterator<Item = &NamespacedVersion> {
+        self.minimum_versions.values()
+    }
+
+    /// Checks the versions a binary references against the minimum versions, returning each minimum version which no
+    /// version is at least as new as, along with the newest version referenced in its namespace, if any.
+    pub fn check_minimum_versions<'a>(
+        &self,
+        versions: impl IntoIterator<Item = &'a NamespacedVersion>,
+    ) -> Vec<(NamespacedVersion, Option<NamespacedVersion>)> {
+        let mut newest: HashMap<&str, &NamespacedVersion> = HashMap::new();
+        for version in versions {
+            match newest.get(version.namespace.as_str()) {
+                Some(existing) if *existing >= version => {}
+                _ => {
+                    newest.insert(&version.namespace, version);
+                }
+            }
+        }
+        let mut below: Vec<(NamespacedVersion, Option<NamespacedVersion>)> = self
+            .minimum_versions
+            .values()
+            .filter_map(
+                |min_version| match newest.get(min_version.namespace.as_str()) {
+                    Some(version) if *version >= min_version => None,
+                    version => Some((min_version.clone(), version.map(|v| (*v).clone()))),
+                },
+            )
+            .collect();
+        below.sort();
+        below
+    }
+
+    /// Returns the maximum version for a namespace, given either for the namespace itself or a pattern matching it.
+    fn find_requirement(&self, namespace: &str) -> Option<&NamespacedVersion> {
+        self.requirements
+            .get(namespace)
+            .filter(|_| !is_namespace_pattern(namespace))
+            .or_else(|| {
+                self.patterns
+                    .iter()
+                    .find(|(matcher, _)| matcher.is_match(namespace))
+                    .map(|(_, version)| version)
+            })
+    }
+
+    /// Returns the maximum version permitted in a namespace, whether given for the namespace itself, by a pattern, or
+    /// for unlisted namespaces.
+    pub fn max_version(&self, namespace: &str) -> Option<Version> {
+        match self.find_requirement(namespace) {
+            Some(requirement) => Some(requirement.version.clone()),
+            None if self.is_listed(namespace) => None,
+            None => match &self.unlisted {
+                UnlistedNamespaces::Max(max) => Some(max.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Checks whether violations in a namespace are only warnings.
+    pub fn is_warn_only(&self, namespace: &str) -> bool {
+        self.find_requirement(namespace)
+            .is_some_and(|requirement| self.warn_only.contains(&requirement.namespace))
+    }
+
+    /// Checks whether a namespace has a maximum version or allowed versions.
+    pub fn is_listed(&self, namespace: &str) -> bool {
+        self.find_requirement(namespace).is_some() || self.allowed_versions.contains_key(namespace)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+            && self.allowed_versions.is_empty()
+            && self.unlisted == UnlistedNamespaces::Permit
+            && self.minimum_versions.is_empty()
     }
 
     pub fn check_symbols(&self, symbols: &[SymbolVersion]) -> Vec<SymbolVersion> {
         symbols
             .iter()
             .filter(|symbol| {
-                self.requirements
-                    .get(&symbol.version.namespace)
-                    .is_some_and(|req| symbol.version > *req)
+                !self.is_permitted(&symbol.version)
+                    && self
+                        .ignored_symbols
+                        .as_ref()
+                        .is_none_or(|set| !set.is_match(&symbol.name))
             })
             .cloned()
             .collect::<Vec<_>>()
     }
+
+    /// Checks whether a version is permitted, i.e. it is no newer than the maximum version for its namespace, or there
+    /// is no maximum version for its namespace, and it is one of the allowed versions for its namespace, if any.
+    /// Versions in namespaces with neither are checked as `UnlistedNamespaces` says.
+    pub fn is_permitted(&self, version: &NamespacedVersion) -> bool {
+        if !self.is_listed(&version.namespace) {
+            return match &self.unlisted {
+                UnlistedNamespaces::Permit => true,
+                UnlistedNamespaces::Max(max) => version.version <= *max,
+                UnlistedNamespaces::Deny => false,
+            };
+        }
+        self.find_requirement(&version.namespace)
+            .is_none_or(|req| version.version <= req.version)
+            && self
+                .allowed_versions
+                .get(&version.namespace)
+                .is_none_or(|allowed| allowed.contains(version))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_util::TempDir;
 
     #[test]
     fn parse_requirements_single_version() {
@@ -91,4 +377,156 @@ mod test {
         let result = VersionRequirements::parse(&requirements).unwrap();
         assert!(result.requirements.is_empty());
     }
+
+    #[test]
+    fn allowed_versions_restrict_their_namespaces() {
+        let parse = |version| NamespacedVersion::parse(version).unwrap();
+        let requirements = VersionRequirements::parse(&["GLIBC_2.17".to_string()])
+            .unwrap()
+            .with_allowed_versions([
+                parse("GLIBC_2.2.5"),
+                parse("GLIBC_2.14"),
+                parse("GLIBC_2.28"),
+            ]);
+        assert!(requirements.is_permitted(&parse("GLIBC_2.14")));
+        assert!(!requirements.is_permitted(&parse("GLIBC_2.3")));
+        assert!(!requirements.is_permitted(&parse("GLIBC_2.28")));
+        assert!(requirements.is_permitted(&parse("GLIBCXX_3.4.30")));
+    }
+
+    #[test]
+    fn unlisted_namespaces() {
+        let parse = |version| NamespacedVersion::parse(version).unwrap();
+        let requirements = VersionRequirements::parse(&["GLIBC_2.17".to_string()]).unwrap();
+        assert!(requirements.is_permitted(&parse("OPENSSL_3.0.0")));
+
+        let requirements =
+            requirements.with_unlisted(UnlistedNamespaces::Max(Version::parse("1.1").unwrap()));
+        assert!(requirements.is_permitted(&parse("GLIBC_2.17")));
+        assert!(requirements.is_permitted(&parse("OPENSSL_1.1.0")));
+        assert!(!requirements.is_permitted(&parse("OPENSSL_3.0.0")));
+
+        let requirements = requirements.with_unlisted(UnlistedNamespaces::Deny);
+        assert!(requirements.is_permitted(&parse("GLIBC_2.17")));
+        assert!(!requirements.is_permitted(&parse("OPENSSL_1.1.0")));
+        assert!(!requirements.is_empty());
+    }
+
+    #[test]
+    fn minimum_versions() {
+        let parse = |version| NamespacedVersion::parse(version).unwrap();
+        let requirements = VersionRequirements::parse(&[])
+            .unwrap()
+            .with_minimum_versions([
+                parse("GLIBC_2.14"),
+                parse("GLIBC_2.17"),
+                parse("GLIBCXX_3.4.19"),
+            ]);
+        assert!(!requirements.is_empty());
+        let referenced = [
+            parse("GLIBC_2.2.5"),
+            parse("GLIBC_2.17"),
+            parse("GLIBCXX_3.4"),
+        ];
+        assert_eq!(
+            requirements.check_minimum_versions(&referenced),
+            vec![(parse("GLIBCXX_3.4.19"), Some(parse("GLIBCXX_3.4")))]
+        );
+        assert_eq!(
+            requirements.check_minimum_versions(&referenced[..1]),
+            vec![
+                (parse("GLIBC_2.17"), Some(parse("GLIBC_2.2.5"))),
+                (parse("GLIBCXX_3.4.19"), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_requirements_with_severity() {
+        let requirements = VersionRequirements::parse(&[
+            "GLIBC_2.17:fail".to_string(),
+            "GLIBCXX_3.4.21:warn".to_string(),
+        ])
+        .unwrap();
+        assert!(!requirements.is_warn_only("GLIBC"));
+        assert!(requirements.is_warn_only("GLIBCXX"));
+        assert!(requirements.is_permitted(&NamespacedVersion::parse("GLIBCXX_3.4.21").unwrap()));
+        assert!(VersionRequirements::parse(&["GLIBC_2.17:error".to_string()]).is_err());
+    }
+
+    #[test]
+    fn patterns_match_namespaces() {
+        let requirements = VersionRequirements::parse(&[
+            "GLIBC*_2.17".to_string(),
+            "GLIBCXX_3.4.19".to_string(),
+            "OPENSSL_*_1.1".to_string(),
+        ])
+        .unwrap();
+        let permitted =
+            |version| requirements.is_permitted(&NamespacedVersion::parse(version).unwrap());
+        assert!(permitted("GLIBC_2.17"));
+        assert!(!permitted("GLIBC_2.18"));
+        assert!(permitted("GLIBCXX_3.4.19"));
+        assert!(!permitted("GLIBCXX_3.4.20"));
+        assert!(permitted("OPENSSL_FIPS_1.1"));
+        assert!(!permitted("OPENSSL_FIPS_1.2"));
+        // OPENSSL_3.0.0 is in the OPENSSL namespace, which OPENSSL_* doesn't match
+        assert!(permitted("OPENSSL_3.0.0"));
+        assert!(requirements.is_listed("GLIBC_ABI_DT_RELR"));
+        assert!(namespace_matches("GLIBC*", "GLIBC_ABI_DT_RELR"));
+        assert!(!namespace_matches("GLIBC", "GLIBCXX"));
+        // GLIBC* only matches on a namespace boundary, so GLIBCXX is left unlisted
+        assert!(!requirements.is_listed("GLIBCXX_ABI"));
+        assert!(!namespace_matches("GLIBC*", "GLIBCXX"));
+        assert!(namespace_matches("GLIBC*", "GLIBC"));
+        assert!(namespace_matches("GLIBCXX*", "GLIBCXX"));
+    }
+
+    #[test]
+    fn ignored_symbols_are_not_checked() {
+        let requirements = VersionRequirements::parse(&["GLIBC_2.17".to_string()])
+            .unwrap()
+            .with_ignored_symbols(&["^__asan_".to_string(), "^pthread_".to_string()])
+            .unwrap();
+        let symbols = [
+            SymbolVersion::parse("__asan_init", "GLIBC_2.34", None).unwrap(),
+            SymbolVersion::parse("pthread_create", "GLIBC_2.34", None).unwrap(),
+            SymbolVersion::parse("stat", "GLIBC_2.33", None).unwrap(),
+        ];
+        let violations = requirements.check_symbols(&symbols);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].name, "stat");
+        assert!(
+            VersionRequirements::parse(&[])
+                .unwrap()
+                .with_ignored_symbols(&["(".to_string()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn splits_requirements() {
+        assert_eq!(
+            split_requirements("GLIBC_2.17, GLIBCXX_3.4.19,"),
+            ["GLIBC_2.17", "GLIBCXX_3.4.19"]
+        );
+        assert!(split_requirements("").is_empty());
+    }
+
+    #[test]
+    fn reads_requirement_files() {
+        let dir = TempDir::new("requirement-files");
+        let file = dir.write(
+            "requirements.txt",
+            b"# RHEL 7\nGLIBC_2.17\n\n  GLIBCXX_3.4.19:warn  # libstdc++\n",
+        );
+        let requirements =
+            read_requirement_files(&[format!("@{}", file.display()), "CXXABI_1.3.7".to_string()])
+                .unwrap();
+        assert_eq!(
+            requirements,
+            ["GLIBC_2.17", "GLIBCXX_3.4.19:warn", "CXXABI_1.3.7"]
+        );
+        assert!(read_requirement_files(&["@missing.txt".to_string()]).is_err());
+    }
 }
@@ -0,0 +1,106 @@
+use crate::version::Version;
+use anyhow::anyhow;
+use std::collections::HashMap;
+
+/// A versioned soname, such as `libssl.so.1.1`, split into the library name `libssl.so` and the version `1.1`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Soname {
+    pub name: String,
+    pub version: Version,
+}
+
+impl Soname {
+    pub fn parse(soname: &str) -> anyhow::Result<Soname> {
+        let (name, version) = soname
+            .find(".so.")
+            .map(|index| soname.split_at(index + 3))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid soname {}: expected a name like libssl.so.1.1",
+                    soname
+                )
+            })?;
+        Ok(Soname {
+            name: name.to_string(),
+            version: Version::parse(&version[1..])?,
+        })
+    }
+}
+
+/// The maximum versions of libraries which files may depend on, e.g. `libssl.so.1.1`.
+#[derive(Clone, Debug, Default)]
+pub struct SonameRequirements {
+    /// The maximum version of each library, along with the soname it was given as
+    requirements: HashMap<String, (Version, String)>,
+}
+
+impl SonameRequirements {
+    pub fn parse(requirements: &[String]) -> anyhow::Result<SonameRequirements> {
+        let mut parsed = HashMap::new();
+        for requirement in requirements {
+            let soname = Soname::parse(requirement)?;
+            if parsed.contains_key(&soname.name) {
+                return Err(anyhow!("Duplicate soname: {}", soname.name));
+            }
+            parsed.insert(soname.name, (soname.version, requirement.clone()));
+        }
+        Ok(SonameRequirements {
+            requirements: parsed,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+    }
+
+    /// Returns the maximum permitted soname for a library a file depends on, if the library is newer than permitted.
+    /// Libraries without a versioned soname, or without a maximum version, are permitted.
+    pub fn check_library(&self, library: &str) -> Option<String> {
+        let soname = Soname::parse(library).ok()?;
+        let (max_version, max_soname) = self.requirements.get(&soname.name)?;
+        (soname.version > *max_version).then(|| max_soname.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements(requirements: &[&str]) -> SonameRequirements {
+        let requirements: Vec<String> = requirements.iter().map(|r| r.to_string()).collect();
+        SonameRequirements::parse(&requirements).unwrap()
+    }
+
+    #[test]
+    fn parses_soname() {
+        let soname = Soname::parse("libssl.so.1.1").unwrap();
+        assert_eq!(soname.name, "libssl.so");
+        assert_eq!(soname.version, Version::parse("1.1").unwrap());
+        assert!(Soname::parse("libssl.so").is_err());
+        assert!(Soname::parse("libssl.so.x").is_err());
+    }
+
+    #[test]
+    fn duplicate_soname_is_error() {
+        let requirements = vec!["libssl.so.1.1".to_string(), "libssl.so.3".to_string()];
+        assert!(SonameRequirements::parse(&requirements).is_err());
+    }
+
+    #[test]
+    fn checks_library_versions() {
+        let requirements = requirements(&["libssl.so.1.1", "libstdc++.so.6", "libpng16.so.16.0"]);
+        assert_eq!(
+            requirements.check_library("libssl.so.3"),
+            Some("libssl.so.1.1".to_string())
+        );
+        assert_eq!(requirements.check_library("libssl.so.1.1"), None);
+        assert_eq!(requirements.check_library("libssl.so.1.0.0"), None);
+        assert_eq!(requirements.check_library("libstdc++.so.6"), None);
+        assert_eq!(requirements.check_library("libcrypto.so.3"), None);
+        assert_eq!(requirements.check_library("libfoo.so"), None);
+        assert_eq!(
+            requirements.check_library("libpng16.so.16.1"),
+            Some("libpng16.so.16.0".to_string())
+        );
+    }
+}
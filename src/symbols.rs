@@ -1,20 +1,105 @@
 use crate::version::NamespacedVersion;
+use object::elf;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// The type of an ELF symbol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolType {
+    /// No type, which undefined symbols are often given (`STT_NOTYPE`)
+    #[cfg_attr(feature = "cli", value(name = "notype"))]
+    NoType,
+    /// A function (`STT_FUNC`)
+    Func,
+    /// A data object (`STT_OBJECT`, or `STT_COMMON`)
+    Object,
+    /// An indirect function, resolved at load time (`STT_GNU_IFUNC`)
+    Ifunc,
+    /// Thread-local storage (`STT_TLS`)
+    Tls,
+}
+
+impl SymbolType {
+    pub fn from_elf(st_type: u8) -> Self {
+        match st_type {
+            elf::STT_FUNC => SymbolType::Func,
+            elf::STT_OBJECT | elf::STT_COMMON => SymbolType::Object,
+            elf::STT_GNU_IFUNC => SymbolType::Ifunc,
+            elf::STT_TLS => SymbolType::Tls,
+            _ => SymbolType::NoType,
+        }
+    }
+}
+
+impl Display for SymbolType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SymbolType::NoType => "NOTYPE",
+            SymbolType::Func => "FUNC",
+            SymbolType::Object => "OBJECT",
+            SymbolType::Ifunc => "IFUNC",
+            SymbolType::Tls => "TLS",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The binding of an ELF symbol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolBinding {
+    /// A global symbol (`STB_GLOBAL`, or `STB_GNU_UNIQUE`), which must be resolved
+    Global,
+    /// A weak symbol (`STB_WEAK`), which is allowed to be missing at run time
+    Weak,
+}
+
+impl SymbolBinding {
+    pub fn from_elf(st_bind: u8) -> Self {
+        match st_bind {
+            elf::STB_WEAK => SymbolBinding::Weak,
+            _ => SymbolBinding::Global,
+        }
+    }
+}
+
+impl Display for SymbolBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SymbolBinding::Global => "GLOBAL",
+            SymbolBinding::Weak => "WEAK",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A reference from a symbol to a version, and the library the version is needed from, if known.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct SymbolVersion {
     pub name: String,
     pub version: NamespacedVersion,
     pub file: Option<String>,
+    /// The type of the symbol, for formats which have symbol types
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub symbol_type: Option<SymbolType>,
+    /// The binding of the symbol, for formats which have symbol bindings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding: Option<SymbolBinding>,
 }
 
 impl SymbolVersion {
     #[cfg(test)]
     pub fn parse(name: &str, version: &str, file: Option<String>) -> anyhow::Result<SymbolVersion> {
-        let version = NamespacedVersion::parse(&version)?;
+        let version = NamespacedVersion::parse(version)?;
         Ok(SymbolVersion {
             name: name.to_string(),
             version,
             file,
+            symbol_type: None,
+            binding: None,
         })
     }
 
@@ -74,4 +159,18 @@ mod tests {
         let symbol = SymbolVersion::parse("main", "LIB_1", None).unwrap();
         assert_eq!(symbol.try_demangle_rust_name(), None);
     }
+
+    #[test]
+    fn converts_elf_symbol_types_and_bindings() {
+        assert_eq!(SymbolType::from_elf(elf::STT_FUNC), SymbolType::Func);
+        assert_eq!(SymbolType::from_elf(elf::STT_COMMON), SymbolType::Object);
+        assert_eq!(SymbolType::from_elf(elf::STT_GNU_IFUNC), SymbolType::Ifunc);
+        assert_eq!(SymbolType::from_elf(elf::STT_TLS), SymbolType::Tls);
+        assert_eq!(SymbolType::from_elf(elf::STT_NOTYPE), SymbolType::NoType);
+        assert_eq!(SymbolBinding::from_elf(elf::STB_WEAK), SymbolBinding::Weak);
+        assert_eq!(
+            SymbolBinding::from_elf(elf::STB_GNU_UNIQUE),
+            SymbolBinding::Global
+        );
+    }
 }
@@ -0,0 +1,596 @@
+use crate::elf::{self, ElfHeader};
+use crate::version::{NamespacedVersion, is_private_version};
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The libraries whose version definitions give a sysroot's maximum versions: glibc's libraries, libstdc++ and
+/// libgcc_s.
+const LIBRARIES: &[&str] = &[
+    "libc.so.6",
+    "libm.so.6",
+    "libpthread.so.0",
+    "libdl.so.2",
+    "librt.so.1",
+    "libresolv.so.2",
+    "libstdc++.so.6",
+    "libgcc_s.so.1",
+];
+
+/// The directories libraries are searched for in, relative to the sysroot. Multiarch directories within them, such
+/// as `usr/lib/x86_64-linux-gnu`, are searched too.
+const LIBRARY_DIRS: &[&str] = &["lib64", "lib", "usr/lib64", "usr/lib"];
+
+/// Returns the maximum versions for binaries which are to run against the libraries in a sysroot: the newest version
+/// defined in each namespace by glibc, libstdc++ and libgcc_s.
+pub fn requirements_from_sysroot(sysroot: &Path) -> anyhow::Result<Vec<String>> {
+    let libraries = find_libraries(sysroot);
+    if !libraries
+        .iter()
+        .any(|library| library.file_name().is_some_and(|name| name == "libc.so.6"))
+    {
+        return Err(anyhow!("No libc.so.6 found in {}", sysroot.display()));
+    }
+    newest_defined_versions(&libraries)
+}
+
+/// Finds the libraries in a sysroot's library directories. A library may be found in more than one directory, such
+/// as in both `lib` and `lib64` for multilib sysroots.
+fn find_libraries(sysroot: &Path) -> Vec<PathBuf> {
+    library_dirs(sysroot)
+        .iter()
+        .flat_map(|dir| LIBRARIES.iter().map(move |library| dir.join(library)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Returns a sysroot's library directories, in the order they are searched, each followed by its multiarch
+/// directories.
+fn library_dirs(sysroot: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for dir in LIBRARY_DIRS {
+        let dir = sysroot.join(dir);
+        let mut multiarch_dirs: Vec<PathBuf> = fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains("-linux-"))
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        multiarch_dirs.sort();
+        dirs.push(dir);
+        dirs.extend(multiarch_dirs);
+    }
+    dirs
+}
+
+/// The symbols a library exports, by name, with the version each is defined with, if any.
+type LibraryExports = HashMap<String, Vec<Option<String>>>;
+
+/// A library's name, with the machine and class of the file needing it, which the library must match, and the
+/// directories of the file's run path.
+type LibraryKey = (String, String, u8, Vec<PathBuf>);
+
+/// A symbol imported by a file which none of the libraries it depends on export.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnresolvedSymbol {
+    pub name: String,
+    pub version: Option<String>,
+    /// The library the version is needed from, if the symbol is versioned
+    #[serde(rename = "file")]
+    pub library: Option<String>,
+}
+
+/// The libraries and symbols a file needs which a sysroot doesn't provide.
+#[derive(Debug, Default, PartialEq)]
+pub struct Resolution {
+    pub missing_libraries: Vec<String>,
+    pub unresolved_symbols: Vec<UnresolvedSymbol>,
+}
+
+/// glibc's own libraries, which provide the versions files are checked against, so aren't followed when finding the
+/// libraries files need. The dynamic linker, e.g. `ld-linux-x86-64.so.2`, is one too.
+const GLIBC_LIBRARIES: &[&str] = &[
+    "libc.so.6",
+    "libm.so.6",
+    "libmvec.so.1",
+    "libpthread.so.0",
+    "libdl.so.2",
+    "librt.so.1",
+    "libresolv.so.2",
+    "libutil.so.1",
+    "libanl.so.1",
+    "libnsl.so.1",
+    "libthread_db.so.1",
+    "libBrokenLocale.so.1",
+];
+
+fn is_glibc_library(library: &str) -> bool {
+    let name = library.rsplit('/').next().unwrap_or(library);
+    GLIBC_LIBRARIES.contains(&name) || name.starts_with("ld-linux") || name.starts_with("ld64.so")
+}
+
+/// The directories the libraries ELF files need are searched for in: any given directories, followed by a sysroot's
+/// library directories, if there is one.
+pub struct LibraryPath {
+    sysroot: Option<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+impl LibraryPath {
+    pub fn new(dirs: &[PathBuf], sysroot: Option<&Path>) -> anyhow::Result<Self> {
+        let mut all_dirs = dirs.to_vec();
+        if let Some(sysroot) = sysroot {
+            if !sysroot.is_dir() {
+                return Err(anyhow!("Sysroot {} is not a directory", sysroot.display()));
+            }
+            all_dirs.extend(library_dirs(sysroot));
+        }
+        Ok(Self {
+            sysroot: sysroot.map(Path::to_path_buf),
+            dirs: all_dirs,
+        })
+    }
+
+    /// Returns where an absolute path is: in the sysroot, if there is one.
+    fn in_sysroot(&self, path: &str) -> PathBuf {
+        match &self.sysroot {
+            Some(sysroot) => sysroot.join(path.trim_start_matches('/')),
+            None => PathBuf::from(path),
+        }
+    }
+
+    /// Returns the directories an ELF file's run path gives, which are searched for the libraries it needs before the
+    /// others. They are in the sysroot, if there is one, other than those relative to `$ORIGIN`, the directory of the
+    /// file, which are only searched when the file's path is known. Directories using other dynamic string tokens,
+    /// such as `$LIB`, or relative to the working directory, are left out.
+    pub fn run_path_dirs(&self, data: &[u8], file: Option<&Path>) -> anyhow::Result<Vec<PathBuf>> {
+        let origin = file.and_then(Path::parent).map(|dir| {
+            if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }
+        });
+        Ok(elf::get_run_path(data)?
+            .into_iter()
+            .filter_map(|dir| {
+                let relative = dir
+                    .strip_prefix("$ORIGIN")
+                    .or_else(|| dir.strip_prefix("${ORIGIN}"))
+                    .filter(|relative| relative.is_empty() || relative.starts_with('/'));
+                match relative {
+                    Some(relative) if !relative.contains('$') => {
+                        Some(origin?.join(relative.trim_start_matches('/')))
+                    }
+                    None if !dir.contains('$') && dir.starts_with('/') => {
+                        Some(self.in_sysroot(&dir))
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    /// Finds the first library with the given name for the same machine and class as the given header, in the
+    /// directories of the run path of the file needing it and then the others, returning its path and contents.
+    /// Libraries needed by path, rather than by name, are looked up at that path in the sysroot.
+    pub fn find(
+        &self,
+        library: &str,
+        header: &ElfHeader,
+        run_path_dirs: &[PathBuf],
+    ) -> anyhow::Result<Option<(PathBuf, Vec<u8>)>> {
+        let candidates = if library.contains('/') {
+            vec![self.in_sysroot(library)]
+        } else {
+            run_path_dirs
+                .iter()
+                .chain(&self.dirs)
+                .map(|dir| dir.join(library))
+                .collect()
+        };
+        for path in candidates.into_iter().filter(|path| path.is_file()) {
+            let data =
+                fs::read(&path).with_context(|| format!("Error reading {}", path.display()))?;
+            match elf::get_header(&data) {
+                Ok(library_header)
+                    if library_header.machine == header.machine
+                        && library_header.class == header.class =>
+                {
+                    return Ok(Some((path, data)));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the libraries an ELF file needs, directly or through the libraries it needs, other than glibc's libraries
+    /// and those already seen, searching the run path of each file needing one first. The canonical paths of the
+    /// libraries found are added to those seen.
+    pub fn find_closure(
+        &self,
+        file: &Path,
+        data: &[u8],
+        seen: &mut HashSet<PathBuf>,
+    ) -> anyhow::Result<Closure> {
+        let mut closure = Closure::default();
+        let mut pending = vec![(file.to_path_buf(), data.to_vec())];
+        while let Some((file, data)) = pending.pop() {
+            let header = elf::get_header(&data)?;
+            let run_path_dirs = self.run_path_dirs(&data, Some(&file))?;
+            for library in elf::get_needed_libraries(&data)? {
+                if is_glibc_library(&library) {
+                    continue;
+                }
+                match self.find(&library, &header, &run_path_dirs)? {
+                    Some((path, library_data)) => {
+                        if seen.insert(fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                            closure.libraries.push(path.clone());
+                            pending.push((path, library_data));
+                        }
+                    }
+                    None => closure.missing.push((library, file.clone())),
+                }
+            }
+        }
+        Ok(closure)
+    }
+}
+
+/// The libraries an ELF file needs, directly or through the libraries it needs.
+#[derive(Debug, Default, PartialEq)]
+pub struct Closure {
+    pub libraries: Vec<PathBuf>,
+    /// The libraries which weren't found, each with the file needing it
+    pub missing: Vec<(String, PathBuf)>,
+}
+
+/// The libraries in a sysroot which ELF files are resolved against. The symbols each library exports are read when a
+/// file first needs it.
+pub struct SysrootLibraries {
+    library_path: LibraryPath,
+    /// The exports of each library looked up, or `None` if it wasn't found
+    exports: RefCell<HashMap<LibraryKey, Option<Rc<LibraryExports>>>>,
+}
+
+impl SysrootLibraries {
+    pub fn new(sysroot: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            library_path: LibraryPath::new(&[], Some(sysroot))?,
+            exports: RefCell::default(),
+        })
+    }
+
+    /// Resolves the libraries an ELF file depends on (`DT_NEEDED`) and the symbols it imports against the sysroot, and
+    /// the directories of the file's run path, `$ORIGIN` being the directory of the file, if its path is given. Each
+    /// symbol must be exported, with the version it references, by one of the libraries. Weak symbols, and symbols
+    /// versioned from libraries which are missing, aren't reported.
+    pub fn resolve(&self, data: &[u8], file: Option<&Path>) -> anyhow::Result<Resolution> {
+        let header = elf::get_header(data)?;
+        let run_path_dirs = self.library_path.run_path_dirs(data, file)?;
+        let mut resolution = Resolution::default();
+        let mut libraries = Vec::new();
+        for library in elf::get_needed_libraries(data)? {
+            match self.exports(&library, &header, &run_path_dirs)? {
+                Some(exports) => libraries.push(exports),
+                None => resolution.missing_libraries.push(library),
+            }
+        }
+        for import in elf::get_symbol_imports(data)? {
+            if import.is_weak
+                || import
+                    .file
+                    .as_ref()
+                    .is_some_and(|file| resolution.missing_libraries.contains(file))
+            {
+                continue;
+            }
+            // As with the dynamic linker, unversioned references and definitions match any version
+            let resolved = libraries.iter().any(|exports| {
+                exports.get(&import.name).is_some_and(|versions| {
+                    import.version.is_none()
+                        || versions.contains(&None)
+                        || versions.contains(&import.version)
+                })
+            });
+            if !resolved {
+                resolution.unresolved_symbols.push(UnresolvedSymbol {
+                    name: import.name,
+                    version: import.version,
+                    library: import.file,
+                });
+            }
+        }
+        Ok(resolution)
+    }
+
+    /// Returns the symbols exported by the first library with the given name, in the given run path directories or the
+    /// sysroot's library directories, for the same machine and class as the given header, or `None` if there isn't one.
+    fn exports(
+        &self,
+        library: &str,
+        header: &ElfHeader,
+        run_path_dirs: &[PathBuf],
+    ) -> anyhow::Result<Option<Rc<LibraryExports>>> {
+        let key: LibraryKey = (
+            library.to_string(),
+            header.machine.clone(),
+            header.class,
+            run_path_dirs.to_vec(),
+        );
+        if let Some(exports) = self.exports.borrow().get(&key) {
+            return Ok(exports.clone());
+        }
+        let exports = match self.library_path.find(library, header, run_path_dirs)? {
+            Some((path, data)) => {
+                let mut library_exports = LibraryExports::new();
+                for symbol in elf::get_exported_symbols(&data).with_context(|| {
+                    format!("Error reading exported symbols from {}", path.display())
+                })? {
+                    library_exports
+                        .entry(symbol.name)
+                        .or_default()
+                        .push(symbol.version);
+                }
+                Some(Rc::new(library_exports))
+            }
+            None => None,
+        };
+        self.exports.borrow_mut().insert(key, exports.clone());
+        Ok(exports)
+    }
+}
+
+/// Returns the newest version each of the given libraries defines in each namespace, ignoring private versions. The
+/// versions are named as the libraries define them, e.g. `GCC_4.8.0`.
+pub fn newest_defined_versions(libraries: &[PathBuf]) -> anyhow::Result<Vec<String>> {
+    let mut newest: BTreeMap<String, (NamespacedVersion, String)> = BTreeMap::new();
+    for library in libraries {
+        for (version, name) in defined_versions(library)? {
+            match newest.get(&version.namespace) {
+                Some((existing, _)) if *existing >= version => {}
+                _ => {
+                    newest.insert(version.namespace.clone(), (version, name));
+                }
+            }
+        }
+    }
+    Ok(newest.into_values().map(|(_, name)| name).collect())
+}
+
+/// Returns the versions a library defines, which are to be the only versions permitted in their namespaces. It is an
+/// error if the library defines no numbered versions.
+pub fn allowed_versions_from_library(library: &Path) -> anyhow::Result<Vec<NamespacedVersion>> {
+    let versions: Vec<NamespacedVersion> = defined_versions(library)?
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect();
+    if versions.is_empty() {
+        return Err(anyhow!("{} defines no versions", library.display()));
+    }
+    Ok(versions)
+}
+
+/// Returns the numbered versions a library defines, with their names, ignoring private versions.
+pub fn defined_versions(library: &Path) -> anyhow::Result<Vec<(NamespacedVersion, String)>> {
+    let definitions = fs::read(library)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| elf::get_version_definitions(&data))
+        .with_context(|| {
+            format!(
+                "Error reading version definitions from {}",
+                library.display()
+            )
+        })?;
+    Ok(definitions
+        .into_iter()
+        .filter(|definition| !is_private_version(definition))
+        .filter_map(|definition| Some((NamespacedVersion::parse(&definition).ok()?, definition)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf::tests::build_shared_library;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn finds_newest_versions_in_sysroot() {
+        let dir = TempDir::new("sysroot");
+        dir.write(
+            "lib/x86_64-linux-gnu/libc.so.6",
+            &build_shared_library(&["GLIBC_2.2.5", "GLIBC_2.17", "GLIBC_PRIVATE"], &[]),
+        );
+        dir.write(
+            "usr/lib64/libstdc++.so.6",
+            &build_shared_library(&["GLIBCXX_3.4", "GLIBCXX_3.4.19", "CXXABI_1.3.7"], &[]),
+        );
+        dir.write(
+            "usr/lib/libgcc_s.so.1",
+            &build_shared_library(&["GCC_3.0", "GCC_4.8.0", "GCC_4.2.0"], &[]),
+        );
+        dir.write(
+            "usr/lib/libssl.so.3",
+            &build_shared_library(&["OPENSSL_3.0.0"], &[]),
+        );
+        assert_eq!(
+            requirements_from_sysroot(&dir.path).unwrap(),
+            vec!["CXXABI_1.3.7", "GCC_4.8.0", "GLIBC_2.17", "GLIBCXX_3.4.19"]
+        );
+    }
+
+    #[test]
+    fn sysroot_without_libc_is_error() {
+        let dir = TempDir::new("sysroot-empty");
+        dir.write(
+            "usr/lib/libgcc_s.so.1",
+            &build_shared_library(&["GCC_4.8.0"], &[]),
+        );
+        assert!(requirements_from_sysroot(&dir.path).is_err());
+    }
+
+    #[test]
+    fn reads_defined_versions() {
+        let dir = TempDir::new("defined-versions");
+        let library = dir.write(
+            "libc.so.6",
+            &build_shared_library(&["GLIBC_2.2.5", "GLIBC_PRIVATE", "GLIBC_2.17"], &[]),
+        );
+        let names: Vec<String> = defined_versions(&library)
+            .unwrap()
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(names, vec!["GLIBC_2.2.5", "GLIBC_2.17"]);
+        assert_eq!(allowed_versions_from_library(&library).unwrap().len(), 2);
+        let unversioned = dir.write("libfoo.so.1", &build_shared_library(&[], &[]));
+        assert!(allowed_versions_from_library(&unversioned).is_err());
+    }
+
+    #[test]
+    fn resolves_against_sysroot_libraries() {
+        let data = fs::read(std::env::current_exe().unwrap()).unwrap();
+        let imports = elf::get_symbol_imports(&data).unwrap();
+        let mut needed = elf::get_needed_libraries(&data).unwrap();
+        let missing = needed.remove(
+            needed
+                .iter()
+                .rposition(|library| library != "libc.so.6")
+                .unwrap(),
+        );
+        let omitted = imports
+            .iter()
+            .find(|import| import.file.as_deref() == Some("libc.so.6"))
+            .unwrap();
+        let dir = TempDir::new("sysroot-resolve");
+        for library in &needed {
+            let exported: Vec<_> = imports
+                .iter()
+                .filter(|import| import.file.as_ref() == Some(library) || import.file.is_none())
+                .filter(|import| *import != omitted)
+                .collect();
+            let mut versions: Vec<&str> = exported
+                .iter()
+                .filter_map(|import| import.version.as_deref())
+                .collect();
+            versions.sort();
+            versions.dedup();
+            let symbols: Vec<_> = exported
+                .iter()
+                .map(|import| (import.name.as_str(), import.version.as_deref(), true))
+                .collect();
+            let mut library_data = build_shared_library(&versions, &symbols);
+            // The libraries must be for the same machine as the test executable
+            library_data[18..20].copy_from_slice(&data[18..20]);
+            dir.write(&format!("usr/lib/{}", library), &library_data);
+        }
+        let resolution = SysrootLibraries::new(&dir.path)
+            .unwrap()
+            .resolve(&data, None)
+            .unwrap();
+        assert_eq!(resolution.missing_libraries, vec![missing]);
+        assert_eq!(
+            resolution.unresolved_symbols,
+            vec![UnresolvedSymbol {
+                name: omitted.name.clone(),
+                version: omitted.version.clone(),
+                library: omitted.file.clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn resolves_libraries_in_run_path() {
+        use object::elf::{DT_NEEDED, DT_RUNPATH};
+        let dir = TempDir::new("sysroot-run-path");
+        let library = build_shared_library(&[], &[]);
+        dir.write("app/lib/libbundled.so.1", &library);
+        dir.write("sysroot/opt/app/lib/libvendor.so.1", &library);
+        dir.write("sysroot/usr/lib/libc.so.6", &library);
+        let data = elf::tests::build_elf_with_dynamic(&[
+            (DT_NEEDED, "libbundled.so.1"),
+            (DT_NEEDED, "libvendor.so.1"),
+            (DT_NEEDED, "libc.so.6"),
+            (DT_RUNPATH, "$ORIGIN/../lib:/opt/app/lib:$LIB/app"),
+        ]);
+        let app = dir.write("app/bin/app", &data);
+        let sysroot = SysrootLibraries::new(&dir.path.join("sysroot")).unwrap();
+        let resolution = sysroot.resolve(&data, Some(&app)).unwrap();
+        assert_eq!(resolution, Resolution::default());
+        // Without the file's path, $ORIGIN can't be resolved
+        let resolution = sysroot.resolve(&data, None).unwrap();
+        assert_eq!(resolution.missing_libraries, ["libbundled.so.1"]);
+    }
+
+    #[test]
+    fn finds_closure_of_needed_libraries() {
+        let data = fs::read(std::env::current_exe().unwrap()).unwrap();
+        let dir = TempDir::new("library-path");
+        let mut expected = Vec::new();
+        for library in elf::get_needed_libraries(&data).unwrap() {
+            let mut library_data = build_shared_library(&[], &[]);
+            library_data[18..20].copy_from_slice(&data[18..20]);
+            let path = dir.write(&format!("libs/{}", library), &library_data);
+            if !is_glibc_library(&library) {
+                expected.push(path);
+            }
+        }
+        let library_path = LibraryPath::new(&[dir.path.join("libs")], None).unwrap();
+        let mut seen = HashSet::new();
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(
+            library_path.find_closure(&exe, &data, &mut seen).unwrap(),
+            Closure {
+                libraries: expected,
+                missing: Vec::new(),
+            }
+        );
+        assert_eq!(
+            library_path.find_closure(&exe, &data, &mut seen).unwrap(),
+            Closure::default()
+        );
+    }
+
+    #[test]
+    fn finds_closure_in_run_path() {
+        use object::elf::{DT_NEEDED, DT_RUNPATH};
+        let dir = TempDir::new("library-path-run-path");
+        let library = elf::tests::build_elf_with_dynamic(&[
+            (DT_NEEDED, "libmissing.so.1"),
+            (DT_RUNPATH, "$ORIGIN"),
+        ]);
+        dir.write("app/lib/libbundled.so.1", &library);
+        let bundled = dir.path.join("app/bin/../lib/libbundled.so.1");
+        let data = elf::tests::build_elf_with_dynamic(&[
+            (DT_NEEDED, "libbundled.so.1"),
+            (DT_NEEDED, "libc.so.6"),
+            (DT_RUNPATH, "$ORIGIN/../lib"),
+        ]);
+        let app = dir.write("app/bin/app", &data);
+        let library_path = LibraryPath::new(&[], None).unwrap();
+        assert_eq!(
+            library_path
+                .find_closure(&app, &data, &mut HashSet::new())
+                .unwrap(),
+            Closure {
+                libraries: vec![bundled.clone()],
+                missing: vec![("libmissing.so.1".to_string(), bundled)],
+            }
+        );
+    }
+
+    #[test]
+    fn sysroot_must_be_directory() {
+        let dir = TempDir::new("sysroot-file");
+        let file = dir.write("sysroot", b"");
+        assert!(SysrootLibraries::new(&file).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use anyhow::Context;
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use std::collections::BTreeSet;
+
+/// A file's result, as explored in the TUI.
+pub struct FileItem {
+    pub file: String,
+    /// The result, e.g. `FAIL`
+    pub result: &'static str,
+    pub entries: Vec<Entry>,
+}
+
+/// A line of a file's details: one of its violations, or the reason it couldn't be checked.
+pub struct Entry {
+    /// The namespace of a symbol's version, e.g. `GLIBC`
+    pub namespace: Option<String>,
+    pub text: String,
+    /// The text with the symbol's name demangled, if it could be demangled
+    pub demangled: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pane {
+    Files,
+    Details,
+}
+
+struct App {
+    files: Vec<FileItem>,
+    /// The namespaces of the files' symbols, which the files and their details can be limited to
+    namespaces: Vec<String>,
+    /// The index in `namespaces` of the namespace shown, or `None` to show all of them
+    namespace: Option<usize>,
+    demangle: bool,
+    failing_only: bool,
+    focus: Pane,
+    /// The index in the visible files of the file selected
+    selected: ListState,
+    details_scroll: u16,
+}
+
+impl App {
+    fn new(files: Vec<FileItem>, demangle: bool) -> Self {
+        let namespaces: BTreeSet<String> = files
+            .iter()
+            .flat_map(|file| &file.entries)
+            .filter_map(|entry| entry.namespace.clone())
+            .collect();
+        let mut app = App {
+            files,
+            namespaces: namespaces.into_iter().collect(),
+            namespace: None,
+            demangle,
+            failing_only: false,
+            focus: Pane::Files,
+            selected: ListState::default(),
+            details_scroll: 0,
+        };
+        app.select(0);
+        app
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        self.namespace.map(|index| self.namespaces[index].as_str())
+    }
+
+    fn entry_visible(&self, entry: &Entry) -> bool {
+        self.namespace()
+            .is_none_or(|namespace| entry.namespace.as_deref() == Some(namespace))
+    }
+
+    /// Returns the files shown, which are those with violations in the namespace shown, if there is one, and those
+    /// which didn't pass, when only failing files are shown.
+    fn visible_files(&self) -> Vec<&FileItem> {
+        self.files
+            .iter()
+            .filter(|file| !self.failing_only || file.result == "FAIL" || file.result == "ERROR")
+            .filter(|file| {
+                self.namespace.is_none() || file.entries.iter().any(|e| self.entry_visible(e))
+            })
+            .collect()
+    }
+
+    fn selected_file(&self) -> Option<&FileItem> {
+        let index = self.selected.selected()?;
+        self.visible_files().get(index).copied()
+    }
+
+    fn select(&mut self, index: usize) {
+        let count = self.visible_files().len();
+        self.selected
+            .select((count > 0).then(|| index.min(count - 1)));
+        self.details_scroll = 0;
+    }
+
+    fn next_namespace(&mut self) {
+        self.namespace = match self.namespace {
+            None if !self.namespaces.is_empty() => Some(0),
+            Some(index) if index + 1 < self.namespaces.len() => Some(index + 1),
+            _ => None,
+        };
+        self.select(0);
+    }
+
+    /// Handles a key, returning false when the TUI should exit.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        let selected = self.selected.selected().unwrap_or(0);
+        match (key, self.focus) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return false,
+            (KeyCode::Tab | KeyCode::BackTab, Pane::Files) => self.focus = Pane::Details,
+            (KeyCode::Tab | KeyCode::BackTab, Pane::Details) => self.focus = Pane::Files,
+            (KeyCode::Char('n'), _) => self.next_namespace(),
+            (KeyCode::Char('d'), _) => self.demangle = !self.demangle,
+            (KeyCode::Char('f'), _) => {
+                self.failing_only = !self.failing_only;
+                self.select(0);
+            }
+            (KeyCode::Down | KeyCode::Char('j'), Pane::Files) => self.select(selected + 1),
+            (KeyCode::Up | KeyCode::Char('k'), Pane::Files) => {
+                self.select(selected.saturating_sub(1))
+            }
+            (KeyCode::PageDown, Pane::Files) => self.select(selected + 10),
+            (KeyCode::PageUp, Pane::Files) => self.select(selected.saturating_sub(10)),
+            (KeyCode::Home, Pane::Files) => self.select(0),
+            (KeyCode::End, Pane::Files) => self.select(usize::MAX),
+            (KeyCode::Down | KeyCode::Char('j'), Pane::Details) => {
+                self.details_scroll = self.details_scroll.saturating_add(1)
+            }
+            (KeyCode::Up | KeyCode::Char('k'), Pane::Details) => {
+                self.details_scroll = self.details_scroll.saturating_sub(1)
+            }
+            (KeyCode::PageDown, Pane::Details) => {
+                self.details_scroll = self.details_scroll.saturating_add(10)
+            }
+            (KeyCode::PageUp, Pane::Details) => {
+                self.details_scroll = self.details_scroll.saturating_sub(10)
+            }
+            (KeyCode::Home, Pane::Details) => self.details_scroll = 0,
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [files_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+        let border_style = |pane| {
+            if self.focus == pane {
+                Style::new().fg(Color::Cyan)
+            } else {
+                Style::new()
+            }
+        };
+
+        let visible_files = self.visible_files();
+        let mut filters = Vec::new();
+        if let Some(namespace) = self.namespace() {
+            filters.push(namespace.to_string());
+        }
+        if self.failing_only {
+            filters.push("failing".to_string());
+        }
+        let files_title = if filters.is_empty() {
+            format!(" Files ({}) ", visible_files.len())
+        } else {
+            format!(" Files ({}, {}) ", visible_files.len(), filters.join(", "))
+        };
+        let items: Vec<ListItem> = visible_files
+            .iter()
+            .map(|file| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<6}", file.result), result_style(file.result)),
+                    Span::raw(" "),
+                    Span::raw(file.file.clone()),
+                ]))
+            })
+            .collect();
+        let files = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(files_title)
+                    .border_style(border_style(Pane::Files)),
+            )
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        let (details_title, lines) = match self.selected_file() {
+            Some(file) => {
+                let lines: Vec<Line> = file
+                    .entries
+                    .iter()
+                    .filter(|entry| self.entry_visible(entry))
+                    .map(|entry| {
+                        let text = match (&entry.demangled, self.demangle) {
+                            (Some(demangled), true) => demangled.clone(),
+                            _ => entry.text.clone(),
+                        };
+                        Line::raw(text)
+                    })
+                    .collect();
+                (format!(" {} ", file.file), lines)
+            }
+            None => (" No files ".to_string(), Vec::new()),
+        };
+        let details = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(details_title)
+                    .border_style(border_style(Pane::Details)),
+            )
+            .scroll((self.details_scroll, 0));
+
+        let help_line = Line::from(vec![
+            "↑↓".bold(),
+            " select  ".into(),
+            "Tab".bold(),
+            " switch pane  ".into(),
+            "n".bold(),
+            " namespace  ".into(),
+            "f".bold(),
+            " failing only  ".into(),
+            "d".bold(),
+            format!(" demangle ({})  ", if self.demangle { "on" } else { "off" }).into(),
+            "q".bold(),
+            " quit".into(),
+        ]);
+
+        frame.render_stateful_widget(files, files_area, &mut self.selected);
+        frame.render_widget(details, details_area);
+        frame.render_widget(Paragraph::new(help_line), help);
+    }
+}
+
+fn result_style(result: &str) -> Style {
+    match result {
+        "FAIL" | "ERROR" => Style::new().fg(Color::Red).bold(),
+        "WARN" => Style::new().fg(Color::Yellow).bold(),
+        _ => Style::new().fg(Color::Green).bold(),
+    }
+}
+
+/// Explores the files' results interactively in the terminal until the user quits, showing demangled symbol names to
+/// start with when `demangle` is set.
+pub fn run(files: Vec<FileItem>, demangle: bool) -> anyhow::Result<()> {
+    // The terminal may have been partly set up before failing
+    let mut terminal = ratatui::try_init()
+        .inspect_err(|_| ratatui::restore())
+        .with_context(|| "Error initializing the terminal")?;
+    let result = explore(&mut terminal, App::new(files, demangle));
+    ratatui::restore();
+    result
+}
+
+fn explore(terminal: &mut DefaultTerminal, mut app: App) -> anyhow::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle_key(key.code)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn symbol(name: &str, version: &str, demangled: Option<&str>) -> Entry {
+        Entry {
+            namespace: Some(version.split_once('_').unwrap().0.to_string()),
+            text: format!("{}@{}", name, version),
+            demangled: demangled.map(|demangled| format!("{}@{}", demangled, version)),
+        }
+    }
+
+    fn files() -> Vec<FileItem> {
+        vec![
+            FileItem {
+                file: "bin/app".to_string(),
+                result: "FAIL",
+                entries: vec![
+                    symbol("stat", "GLIBC_2.33", None),
+                    symbol(
+                        "_ZNSt7__cxx1112basic_stringIcE",
+                        "GLIBCXX_3.4.21",
+                        Some("std::string"),
+                    ),
+                ],
+            },
+            FileItem {
+                file: "bin/tool".to_string(),
+                result: "PASS",
+                entries: Vec::new(),
+            },
+            FileItem {
+                file: "lib/libfoo.so".to_string(),
+                result: "FAIL",
+                entries: vec![symbol("fcntl64", "GLIBC_2.28", None)],
+            },
+        ]
+    }
+
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn filters_files_by_namespace_and_result() {
+        let mut app = App::new(files(), false);
+        assert_eq!(app.namespaces, vec!["GLIBC", "GLIBCXX"]);
+        assert_eq!(app.visible_files().len(), 3);
+
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.namespace(), Some("GLIBC"));
+        assert_eq!(app.visible_files().len(), 2);
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.namespace(), Some("GLIBCXX"));
+        assert_eq!(app.visible_files().len(), 1);
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.namespace(), None);
+
+        app.handle_key(KeyCode::Char('f'));
+        let visible: Vec<&str> = app
+            .visible_files()
+            .iter()
+            .map(|f| f.file.as_str())
+            .collect();
+        assert_eq!(visible, vec!["bin/app", "lib/libfoo.so"]);
+        app.handle_key(KeyCode::End);
+        assert_eq!(app.selected_file().unwrap().file, "lib/libfoo.so");
+        assert!(!app.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn shows_selected_file_details() {
+        let mut app = App::new(files(), false);
+        let screen = render(&mut app);
+        assert!(screen.contains("Files (3)"));
+        assert!(screen.contains("stat@GLIBC_2.33"));
+        assert!(screen.contains("_ZNSt7__cxx1112basic_stringIcE@GLIBCXX_3.4.21"));
+        assert!(!screen.contains("fcntl64"));
+
+        app.handle_key(KeyCode::Char('d'));
+        app.handle_key(KeyCode::Char('n'));
+        app.handle_key(KeyCode::Char('n'));
+        let screen = render(&mut app);
+        assert!(screen.contains("Files (1, GLIBCXX)"));
+        assert!(screen.contains("std::string@GLIBCXX_3.4.21"));
+        assert!(!screen.contains("stat@GLIBC_2.33"));
+        assert!(screen.contains("demangle (on)"));
+    }
+}
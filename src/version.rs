@@ -1,7 +1,10 @@
 use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display, Formatter};
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// A version in a namespace, e.g. `GLIBC_2.17`. It is serialized as a string in this form.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct NamespacedVersion {
     pub namespace: String,
     pub version: Version,
@@ -45,8 +48,35 @@ impl Display for NamespacedVersion {
     }
 }
 
+impl From<NamespacedVersion> for String {
+    fn from(version: NamespacedVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl TryFrom<String> for NamespacedVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: String) -> anyhow::Result<Self> {
+        NamespacedVersion::parse(&version)
+    }
+}
+
+/// Checks whether a version is private to the library defining it, and may change incompatibly between any two
+/// releases. Private versions are named `<NAMESPACE>_PRIVATE` on Linux, such as `GLIBC_PRIVATE`, and
+/// `<namespace>private`, optionally numbered, on FreeBSD and illumos, such as `FBSDprivate_1.0` or `SUNWprivate_1.1`.
+pub fn is_private_version(version: &str) -> bool {
+    let namespace = match version.rsplit_once('_') {
+        Some((namespace, number)) if number.starts_with(|c: char| c.is_ascii_digit()) => namespace,
+        _ => version,
+    };
+    namespace.ends_with("_PRIVATE") || namespace.ends_with("private")
+}
+
 /// Version number. A version number consists of a series of one or more non-negative integers separated by periods.
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// It is serialized as a string, e.g. `2.17`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct Version {
     values: Vec<u32>,
 }
@@ -65,10 +95,18 @@ impl Version {
         }
         Ok(Version { values })
     }
+
+    /// The numbers the version consists of, without trailing zeroes.
+    pub fn parts(&self) -> &[u32] {
+        &self.values
+    }
 }
 
 impl Display for Version {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.values.is_empty() {
+            return write!(f, "0");
+        }
         write!(
             f,
             "{}",
@@ -81,6 +119,20 @@ impl Display for Version {
     }
 }
 
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = anyhow::Error;
+
+    fn try_from(version: String) -> anyhow::Result<Self> {
+        Version::parse(&version)
+    }
+}
+
 impl Debug for Version {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Version({})", self)
@@ -136,6 +188,17 @@ mod test {
         assert_eq!(Version::parse("0.1.0.0").unwrap().values, vec![0, 1]);
     }
 
+    #[test]
+    fn version_display() {
+        assert_eq!(Version::parse("2.17").unwrap().to_string(), "2.17");
+        assert_eq!(Version::parse("1.0.1.0").unwrap().to_string(), "1.0.1");
+    }
+
+    #[test]
+    fn version_display_zero() {
+        assert_eq!(Version::parse("0.0").unwrap().to_string(), "0");
+    }
+
     #[test]
     fn version_cmp() {
         assert!(Version::parse("1").unwrap() < Version::parse("42").unwrap());
@@ -157,6 +220,17 @@ mod test {
         );
     }
 
+    #[test]
+    fn detects_private_versions() {
+        assert!(is_private_version("GLIBC_PRIVATE"));
+        assert!(is_private_version("FBSDprivate_1.0"));
+        assert!(is_private_version("SUNWprivate_1.1"));
+        assert!(is_private_version("SUNWprivate"));
+        assert!(!is_private_version("GLIBC_2.17"));
+        assert!(!is_private_version("FBSD_1.7"));
+        assert!(!is_private_version("GLIBC_ABI_DT_RELR"));
+    }
+
     #[test]
     fn namespaced_version_parses() {
         let version = NamespacedVersion::parse("GLIBC_2.17").unwrap();
@@ -0,0 +1,287 @@
+use crate::config::CONFIG_FILE_NAME;
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Options controlling how directories are walked.
+#[derive(Clone, Debug, Default)]
+pub struct WalkOptions {
+    /// Follow symbolic links to files and directories. When not set, symbolic links are skipped.
+    pub follow_symlinks: bool,
+    /// Skip files excluded by .gitignore files, the repository's exclude file and the global gitignore file, as
+    /// well as the .git directory itself.
+    pub respect_gitignore: bool,
+    /// When not empty, only files matching one of these globs are included.
+    pub include: GlobSet,
+    /// Files and directories matching any of these globs are skipped.
+    pub exclude: GlobSet,
+}
+
+/// Builds a set of globs from patterns such as `*.so*`. Patterns are matched against both the file name and the
+/// path relative to the directory being searched.
+pub fn build_globs(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).with_context(|| format!("Invalid glob: {}", pattern))?);
+    }
+    builder.build().with_context(|| "Error building globs")
+}
+
+fn glob_matches(globs: &GlobSet, root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    globs.is_match(relative) || path.file_name().is_some_and(|name| globs.is_match(name))
+}
+
+/// Error encountered while walking a directory.
+pub struct WalkError {
+    pub path: PathBuf,
+    pub error: anyhow::Error,
+}
+
+/// Expands the given paths into the files to check. Directories are walked recursively, yielding the regular files
+/// within them in sorted order, other than config files. Paths which aren't directories are returned as is. When
+/// following symbolic links, files reached through more than one path are only returned once, and symbolic link loops
+/// are skipped.
+pub fn expand_paths(paths: &[PathBuf], options: &WalkOptions) -> Vec<Result<PathBuf, WalkError>> {
+    let mut seen = HashSet::new();
+    paths
+        .iter()
+        .flat_map(|path| {
+            if path.is_dir() {
+                walk_dir(path, options, &mut seen)
+            } else {
+                vec![Ok(path.clone())]
+            }
+        })
+        .collect()
+}
+
+fn walk_dir(
+    dir: &Path,
+    options: &WalkOptions,
+    seen: &mut HashSet<PathBuf>,
+) -> Vec<Result<PathBuf, WalkError>> {
+    let respect_gitignore = options.respect_gitignore;
+    let exclude = options.exclude.clone();
+    let root = dir.to_path_buf();
+    WalkBuilder::new(dir)
+        .standard_filters(false)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .parents(respect_gitignore)
+        .filter_entry(move |entry| {
+            let is_git_dir = respect_gitignore && entry.file_name() == ".git";
+            let is_excluded = entry.depth() > 0 && glob_matches(&exclude, &root, entry.path());
+            !is_git_dir && !is_excluded
+        })
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                let path = entry.into_path();
+                if path
+                    .file_name()
+                    .is_some_and(|name| name == CONFIG_FILE_NAME)
+                {
+                    return None;
+                }
+                if !options.include.is_empty() && !glob_matches(&options.include, dir, &path) {
+                    return None;
+                }
+                if options.follow_symlinks {
+                    let target = path.canonicalize().unwrap_or_else(|_| path.clone());
+                    if !seen.insert(target) {
+                        return None;
+                    }
+                }
+                Some(Ok(path))
+            }
+            Ok(_) => None,
+            Err(e) if is_loop(&e) => None,
+            Err(e) => Some(Err(WalkError {
+                path: dir.to_path_buf(),
+                error: anyhow::Error::new(e).context("Error reading directory"),
+            })),
+        })
+        .collect()
+}
+
+fn is_loop(error: &ignore::Error) -> bool {
+    match error {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_loop(err),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    fn expanded(paths: &[PathBuf]) -> Vec<PathBuf> {
+        expanded_with(paths, &WalkOptions::default())
+    }
+
+    fn expanded_with(paths: &[PathBuf], options: &WalkOptions) -> Vec<PathBuf> {
+        expand_paths(paths, options)
+            .into_iter()
+            .map(|p| p.unwrap_or_else(|e| panic!("{}", e.error)))
+            .collect()
+    }
+
+    #[test]
+    fn files_are_returned_as_is() {
+        let paths = vec![PathBuf::from("a.so"), PathBuf::from("missing/b.so")];
+        assert_eq!(expanded(&paths), paths);
+    }
+
+    #[test]
+    fn directories_are_walked_recursively_in_order() {
+        let dir = TempDir::new("walk");
+        let b = dir.write("usr/lib/b.so", b"");
+        let a = dir.write("usr/bin/a", b"");
+        let c = dir.write("c", b"");
+        assert_eq!(expanded(std::slice::from_ref(&dir.path)), vec![c, a, b]);
+    }
+
+    #[test]
+    fn config_files_are_skipped() {
+        let dir = TempDir::new("walk-config");
+        let app = dir.write("app", b"");
+        dir.write(CONFIG_FILE_NAME, b"{}");
+        assert_eq!(expanded(std::slice::from_ref(&dir.path)), vec![app]);
+    }
+
+    #[test]
+    fn symlinks_are_skipped_by_default() {
+        let dir = TempDir::new("symlinks-skipped");
+        let a = dir.write("lib/liba.so.1", b"");
+        std::os::unix::fs::symlink("liba.so.1", dir.path.join("lib/liba.so")).unwrap();
+        std::os::unix::fs::symlink("lib", dir.path.join("lib64")).unwrap();
+        assert_eq!(expanded(std::slice::from_ref(&dir.path)), vec![a]);
+    }
+
+    #[test]
+    fn followed_symlinks_are_deduplicated() {
+        let dir = TempDir::new("symlinks-followed");
+        dir.write("lib/liba.so.1", b"");
+        dir.write("other/libb.so", b"");
+        std::os::unix::fs::symlink("liba.so.1", dir.path.join("lib/liba.so")).unwrap();
+        std::os::unix::fs::symlink("../other", dir.path.join("lib/other")).unwrap();
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            expanded_with(std::slice::from_ref(&dir.path), &options),
+            vec![
+                dir.path.join("lib/liba.so"),
+                dir.path.join("lib/other/libb.so")
+            ]
+        );
+    }
+
+    #[test]
+    fn followed_symlink_loops_are_skipped() {
+        let dir = TempDir::new("symlink-loop");
+        let a = dir.write("usr/bin/a", b"");
+        std::os::unix::fs::symlink("..", dir.path.join("usr/bin/up")).unwrap();
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            expanded_with(std::slice::from_ref(&dir.path), &options),
+            vec![a]
+        );
+    }
+
+    #[test]
+    fn gitignore_is_not_respected_by_default() {
+        let dir = TempDir::new("gitignore-default");
+        fs::create_dir_all(dir.path.join(".git")).unwrap();
+        let gitignore = dir.write(".gitignore", b"build/\n");
+        let built = dir.write("build/app", b"");
+        assert_eq!(
+            expanded(std::slice::from_ref(&dir.path)),
+            vec![gitignore, built]
+        );
+    }
+
+    #[test]
+    fn gitignore_is_respected_when_requested() {
+        let dir = TempDir::new("gitignore-respected");
+        dir.write(".git/config", b"");
+        let gitignore = dir.write(".gitignore", b"build/\n*.o\n");
+        dir.write("build/app", b"");
+        dir.write("src/main.o", b"");
+        let vendored = dir.write("vendor/lib/libfoo.so", b"");
+        let options = WalkOptions {
+            respect_gitignore: true,
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            expanded_with(std::slice::from_ref(&dir.path), &options),
+            vec![gitignore, vendored]
+        );
+    }
+
+    fn globs(patterns: &[&str]) -> GlobSet {
+        build_globs(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn invalid_glob_is_error() {
+        assert!(build_globs(&["[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn include_globs_select_files() {
+        let dir = TempDir::new("include");
+        let libc = dir.write("usr/lib/libc.so.6", b"");
+        dir.write("usr/lib/libc.a", b"");
+        dir.write("usr/share/doc/README", b"");
+        let options = WalkOptions {
+            include: globs(&["*.so*"]),
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            expanded_with(std::slice::from_ref(&dir.path), &options),
+            vec![libc]
+        );
+    }
+
+    #[test]
+    fn exclude_globs_skip_files_and_directories() {
+        let dir = TempDir::new("exclude");
+        let app = dir.write("usr/bin/app", b"");
+        dir.write("usr/bin/app.debug", b"");
+        dir.write("usr/share/doc/README", b"");
+        let options = WalkOptions {
+            exclude: globs(&["*.debug", "usr/share"]),
+            ..WalkOptions::default()
+        };
+        assert_eq!(
+            expanded_with(std::slice::from_ref(&dir.path), &options),
+            vec![app]
+        );
+    }
+
+    #[test]
+    fn globs_dont_apply_to_explicit_files() {
+        let options = WalkOptions {
+            exclude: globs(&["*.debug"]),
+            ..WalkOptions::default()
+        };
+        let paths = vec![PathBuf::from("app.debug")];
+        assert_eq!(expanded_with(&paths, &options), paths);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::walk::{self, WalkOptions};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// When a file was last modified, and its size, by which rewritten files are noticed.
+type Stamp = (Option<SystemTime>, u64);
+
+/// Finds the files found from the watched paths which have been rewritten, once they have stopped changing.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    options: WalkOptions,
+    /// The stamps of the files when they were last checked
+    checked: HashMap<PathBuf, Stamp>,
+    /// The stamps of the files when they were last polled
+    polled: HashMap<PathBuf, Stamp>,
+}
+
+impl Watcher {
+    /// Creates a watcher for the files found from the given paths, which have just been checked. Directories are
+    /// walked again on each poll, so files added to them are found.
+    pub fn new(paths: &[PathBuf], options: WalkOptions) -> Self {
+        let mut watcher = Watcher {
+            paths: paths.to_vec(),
+            options,
+            checked: HashMap::new(),
+            polled: HashMap::new(),
+        };
+        watcher.checked = watcher.stamps();
+        watcher.polled = watcher.checked.clone();
+        watcher
+    }
+
+    fn stamps(&self) -> HashMap<PathBuf, Stamp> {
+        walk::expand_paths(&self.paths, &self.options)
+            .into_iter()
+            .flatten()
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                Some((path, (metadata.modified().ok(), metadata.len())))
+            })
+            .collect()
+    }
+
+    /// Returns the files which are new or have changed since they were last checked, but not since the last poll, so
+    /// that files still being written by the build are left until it has finished with them.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let stamps = self.stamps();
+        let mut changed: Vec<PathBuf> = stamps
+            .iter()
+            .filter(|&(path, stamp)| {
+                self.checked.get(path) != Some(stamp) && self.polled.get(path) == Some(stamp)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+        for path in &changed {
+            self.checked.insert(path.clone(), stamps[path]);
+        }
+        self.checked.retain(|path, _| stamps.contains_key(path));
+        self.polled = stamps;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn reports_rewritten_files_once_unchanged() {
+        let dir = TempDir::new("watch");
+        dir.write("bin/app", b"old");
+        dir.write("bin/tool", b"tool");
+        let mut watcher = Watcher::new(std::slice::from_ref(&dir.path), WalkOptions::default());
+        assert!(watcher.poll().is_empty());
+
+        dir.write("bin/app", b"new contents");
+        dir.write("lib/libfoo.so", b"lib");
+        // Files are left until they stop changing
+        assert!(watcher.poll().is_empty());
+        assert_eq!(
+            watcher.poll(),
+            vec![dir.path.join("bin/app"), dir.path.join("lib/libfoo.so")]
+        );
+        assert!(watcher.poll().is_empty());
+
+        fs::remove_file(dir.path.join("bin/tool")).unwrap();
+        assert!(watcher.poll().is_empty());
+    }
+}